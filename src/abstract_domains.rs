@@ -8,6 +8,7 @@ use crate::constant_domain::ConstantDomain;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType};
 use crate::interval_domain::{self, IntervalDomain};
+use crate::string_domain::StringDomain;
 
 use rustc::ty::TyKind;
 use std::fmt::{Debug, Formatter, Result};
@@ -29,6 +30,10 @@ pub struct AbstractDomain {
     /// Cached interval computed on demand by get_as_interval.
     #[serde(skip)]
     interval: Option<IntervalDomain>,
+    /// True if this element is the result of simplify, so that simplifying it again, or an element
+    /// that contains it, does not have to traverse it again.
+    #[serde(skip)]
+    is_simplified: bool,
 }
 
 impl Debug for AbstractDomain {
@@ -54,24 +59,28 @@ impl PartialEq for AbstractDomain {
 pub const BOTTOM: AbstractDomain = AbstractDomain {
     expression: Expression::Bottom,
     interval: None,
+    is_simplified: false,
 };
 
 /// An abstract domain element that all represent the single concrete value, false.
 pub const FALSE: AbstractDomain = AbstractDomain {
     expression: Expression::CompileTimeConstant(ConstantDomain::False),
     interval: None,
+    is_simplified: false,
 };
 
 /// An abstract domain element that all represents all possible concrete values.
 pub const TOP: AbstractDomain = AbstractDomain {
     expression: Expression::Top,
     interval: None,
+    is_simplified: false,
 };
 
 /// An abstract domain element that all represent the single concrete value, true.
 pub const TRUE: AbstractDomain = AbstractDomain {
     expression: Expression::CompileTimeConstant(ConstantDomain::True),
     interval: None,
+    is_simplified: false,
 };
 
 impl<'a> From<&TyKind<'a>> for ExpressionType {
@@ -104,11 +113,13 @@ impl From<bool> for AbstractDomain {
             AbstractDomain {
                 expression: Expression::CompileTimeConstant(ConstantDomain::True),
                 interval: None,
+                is_simplified: false,
             }
         } else {
             AbstractDomain {
                 expression: Expression::CompileTimeConstant(ConstantDomain::False),
                 interval: None,
+                is_simplified: false,
             }
         }
     }
@@ -119,6 +130,7 @@ impl From<ConstantDomain> for AbstractDomain {
         AbstractDomain {
             expression: Expression::CompileTimeConstant(cv),
             interval: None,
+            is_simplified: false,
         }
     }
 }
@@ -128,6 +140,7 @@ impl From<Expression> for AbstractDomain {
         AbstractDomain {
             expression: expr,
             interval: None,
+            is_simplified: false,
         }
     }
 }
@@ -316,6 +329,17 @@ impl AbstractDomain {
                     }
                 }
             }
+            // The address of a place or of a heap block is never null.
+            (
+                Expression::Reference(..),
+                Expression::CompileTimeConstant(ConstantDomain::U128(0)),
+            )
+            | (
+                Expression::AbstractHeapAddress(..),
+                Expression::CompileTimeConstant(ConstantDomain::U128(0)),
+            ) => {
+                return false.into();
+            }
             // x == 0 is the same as !x when x is Boolean variable. Canonicalize it to the latter.
             (
                 Expression::Variable { var_type, .. },
@@ -336,6 +360,25 @@ impl AbstractDomain {
                     }
                 }
             }
+            // (c ? x : y) == z is the same as c && x == z if y == z is known to be false, and as
+            // !c && y == z if x == z is known to be false. An impossible (bottom) branch is never taken.
+            (
+                Expression::ConditionalExpression {
+                    condition,
+                    consequent,
+                    alternate,
+                },
+                Expression::CompileTimeConstant(..),
+            ) => {
+                let consequent_equals = consequent.equals(other);
+                let alternate_equals = alternate.equals(other);
+                if alternate.is_bottom() || !alternate_equals.as_bool_if_known().unwrap_or(true) {
+                    return condition.and(&consequent_equals);
+                }
+                if consequent.is_bottom() || !consequent_equals.as_bool_if_known().unwrap_or(true) {
+                    return condition.not().and(&alternate_equals);
+                }
+            }
             _ => {
                 // If self and other are the same expression and the expression could not result in NaN
                 // and the expression represents exactly one value, we can simplify this to true.
@@ -417,6 +460,12 @@ impl AbstractDomain {
         // x => true, is always true
         // false => x, is always true
         // x => x, is always true
+        // x && y => z, if x => z or y => z
+        if let Expression::And { left, right } = &self.expression {
+            if left.implies(other) || right.implies(other) {
+                return true;
+            }
+        }
         other.as_bool_if_known().unwrap_or(false)
             || !self.as_bool_if_known().unwrap_or(true)
             || self.equals(other).as_bool_if_known().unwrap_or(false)
@@ -430,6 +479,12 @@ impl AbstractDomain {
         if !other.as_bool_if_known().unwrap_or(true) || !self.as_bool_if_known().unwrap_or(true) {
             return true;
         };
+        // x && y => !z, if x => !z or y => !z
+        if let Expression::And { left, right } = &self.expression {
+            if left.implies_not(other) || right.implies_not(other) {
+                return true;
+            }
+        }
         // !x => !x
         if let Expression::Not { ref operand } = self.expression {
             return (**operand)
@@ -747,6 +802,18 @@ impl AbstractDomain {
         .into()
     }
 
+    /// Returns an element that is the string self followed by the string other.
+    pub fn string_concat(&self, other: &Self) -> Self {
+        if let (Some(s1), Some(s2)) = (self.as_constant_string(), other.as_constant_string()) {
+            return ConstantDomain::Str([s1, s2].concat()).into();
+        }
+        Expression::StringConcat {
+            left: box self.clone(),
+            right: box other.clone(),
+        }
+        .into()
+    }
+
     /// Returns an element that is "self - other".
     pub fn sub(&self, other: &Self) -> Self {
         if let (Expression::CompileTimeConstant(v1), Expression::CompileTimeConstant(v2)) =
@@ -867,6 +934,63 @@ impl AbstractDomain {
         }
     }
 
+    /// Returns the string that self is known to be, if it is a constant.
+    pub fn as_constant_string(&self) -> Option<&str> {
+        match &self.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Str(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Constructs an element of the String domain for expressions whose values are strings.
+    /// Values that are not known to be strings are treated as unknown strings.
+    pub fn get_as_string(&self) -> StringDomain {
+        match &self.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Str(s)) => {
+                StringDomain::from(s.as_str())
+            }
+            Expression::ConditionalExpression {
+                consequent,
+                alternate,
+                ..
+            } => consequent.get_as_string().join(&alternate.get_as_string()),
+            Expression::StringConcat { left, right } => {
+                left.get_as_string().concat(&right.get_as_string())
+            }
+            _ => StringDomain::top(),
+        }
+    }
+
+    /// Constructs an element of the Interval domain for simple expressions, assuming that the
+    /// unknown value of the variable at the given path lies within the given interval.
+    pub fn get_as_interval_assuming(
+        &self,
+        path: &Path,
+        interval: &IntervalDomain,
+    ) -> IntervalDomain {
+        match &self.expression {
+            Expression::Add { left, right } => left
+                .get_as_interval_assuming(path, interval)
+                .add(&right.get_as_interval_assuming(path, interval)),
+            Expression::ConditionalExpression {
+                consequent,
+                alternate,
+                ..
+            } => consequent
+                .get_as_interval_assuming(path, interval)
+                .widen(&alternate.get_as_interval_assuming(path, interval)),
+            Expression::Mul { left, right } => left
+                .get_as_interval_assuming(path, interval)
+                .mul(&right.get_as_interval_assuming(path, interval)),
+            Expression::Neg { operand } => operand.get_as_interval_assuming(path, interval).neg(),
+            Expression::Sub { left, right } => left
+                .get_as_interval_assuming(path, interval)
+                .sub(&right.get_as_interval_assuming(path, interval)),
+            Expression::Variable { path: var_path, .. } if **var_path == *path => interval.clone(),
+            _ => self.get_as_interval(),
+        }
+    }
+
     /// Recursively applies refine_paths to every sub expression of self.
     /// Replaces occurrences of Expression::Variable(path) with the value at that path
     /// in the given environment (if there is such a value).
@@ -973,6 +1097,9 @@ impl AbstractDomain {
             } => left
                 .refine_paths(environment)
                 .shr_overflows(&mut right.refine_paths(environment), result_type.clone()),
+            Expression::StringConcat { left, right } => left
+                .refine_paths(environment)
+                .string_concat(&right.refine_paths(environment)),
             Expression::Sub { left, right } => left
                 .refine_paths(environment)
                 .sub(&right.refine_paths(environment)),
@@ -983,6 +1110,19 @@ impl AbstractDomain {
             } => left
                 .refine_paths(environment)
                 .sub_overflows(&mut right.refine_paths(environment), result_type.clone()),
+            Expression::UninterpretedCall {
+                callee,
+                arguments: call_arguments,
+                result_type,
+            } => Expression::UninterpretedCall {
+                callee: box callee.refine_paths(environment),
+                arguments: call_arguments
+                    .iter()
+                    .map(|a| a.refine_paths(environment))
+                    .collect(),
+                result_type: result_type.clone(),
+            }
+            .into(),
             Expression::Variable { path, .. } => {
                 if let Some(val) = environment.value_at(path) {
                     val.domain.clone()
@@ -1097,6 +1237,9 @@ impl AbstractDomain {
             } => left
                 .refine_parameters(arguments)
                 .shr_overflows(&mut right.refine_parameters(arguments), result_type.clone()),
+            Expression::StringConcat { left, right } => left
+                .refine_parameters(arguments)
+                .string_concat(&right.refine_parameters(arguments)),
             Expression::Sub { left, right } => left
                 .refine_parameters(arguments)
                 .sub(&right.refine_parameters(arguments)),
@@ -1107,9 +1250,43 @@ impl AbstractDomain {
             } => left
                 .refine_parameters(arguments)
                 .sub_overflows(&mut right.refine_parameters(arguments), result_type.clone()),
-            Expression::Variable { path, .. } => match **path {
-                Path::LocalVariable { ordinal } if 0 < ordinal && ordinal <= arguments.len() => {
-                    arguments[ordinal - 1].domain.clone()
+            Expression::UninterpretedCall {
+                callee,
+                arguments: call_arguments,
+                result_type,
+            } => Expression::UninterpretedCall {
+                callee: box callee.refine_parameters(arguments),
+                arguments: call_arguments
+                    .iter()
+                    .map(|a| a.refine_parameters(arguments))
+                    .collect(),
+                result_type: result_type.clone(),
+            }
+            .into(),
+            Expression::Variable { path, var_type } => match path.get_root() {
+                Path::LocalVariable { ordinal } if 0 < *ordinal && *ordinal <= arguments.len() => {
+                    let argument = &arguments[*ordinal - 1].domain;
+                    if let Path::LocalVariable { .. } = **path {
+                        return argument.clone();
+                    }
+                    // The path selects a part of what the parameter refers to. If the argument is
+                    // a known reference, select the same part of the place it refers to. If the
+                    // argument is the value of a place, such as a struct passed by value, select
+                    // the same part of that place.
+                    let target = match &argument.expression {
+                        Expression::Reference(target) => Some(target),
+                        Expression::Variable { path: target, .. } => Some(&**target),
+                        _ => None,
+                    };
+                    if let Some(target) = target {
+                        let root = Path::LocalVariable { ordinal: *ordinal };
+                        return Expression::Variable {
+                            path: box path.replace_root(&root, target.clone()),
+                            var_type: var_type.clone(),
+                        }
+                        .into();
+                    }
+                    self.clone()
                 }
                 _ => self.clone(),
             },
@@ -1129,9 +1306,17 @@ impl AbstractDomain {
                 left,
                 right,
                 result_type,
-            } => left
-                .refine_with(path_condition)
-                .add_overflows(&mut right.refine_with(path_condition), result_type.clone()),
+            } => {
+                let mut refined_left = left.refine_with(path_condition);
+                let mut refined_right = right.refine_with(path_condition);
+                // x + 1 cannot overflow if x < y, since y is no more than the maximum value of the type.
+                if refined_right.as_constant_integer() == Some(1)
+                    && path_condition.has_strict_upper_bound(&refined_left)
+                {
+                    return false.into();
+                }
+                refined_left.add_overflows(&mut refined_right, result_type.clone())
+            }
             Expression::And { left, right } => {
                 if path_condition.implies(&**left) && path_condition.implies(&**right) {
                     true.into()
@@ -1261,6 +1446,9 @@ impl AbstractDomain {
             } => left
                 .refine_with(path_condition)
                 .shr_overflows(&mut right.refine_with(path_condition), result_type.clone()),
+            Expression::StringConcat { left, right } => left
+                .refine_with(path_condition)
+                .string_concat(&right.refine_with(path_condition)),
             Expression::Sub { left, right } => left
                 .refine_with(path_condition)
                 .sub(&right.refine_with(path_condition)),
@@ -1271,11 +1459,26 @@ impl AbstractDomain {
             } => left
                 .refine_with(path_condition)
                 .sub_overflows(&mut right.refine_with(path_condition), result_type.clone()),
+            Expression::UninterpretedCall {
+                callee,
+                arguments: call_arguments,
+                result_type,
+            } => Expression::UninterpretedCall {
+                callee: box callee.refine_with(path_condition),
+                arguments: call_arguments
+                    .iter()
+                    .map(|a| a.refine_with(path_condition))
+                    .collect(),
+                result_type: result_type.clone(),
+            }
+            .into(),
             Expression::Variable { .. } => {
                 if path_condition.implies(&self) {
                     true.into()
                 } else if path_condition.implies_not(&self) {
                     false.into()
+                } else if let Some(value) = path_condition.get_known_value_of(&self) {
+                    value
                 } else {
                     self.clone()
                 }
@@ -1283,6 +1486,468 @@ impl AbstractDomain {
         }
     }
 
+    /// If self is a condition that includes the conjunct "operand == value", returns value.
+    /// Only the left operand of an equality is replaced, so that refining both sides of a
+    /// comparison does not simply swap them.
+    fn get_known_value_of(&self, operand: &Self) -> Option<Self> {
+        match &self.expression {
+            Expression::And { left, right } => left
+                .get_known_value_of(operand)
+                .or_else(|| right.get_known_value_of(operand)),
+            Expression::Equals { left, right } if **left == *operand => Some((**right).clone()),
+            _ => None,
+        }
+    }
+
+    /// True if self is a condition that includes the conjunct "operand < x" for some x.
+    fn has_strict_upper_bound(&self, operand: &Self) -> bool {
+        match &self.expression {
+            Expression::And { left, right } => {
+                left.has_strict_upper_bound(operand) || right.has_strict_upper_bound(operand)
+            }
+            Expression::LessThan { left, .. } => **left == *operand,
+            Expression::GreaterThan { right, .. } => **right == *operand,
+            _ => false,
+        }
+    }
+
+    /// Returns an element that is equal to self, but is in a canonical form, so that elements that
+    /// are equal because operators such as + and && are commutative have the same expression. The
+    /// operands of commutative operators are ordered, with constants last, constants that are added
+    /// to the same expression are folded, and > and >= become < and <=. Elements that differ only
+    /// in these ways thus get the same value number (see Environment::number_values).
+    pub fn canonicalize(&self) -> Self {
+        match &self.expression {
+            Expression::Add { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                // (x + c1) + c2 = x + (c1 + c2)
+                if let (
+                    Expression::Add {
+                        left: operand,
+                        right: constant,
+                    },
+                    Expression::CompileTimeConstant(..),
+                ) = (&left.expression, &right.expression)
+                {
+                    if let Expression::CompileTimeConstant(..) = constant.expression {
+                        return operand.add(&constant.add(&right));
+                    }
+                }
+                left.add(&right)
+            }
+            Expression::And { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.and(&right)
+            }
+            Expression::BitAnd { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.bit_and(&right)
+            }
+            Expression::BitOr { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.bit_or(&right)
+            }
+            Expression::BitXor { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.bit_xor(&right)
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => Expression::ConditionalExpression {
+                condition: box condition.canonicalize(),
+                consequent: box consequent.canonicalize(),
+                alternate: box alternate.canonicalize(),
+            }
+            .into(),
+            Expression::Div { left, right } => left.canonicalize().div(&right.canonicalize()),
+            Expression::Equals { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.equals(&right)
+            }
+            Expression::GreaterOrEqual { left, right } => {
+                right.canonicalize().less_or_equal(&mut left.canonicalize())
+            }
+            Expression::GreaterThan { left, right } => {
+                right.canonicalize().less_than(&mut left.canonicalize())
+            }
+            Expression::LessOrEqual { left, right } => {
+                left.canonicalize().less_or_equal(&mut right.canonicalize())
+            }
+            Expression::LessThan { left, right } => {
+                left.canonicalize().less_than(&mut right.canonicalize())
+            }
+            Expression::Mul { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.mul(&right)
+            }
+            Expression::Ne { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.not_equals(&right)
+            }
+            Expression::Neg { operand } => operand.canonicalize().neg(),
+            Expression::Not { operand } => operand.canonicalize().not(),
+            Expression::Or { left, right } => {
+                let (left, right) = Self::order_operands(left.canonicalize(), right.canonicalize());
+                left.or(&right)
+            }
+            Expression::Rem { left, right } => left.canonicalize().rem(&right.canonicalize()),
+            Expression::StringConcat { left, right } => {
+                left.canonicalize().string_concat(&right.canonicalize())
+            }
+            Expression::Sub { left, right } => left.canonicalize().sub(&right.canonicalize()),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the operands of a commutative operator in canonical order: constants come last and
+    /// other operands are ordered by their expressions.
+    fn order_operands(left: Self, right: Self) -> (Self, Self) {
+        let is_constant = |operand: &Self| match operand.expression {
+            Expression::CompileTimeConstant(..) => true,
+            _ => false,
+        };
+        let swap = match (is_constant(&left), is_constant(&right)) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => right.expression < left.expression,
+        };
+        if swap {
+            (right, left)
+        } else {
+            (left, right)
+        }
+    }
+
+    /// Returns an element that is equal to self, but whose expression is simplified bottom up,
+    /// so that the solver and the summary store are not burdened with expressions that are much
+    /// bigger than they need to be. The operands of every operation are simplified first, after
+    /// which the constructor of the operation gets to fold constants and decide comparisons using
+    /// intervals. On top of that, operations with an integer identity element (x + 0, x * 1,
+    /// x | 0 and the like) are eliminated, equalities between integers whose intervals do not
+    /// overlap are decided, and nested conditional expressions with the same condition or the same
+    /// branch are flattened. The result is marked as simplified, so that the parts of an expression
+    /// that have been simplified before, such as the entry condition of a predecessor block that
+    /// is part of the entry condition of a block, are not simplified again.
+    pub fn simplify(&self) -> Self {
+        if self.is_simplified {
+            return self.clone();
+        }
+        let mut simplified = self.simplify_operation();
+        simplified.is_simplified = true;
+        simplified
+    }
+
+    /// Simplifies the operands of the operation of self and then the operation itself.
+    /// See simplify.
+    fn simplify_operation(&self) -> Self {
+        match &self.expression {
+            Expression::Add { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left.is_integer(0) {
+                    right
+                } else if right.is_integer(0) {
+                    left
+                } else {
+                    left.add(&right)
+                }
+            }
+            Expression::AddOverflows {
+                left,
+                right,
+                result_type,
+            } => left
+                .simplify()
+                .add_overflows(&mut right.simplify(), result_type.clone()),
+            Expression::And { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left == right {
+                    left
+                } else {
+                    left.and(&right)
+                }
+            }
+            Expression::BitAnd { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left.is_integer(0) {
+                    left
+                } else if right.is_integer(0) {
+                    right
+                } else {
+                    left.bit_and(&right)
+                }
+            }
+            Expression::BitOr { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left.is_integer(0) {
+                    right
+                } else if right.is_integer(0) {
+                    left
+                } else {
+                    left.bit_or(&right)
+                }
+            }
+            Expression::BitXor { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left.is_integer(0) {
+                    right
+                } else if right.is_integer(0) {
+                    left
+                } else {
+                    left.bit_xor(&right)
+                }
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => Self::simplify_conditional_expression(
+                condition.simplify(),
+                consequent.simplify(),
+                alternate.simplify(),
+            ),
+            Expression::Div { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if right.is_integer(1) {
+                    left
+                } else {
+                    left.div(&right)
+                }
+            }
+            Expression::Equals { left, right } => {
+                let (mut left, mut right) = (left.simplify(), right.simplify());
+                if left.has_disjoint_interval(&mut right) {
+                    false.into()
+                } else {
+                    left.equals(&right)
+                }
+            }
+            Expression::GreaterOrEqual { left, right } => {
+                left.simplify().greater_or_equal(&mut right.simplify())
+            }
+            Expression::GreaterThan { left, right } => {
+                left.simplify().greater_than(&mut right.simplify())
+            }
+            Expression::LessOrEqual { left, right } => {
+                left.simplify().less_or_equal(&mut right.simplify())
+            }
+            Expression::LessThan { left, right } => {
+                left.simplify().less_than(&mut right.simplify())
+            }
+            Expression::Mul { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left.is_integer(1) || right.is_integer(0) {
+                    right
+                } else if right.is_integer(1) || left.is_integer(0) {
+                    left
+                } else {
+                    left.mul(&right)
+                }
+            }
+            Expression::MulOverflows {
+                left,
+                right,
+                result_type,
+            } => left
+                .simplify()
+                .mul_overflows(&mut right.simplify(), result_type.clone()),
+            Expression::Ne { left, right } => {
+                let (mut left, mut right) = (left.simplify(), right.simplify());
+                if left.has_disjoint_interval(&mut right) {
+                    true.into()
+                } else {
+                    left.not_equals(&right)
+                }
+            }
+            Expression::Neg { operand } => operand.simplify().neg(),
+            Expression::Not { operand } => operand.simplify().not(),
+            Expression::Or { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if left == right {
+                    left
+                } else {
+                    left.or(&right)
+                }
+            }
+            Expression::Offset { left, right } => left.simplify().offset(&right.simplify()),
+            Expression::Rem { left, right } => left.simplify().rem(&right.simplify()),
+            Expression::Shl { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if right.is_integer(0) {
+                    left
+                } else {
+                    left.shl(&right)
+                }
+            }
+            Expression::ShlOverflows {
+                left,
+                right,
+                result_type,
+            } => left
+                .simplify()
+                .shl_overflows(&mut right.simplify(), result_type.clone()),
+            Expression::Shr {
+                left,
+                right,
+                result_type,
+            } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if right.is_integer(0) {
+                    left
+                } else {
+                    left.shr(&right, result_type.clone())
+                }
+            }
+            Expression::ShrOverflows {
+                left,
+                right,
+                result_type,
+            } => left
+                .simplify()
+                .shr_overflows(&mut right.simplify(), result_type.clone()),
+            Expression::StringConcat { left, right } => {
+                left.simplify().string_concat(&right.simplify())
+            }
+            Expression::Sub { left, right } => {
+                let (left, right) = (left.simplify(), right.simplify());
+                if right.is_integer(0) {
+                    left
+                } else {
+                    left.sub(&right)
+                }
+            }
+            Expression::SubOverflows {
+                left,
+                right,
+                result_type,
+            } => left
+                .simplify()
+                .sub_overflows(&mut right.simplify(), result_type.clone()),
+            Expression::UninterpretedCall {
+                callee,
+                arguments,
+                result_type,
+            } => Expression::UninterpretedCall {
+                callee: box callee.simplify(),
+                arguments: arguments.iter().map(Self::simplify).collect(),
+                result_type: result_type.clone(),
+            }
+            .into(),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the simplification of "condition ? consequent : alternate", given simplified
+    /// operands. A conditional expression that is nested in a branch and that has the same
+    /// condition reduces to the corresponding branch of the nested expression. One that has the
+    /// same value in one of its branches as the enclosing expression is merged with it by
+    /// combining the conditions. Conditional expressions that select between true and false are
+    /// just their (negated) conditions.
+    fn simplify_conditional_expression(
+        condition: Self,
+        mut consequent: Self,
+        mut alternate: Self,
+    ) -> Self {
+        // c ? (c ? x : y) : z is just c ? x : z
+        if let Expression::ConditionalExpression {
+            condition: c2,
+            consequent: x,
+            ..
+        } = &consequent.expression
+        {
+            if **c2 == condition {
+                consequent = (**x).clone();
+            }
+        }
+        // c ? x : (c ? y : z) is just c ? x : z
+        if let Expression::ConditionalExpression {
+            condition: c2,
+            alternate: z,
+            ..
+        } = &alternate.expression
+        {
+            if **c2 == condition {
+                alternate = (**z).clone();
+            }
+        }
+        // c1 ? x : (c2 ? x : y) is (c1 || c2) ? x : y
+        if let Expression::ConditionalExpression {
+            condition: c2,
+            consequent: x,
+            alternate: y,
+        } = &alternate.expression
+        {
+            if **x == consequent {
+                return consequent.join(y, &condition.or(c2));
+            }
+        }
+        // c1 ? (c2 ? x : y) : y is (c1 && c2) ? x : y
+        if let Expression::ConditionalExpression {
+            condition: c2,
+            consequent: x,
+            alternate: y,
+        } = &consequent.expression
+        {
+            if **y == alternate {
+                return x.join(y, &condition.and(c2));
+            }
+        }
+        match (consequent.as_bool_if_known(), alternate.as_bool_if_known()) {
+            // c ? true : false is just c
+            (Some(true), Some(false)) => condition,
+            // c ? false : true is just !c
+            (Some(false), Some(true)) => condition.not(),
+            _ => consequent.join(&alternate, &condition),
+        }
+    }
+
+    /// True if self is the given integer constant.
+    fn is_integer(&self, value: i128) -> bool {
+        self.as_constant_integer() == Some(value)
+    }
+
+    /// True if the intervals of self and other are known and do not overlap, so that self and
+    /// other cannot be equal.
+    fn has_disjoint_interval(&mut self, other: &mut Self) -> bool {
+        let (interval, other_interval) = (self.get_cached_interval(), other.get_cached_interval());
+        interval.less_than(&other_interval).unwrap_or(false)
+            || other_interval.less_than(&interval).unwrap_or(false)
+    }
+
+    /// If self is known to be base plus some constant, returns the constant.
+    pub fn offset_from(&self, base: &Self) -> Option<i128> {
+        if self.expression == base.expression {
+            return Some(0);
+        }
+        match (&self.expression, &base.expression) {
+            (Expression::CompileTimeConstant(..), Expression::CompileTimeConstant(..)) => self
+                .as_constant_integer()?
+                .checked_sub(base.as_constant_integer()?),
+            (Expression::Add { left, right }, _) => {
+                let constant = right.as_constant_integer()?;
+                left.offset_from(base)?.checked_add(constant)
+            }
+            (_, Expression::Add { left, right }) => {
+                let constant = right.as_constant_integer()?;
+                self.offset_from(left)?.checked_sub(constant)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of self if it is a compile time constant integer.
+    pub fn as_constant_integer(&self) -> Option<i128> {
+        if let Expression::CompileTimeConstant(..) = &self.expression {
+            let interval = self.get_as_interval();
+            if interval.lower_bound().is_some() && interval.lower_bound() == interval.upper_bound()
+            {
+                return interval.lower_bound();
+            }
+        }
+        None
+    }
+
     /// Returns a domain whose corresponding set of concrete values include all of the values
     /// corresponding to self and other.The set of values may be less precise (more inclusive) than
     /// the set returned by join. The chief requirement is that a small number of widen calls
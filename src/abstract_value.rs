@@ -9,6 +9,7 @@ use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType};
 
 use rustc::hir::def_id::DefId;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::{Debug, Formatter, Result};
 use std::hash::{Hash, Hasher};
 use syntax_pos::Span;
@@ -478,6 +479,25 @@ impl AbstractValue {
         }
     }
 
+    /// Returns a value that is equal to this value, but is in a canonical form.
+    /// See AbstractDomain::canonicalize.
+    pub fn canonicalize(&self) -> AbstractValue {
+        AbstractValue {
+            provenance: self.provenance.clone(),
+            domain: self.domain.canonicalize(),
+        }
+    }
+
+    /// Returns a value that is equal to this value, but whose expression is simplified algebraically.
+    /// This is done once for the entry condition of each basic block, for each condition that has
+    /// to be proven and for the values that are stored in a summary. See AbstractDomain::simplify.
+    pub fn simplify(&self) -> AbstractValue {
+        AbstractValue {
+            provenance: self.provenance.clone(),
+            domain: self.domain.simplify(),
+        }
+    }
+
     /// Returns a value that is simplified (refined) by replacing values with Variable(path) expressions
     /// with the value at that path (if there is one). If no refinement is possible
     /// the result is simply a clone of this value. This refinement only makes sense
@@ -513,6 +533,22 @@ impl AbstractValue {
         }
     }
 
+    /// Returns a copy of this value in which the ordinal of every heap block is replaced by the
+    /// ordinal that the given function returns for it.
+    pub fn renumber_heap_addresses(
+        &self,
+        renumber: &mut dyn FnMut(usize) -> usize,
+    ) -> AbstractValue {
+        AbstractValue {
+            provenance: self.provenance.clone(),
+            domain: self
+                .domain
+                .expression
+                .renumber_heap_addresses(renumber)
+                .into(),
+        }
+    }
+
     /// Returns an abstract value whose corresponding set of concrete values include all of the
     /// values resulting from applying "<<" to each element of the cross product of the concrete
     /// values or self and other.
@@ -584,6 +620,23 @@ impl AbstractValue {
         }
     }
 
+    /// Returns an abstract value whose corresponding set of concrete values include all of the
+    /// strings resulting from appending each string of other to each string of self.
+    pub fn string_concat(
+        &self,
+        other: &AbstractValue,
+        expression_provenance: Option<Span>,
+    ) -> AbstractValue {
+        AbstractValue {
+            provenance: Self::binary_provenance(
+                expression_provenance,
+                &self.provenance,
+                &other.provenance,
+            ),
+            domain: self.domain.string_concat(&other.domain),
+        }
+    }
+
     /// Returns an abstract value whose corresponding set of concrete values include all of the
     /// values resulting from applying "-" to each element of the cross product of the concrete
     /// values or self and other.
@@ -697,6 +750,22 @@ pub enum Path {
 }
 
 impl Path {
+    /// Returns the path that is qualified by all the selectors of this path.
+    pub fn get_root(&self) -> &Path {
+        match self {
+            Path::QualifiedPath { qualifier, .. } => qualifier.get_root(),
+            _ => self,
+        }
+    }
+
+    /// True if the root of this path is a static variable. This includes the static variable itself.
+    pub fn is_rooted_by_static(&self) -> bool {
+        match self.get_root() {
+            Path::StaticVariable { .. } => true,
+            _ => false,
+        }
+    }
+
     /// True if path qualifies root, or another qualified path rooted by root.
     pub fn is_rooted_by(&self, root: &Path) -> bool {
         match self {
@@ -715,8 +784,41 @@ impl Path {
         }
     }
 
+    /// Returns a copy of this path in which the ordinal of every heap block, including those in
+    /// the values of index selectors, is replaced by the ordinal that the given function returns
+    /// for it.
+    pub fn renumber_heap_addresses(&self, renumber: &mut dyn FnMut(usize) -> usize) -> Path {
+        match self {
+            Path::AbstractHeapAddress { ordinal } => Path::AbstractHeapAddress {
+                ordinal: renumber(*ordinal),
+            },
+            Path::QualifiedPath {
+                length,
+                qualifier,
+                selector,
+            } => {
+                let selector = match &**selector {
+                    PathSelector::Index(index) => {
+                        PathSelector::Index(box index.renumber_heap_addresses(renumber))
+                    }
+                    _ => (**selector).clone(),
+                };
+                Path::QualifiedPath {
+                    length: *length,
+                    qualifier: box qualifier.renumber_heap_addresses(renumber),
+                    selector: box selector,
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+
     /// Returns a copy path with the root replaced by new_root.
+    /// The root need not be a root of the path proper, it can be any path that qualifies it.
     pub fn replace_root(&self, old_root: &Path, new_root: Path) -> Path {
+        if *self == *old_root {
+            return new_root;
+        }
         match self {
             Path::QualifiedPath {
                 qualifier,
@@ -754,6 +856,12 @@ pub enum PathSelector {
     /// Select the collection element with the index specified by the abstract value.
     Index(Box<AbstractValue>),
 
+    /// Select a summary of the elements of an array or slice that are not tracked individually,
+    /// either because their indices are too large (see k_limits::MAX_TRACKED_ARRAY_ELEMENTS) or
+    /// because they may have been written to at an index that is not known. If there is a value
+    /// at this path, it includes the values of all elements that have no path of their own.
+    SmashedElement,
+
     /// These indices are generated by slice patterns. Easiest to explain
     /// by example:
     ///
@@ -780,4 +888,64 @@ pub enum PathSelector {
     /// "Downcast" to a variant of an ADT. Currently, MIR only introduces
     /// this for ADTs with more than one variant. The value is the ordinal of the variant.
     Downcast(usize),
+
+    /// The number of elements that an iterator over a collection has produced so far. Like
+    /// ArrayLength, this does not correspond to a field of the iterator. The length of the
+    /// collection is tracked with the ArrayLength selector of the iterator.
+    IterationCount,
+
+    /// Select a property of the value, such as having been validated in some way. This does not
+    /// correspond to a place in memory. The value at a tag path is a condition that is true if
+    /// the value has the property. Since tags are rooted by the values they describe, they are
+    /// copied and moved along with them.
+    Tag(Tag),
+}
+
+/// Properties of values that are established by calls to functions such as validators and that
+/// can be required by calls to other functions.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Tag {
+    /// The value is an endpoint of a channel. The value of the tag is the abstract heap address
+    /// that identifies the channel, which is shared by its sender and its receiver.
+    Channel,
+    /// The value is (part of) the value of an environment variable. The value of the tag is the
+    /// name of the variable.
+    EnvironmentVariable,
+    /// The value comes from outside the program, for example from the command line or the
+    /// environment, so nothing is known about it.
+    Untrusted,
+    /// The value is a channel, identified by an abstract heap address, whose receiver has been
+    /// dropped, so that sending on it fails.
+    ReceiverDropped,
+    /// The value has a property that is established by a sanitizer or validator that is declared
+    /// in the options. The property is identified by the hash of its name (see Tag::sanitized),
+    /// so that the tag can be stored in summaries.
+    Sanitized(u64),
+    /// The bytes of a byte slice are valid UTF-8.
+    Utf8Valid,
+    /// The value is a shared pointer, an Rc or an Arc. The value of the tag is the abstract heap
+    /// address of the block that holds the shared value, which is the same for all clones of the
+    /// pointer.
+    SharedBlock,
+    /// The value is a heap block that is shared by Rc or Arc pointers. The value of the tag is the
+    /// number of pointers that share the block, as far as it is known.
+    StrongCount,
+    /// The value is obtained by iterating over a HashMap or HashSet, so it depends on the order
+    /// in which they produce their elements, which differs from run to run. The provenance of the
+    /// tag is the location of the iteration.
+    UnorderedIteration,
+    /// The value depends on a source of nondeterminism other than the iteration order of a hash
+    /// map or set, such as a random number generator, a clock or the address of a value. The
+    /// provenance of the tag is the location where the value was obtained from the source.
+    Nondeterministic,
+}
+
+impl Tag {
+    /// Returns the tag for the property with the given name, as declared by the sanitizers and
+    /// sinks in the options.
+    pub fn sanitized(name: &str) -> Tag {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        Tag::Sanitized(hasher.finish())
+    }
 }
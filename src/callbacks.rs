@@ -4,23 +4,76 @@
 // LICENSE file in the root directory of this source tree.
 #![allow(clippy::borrowed_box)]
 
+use crate::certificate::{self, CertifiedFunction};
 use crate::constant_domain::ConstantValueCache;
+use crate::contracts;
+use crate::cvc5_solver::Cvc5Solver;
+use crate::expression::Expression;
 use crate::k_limits;
-use crate::smt_solver::SolverStub;
+use crate::known_names::KnownNamesCache;
+use crate::metrics::{self, CrateMetrics};
+use crate::options::{DiagnosticFormat, Options, SmtBackend};
+use crate::remote_cache;
+use crate::sarif;
+use crate::security;
+use crate::smt_lib::{self, AssumedObligation};
+use crate::smt_solver::{SmtSolver, SolverStub};
+use crate::solver_process::IsolatedSolver;
 use crate::summaries;
-use crate::visitors::{MirVisitor, MirVisitorCrateContext};
+use crate::utils;
+use crate::visitors::{Confidence, MirVisitor, MirVisitorCrateContext};
 
-use rustc::hir::def_id::DefId;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
 use rustc::session::config::{self, ErrorOutputType, Input};
 use rustc::session::Session;
+use rustc::ty::TyCtxt;
 use rustc_codegen_utils::codegen_backend::CodegenBackend;
 use rustc_driver::{driver, Compilation, CompilerCalls, RustcDefaultCalls};
 use rustc_metadata::cstore::CStore;
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::iter::FromIterator;
-use std::path::PathBuf;
-use syntax::errors::{Diagnostic, DiagnosticBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use syntax::errors::{Diagnostic, DiagnosticBuilder, DiagnosticId};
 use syntax::{ast, errors};
+use syntax_pos::{FileName, Span};
+
+/// The code of the diagnostics that report how the behavior of a function differs from its behavior
+/// in the baseline summary store. These are not problems, so they have no confidence.
+const BEHAVIOR_CHANGE_CODE: &str = "behavior_change";
+
+/// The state of the analysis that does not depend on the crate being analyzed, so that it can be
+/// shared by all of the crates that are analyzed in the same process, such as the test cases of
+/// the integration tests, rather than being rebuilt for each of them. Everything that depends on
+/// the crate, such as the summaries and the constants that refer to its definitions, is kept
+/// per crate.
+#[derive(Clone)]
+pub struct SharedState {
+    /// The options read from the environment and the configuration file.
+    options: Arc<Options>,
+    /// The models of well known functions, along with the results of looking them up.
+    known_names_cache: Arc<KnownNamesCache>,
+}
+
+impl SharedState {
+    /// Reads the options and the known names tables from the environment.
+    pub fn from_environment() -> SharedState {
+        SharedState {
+            options: Arc::new(Options::from_environment()),
+            known_names_cache: Arc::new(KnownNamesCache::create_cache_from_environment()),
+        }
+    }
+
+    /// Makes the analysis use the given options, rather than the ones read from the environment,
+    /// while keeping the known names tables.
+    pub fn with_options(mut self, options: Options) -> SharedState {
+        self.options = Arc::new(options);
+        self
+    }
+}
 
 /// Private state used to implement the callbacks.
 pub struct MiraiCallbacks {
@@ -29,12 +82,16 @@ pub struct MiraiCallbacks {
     consume_buffered_diagnostics: Box<dyn Fn(&Vec<Diagnostic>) -> ()>,
     /// Use these to just defer to the Rust compiler's implementations.
     default_calls: Box<RustcDefaultCalls>,
+    /// The format in which diagnostics are reported, in addition to the console.
+    diagnostic_format: DiagnosticFormat,
     /// Called when static analysis reports a diagnostic message.
     /// By default, this just emits the message. When overridden it can
     /// intercept and buffer the diagnostics, which is used by the test harness.
     emit_diagnostic: fn(&mut DiagnosticBuilder<'_>, &mut Vec<Diagnostic>) -> (),
     /// A path to the directory where analysis output, such as the summary cache, should be stored.
     output_directory: PathBuf,
+    /// The state shared with the other crates analyzed in the same process, if any.
+    shared_state: Option<SharedState>,
     /// True if this run is done via cargo test
     test_run: bool,
 }
@@ -45,8 +102,10 @@ impl MiraiCallbacks {
         MiraiCallbacks {
             consume_buffered_diagnostics: box |_bd: &Vec<Diagnostic>| {},
             default_calls: box RustcDefaultCalls,
+            diagnostic_format: DiagnosticFormat::Human,
             emit_diagnostic: |db: &mut DiagnosticBuilder<'_>, _buf: &mut Vec<Diagnostic>| db.emit(),
             output_directory: PathBuf::default(),
+            shared_state: None,
             test_run: false,
         }
     }
@@ -58,11 +117,33 @@ impl MiraiCallbacks {
         MiraiCallbacks {
             consume_buffered_diagnostics,
             default_calls: box RustcDefaultCalls,
+            diagnostic_format: DiagnosticFormat::Human,
             emit_diagnostic,
             output_directory: PathBuf::default(),
+            shared_state: None,
             test_run: true,
         }
     }
+
+    /// Makes the analysis use the given state, rather than building its own from the environment.
+    pub fn with_shared_state(mut self, shared_state: SharedState) -> MiraiCallbacks {
+        self.shared_state = Some(shared_state);
+        self
+    }
+
+    /// Makes the analysis report its diagnostics in the given format, in addition to the console.
+    /// Unless the diagnostics are already buffered by the test harness, they are then also
+    /// buffered, so that they can be written out once the analysis is complete.
+    pub fn with_diagnostic_format(mut self, diagnostic_format: DiagnosticFormat) -> MiraiCallbacks {
+        self.diagnostic_format = diagnostic_format;
+        if diagnostic_format != DiagnosticFormat::Human && !self.test_run {
+            self.emit_diagnostic = |db: &mut DiagnosticBuilder<'_>, buf: &mut Vec<Diagnostic>| {
+                db.clone().buffer(buf);
+                db.emit()
+            };
+        }
+        self
+    }
 }
 impl Default for MiraiCallbacks {
     fn default() -> Self {
@@ -136,13 +217,16 @@ impl<'a> CompilerCalls<'a> for MiraiCallbacks {
         _matches: &::getopts::Matches,
     ) -> driver::CompileController<'a> {
         let test_run = self.test_run;
+        let diagnostic_format = self.diagnostic_format;
         let mut controller = driver::CompileController::basic();
         controller.after_analysis.callback = Box::new(move |state| {
             after_analysis(
                 state,
                 &self.consume_buffered_diagnostics,
                 self.emit_diagnostic,
+                diagnostic_format,
                 &mut self.output_directory.clone(),
+                self.shared_state.clone(),
             )
         });
         if test_run {
@@ -162,24 +246,88 @@ fn after_analysis(
     state: &mut driver::CompileState<'_, '_>,
     consume_buffered_diagnostics: &Box<dyn Fn(&Vec<Diagnostic>) -> ()>,
     emit_diagnostic: fn(&mut DiagnosticBuilder<'_>, &mut Vec<Diagnostic>) -> (),
+    diagnostic_format: DiagnosticFormat,
     output_directory: &mut PathBuf,
+    shared_state: Option<SharedState>,
 ) {
     let session = state.session;
     let tcx = state.tcx.unwrap();
+    // The summary stores of all of the crates that are compiled into the same output directory
+    // are kept together, so that the summaries of a crate are available to the crates that depend on it.
     output_directory.set_file_name(".summary_store");
-    output_directory.set_extension("sled");
-    let summary_store_path = String::from(output_directory.to_str().unwrap());
-    info!("storing summaries at {}", summary_store_path);
-    let mut persistent_summary_cache =
-        summaries::PersistentSummaryCache::new(&tcx, summary_store_path);
-    let mut constant_value_cache = ConstantValueCache::default();
-    let mut defs_to_analyze: HashSet<DefId> = HashSet::from_iter(tcx.body_owners());
+    info!("storing summaries in {}", output_directory.display());
+    let shared_state = shared_state.unwrap_or_else(SharedState::from_environment);
+    let mut persistent_summary_cache = summaries::PersistentSummaryCache::new(
+        &tcx,
+        output_directory.clone(),
+        shared_state.known_names_cache.clone(),
+    );
+    let mut constant_value_cache =
+        ConstantValueCache::with_known_names(shared_state.known_names_cache.clone());
+    let options: &Options = &shared_state.options;
+    // The summaries of a crate from a registry may already have been computed on another machine
+    // and stored in the remote summary cache, in which case they are the starting point of the
+    // analysis, just like the summaries stored by a previous run on this machine.
+    let remote_key = options.remote_summary_cache.as_ref().and_then(|_| {
+        remote_cache::get_remote_key(
+            &tcx.crate_name(LOCAL_CRATE).to_string(),
+            &get_configuration(session),
+        )
+    });
+    let mut fetched_remote_summaries = false;
+    if let (Some(url), Some(key)) = (&options.remote_summary_cache, &remote_key) {
+        if !persistent_summary_cache.has_stored_entries() {
+            if let Some(blob) = remote_cache::download(url, key) {
+                fetched_remote_summaries = persistent_summary_cache.import_database(&blob);
+            }
+        }
+    }
+    persistent_summary_cache.record_summary_keys();
+    let mut smt_solver = create_smt_solver(options);
+    // The code that the test harness generates when the crate is compiled with --test only calls
+    // into libtest, so it is not analyzed. The #[test] functions that it calls are roots instead.
+    let test_functions = utils::get_test_functions(&tcx);
+    let mut defs_to_analyze: HashSet<DefId> = if options.smoke {
+        get_smoke_defs(&tcx, &test_functions)
+    } else {
+        tcx.body_owners()
+            .filter(|def_id| !utils::is_test_harness_code(*def_id, &tcx))
+            .collect()
+    };
     let mut defs_to_reanalyze: HashSet<DefId> = HashSet::new();
     let mut defs_to_check: HashSet<DefId> = HashSet::new();
+    let mut escalated_defs: HashSet<DefId> = HashSet::new();
     let mut diagnostics_for: HashMap<DefId, Vec<Diagnostic>> = HashMap::new();
+    let mut assumptions_for: HashMap<DefId, Vec<AssumedObligation>> = HashMap::new();
+    // The derived methods that are analyzed on behalf of all of the derived methods with the same
+    // shape, keyed by shape. See utils::get_derived_shape.
+    let mut derived_templates: HashMap<String, DefId> = HashMap::new();
+    // The summaries stored by a previous run are the starting point of the analysis. They are used
+    // for calls to functions that have not yet been analyzed in this run, but are only trusted once
+    // analyzing the function has reproduced them.
+    let warm_started_defs: HashSet<DefId> = tcx
+        .body_owners()
+        .filter(|def_id| persistent_summary_cache.has_persistent_summary_for(*def_id))
+        .collect();
+    info!(
+        "starting from {} summaries computed by a previous run",
+        warm_started_defs.len()
+    );
+    if options.reuse_dependency_summaries && !warm_started_defs.is_empty() {
+        // The crate is a dependency of the workspace being analyzed and it has been analyzed
+        // before, so the crates of the workspace can use the summaries that are already stored.
+        info!("using the stored summaries of this dependency");
+        consume_buffered_diagnostics(&vec![]);
+        return;
+    }
+    let max_iterations = options
+        .summary_recursion_depth
+        .unwrap_or(k_limits::MAX_OUTER_FIXPOINT_ITERATIONS);
     let mut not_done = true;
+    let mut stopped_at_error = false;
     let mut iteration_count = 0;
-    while not_done && iteration_count < k_limits::MAX_OUTER_FIXPOINT_ITERATIONS {
+    while not_done && !stopped_at_error && iteration_count < max_iterations {
+        not_done = false;
         for def_id in tcx.body_owners() {
             let analyze_it = defs_to_analyze.contains(&def_id);
             let check_it = !analyze_it && defs_to_check.contains(&def_id);
@@ -187,6 +335,23 @@ fn after_analysis(
                 continue;
             }
             not_done = true;
+            let derived_template = utils::get_derived_shape(def_id, &tcx)
+                .map(|shape| *derived_templates.entry(shape).or_insert(def_id))
+                .filter(|template_def_id| *template_def_id != def_id);
+            if iteration_count == 0
+                && warm_started_defs.contains(&def_id)
+                && persistent_summary_cache.is_unchanged_since_last_run(def_id)
+            {
+                // The summary computed by a previous run was computed from the same body and the
+                // same callee summaries, and there was nothing to report, so analyzing the body
+                // again would not change anything. If a callee summary changes in this run,
+                // def_id becomes a dependent that is reanalyzed.
+                info!(
+                    "skipping({:?})",
+                    persistent_summary_cache.get_summary_key_for(def_id)
+                );
+                continue;
+            }
             {
                 let name = persistent_summary_cache.get_summary_key_for(def_id);
                 if check_it {
@@ -199,24 +364,38 @@ fn after_analysis(
             }
             // By this time all analyses have been carried out, so it should be safe to borrow this now.
             let mut buffered_diagnostics: Vec<Diagnostic> = vec![];
-            let old_summary_if_changed = {
+            let mut assumed_obligations: Vec<AssumedObligation> = vec![];
+            let old_summary_if_changed = if let Some(template_def_id) = derived_template {
+                copy_template_summary(def_id, template_def_id, &mut persistent_summary_cache)
+            } else {
                 let mir = tcx.optimized_mir(def_id);
-                // todo: #3 provide a helper that returns the solver as specified by a compiler switch.
-                let mut smt_solver = SolverStub::default();
-                let mut mir_visitor = MirVisitor::new(MirVisitorCrateContext {
+                // The diagnostics of the function are held back, since they are only final once
+                // its summary has reached a fixed point and they are reported once per crate.
+                MirVisitor::new(MirVisitorCrateContext {
                     buffered_diagnostics: &mut buffered_diagnostics,
-                    emit_diagnostic,
+                    emit_diagnostic: hold_back_diagnostic,
                     session,
                     tcx,
                     def_id,
                     mir,
+                    options,
                     summary_cache: &mut persistent_summary_cache,
                     constant_value_cache: &mut constant_value_cache,
-                    smt_solver: &mut smt_solver,
-                });
-                mir_visitor.visit_body()
+                    smt_solver: smt_solver.as_mut(),
+                    escalated_defs: &mut escalated_defs,
+                    assumption_log: &mut assumed_obligations,
+                    test_functions: &test_functions,
+                })
+                .visit_body()
             };
+            if options.fail_fast && buffered_diagnostics.iter().any(Diagnostic::is_error) {
+                info!("stopping at the first error");
+                diagnostics_for.insert(def_id, buffered_diagnostics);
+                stopped_at_error = true;
+                break;
+            }
             diagnostics_for.insert(def_id, buffered_diagnostics);
+            assumptions_for.insert(def_id, assumed_obligations);
             if let Some(old_summary) = old_summary_if_changed {
                 // Bodies should not get checked before their summaries have reached a fixed point.
                 if check_it {
@@ -230,6 +409,12 @@ fn after_analysis(
                 for dep_id in persistent_summary_cache.get_dependents(&def_id).iter() {
                     defs_to_reanalyze.insert(dep_id.clone());
                 }
+            } else if check_it || (iteration_count == 0 && warm_started_defs.contains(&def_id)) {
+                // Either def_id has been checked after its summary reached a fixed point, or its
+                // summary confirms the one computed by a previous run. The diagnostics found just now
+                // are final, unless a body that def_id depends on changes, which causes def_id to be
+                // reanalyzed.
+                defs_to_check.remove(&def_id);
             } else {
                 // Provided that no other body that def_id depends on has changed in this round,
                 // the summary for def_id should now be at a fixed point.
@@ -241,10 +426,567 @@ fn after_analysis(
         iteration_count += 1;
         info!("outer fixed point iterations {}", iteration_count);
     }
-    let mut all_diagnostics: Vec<Diagnostic> = vec![];
-    for (_, mut diagnostics) in diagnostics_for.drain() {
-        all_diagnostics.append(&mut diagnostics);
+    if stopped_at_error {
+        // The summaries have not reached a fixed point, so nothing is derived from them.
+        let all_diagnostics =
+            deduplicate_diagnostics(&mut diagnostics_for, &mut persistent_summary_cache);
+        let mut emitted_diagnostics: Vec<Diagnostic> = vec![];
+        report_diagnostics(
+            &all_diagnostics,
+            session,
+            emit_diagnostic,
+            &mut emitted_diagnostics,
+        );
+        export_diagnostics(
+            &all_diagnostics,
+            options,
+            diagnostic_format,
+            output_directory,
+            &tcx,
+        );
+        consume_buffered_diagnostics(&emitted_diagnostics);
+        return;
+    }
+    // The functions that still have to be reanalyzed did not reach a fixed point because the
+    // iteration limit cut the analysis short. Unless this happened because of some (maybe very
+    // long) chain of calls, they are part of call cycles, which are reported so that users know
+    // why the summaries of these functions are imprecise and which limit to raise.
+    let mut cycle_diagnostics: Vec<Diagnostic> = vec![];
+    let mut reported_defs: HashSet<DefId> = HashSet::new();
+    let mut unconverged_defs: Vec<DefId> = defs_to_analyze.into_iter().collect();
+    unconverged_defs.sort_by_key(|def_id| utils::summary_key_str(&tcx, *def_id));
+    for def_id in unconverged_defs {
+        if reported_defs.contains(&def_id) {
+            continue;
+        }
+        if let Some(cycle) = find_call_cycle(def_id, &mut persistent_summary_cache) {
+            let names: Vec<String> = cycle
+                .iter()
+                .map(|def_id| utils::summary_key_str(&tcx, *def_id))
+                .collect();
+            let message = format!(
+                "the summaries of the recursive calls {} did not converge within {} iterations, so they are imprecise",
+                names.join(" -> "),
+                max_iterations
+            );
+            let mut warning = session.struct_span_warn(tcx.def_span(def_id), message.as_str());
+            warning.code(Confidence::LimitedPrecision.diagnostic_code());
+            emit_diagnostic(&mut warning, &mut cycle_diagnostics);
+            reported_defs.extend(cycle);
+        }
+    }
+    if options.report_equivalent_functions {
+        report_equivalent_functions(
+            session,
+            &tcx,
+            &mut persistent_summary_cache,
+            emit_diagnostic,
+            &mut cycle_diagnostics,
+        );
+    }
+    if let Some(baseline_summary_store) = &options.baseline_summary_store {
+        report_behavior_changes(
+            session,
+            &tcx,
+            &mut persistent_summary_cache,
+            baseline_summary_store,
+            emit_diagnostic,
+            &mut cycle_diagnostics,
+        );
+    }
+    if let Some(certificate_file) = &options.unsafe_free_certificate_file {
+        certify_unsafe_free(
+            session,
+            &tcx,
+            &mut persistent_summary_cache,
+            certificate_file,
+            options,
+            emit_diagnostic,
+            &mut cycle_diagnostics,
+        );
+    }
+    if let Some(contracts_file) = &options.contracts_file {
+        let public_functions: Vec<DefId> = tcx
+            .body_owners()
+            .filter(|def_id| utils::is_public(*def_id, &tcx))
+            .collect();
+        let contracts = public_functions
+            .into_iter()
+            .filter_map(|def_id| {
+                let summary = persistent_summary_cache.get_summary_for(def_id, None);
+                contracts::extract_contract(&tcx, def_id, summary)
+            })
+            .collect();
+        contracts::write_contracts(contracts_file, contracts);
+    }
+    if let Some(inferred_contracts_file) = &options.inferred_contracts_file {
+        let public_functions: Vec<DefId> = tcx
+            .body_owners()
+            .filter(|def_id| utils::is_public(*def_id, &tcx))
+            .collect();
+        let functions = public_functions
+            .into_iter()
+            .filter_map(|def_id| {
+                let summary = persistent_summary_cache.get_summary_for(def_id, None);
+                contracts::extract_inferred_conditions(&tcx, def_id, summary)
+            })
+            .collect();
+        contracts::write_inferred_conditions(inferred_contracts_file, functions);
+    }
+    if let Some(summary_export_directory) = &options.summary_export_directory {
+        let public_functions: Vec<DefId> = tcx
+            .body_owners()
+            .filter(|def_id| utils::is_public(*def_id, &tcx))
+            .collect();
+        persistent_summary_cache.export_summaries(summary_export_directory, &public_functions);
+    }
+    if let Some(assumption_log) = &options.assumption_log {
+        let assumed_obligations = assumptions_for
+            .drain()
+            .flat_map(|(_, obligations)| obligations)
+            .collect();
+        smt_lib::write_assumption_log(assumption_log, assumed_obligations);
     }
-    consume_buffered_diagnostics(&all_diagnostics);
+    let mut crate_metrics = CrateMetrics::default();
+    for def_id in tcx.body_owners() {
+        match tcx.describe_def(def_id) {
+            Some(Def::Fn(..)) | Some(Def::Method(..)) => {}
+            _ => continue,
+        }
+        if utils::is_test_harness_code(def_id, &tcx) {
+            continue;
+        }
+        let diagnostic_count = diagnostics_for.get(&def_id).map_or(0, Vec::len);
+        let summary = persistent_summary_cache.get_summary_for(def_id, None);
+        crate_metrics.add_function(diagnostic_count, summary);
+    }
+    let function_diagnostics =
+        deduplicate_diagnostics(&mut diagnostics_for, &mut persistent_summary_cache);
+    let mut all_diagnostics: Vec<Diagnostic> = cycle_diagnostics.clone();
+    all_diagnostics.extend(function_diagnostics.iter().cloned());
+    crate_metrics.diagnostics = all_diagnostics.len();
+    let mut emitted_diagnostics: Vec<Diagnostic> = cycle_diagnostics;
+    report_diagnostics(
+        &function_diagnostics,
+        session,
+        emit_diagnostic,
+        &mut emitted_diagnostics,
+    );
+    if !options.smoke {
+        // The metrics of all of the crates that are compiled into the same output directory are
+        // written next to their summary store, where cargo mirai collects them.
+        let crate_name = tcx.crate_name(LOCAL_CRATE).to_string();
+        let metrics_file = output_directory.with_file_name(format!(
+            "{}{}",
+            crate_name,
+            metrics::METRICS_FILE_SUFFIX
+        ));
+        metrics::write_metrics(&metrics_file, &crate_name, &crate_metrics);
+    }
+    if let (Some(url), Some(key)) = (&options.remote_summary_cache, &remote_key) {
+        if options.upload_summaries && !fetched_remote_summaries && !options.smoke {
+            remote_cache::upload(url, key, &persistent_summary_cache.export_database());
+        }
+    }
+    export_diagnostics(
+        &all_diagnostics,
+        options,
+        diagnostic_format,
+        output_directory,
+        &tcx,
+    );
+    consume_buffered_diagnostics(&emitted_diagnostics);
     info!("done with analysis");
 }
+
+/// Holds the diagnostic of the given builder back in the given buffer, rather than emitting it.
+fn hold_back_diagnostic(db: &mut DiagnosticBuilder<'_>, buf: &mut Vec<Diagnostic>) {
+    db.clone().buffer(buf);
+    db.cancel();
+}
+
+/// Drains the diagnostics that were found for the analyzed functions and merges the ones that
+/// have the same primary span and message into one. Such duplicates arise when the same code is
+/// reached from several functions, such as a generic function or closure whose body is analyzed
+/// again for each of its instantiations at call sites. A merged diagnostic gets a note that lists
+/// the functions it applies to. The diagnostics are ordered by the summary keys of the first
+/// functions they were found for, so that the order does not depend on the order of analysis.
+fn deduplicate_diagnostics(
+    diagnostics_for: &mut HashMap<DefId, Vec<Diagnostic>>,
+    summary_cache: &mut summaries::PersistentSummaryCache<'_, '_>,
+) -> Vec<Diagnostic> {
+    let mut keyed_diagnostics: Vec<(String, Vec<Diagnostic>)> = diagnostics_for
+        .drain()
+        .map(|(def_id, diagnostics)| {
+            (
+                summary_cache.get_summary_key_for(def_id).clone(),
+                diagnostics,
+            )
+        })
+        .collect();
+    keyed_diagnostics.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+    let mut index_of: HashMap<(Vec<Span>, String), usize> = HashMap::new();
+    let mut merged_diagnostics: Vec<(Diagnostic, Vec<String>)> = vec![];
+    for (summary_key, diagnostics) in keyed_diagnostics {
+        for diagnostic in diagnostics {
+            let group = (
+                diagnostic.span.primary_spans().to_vec(),
+                diagnostic.message(),
+            );
+            match index_of.entry(group) {
+                Entry::Occupied(entry) => {
+                    let functions = &mut merged_diagnostics[*entry.get()].1;
+                    if !functions.contains(&summary_key) {
+                        functions.push(summary_key.clone());
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(merged_diagnostics.len());
+                    merged_diagnostics.push((diagnostic, vec![summary_key.clone()]));
+                }
+            }
+        }
+    }
+    merged_diagnostics
+        .into_iter()
+        .map(|(mut diagnostic, functions)| {
+            if functions.len() > 1 {
+                diagnostic.note(&format!(
+                    "this applies to {} instantiations: {}",
+                    functions.len(),
+                    functions.join(", ")
+                ));
+            }
+            diagnostic
+        })
+        .collect()
+}
+
+/// Emits the given diagnostics with the given function, which may also buffer them in the given
+/// buffer.
+fn report_diagnostics(
+    diagnostics: &[Diagnostic],
+    session: &Session,
+    emit_diagnostic: fn(&mut DiagnosticBuilder<'_>, &mut Vec<Diagnostic>) -> (),
+    buffer: &mut Vec<Diagnostic>,
+) {
+    for diagnostic in diagnostics {
+        let mut db = DiagnosticBuilder::new_diagnostic(session.diagnostic(), diagnostic.clone());
+        emit_diagnostic(&mut db, buffer);
+    }
+}
+
+/// Writes the diagnostics of the crate to the files that the options and the diagnostic format
+/// ask for, if any.
+fn export_diagnostics(
+    all_diagnostics: &[Diagnostic],
+    options: &Options,
+    diagnostic_format: DiagnosticFormat,
+    output_directory: &Path,
+    tcx: &TyCtxt<'_, '_, '_>,
+) {
+    if let Some(findings_file) = &options.security_findings_file {
+        security::export_findings(findings_file, all_diagnostics, tcx);
+    }
+    if diagnostic_format == DiagnosticFormat::Sarif {
+        // The diagnostics of all of the crates that are compiled into the same output directory
+        // are written next to their summary store, in a file per crate.
+        let sarif_file =
+            output_directory.with_file_name(format!("{}.sarif", tcx.crate_name(LOCAL_CRATE)));
+        info!("writing diagnostics to {}", sarif_file.display());
+        sarif::write_sarif(&sarif_file, all_diagnostics, tcx);
+    }
+}
+
+/// Gives the derived method def_id the summary of the derived method with the same shape that was
+/// analyzed in its place. Since def_id becomes a dependent of the template, it gets the summary
+/// again whenever the summary of the template changes. Returns the old summary of def_id if it
+/// changed.
+fn copy_template_summary(
+    def_id: DefId,
+    template_def_id: DefId,
+    summary_cache: &mut summaries::PersistentSummaryCache<'_, '_>,
+) -> Option<summaries::Summary> {
+    let summary = summary_cache
+        .get_summary_for(template_def_id, Some(def_id))
+        .clone();
+    let old_summary_if_changed = if *summary_cache.get_summary_for(def_id, None) != summary {
+        summary_cache.set_summary_for(def_id, summary)
+    } else {
+        None
+    };
+    summary_cache.set_fingerprint_for(def_id, false);
+    old_summary_if_changed
+}
+
+/// Returns the cfg settings with which the crate is compiled, such as its features and its target,
+/// in a canonical order.
+fn get_configuration(session: &Session) -> Vec<String> {
+    let mut configuration: Vec<String> = session
+        .parse_sess
+        .config
+        .iter()
+        .map(|(name, value)| match value {
+            Some(value) => format!("{}=\"{}\"", name, value),
+            None => name.to_string(),
+        })
+        .collect();
+    configuration.sort();
+    configuration
+}
+
+/// Returns the functions that a smoke analysis analyzes: the entry points of the crate and the
+/// functions of the crate that they call directly. The entry points are main (or, in a crate that
+/// is compiled with --test, the given #[test] functions), the functions with the
+/// #[mirai::entry_point] attribute and the functions exported to C or, if the crate has none of
+/// these, its public functions.
+fn get_smoke_defs(tcx: &TyCtxt<'_, '_, '_>, test_functions: &HashSet<DefId>) -> HashSet<DefId> {
+    let body_owners: HashSet<DefId> = tcx
+        .body_owners()
+        .filter(|def_id| !utils::is_test_harness_code(*def_id, tcx))
+        .collect();
+    let mut entry_points: Vec<DefId> = tcx
+        .body_owners()
+        .filter(|def_id| body_owners.contains(def_id))
+        .filter(|def_id| {
+            utils::is_entry_point(*def_id, tcx)
+                || test_functions.contains(def_id)
+                || utils::is_annotated_entry_point(*def_id, tcx)
+                || utils::is_exported_to_c(*def_id, tcx)
+        })
+        .collect();
+    if entry_points.is_empty() {
+        entry_points = tcx
+            .body_owners()
+            .filter(|def_id| utils::is_public(*def_id, tcx))
+            .collect();
+    }
+    let mut smoke_defs: HashSet<DefId> = HashSet::from_iter(entry_points.iter().cloned());
+    for def_id in entry_points {
+        smoke_defs.extend(
+            utils::get_direct_callees(def_id, tcx)
+                .into_iter()
+                .filter(|callee| body_owners.contains(callee)),
+        );
+    }
+    info!("smoke analysis of {} functions", smoke_defs.len());
+    smoke_defs
+}
+
+/// Returns the solver that the options select for proving obligations.
+fn create_smt_solver(options: &Options) -> Box<dyn SmtSolver<Expression>> {
+    let time_limit = Duration::from_millis(
+        options
+            .solver_time_limit_ms
+            .unwrap_or(k_limits::MAX_SOLVER_QUERY_MILLISECONDS),
+    );
+    match (options.get_smt_backend(), &options.solver_command) {
+        (SmtBackend::Process, Some(command)) => {
+            box IsolatedSolver::new(command, time_limit, options.solver_memory_limit_mb)
+        }
+        (SmtBackend::Process, None) => {
+            println!("the process solver backend needs a command in the [solver] section");
+            box SolverStub::default()
+        }
+        (SmtBackend::Cvc5, _) => box Cvc5Solver::new(time_limit, options.solver_memory_limit_mb),
+        (SmtBackend::None, _) => box SolverStub::default(),
+    }
+}
+
+/// Reports the public functions of the crate whose summaries describe behavior that their summaries
+/// in the given summary store, of an earlier version of the crate, do not (see
+/// Summary::differences_from). Each function gets a diagnostic with a note per difference.
+/// Functions that are new, or that the earlier version did not analyze, are not compared.
+fn report_behavior_changes(
+    session: &Session,
+    tcx: &TyCtxt<'_, '_, '_>,
+    summary_cache: &mut summaries::PersistentSummaryCache<'_, '_>,
+    baseline_summary_store: &str,
+    emit_diagnostic: fn(&mut DiagnosticBuilder<'_>, &mut Vec<Diagnostic>) -> (),
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let baseline_summaries = summary_cache.read_baseline_summaries(baseline_summary_store);
+    if baseline_summaries.is_empty() {
+        println!(
+            "no summaries of {} in {}",
+            tcx.crate_name(LOCAL_CRATE),
+            baseline_summary_store
+        );
+        return;
+    }
+    let mut functions: Vec<DefId> = tcx
+        .body_owners()
+        .filter(|def_id| utils::is_public(*def_id, tcx))
+        .filter(|def_id| match tcx.describe_def(*def_id) {
+            Some(Def::Fn(..)) | Some(Def::Method(..)) => true,
+            _ => false,
+        })
+        .collect();
+    functions.sort_by_key(|def_id| utils::summary_key_str(tcx, *def_id));
+    for def_id in functions {
+        let summary_key = utils::summary_key_str(tcx, def_id);
+        let baseline_summary = match baseline_summaries.get(&summary_key) {
+            Some(baseline_summary) => baseline_summary,
+            None => continue,
+        };
+        let differences = summary_cache
+            .get_summary_for(def_id, None)
+            .differences_from(baseline_summary);
+        if differences.is_empty() {
+            continue;
+        }
+        let mut warning = session.struct_span_warn(
+            tcx.def_span(def_id),
+            "the behavior of this function has changed since the baseline",
+        );
+        warning.code(DiagnosticId::Lint(String::from(BEHAVIOR_CHANGE_CODE)));
+        for difference in differences {
+            warning.note(&difference);
+        }
+        emit_diagnostic(&mut warning, diagnostics);
+    }
+}
+
+/// Reports the public functions of the crate from which unsafe code can be reached, as errors,
+/// and writes a certificate that records whether there are any to the given file, along with what
+/// the outcome depends on. Unsafe code in crates that the options trust does not count.
+fn certify_unsafe_free(
+    session: &Session,
+    tcx: &TyCtxt<'_, '_, '_>,
+    summary_cache: &mut summaries::PersistentSummaryCache<'_, '_>,
+    certificate_file: &str,
+    options: &Options,
+    emit_diagnostic: fn(&mut DiagnosticBuilder<'_>, &mut Vec<Diagnostic>) -> (),
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut public_functions: Vec<DefId> = tcx
+        .body_owners()
+        .filter(|def_id| utils::is_public(*def_id, tcx))
+        .filter(|def_id| !utils::is_test_harness_code(*def_id, tcx))
+        .filter(|def_id| match tcx.describe_def(*def_id) {
+            Some(Def::Fn(..)) | Some(Def::Method(..)) => true,
+            _ => false,
+        })
+        .collect();
+    public_functions.sort_by_key(|def_id| utils::summary_key_str(tcx, *def_id));
+    let mut functions = Vec::with_capacity(public_functions.len());
+    for def_id in public_functions {
+        let unsafe_call_chain = summary_cache
+            .get_summary_for(def_id, None)
+            .unsafe_call_chain
+            .clone();
+        if !unsafe_call_chain.is_empty() {
+            let mut error = session.struct_span_err(
+                tcx.def_span(def_id),
+                "unsafe code can be reached from this public function",
+            );
+            error.code(Confidence::Definite.diagnostic_code());
+            error.note(&format!(
+                "the chain of calls to it is {}",
+                unsafe_call_chain.join(" -> ")
+            ));
+            emit_diagnostic(&mut error, diagnostics);
+        }
+        functions.push(CertifiedFunction {
+            summary_key: utils::summary_key_str(tcx, def_id),
+            unsafe_call_chain,
+        });
+    }
+    let source_files = session
+        .source_map()
+        .files()
+        .iter()
+        .filter(|file| !file.is_imported())
+        .filter_map(|file| match &file.name {
+            FileName::Real(path) => Some((path.display().to_string(), file.src_hash)),
+            _ => None,
+        })
+        .collect();
+    certificate::write_unsafe_free_certificate(
+        certificate_file,
+        &tcx.crate_name(LOCAL_CRATE).to_string(),
+        &options.trust_levels,
+        source_files,
+        functions,
+    );
+}
+
+/// Reports the functions of the crate whose summaries are equal to the summary of another function
+/// with the same number of parameters. Such functions require the same of their callers and have
+/// the same effects, so they may duplicate logic that could be consolidated. Only functions with
+/// informative summaries are considered (see Summary::is_informative). Each function is paired
+/// with the first function, in the order of their summary keys, that has the same summary.
+fn report_equivalent_functions(
+    session: &Session,
+    tcx: &TyCtxt<'_, '_, '_>,
+    summary_cache: &mut summaries::PersistentSummaryCache<'_, '_>,
+    emit_diagnostic: fn(&mut DiagnosticBuilder<'_>, &mut Vec<Diagnostic>) -> (),
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut functions: Vec<DefId> = tcx
+        .body_owners()
+        .filter(|def_id| match tcx.describe_def(*def_id) {
+            Some(Def::Fn(..)) | Some(Def::Method(..)) => true,
+            _ => false,
+        })
+        .collect();
+    functions.sort_by_key(|def_id| utils::summary_key_str(tcx, *def_id));
+    let mut first_function_with: HashMap<(usize, summaries::Summary), DefId> = HashMap::new();
+    for def_id in functions {
+        let summary = summary_cache.get_summary_for(def_id, None).clone();
+        if !summary.is_informative() {
+            continue;
+        }
+        let parameter_count = tcx.fn_sig(def_id).skip_binder().inputs().len();
+        match first_function_with.entry((parameter_count, summary)) {
+            Entry::Occupied(entry) => {
+                let other_def_id = *entry.get();
+                let message = format!(
+                    "this function has the same summary as {}, so it may duplicate its logic",
+                    utils::summary_key_str(tcx, other_def_id)
+                );
+                let mut warning = session.struct_span_warn(tcx.def_span(def_id), message.as_str());
+                warning.span_note(
+                    tcx.def_span(other_def_id),
+                    "the function with the same summary is defined here",
+                );
+                emit_diagnostic(&mut warning, diagnostics);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(def_id);
+            }
+        }
+    }
+}
+
+/// Returns the functions of a cycle of calls that starts and ends with def_id, in the order in
+/// which they call each other, or None if def_id is not part of a cycle.
+fn find_call_cycle(
+    def_id: DefId,
+    summary_cache: &mut summaries::PersistentSummaryCache<'_, '_>,
+) -> Option<Vec<DefId>> {
+    // Search breadth first through the callers of def_id, remembering for each caller the
+    // function it calls, until def_id itself is found to be a caller.
+    let mut callee_of: HashMap<DefId, DefId> = HashMap::new();
+    let mut queue: VecDeque<DefId> = VecDeque::new();
+    queue.push_back(def_id);
+    while let Some(callee) = queue.pop_front() {
+        for caller in summary_cache.get_dependents(&callee).clone() {
+            if caller == def_id {
+                // def_id calls callee, which leads back to def_id by following callee_of.
+                let mut cycle = vec![def_id, callee];
+                let mut current = callee;
+                while current != def_id {
+                    current = callee_of[&current];
+                    cycle.push(current);
+                }
+                return Some(cycle);
+            }
+            if !callee_of.contains_key(&caller) {
+                callee_of.insert(caller, callee);
+                queue.push_back(caller);
+            }
+        }
+    }
+    None
+}
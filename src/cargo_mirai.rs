@@ -0,0 +1,393 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// A cargo subcommand that analyzes all of the crates of a package or workspace with Mirai:
+//
+//     cargo mirai [--jobs N] [--workspace-only] [other cargo build options]
+//     cargo mirai --gc-summaries [--target-dir DIR] [--target TRIPLE] [--release]
+//
+// Mirai is run in place of rustc (via RUSTC_WRAPPER), so cargo schedules the analyses: a crate is
+// analyzed once all of the crates it depends on have been analyzed and their summaries are available,
+// and crates that do not depend on each other are analyzed in parallel, up to the job limit.
+// Pipelined compilation is turned off, since it starts on a crate as soon as the metadata of its
+// dependencies is available, which is before they have been analyzed.
+//
+// With --workspace-only, only the crates of the workspace are analyzed. Dependencies that have been
+// analyzed by a previous run are just compiled, and their stored summaries are used as they are.
+//
+// Once cargo is done, the metrics that Mirai recorded for each crate are added up into a summary of
+// the workspace, along with a badge that shows the percentage of verified functions.
+//
+// With --gc-summaries, nothing is built. Instead, the entries of the summary databases that belong
+// to functions which no longer exist, according to the last analysis of their crates, are removed
+// and the databases are compacted.
+
+use mirai::metrics::METRICS_FILE_SUFFIX;
+use mirai::options::WORKSPACE_ONLY_VARIABLE;
+use mirai::summaries::{FINGERPRINT_KEY_SUFFIX, SUMMARY_KEYS_KEY};
+use sled::Db;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+/// The environment variable that sets the number of crates that are analyzed in parallel, if the
+/// job limit is not given on the command line. Cargo's default is the number of CPUs.
+const JOBS_VARIABLE: &str = "MIRAI_JOBS";
+
+/// The command line option that limits the analysis to the crates of the workspace. It is not
+/// passed on to cargo.
+const WORKSPACE_ONLY_OPTION: &str = "--workspace-only";
+
+/// The command line option that collects the garbage in the summary store, rather than analyzing
+/// anything. It is not passed on to cargo.
+const GC_SUMMARIES_OPTION: &str = "--gc-summaries";
+
+/// The directory, in the output directory of the build profile, that holds the summary databases
+/// of the crates, one per crate, named after the crate.
+const SUMMARY_STORE_DIRECTORY: &str = ".summary_store";
+
+/// The file, in the output directory of the build profile, to which the metrics of the workspace
+/// are written.
+const SUMMARY_FILE: &str = "mirai_summary.json";
+
+/// The file, next to the summary file, to which the percentage of verified functions is written
+/// as a shields.io endpoint badge.
+const BADGE_FILE: &str = "mirai_badge.json";
+
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    // Cargo runs a subcommand with the name of the subcommand as the first argument.
+    if args.peek().map(String::as_str) == Some("mirai") {
+        args.next();
+    }
+    let mut cargo_args: Vec<String> = args.collect();
+    if cargo_args.iter().any(|arg| arg == GC_SUMMARIES_OPTION) {
+        cargo_args.retain(|arg| arg != GC_SUMMARIES_OPTION);
+        collect_summary_garbage(&get_profile_directory(&cargo_args).join(SUMMARY_STORE_DIRECTORY));
+        return;
+    }
+    let workspace_only = cargo_args.iter().any(|arg| arg == WORKSPACE_ONLY_OPTION);
+    cargo_args.retain(|arg| arg != WORKSPACE_ONLY_OPTION);
+    let has_job_limit = cargo_args
+        .iter()
+        .any(|arg| arg.starts_with("-j") || arg.starts_with("--jobs"));
+    if !has_job_limit {
+        if let Ok(jobs) = env::var(JOBS_VARIABLE) {
+            cargo_args.push(String::from("--jobs"));
+            cargo_args.push(jobs);
+        }
+    }
+
+    // The mirai binary is installed next to this one.
+    let mirai = match env::current_exe() {
+        Ok(path) => path.with_file_name(format!("mirai{}", env::consts::EXE_SUFFIX)),
+        Err(err) => {
+            println!("unable to locate the mirai binary: {}", err);
+            process::exit(1);
+        }
+    };
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let mut command = Command::new(cargo);
+    command
+        .arg("build")
+        .args(&cargo_args)
+        .env("RUSTC_WRAPPER", &mirai)
+        .env("CARGO_BUILD_PIPELINING", "false");
+    if workspace_only {
+        command.env(WORKSPACE_ONLY_VARIABLE, "1");
+    }
+    let status = command.status();
+    match status {
+        Ok(status) => {
+            if status.success() {
+                summarize_workspace(&get_profile_directory(&cargo_args));
+            }
+            process::exit(status.code().unwrap_or(1))
+        }
+        Err(err) => {
+            println!("unable to run cargo: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Returns the value of the given cargo option, which may be given as "--option value" or as
+/// "--option=value".
+fn get_option_value(cargo_args: &[String], option: &str) -> Option<String> {
+    let prefix = format!("{}=", option);
+    let mut args = cargo_args.iter();
+    while let Some(arg) = args.next() {
+        if arg == option {
+            return args.next().cloned();
+        }
+        if arg.starts_with(&prefix) {
+            return Some(String::from(&arg[prefix.len()..]));
+        }
+    }
+    None
+}
+
+/// Returns the directory into which cargo builds the crates for the given arguments, such as
+/// target/debug. Mirai writes the metrics of the crates into this directory.
+fn get_profile_directory(cargo_args: &[String]) -> PathBuf {
+    let mut directory = match get_option_value(cargo_args, "--target-dir") {
+        Some(target_dir) => PathBuf::from(target_dir),
+        None => {
+            PathBuf::from(env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| String::from("target")))
+        }
+    };
+    if let Some(target) = get_option_value(cargo_args, "--target") {
+        directory.push(target);
+    }
+    if cargo_args.iter().any(|arg| arg == "--release") {
+        directory.push("release");
+    } else {
+        directory.push("debug");
+    }
+    directory
+}
+
+/// Returns the count that follows the given key in the contents of a metrics file.
+fn get_count(contents: &str, key: &str) -> u64 {
+    let label = format!("\"{}\": ", key);
+    match contents.find(&label) {
+        Some(start) => contents[start + label.len()..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0),
+        None => 0,
+    }
+}
+
+/// Returns the given part of the given whole as a percentage, with one decimal. An empty workspace
+/// counts as fully verified.
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 100.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).floor() / 10.0
+}
+
+/// Adds up the metrics of the crates that were analyzed into the given directory and writes them
+/// to a summary file, along with the percentages of verified and panic free functions. Also writes
+/// a badge file that shields.io can render as the percentage of verified functions. Crates that
+/// cargo did not have to build again keep the metrics of the run that last analyzed them.
+/// Reports and otherwise ignores failures.
+fn summarize_workspace(directory: &Path) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            println!(
+                "unable to read metrics from {}: {}",
+                directory.display(),
+                err
+            );
+            return;
+        }
+    };
+    let (mut crates, mut functions, mut verified, mut panic_free, mut diagnostics) =
+        (0, 0, 0, 0, 0);
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let is_metrics_file = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.ends_with(METRICS_FILE_SUFFIX));
+        if !is_metrics_file {
+            continue;
+        }
+        if let Ok(contents) = fs::read_to_string(&path) {
+            crates += 1;
+            functions += get_count(&contents, "functions");
+            verified += get_count(&contents, "verified_functions");
+            panic_free += get_count(&contents, "panic_free_functions");
+            diagnostics += get_count(&contents, "diagnostics");
+        }
+    }
+    let verified_percent = percentage(verified, functions);
+    let summary = format!(
+        "{{\"crates\": {}, \"functions\": {}, \"verified_functions\": {}, \"verified_percent\": {:.1}, \
+         \"panic_free_functions\": {}, \"panic_free_percent\": {:.1}, \"diagnostics\": {}}}\n",
+        crates,
+        functions,
+        verified,
+        verified_percent,
+        panic_free,
+        percentage(panic_free, functions),
+        diagnostics
+    );
+    let color = if verified_percent >= 100.0 {
+        "brightgreen"
+    } else if verified_percent >= 80.0 {
+        "green"
+    } else if verified_percent >= 50.0 {
+        "yellow"
+    } else {
+        "red"
+    };
+    let badge = format!(
+        "{{\"schemaVersion\": 1, \"label\": \"mirai verified\", \"message\": \"{:.1}%\", \"color\": \"{}\"}}\n",
+        verified_percent, color
+    );
+    let summary_file = directory.join(SUMMARY_FILE);
+    let badge_file = directory.join(BADGE_FILE);
+    let result = fs::write(&summary_file, summary).and_then(|_| fs::write(&badge_file, badge));
+    match result {
+        Ok(_) => println!(
+            "wrote the verification summary to {}",
+            summary_file.display()
+        ),
+        Err(err) => println!("unable to write the verification summary: {}", err),
+    }
+}
+
+/// What collecting the garbage in the summary database of a crate did.
+struct CollectedGarbage {
+    /// The number of entries that were removed.
+    removed_entries: usize,
+    /// The number of entries that were kept.
+    kept_entries: usize,
+    /// The space the database took up on disk before, in bytes.
+    bytes_before: u64,
+    /// The space the database takes up on disk now, in bytes.
+    bytes_after: u64,
+}
+
+/// Collects the garbage in each of the summary databases in the given directory and prints how
+/// many entries were removed and how much space was reclaimed. Reports and otherwise ignores
+/// failures.
+fn collect_summary_garbage(directory: &Path) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            println!(
+                "unable to read the summary store {}: {}",
+                directory.display(),
+                err
+            );
+            return;
+        }
+    };
+    let (mut removed_entries, mut reclaimed_bytes) = (0, 0);
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("sled") {
+            continue;
+        }
+        let crate_name = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match compact_summary_database(&path) {
+            Ok(Some(garbage)) => {
+                let reclaimed = garbage.bytes_before.saturating_sub(garbage.bytes_after);
+                println!(
+                    "{}: removed {} of {} entries, reclaimed {} bytes ({} bytes remain)",
+                    crate_name,
+                    garbage.removed_entries,
+                    garbage.removed_entries + garbage.kept_entries,
+                    reclaimed,
+                    garbage.bytes_after
+                );
+                removed_entries += garbage.removed_entries;
+                reclaimed_bytes += reclaimed;
+            }
+            Ok(None) => println!(
+                "{}: skipped, since the summary store does not record the functions of the crate; analyze it again first",
+                crate_name
+            ),
+            Err(err) => println!("{}: unable to collect garbage: {}", crate_name, err),
+        }
+    }
+    println!(
+        "removed {} entries from the summary store, reclaimed {} bytes",
+        removed_entries, reclaimed_bytes
+    );
+}
+
+/// Removes the entries of the summary database at the given path that belong to functions which
+/// are not among the functions that the last analysis of the crate recorded, as well as the
+/// fingerprints of summaries that are not there. The remaining entries are then copied into a new
+/// database that replaces the old one, since sled does not give the space of removed entries back
+/// to the file system. Returns None if the database does not record the functions of its crate,
+/// because it was written by an earlier version of mirai.
+fn compact_summary_database(path: &Path) -> Result<Option<CollectedGarbage>, String> {
+    let bytes_before = get_disk_usage(path);
+    let all_entries: Vec<(String, Vec<u8>)> = {
+        let db = Db::start_default(path).map_err(|err| format!("{:?}", err))?;
+        db.iter()
+            .filter_map(Result::ok)
+            .filter_map(|(key, value)| {
+                Some((String::from_utf8(key.to_vec()).ok()?, value.to_vec()))
+            })
+            .collect()
+    };
+    let summary_keys: HashSet<&str> =
+        match all_entries.iter().find(|(key, _)| key == SUMMARY_KEYS_KEY) {
+            Some((_, value)) => std::str::from_utf8(value)
+                .map_err(|err| err.to_string())?
+                .lines()
+                .collect(),
+            None => return Ok(None),
+        };
+    let stored_summaries: HashSet<&str> = all_entries
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .filter(|key| summary_keys.contains(key))
+        .collect();
+    let is_live = |key: &str| {
+        key == SUMMARY_KEYS_KEY
+            || summary_keys.contains(key)
+            || (key.ends_with(FINGERPRINT_KEY_SUFFIX)
+                && stored_summaries.contains(&key[..key.len() - FINGERPRINT_KEY_SUFFIX.len()]))
+    };
+    let live_entries: Vec<&(String, Vec<u8>)> =
+        all_entries.iter().filter(|(key, _)| is_live(key)).collect();
+    let compacted_path = path.with_extension("sled_gc");
+    remove_path(&compacted_path);
+    {
+        let db = Db::start_default(&compacted_path).map_err(|err| format!("{:?}", err))?;
+        for (key, value) in live_entries.iter() {
+            db.set(key.as_bytes(), value.clone())
+                .map_err(|err| format!("{:?}", err))?;
+        }
+        db.flush().map_err(|err| format!("{:?}", err))?;
+    }
+    remove_path(path);
+    fs::rename(&compacted_path, path).map_err(|err| err.to_string())?;
+    Ok(Some(CollectedGarbage {
+        removed_entries: all_entries.len() - live_entries.len(),
+        kept_entries: live_entries.len(),
+        bytes_before,
+        bytes_after: get_disk_usage(path),
+    }))
+}
+
+/// Returns the number of bytes that the file, or the files in the directory, at the given path
+/// take up.
+fn get_disk_usage(path: &Path) -> u64 {
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|entry| get_disk_usage(&entry.path()))
+            .sum(),
+        Err(_) => fs::metadata(path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0),
+    }
+}
+
+/// Removes the file or directory at the given path, if there is one.
+fn remove_path(path: &Path) {
+    let _ = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+}
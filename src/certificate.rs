@@ -0,0 +1,94 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::options::TrustLevel;
+use crate::utils::json_string;
+
+use std::collections::HashMap;
+use std::fs;
+
+/// The version of MIRAI that issues the certificates, since another version may come to another
+/// conclusion about the same sources.
+const MIRAI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// What the analysis found about a public function of a crate that is to be certified free of
+/// unsafe code.
+#[derive(Debug)]
+pub struct CertifiedFunction {
+    /// The summary key of the function.
+    pub summary_key: String,
+    /// The chain of calls that leads from the function to unsafe code, which is empty if no unsafe
+    /// code can be reached from the function. See Summary::unsafe_call_chain.
+    pub unsafe_call_chain: Vec<String>,
+}
+
+/// Writes a certificate that says whether unsafe code can be reached from the public functions of
+/// the crate with the given name to a JSON file at the given path. The crate is certified if none
+/// of its public functions reach unsafe code. Along with the verdict and the functions, the
+/// certificate records what it depends on, so that a consumer can check that it applies to the
+/// sources at hand and reproduce it: the version of MIRAI, the trust levels of the dependencies,
+/// and the source files of the crate with their hashes. Reports and otherwise ignores failures.
+pub fn write_unsafe_free_certificate(
+    path: &str,
+    crate_name: &str,
+    trust_levels: &HashMap<String, TrustLevel>,
+    mut source_files: Vec<(String, u128)>,
+    mut functions: Vec<CertifiedFunction>,
+) {
+    let mut trust_entries: Vec<String> = trust_levels
+        .iter()
+        .map(|(crate_name, trust_level)| {
+            let level = match trust_level {
+                TrustLevel::Trusted => "trusted",
+                TrustLevel::Verified => "verified",
+                TrustLevel::Untrusted => "untrusted",
+            };
+            format!("{}: {}", json_string(crate_name), json_string(level))
+        })
+        .collect();
+    trust_entries.sort();
+    source_files.sort();
+    let sources: Vec<String> = source_files
+        .iter()
+        .map(|(file, hash)| {
+            format!(
+                "{{\"file\": {}, \"hash\": \"{:032x}\"}}",
+                json_string(file),
+                hash
+            )
+        })
+        .collect();
+    functions.sort_by(|f1, f2| f1.summary_key.cmp(&f2.summary_key));
+    let certified = functions
+        .iter()
+        .all(|function| function.unsafe_call_chain.is_empty());
+    let function_objects: Vec<String> = functions
+        .iter()
+        .map(|function| {
+            let chain: Vec<String> = function
+                .unsafe_call_chain
+                .iter()
+                .map(|key| json_string(key))
+                .collect();
+            format!(
+                "{{\"function\": {}, \"unsafe_call_chain\": [{}]}}",
+                json_string(&function.summary_key),
+                chain.join(", ")
+            )
+        })
+        .collect();
+    let contents = format!(
+        "{{\"crate\": {}, \"mirai_version\": {}, \"unsafe_free\": {},\n\"trust\": {{{}}},\n\"sources\": [{}],\n\"functions\": [{}]}}\n",
+        json_string(crate_name),
+        json_string(MIRAI_VERSION),
+        certified,
+        trust_entries.join(", "),
+        sources.join(",\n"),
+        function_objects.join(",\n")
+    );
+    if let Err(err) = fs::write(path, contents) {
+        println!("unable to write certificate to {}: {}", path, err);
+    }
+}
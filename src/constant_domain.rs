@@ -5,12 +5,14 @@
 #![allow(clippy::float_cmp)]
 
 use crate::expression::{Expression, ExpressionType};
+use crate::known_names::{KnownFunction, KnownNamesCache};
 use crate::summaries::PersistentSummaryCache;
-use crate::utils::is_rust_intrinsic;
+use crate::utils::{is_rust_intrinsic, self_type_key_str};
 
 use rustc::hir::def_id::DefId;
 use rustc::ty::TyCtxt;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Abstracts over constant values referenced in MIR and adds information
 /// that is useful for the abstract interpreter. More importantly, this
@@ -35,6 +37,11 @@ pub enum ConstantDomain {
         is_intrinsic: bool,
         /// The key to use when retrieving a summary for the function from the summary cache
         summary_cache_key: String,
+        /// The Self type of the impl block that defines the function, if any, as identified by
+        /// utils::self_type_key_str. Like def_id, this is not available for functions that come
+        /// from other crates by way of their summaries.
+        #[serde(skip)]
+        self_type: Option<String>,
     },
     /// Signed 16 byte integer.
     I128(i128),
@@ -65,6 +72,7 @@ impl ConstantDomain {
             def_id: Some(def_id),
             is_intrinsic: is_rust_intrinsic(def_id, tcx),
             summary_cache_key: summary_cache_key.to_owned(),
+            self_type: self_type_key_str(tcx, def_id),
         }
     }
 }
@@ -418,32 +426,7 @@ impl ConstantDomain {
 
     /// Returns a constant that is true if "self << other" is not in range of target_type.
     pub fn shl_overflows(&self, other: &Self, target_type: &ExpressionType) -> Self {
-        let other_as_u32 = match &other {
-            ConstantDomain::I128(val2) => Some(*val2 as u32),
-            ConstantDomain::U128(val2) => Some(*val2 as u32),
-            _ => None,
-        };
-        match (&self, other_as_u32) {
-            (ConstantDomain::I128(val1), Some(val2)) => match target_type {
-                ExpressionType::I128 => i128::overflowing_shl(*val1, val2).1,
-                ExpressionType::I64 => i64::overflowing_shl(*val1 as i64, val2).1,
-                ExpressionType::I32 => i32::overflowing_shl(*val1 as i32, val2).1,
-                ExpressionType::I16 => i16::overflowing_shl(*val1 as i16, val2).1,
-                ExpressionType::I8 => i8::overflowing_shl(*val1 as i8, val2).1,
-                _ => unreachable!(),
-            }
-            .into(),
-            (ConstantDomain::U128(val1), Some(val2)) => match target_type {
-                ExpressionType::U128 => u128::overflowing_shl(*val1, val2).1,
-                ExpressionType::U64 => u64::overflowing_shl(*val1 as u64, val2).1,
-                ExpressionType::U32 => u32::overflowing_shl(*val1 as u32, val2).1,
-                ExpressionType::U16 => u16::overflowing_shl(*val1 as u16, val2).1,
-                ExpressionType::U8 => u8::overflowing_shl(*val1 as u8, val2).1,
-                _ => unreachable!(),
-            }
-            .into(),
-            _ => ConstantDomain::Bottom,
-        }
+        self.shift_overflows(other, target_type)
     }
 
     /// Returns a constant that is "self >> other".
@@ -466,30 +449,24 @@ impl ConstantDomain {
 
     /// Returns a constant that is true if "self >> other" shifts away all bits.
     pub fn shr_overflows(&self, other: &Self, target_type: &ExpressionType) -> Self {
-        let other_as_u32 = match &other {
-            ConstantDomain::I128(val2) => Some(*val2 as u32),
-            ConstantDomain::U128(val2) => Some(*val2 as u32),
-            _ => None,
-        };
-        match (&self, other_as_u32) {
-            (ConstantDomain::I128(val1), Some(val2)) => match target_type {
-                ExpressionType::I128 => i128::overflowing_shl(*val1, val2).1,
-                ExpressionType::I64 => i64::overflowing_shl(*val1 as i64, val2).1,
-                ExpressionType::I32 => i32::overflowing_shl(*val1 as i32, val2).1,
-                ExpressionType::I16 => i16::overflowing_shl(*val1 as i16, val2).1,
-                ExpressionType::I8 => i8::overflowing_shl(*val1 as i8, val2).1,
-                _ => unreachable!(),
-            }
-            .into(),
-            (ConstantDomain::U128(val1), Some(val2)) => match target_type {
-                ExpressionType::U128 => u128::overflowing_shl(*val1, val2).1,
-                ExpressionType::U64 => u64::overflowing_shl(*val1 as u64, val2).1,
-                ExpressionType::U32 => u32::overflowing_shl(*val1 as u32, val2).1,
-                ExpressionType::U16 => u16::overflowing_shl(*val1 as u16, val2).1,
-                ExpressionType::U8 => u8::overflowing_shl(*val1 as u8, val2).1,
-                _ => unreachable!(),
+        self.shift_overflows(other, target_type)
+    }
+
+    /// Returns a constant that is true if shifting self by other shifts away all of the bits of
+    /// a value of type target_type. This is the case if other is negative or if other is not less
+    /// than the number of bits used to represent the type.
+    fn shift_overflows(&self, other: &Self, target_type: &ExpressionType) -> Self {
+        match &self {
+            ConstantDomain::I128(..) | ConstantDomain::U128(..) => {
+                let bit_length = target_type.bit_length();
+                match &other {
+                    ConstantDomain::I128(val2) => {
+                        (*val2 < 0 || *val2 >= i128::from(bit_length)).into()
+                    }
+                    ConstantDomain::U128(val2) => (*val2 >= u128::from(bit_length)).into(),
+                    _ => ConstantDomain::Bottom,
+                }
             }
-            .into(),
             _ => ConstantDomain::Bottom,
         }
     }
@@ -547,6 +524,65 @@ impl ConstantDomain {
             _ => ConstantDomain::Bottom,
         }
     }
+
+    /// Returns the constant that "self << other" is when overflow checks are disabled: the shift
+    /// amount is masked to the number of bits of target_type and the bits shifted out are lost.
+    pub fn wrapping_shl(&self, other: &Self, target_type: &ExpressionType) -> Self {
+        match other.mask_shift_amount(target_type) {
+            Some(amount) => self.shl(&amount).wrap_to(target_type),
+            None => ConstantDomain::Bottom,
+        }
+    }
+
+    /// Returns the constant that "self >> other" is when overflow checks are disabled: the shift
+    /// amount is masked to the number of bits of target_type.
+    pub fn wrapping_shr(&self, other: &Self, target_type: &ExpressionType) -> Self {
+        match other.mask_shift_amount(target_type) {
+            Some(amount) => self.shr(&amount),
+            None => ConstantDomain::Bottom,
+        }
+    }
+
+    /// Returns the shift amount self masked to bit_length(target_type) - 1, which is the amount
+    /// by which a value of target_type is shifted when overflow checks are disabled.
+    fn mask_shift_amount(&self, target_type: &ExpressionType) -> Option<Self> {
+        let bit_length = u128::from(target_type.bit_length());
+        if bit_length == 0 {
+            return None;
+        }
+        match self {
+            ConstantDomain::I128(val) => {
+                Some(ConstantDomain::U128((*val as u128) & (bit_length - 1)))
+            }
+            ConstantDomain::U128(val) => Some(ConstantDomain::U128(*val & (bit_length - 1))),
+            _ => None,
+        }
+    }
+
+    /// Returns the constant that an integer constant becomes when it is wrapped around to fit
+    /// into target_type, which is what happens to the result of an overflowing operation when
+    /// overflow checks are disabled.
+    pub fn wrap_to(&self, target_type: &ExpressionType) -> Self {
+        match self {
+            ConstantDomain::I128(val) => ConstantDomain::I128(match target_type {
+                ExpressionType::Isize => *val as isize as i128,
+                ExpressionType::I64 => *val as i64 as i128,
+                ExpressionType::I32 => *val as i32 as i128,
+                ExpressionType::I16 => *val as i16 as i128,
+                ExpressionType::I8 => *val as i8 as i128,
+                _ => *val,
+            }),
+            ConstantDomain::U128(val) => ConstantDomain::U128(match target_type {
+                ExpressionType::Usize => *val as usize as u128,
+                ExpressionType::U64 => *val as u64 as u128,
+                ExpressionType::U32 => *val as u32 as u128,
+                ExpressionType::U16 => *val as u16 as u128,
+                ExpressionType::U8 => *val as u8 as u128,
+                _ => *val,
+            }),
+            _ => self.clone(),
+        }
+    }
 }
 
 /// Keeps track of MIR constants that have already been mapped onto ConstantDomain instances.
@@ -558,13 +594,18 @@ pub struct ConstantValueCache {
     i128_cache: HashMap<i128, ConstantDomain>,
     u128_cache: HashMap<u128, ConstantDomain>,
     str_cache: HashMap<String, ConstantDomain>,
-    std_intrinsics_unreachable_function: Option<ConstantDomain>,
-    std_panicking_panic_function: Option<ConstantDomain>,
+    known_names_cache: Arc<KnownNamesCache>,
     heap_address_counter: usize,
 }
 
 impl ConstantValueCache {
     pub fn new() -> ConstantValueCache {
+        Self::with_known_names(Arc::new(KnownNamesCache::create_cache_from_environment()))
+    }
+
+    /// Creates a cache that looks up the models of well known functions in the given table,
+    /// which may be shared with the caches of other crates.
+    pub fn with_known_names(known_names_cache: Arc<KnownNamesCache>) -> ConstantValueCache {
         ConstantValueCache {
             char_cache: HashMap::default(),
             function_cache: HashMap::default(),
@@ -573,8 +614,7 @@ impl ConstantValueCache {
             i128_cache: HashMap::default(),
             u128_cache: HashMap::default(),
             str_cache: HashMap::default(),
-            std_intrinsics_unreachable_function: None,
-            std_panicking_panic_function: None,
+            known_names_cache,
             heap_address_counter: 0,
         }
     }
@@ -641,48 +681,14 @@ impl ConstantValueCache {
             .or_insert_with(|| ConstantDomain::for_function(def_id, tcx, summary_cache))
     }
 
-    /// Does an expensive check to see if the given function is std::intrinsics::unreachable.
-    /// Once it finds the function it caches it so that subsequent checks are cheaper.
+    /// Returns the built in model that the known names table associates with the given function.
+    pub fn get_known_function_for(&mut self, fun: &ConstantDomain) -> Option<KnownFunction> {
+        self.known_names_cache.get(fun)
+    }
+
+    /// Checks if the given function is std::intrinsics::unreachable.
     pub fn check_if_std_intrinsics_unreachable_function(&mut self, fun: &ConstantDomain) -> bool {
-        let result = self
-            .std_intrinsics_unreachable_function
-            .as_ref()
-            .map(|std_fun| *std_fun == *fun);
-        result.unwrap_or_else(|| {
-            let result = match fun {
-                ConstantDomain::Function {
-                    is_intrinsic,
-                    summary_cache_key,
-                    ..
-                } => *is_intrinsic && summary_cache_key.ends_with("unreachable"),
-                _ => false,
-            };
-            if result {
-                self.std_intrinsics_unreachable_function = Some(fun.clone());
-            };
-            result
-        })
-    }
-
-    /// Does an expensive check to see if the given function is std.panicking.begin_panic.
-    /// Once it finds the function it caches it so that subsequent checks are cheaper.
-    pub fn check_if_std_panicking_begin_panic_function(&mut self, fun: &ConstantDomain) -> bool {
-        let result = self
-            .std_panicking_panic_function
-            .as_ref()
-            .map(|std_fun| *std_fun == *fun);
-        result.unwrap_or_else(|| {
-            let result = match fun {
-                ConstantDomain::Function {
-                    summary_cache_key, ..
-                } => summary_cache_key == "std.panicking.begin_panic",
-                _ => false,
-            };
-            if result {
-                self.std_panicking_panic_function = Some(fun.clone());
-            };
-            result
-        })
+        self.get_known_function_for(fun) == Some(KnownFunction::StdIntrinsicsUnreachable)
     }
 }
 
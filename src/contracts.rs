@@ -0,0 +1,399 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::abstract_domains::AbstractDomain;
+use crate::abstract_value::{AbstractValue, Path, PathSelector};
+use crate::constant_domain::ConstantDomain;
+use crate::expression::Expression;
+use crate::summaries::Summary;
+use crate::utils;
+
+use rustc::hir::def_id::DefId;
+use rustc::mir;
+use rustc::ty::{TyCtxt, TyKind};
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::fs;
+use syntax_pos::Span;
+
+/// The facts that the analysis has inferred about a public function, in a form that can be
+/// published along with the API of the crate, so that its users benefit from them even if they
+/// do not verify their own code.
+#[derive(Serialize, Debug, Default)]
+pub struct FunctionContract {
+    /// The summary key of the function.
+    pub function: String,
+    /// If set, the result is always within this (inclusive) range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_range: Option<(i64, i64)>,
+    /// True if the result is a collection that is never empty.
+    #[serde(skip_serializing_if = "is_false")]
+    pub never_empty: bool,
+    /// True if the result is an Option that is never None.
+    #[serde(skip_serializing_if = "is_false")]
+    pub never_none: bool,
+    /// True if the result is a Result that is never an Err.
+    #[serde(skip_serializing_if = "is_false")]
+    pub never_err: bool,
+    /// What goes wrong if the arguments do not satisfy the preconditions of the function.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preconditions: Vec<String>,
+    /// The facts above as sentences that can be pasted into the documentation of the function.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub documentation: Vec<String>,
+}
+
+/// The contents of a contracts file.
+#[derive(Serialize, Debug, Default)]
+struct ContractsFile {
+    function: Vec<FunctionContract>,
+}
+
+/// Used to leave flags that are false out of the contracts file.
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Extracts the contract of the function identified by def_id from its summary.
+/// Returns None if nothing worth publishing is known about the function.
+pub fn extract_contract(
+    tcx: &TyCtxt<'_, '_, '_>,
+    def_id: DefId,
+    summary: &Summary,
+) -> Option<FunctionContract> {
+    let mut contract = FunctionContract::default();
+    if let Some(result) = &summary.result {
+        let interval = result.domain.get_as_interval();
+        if let (Some(lower), Some(upper)) = (interval.lower_bound(), interval.upper_bound()) {
+            let return_type = tcx.fn_sig(def_id).skip_binder().output();
+            match &return_type.sty {
+                TyKind::Int(..) | TyKind::Uint(..) => {
+                    if let (Ok(lower), Ok(upper)) = (i64::try_from(lower), i64::try_from(upper)) {
+                        contract.result_range = Some((lower, upper));
+                        contract.documentation.push(format!(
+                            "The result is always within {}..={}.",
+                            lower, upper
+                        ));
+                    }
+                }
+                TyKind::Adt(def, _) if lower == upper => {
+                    match utils::summary_key_str(tcx, def.did).as_str() {
+                        "core.option.Option" if lower == 1 => {
+                            contract.never_none = true;
+                            contract
+                                .documentation
+                                .push(String::from("Never returns None."));
+                        }
+                        "core.result.Result" if lower == 0 => {
+                            contract.never_err = true;
+                            contract
+                                .documentation
+                                .push(String::from("Never returns an error."));
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let result_length_path = Path::QualifiedPath {
+        length: 2,
+        qualifier: box Path::LocalVariable { ordinal: 0 },
+        selector: box PathSelector::ArrayLength,
+    };
+    for (path, value) in summary.side_effects.iter() {
+        if *path == result_length_path {
+            let interval = value.domain.get_as_interval();
+            if !interval.is_bottom() && interval.lower_bound().unwrap_or(0) >= 1 {
+                contract.never_empty = true;
+                contract
+                    .documentation
+                    .push(String::from("Never returns an empty collection."));
+            }
+        }
+    }
+    for (_, message) in summary.preconditions.iter() {
+        if !contract.preconditions.contains(message) {
+            contract.preconditions.push(message.clone());
+        }
+    }
+    if contract.documentation.is_empty() && contract.preconditions.is_empty() {
+        return None;
+    }
+    contract.function = utils::summary_key_str(tcx, def_id);
+    Some(contract)
+}
+
+/// Writes the given contracts to a TOML file at the given path, in the order of their keys.
+/// Reports and otherwise ignores failures.
+pub fn write_contracts(path: &str, mut contracts: Vec<FunctionContract>) {
+    contracts.sort_by(|c1, c2| c1.function.cmp(&c2.function));
+    let file = ContractsFile {
+        function: contracts,
+    };
+    write_file(path, toml::to_string(&file), "contracts");
+}
+
+/// The conditions that the analysis has inferred for a public function, so that its author can
+/// review them and turn them into explicit annotations.
+#[derive(Serialize, Debug)]
+pub struct InferredConditions {
+    /// The summary key of the function.
+    pub function: String,
+    /// The conditions that the arguments must satisfy.
+    pub preconditions: Vec<InferredCondition>,
+    /// The conditions that hold when the function returns.
+    pub postconditions: Vec<InferredCondition>,
+}
+
+/// A condition inferred for a function, along with the source locations that gave rise to it.
+#[derive(Serialize, Debug)]
+pub struct InferredCondition {
+    /// The condition, written the way it would be written in the source of the function.
+    pub condition: String,
+    /// What goes wrong if a precondition is not satisfied. Postconditions have no message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The source locations that gave rise to the condition.
+    pub provenance: Vec<SourceLocation>,
+}
+
+/// The file, line and column of the start of a span.
+#[derive(Serialize, Debug)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Returns the conditions that the analysis has inferred for the function identified by def_id,
+/// or None if no conditions have been inferred for it.
+pub fn extract_inferred_conditions(
+    tcx: &TyCtxt<'_, '_, '_>,
+    def_id: DefId,
+    summary: &Summary,
+) -> Option<InferredConditions> {
+    if summary.preconditions.is_empty() && summary.post_conditions.is_empty() {
+        return None;
+    }
+    let mir = tcx.optimized_mir(def_id);
+    let parameter_names: Vec<String> = (1..=mir.arg_count)
+        .map(
+            |ordinal| match mir.local_decls[mir::Local::from(ordinal)].name {
+                Some(name) => name.to_string(),
+                None => format!("_{}", ordinal),
+            },
+        )
+        .collect();
+    let to_inferred_condition =
+        |condition: &AbstractValue, message: Option<&String>| InferredCondition {
+            condition: expression_to_source(&condition.domain.expression, &parameter_names),
+            message: message.cloned(),
+            provenance: condition
+                .provenance
+                .iter()
+                .map(|span| span_to_location(tcx, *span))
+                .collect(),
+        };
+    Some(InferredConditions {
+        function: utils::summary_key_str(tcx, def_id),
+        preconditions: summary
+            .preconditions
+            .iter()
+            .map(|(condition, message)| to_inferred_condition(condition, Some(message)))
+            .collect(),
+        postconditions: summary
+            .post_conditions
+            .iter()
+            .map(|condition| to_inferred_condition(condition, None))
+            .collect(),
+    })
+}
+
+/// Writes the given inferred conditions to a JSON file at the given path, in the order of the
+/// keys of their functions. Reports and otherwise ignores failures.
+pub fn write_inferred_conditions(path: &str, mut functions: Vec<InferredConditions>) {
+    functions.sort_by(|f1, f2| f1.function.cmp(&f2.function));
+    write_file(
+        path,
+        serde_json::to_string_pretty(&functions),
+        "inferred contracts",
+    );
+}
+
+/// Writes the serialized contents to a file at the given path.
+/// Reports and otherwise ignores failures.
+fn write_file<E: Display>(path: &str, contents: Result<String, E>, what: &str) {
+    match contents {
+        Ok(contents) => {
+            if let Err(err) = fs::write(path, contents) {
+                println!("unable to write {} to {}: {}", what, path, err);
+            }
+        }
+        Err(err) => println!("unable to serialize {}: {}", what, err),
+    }
+}
+
+/// Returns the file, line and column of the start of the given span.
+fn span_to_location(tcx: &TyCtxt<'_, '_, '_>, span: Span) -> SourceLocation {
+    let loc = tcx.sess.source_map().lookup_char_pos(span.lo());
+    SourceLocation {
+        file: loc.file.name.to_string(),
+        line: loc.line,
+        column: loc.col.0 + 1,
+    }
+}
+
+/// Returns the given expression, which refers to the parameters of a function, the way it would be
+/// written in the source of the function. Operands that are themselves operations are put in
+/// parentheses, so that the result reads the same regardless of operator precedence.
+fn expression_to_source(expression: &Expression, parameter_names: &[String]) -> String {
+    let operand = |domain: &AbstractDomain| match &domain.expression {
+        Expression::CompileTimeConstant(..)
+        | Expression::Reference(..)
+        | Expression::Variable { .. } => expression_to_source(&domain.expression, parameter_names),
+        _ => format!(
+            "({})",
+            expression_to_source(&domain.expression, parameter_names)
+        ),
+    };
+    let binary = |left: &AbstractDomain, operator: &str, right: &AbstractDomain| {
+        format!("{} {} {}", operand(left), operator, operand(right))
+    };
+    match expression {
+        Expression::Top => String::from("unknown"),
+        Expression::Bottom => String::from("unreachable"),
+        Expression::AbstractHeapAddress(ordinal) => format!("heap_block_{}", ordinal),
+        Expression::Add { left, right } => binary(left, "+", right),
+        Expression::AddOverflows { left, right, .. } => {
+            format!("overflows({})", binary(left, "+", right))
+        }
+        Expression::And { left, right } => binary(left, "&&", right),
+        Expression::BitAnd { left, right } => binary(left, "&", right),
+        Expression::BitOr { left, right } => binary(left, "|", right),
+        Expression::BitXor { left, right } => binary(left, "^", right),
+        Expression::CompileTimeConstant(constant) => constant_to_source(constant),
+        Expression::ConditionalExpression {
+            condition,
+            consequent,
+            alternate,
+        } => format!(
+            "if {} {{ {} }} else {{ {} }}",
+            expression_to_source(&condition.expression, parameter_names),
+            expression_to_source(&consequent.expression, parameter_names),
+            expression_to_source(&alternate.expression, parameter_names)
+        ),
+        Expression::Div { left, right } => binary(left, "/", right),
+        Expression::Equals { left, right } => binary(left, "==", right),
+        Expression::GreaterOrEqual { left, right } => binary(left, ">=", right),
+        Expression::GreaterThan { left, right } => binary(left, ">", right),
+        Expression::LessOrEqual { left, right } => binary(left, "<=", right),
+        Expression::LessThan { left, right } => binary(left, "<", right),
+        Expression::Mul { left, right } => binary(left, "*", right),
+        Expression::MulOverflows { left, right, .. } => {
+            format!("overflows({})", binary(left, "*", right))
+        }
+        Expression::Ne { left, right } => binary(left, "!=", right),
+        Expression::Neg { operand: value } => format!("-{}", operand(value)),
+        Expression::Not { operand: value } => format!("!{}", operand(value)),
+        Expression::Or { left, right } => binary(left, "||", right),
+        Expression::Offset { left, right } => {
+            format!("{}.offset({})", operand(left), operand(right))
+        }
+        Expression::Reference(path) => format!("&{}", path_to_source(path, parameter_names)),
+        Expression::Rem { left, right } => binary(left, "%", right),
+        Expression::Shl { left, right } => binary(left, "<<", right),
+        Expression::ShlOverflows { left, right, .. } => {
+            format!("overflows({})", binary(left, "<<", right))
+        }
+        Expression::Shr { left, right, .. } => binary(left, ">>", right),
+        Expression::ShrOverflows { left, right, .. } => {
+            format!("overflows({})", binary(left, ">>", right))
+        }
+        Expression::StringConcat { left, right } => binary(left, "+", right),
+        Expression::Sub { left, right } => binary(left, "-", right),
+        Expression::SubOverflows { left, right, .. } => {
+            format!("overflows({})", binary(left, "-", right))
+        }
+        Expression::UninterpretedCall {
+            callee, arguments, ..
+        } => {
+            let arguments: Vec<String> = arguments
+                .iter()
+                .map(|argument| expression_to_source(&argument.expression, parameter_names))
+                .collect();
+            format!("{}({})", operand(callee), arguments.join(", "))
+        }
+        Expression::Variable { path, .. } => path_to_source(path, parameter_names),
+    }
+}
+
+/// Returns the given constant the way it would be written in source.
+fn constant_to_source(constant: &ConstantDomain) -> String {
+    match constant {
+        ConstantDomain::Bottom | ConstantDomain::Unimplemented => String::from("unknown"),
+        ConstantDomain::Char(ch) => format!("{:?}", ch),
+        ConstantDomain::False => String::from("false"),
+        ConstantDomain::Function {
+            summary_cache_key, ..
+        } => summary_cache_key.clone(),
+        ConstantDomain::I128(value) => value.to_string(),
+        ConstantDomain::F64(bits) => f64::from_bits(*bits).to_string(),
+        ConstantDomain::F32(bits) => f32::from_bits(*bits).to_string(),
+        ConstantDomain::Str(value) => format!("{:?}", value),
+        ConstantDomain::True => String::from("true"),
+        ConstantDomain::U128(value) => value.to_string(),
+    }
+}
+
+/// Returns the given path, which refers to the parameters of a function, the way it would be
+/// written in the source of the function. The result of the function is called "result".
+fn path_to_source(path: &Path, parameter_names: &[String]) -> String {
+    match path {
+        Path::AbstractHeapAddress { ordinal } => format!("heap_block_{}", ordinal),
+        Path::LocalVariable { ordinal: 0 } => String::from("result"),
+        Path::LocalVariable { ordinal } => parameter_names
+            .get(ordinal - 1)
+            .cloned()
+            .unwrap_or_else(|| format!("_{}", ordinal)),
+        Path::StaticVariable {
+            summary_cache_key, ..
+        } => summary_cache_key.clone(),
+        Path::PromotedConstant { ordinal } => format!("promoted_{}", ordinal),
+        Path::QualifiedPath {
+            qualifier,
+            selector,
+            ..
+        } => {
+            let qualifier = path_to_source(qualifier, parameter_names);
+            match &**selector {
+                PathSelector::ArrayLength => format!("{}.len()", qualifier),
+                // References are dereferenced implicitly in most source expressions.
+                PathSelector::Deref => qualifier,
+                PathSelector::Field(ordinal) => format!("{}.{}", qualifier, ordinal),
+                PathSelector::Index(index) => format!(
+                    "{}[{}]",
+                    qualifier,
+                    expression_to_source(&index.domain.expression, parameter_names)
+                ),
+                PathSelector::ConstantIndex {
+                    offset,
+                    from_end: false,
+                    ..
+                } => format!("{}[{}]", qualifier, offset),
+                PathSelector::ConstantIndex {
+                    offset,
+                    from_end: true,
+                    ..
+                } => format!("{}[{}.len() - {}]", qualifier, qualifier, offset),
+                PathSelector::Subslice { from, to } => {
+                    format!("{}[{}..{}.len() - {}]", qualifier, from, qualifier, to)
+                }
+                selector => format!("{}.{:?}", qualifier, selector),
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::expression::Expression;
+use crate::smt_lib;
+use crate::smt_solver::{SmtResult, SmtSolver};
+use crate::solver_process::RunningSolver;
+
+use std::env;
+use std::time::Duration;
+
+/// The environment variable that can be used to point at the cvc5 executable. If it is not set,
+/// cvc5 is looked up on the PATH.
+const CVC5_VARIABLE: &str = "MIRAI_CVC5";
+
+/// The arguments that make cvc5 answer a sequence of SMT-LIB2 queries from its standard input and
+/// produce models for the queries that are satisfiable.
+const CVC5_ARGUMENTS: &str = "--lang=smt2 --incremental --produce-models";
+
+/// A solver that uses CVC5, running in a separate process to which the assertions are sent as
+/// SMT-LIB2 (see smt_lib::query_to_smt_lib). Since no native library is linked into MIRAI, this
+/// works wherever a cvc5 executable is available. Like IsolatedSolver, the process is killed if it
+/// takes longer than the time limit to answer a query and it is restarted when the next query comes
+/// along. Every query starts with a reset, so that queries do not affect each other.
+///
+/// The strategies configured for classes of obligations are used as the SMT-LIB2 logic of their
+/// queries, such as QF_LIA. Queries without a strategy use the logic ALL.
+pub struct Cvc5Solver {
+    /// The shell command that starts cvc5.
+    command: String,
+    /// The longest time cvc5 may take to answer a query.
+    time_limit: Duration,
+    /// The factor by which the time limit is multiplied for the queries that follow.
+    time_limit_factor: usize,
+    /// If set, cvc5 may not use more than this many megabytes of virtual memory.
+    memory_limit_mb: Option<u64>,
+    /// The logic to use for the queries that follow.
+    logic: Option<String>,
+    /// The expressions asserted so far, in all of the nested contexts.
+    assertions: Vec<Expression>,
+    /// For each nested context, the number of assertions that were made before it was created.
+    backtrack_positions: Vec<usize>,
+    /// The cvc5 process, if one is running.
+    process: Option<RunningSolver>,
+}
+
+impl Cvc5Solver {
+    pub fn new(time_limit: Duration, memory_limit_mb: Option<u64>) -> Cvc5Solver {
+        let executable = env::var(CVC5_VARIABLE).unwrap_or_else(|_| String::from("cvc5"));
+        Cvc5Solver {
+            command: format!("{} {}", executable, CVC5_ARGUMENTS),
+            time_limit,
+            time_limit_factor: 1,
+            memory_limit_mb,
+            logic: None,
+            assertions: Vec::new(),
+            backtrack_positions: Vec::new(),
+            process: None,
+        }
+    }
+
+    /// Sends the given commands to cvc5, starting it if need be, and waits for the line that
+    /// answers them. Returns None if cvc5 crashed or timed out, in which case it is killed so
+    /// that the next query starts a fresh one.
+    fn ask(&mut self, commands: &str) -> Option<String> {
+        if self.process.is_none() {
+            self.process = RunningSolver::start(&self.command, self.memory_limit_mb);
+        }
+        let time_limit = self.time_limit * self.time_limit_factor as u32;
+        let response = self.process.as_mut()?.ask(commands.as_bytes(), time_limit);
+        if response.is_none() {
+            info!("cvc5 failed to answer a query");
+            self.stop_process();
+        }
+        response
+    }
+
+    /// Kills the cvc5 process, if it is running.
+    fn stop_process(&mut self) {
+        if let Some(process) = self.process.take() {
+            process.stop();
+        }
+    }
+}
+
+impl Drop for Cvc5Solver {
+    fn drop(&mut self) {
+        self.stop_process();
+    }
+}
+
+impl SmtSolver<Expression> for Cvc5Solver {
+    fn as_debug_string(&self, expression: &Expression) -> String {
+        format!("{:?}", expression)
+    }
+
+    fn assert(&mut self, expression: &Expression) {
+        self.assertions.push(expression.clone());
+    }
+
+    fn backtrack(&mut self) {
+        if let Some(position) = self.backtrack_positions.pop() {
+            self.assertions.truncate(position);
+        }
+    }
+
+    fn get_as_smt_predicate(&mut self, mirai_expression: &Expression) -> Expression {
+        mirai_expression.clone()
+    }
+
+    fn set_backtrack_position(&mut self) {
+        self.backtrack_positions.push(self.assertions.len());
+    }
+
+    fn set_strategy(&mut self, strategy: Option<&str>) {
+        self.logic = strategy.map(String::from);
+    }
+
+    fn set_time_limit_factor(&mut self, factor: usize) {
+        self.time_limit_factor = factor;
+    }
+
+    fn solve(&mut self) -> SmtResult {
+        self.solve_for_model(&[]).0
+    }
+
+    fn solve_for_model(&mut self, expressions: &[Expression]) -> (SmtResult, Vec<Option<String>>) {
+        let logic = self.logic.clone().unwrap_or_else(|| String::from("ALL"));
+        let (commands, model_terms) =
+            smt_lib::query_to_smt_lib(&logic, &self.assertions, expressions);
+        let answer = self.ask(&format!("(reset)\n{}", commands));
+        match answer.as_ref().map(String::as_str) {
+            Some("sat") => {
+                if model_terms.is_empty() {
+                    return (SmtResult::Satisfiable, Vec::new());
+                }
+                let values = self
+                    .ask(&format!("(get-value ({}))\n", model_terms.join(" ")))
+                    .and_then(|response| parse_values(&response))
+                    .unwrap_or_default();
+                (
+                    SmtResult::Satisfiable,
+                    values.into_iter().map(Some).collect(),
+                )
+            }
+            Some("unsat") => (SmtResult::Unsatisfiable, Vec::new()),
+            Some("unknown") | None => (SmtResult::Undefined, Vec::new()),
+            Some(error) => {
+                // cvc5 did not understand the query, for instance because the logic is not one
+                // that it supports. Whatever else it has to say would be mistaken for the answers
+                // to later queries, so it is restarted.
+                info!("cvc5 rejected a query: {}", error);
+                self.stop_process();
+                (SmtResult::Undefined, Vec::new())
+            }
+        }
+    }
+}
+
+/// A term of an s-expression, as written by cvc5.
+enum SExpression {
+    Atom(String),
+    List(Vec<SExpression>),
+}
+
+impl SExpression {
+    /// Parses the s-expression at the start of the given tokens, removing its tokens.
+    fn parse(tokens: &mut Vec<String>) -> Option<SExpression> {
+        if tokens.is_empty() {
+            return None;
+        }
+        let token = tokens.remove(0);
+        match token.as_str() {
+            "(" => {
+                let mut elements = Vec::new();
+                while tokens.first().map(String::as_str) != Some(")") {
+                    elements.push(Self::parse(tokens)?);
+                }
+                tokens.remove(0);
+                Some(SExpression::List(elements))
+            }
+            ")" => None,
+            _ => Some(SExpression::Atom(token)),
+        }
+    }
+
+    /// Returns the value as it would be written in Rust, so that negative numbers, which SMT-LIB2
+    /// writes as (- 1), read as -1.
+    fn to_value_string(&self) -> String {
+        match self {
+            SExpression::Atom(atom) => atom.clone(),
+            SExpression::List(elements) => match elements.as_slice() {
+                [SExpression::Atom(minus), SExpression::Atom(magnitude)] if minus == "-" => {
+                    format!("-{}", magnitude)
+                }
+                _ => {
+                    let elements: Vec<String> =
+                        elements.iter().map(Self::to_value_string).collect();
+                    format!("({})", elements.join(" "))
+                }
+            },
+        }
+    }
+}
+
+/// Returns the values in the response of cvc5 to a get-value command, which is a list of pairs of
+/// a term and its value, or None if the response is not of this form.
+fn parse_values(response: &str) -> Option<Vec<String>> {
+    let mut tokens: Vec<String> = response
+        .replace("(", " ( ")
+        .replace(")", " ) ")
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    match SExpression::parse(&mut tokens)? {
+        SExpression::List(pairs) => pairs
+            .iter()
+            .map(|pair| match pair {
+                SExpression::List(term_and_value) if term_and_value.len() == 2 => {
+                    Some(term_and_value[1].to_value_string())
+                }
+                _ => None,
+            })
+            .collect(),
+        SExpression::Atom(_) => None,
+    }
+}
@@ -4,13 +4,19 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::abstract_domains::AbstractDomain;
-use crate::abstract_value::{self, AbstractValue, Path};
-use crate::expression::Expression;
+use crate::abstract_value::{self, AbstractValue, Path, PathSelector, Tag};
+use crate::constant_domain::ConstantDomain;
+use crate::expression::{Expression, ExpressionType};
+use crate::interval_domain::{self, IntervalDomain};
+use crate::k_limits;
 
 use rpds::HashTrieMap;
 use rustc::mir::BasicBlock;
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
+use std::hash::{Hash, Hasher};
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct Environment {
@@ -47,12 +53,32 @@ impl Environment {
     }
 
     /// Updates the path to value map so that the given path now points to the given value.
+    /// If the path is part of a heap block that is shared by Rc or Arc pointers, the update is weak,
+    /// which means that the path may still have its old value afterwards.
     pub fn update_value_at(&mut self, path: Path, value: AbstractValue) {
         debug!("updating value of {:?} to {:?}", path, value);
         if value.is_bottom() {
             self.value_map = self.value_map.remove(&path);
             return;
         }
+        let value = if self.is_in_shared_block(&path) {
+            let top = abstract_value::TOP;
+            value.join(self.value_at(&path).unwrap_or(&top), &top)
+        } else {
+            value
+        };
+        if let Path::QualifiedPath {
+            qualifier,
+            selector,
+            ..
+        } = &path
+        {
+            if let PathSelector::Index(index) = &**selector {
+                if !self.update_other_elements(qualifier, index, &value) {
+                    return;
+                }
+            }
+        }
         if let Some((join_condition, true_path, false_path)) = self.try_to_split(&path) {
             // If path is an abstraction that can match more than one path, we need to do weak updates.
             let top = abstract_value::TOP;
@@ -67,6 +93,272 @@ impl Environment {
         self.value_map = self.value_map.insert(path, value);
     }
 
+    /// Returns true if the given path is part of a heap block that is shared by Rc or Arc pointers
+    /// (see Tag::StrongCount) and that may have escaped from the function being analyzed. Such a
+    /// block may be updated through pointers that the function does not know about, possibly by
+    /// other threads, so updates of it must not replace what is known about it. The number of
+    /// pointers to a block that has not escaped is known, since all of them are local to the
+    /// function, so such a block is updated like any other heap block. The number becomes unknown
+    /// once a pointer is moved into a function that is not modeled, or stored in a place that is
+    /// reachable from a parameter or a static variable.
+    fn is_in_shared_block(&self, path: &Path) -> bool {
+        let root = path.get_root();
+        if let Path::AbstractHeapAddress { .. } = root {
+            let count_path = Path::QualifiedPath {
+                length: root.path_length() + 1,
+                qualifier: box root.clone(),
+                selector: box PathSelector::Tag(Tag::StrongCount),
+            };
+            if *path == count_path {
+                return false;
+            }
+            match self.value_at(&count_path) {
+                Some(count) => match count.domain.expression {
+                    Expression::CompileTimeConstant(ConstantDomain::U128(..)) => false,
+                    _ => true,
+                },
+                None => false,
+            }
+        } else {
+            false
+        }
+    }
+
+    /// Updates the values of the elements of the array at array_path, other than the element at
+    /// the given index, to reflect that the given value is stored at the index. Elements that may
+    /// be at the same index get conditional values. If the index may select an element that has no
+    /// path of its own, the value is smashed into the summary element of the array.
+    /// Returns false if the element at the index is not tracked individually, which is the case
+    /// for constant indices that are not less than k_limits::MAX_TRACKED_ARRAY_ELEMENTS.
+    fn update_other_elements(
+        &mut self,
+        array_path: &Path,
+        index: &AbstractValue,
+        value: &AbstractValue,
+    ) -> bool {
+        let other_elements: Vec<(Path, AbstractValue)> = self
+            .value_map
+            .iter()
+            .filter_map(|(path, element_value)| match path {
+                Path::QualifiedPath {
+                    qualifier,
+                    selector,
+                    ..
+                } if **qualifier == *array_path => match &**selector {
+                    PathSelector::Index(element_index) if **element_index != *index => {
+                        let same_index = index.equals(element_index, None);
+                        if same_index.as_bool_if_known().unwrap_or(true) {
+                            Some((path.clone(), value.join(element_value, &same_index)))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        for (path, element_value) in other_elements {
+            self.value_map = self.value_map.insert(path, element_value);
+        }
+        let limit = k_limits::MAX_TRACKED_ARRAY_ELEMENTS as i128;
+        let interval = self.get_index_interval(array_path, index);
+        let is_constant_index = match (interval.lower_bound(), interval.upper_bound()) {
+            (Some(lower_bound), Some(upper_bound)) => lower_bound == upper_bound,
+            _ => false,
+        };
+        let lower_bound = cmp::max(interval.lower_bound().unwrap_or(0), 0);
+        if is_constant_index && lower_bound < limit {
+            // The element is tracked individually.
+            return true;
+        }
+        let may_select_untracked_element = match interval.upper_bound() {
+            Some(upper_bound) if upper_bound < limit => (lower_bound..=upper_bound)
+                .any(|i| self.value_at(&Self::element_path(array_path, i)).is_none()),
+            _ => !interval.is_bottom(),
+        };
+        if may_select_untracked_element {
+            let smashed_path = Self::smashed_element_path(array_path);
+            // If there is no smashed element yet, the untracked elements have their initial
+            // values, which are not known, so their summary is not known either.
+            let smashed_value = match self.value_at(&smashed_path) {
+                Some(old_value) => old_value.widen(value, &abstract_value::TRUE),
+                None => abstract_value::TOP,
+            };
+            self.value_map = self.value_map.insert(smashed_path, smashed_value);
+        }
+        !is_constant_index
+    }
+
+    /// Stores the given values as the elements of the array at array_path, in order, replacing
+    /// whatever was known about its elements. Elements beyond the first
+    /// k_limits::MAX_TRACKED_ARRAY_ELEMENTS are not tracked individually, their values are
+    /// widened into the summary element of the array.
+    pub fn initialize_elements_at(
+        &mut self,
+        array_path: &Path,
+        values: impl Iterator<Item = AbstractValue>,
+    ) {
+        let stale_paths: Vec<Path> = self
+            .value_map
+            .keys()
+            .filter(|p| p.is_rooted_by(array_path))
+            .cloned()
+            .collect();
+        for stale_path in stale_paths.iter() {
+            self.value_map = self.value_map.remove(stale_path);
+        }
+        let mut smashed_value: Option<AbstractValue> = None;
+        for (i, value) in values.enumerate() {
+            if i < k_limits::MAX_TRACKED_ARRAY_ELEMENTS {
+                self.value_map = self
+                    .value_map
+                    .insert(Self::element_path(array_path, i as i128), value);
+            } else {
+                smashed_value = Some(match smashed_value {
+                    Some(smashed_value) => smashed_value.widen(&value, &abstract_value::TRUE),
+                    None => value,
+                });
+            }
+        }
+        if let Some(smashed_value) = smashed_value {
+            self.value_map = self
+                .value_map
+                .insert(Self::smashed_element_path(array_path), smashed_value);
+        }
+    }
+
+    /// Returns the value of the element of an array or slice that the given path selects, if the
+    /// path has no value of its own, but the value follows from the values of the elements that
+    /// are tracked individually and the summary element of the array. If the index is not known,
+    /// this is a conditional expression that selects one of these values.
+    pub fn element_value_at(&self, path: &Path) -> Option<AbstractValue> {
+        let (array_path, index) = match path {
+            Path::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } => match &**selector {
+                PathSelector::Index(index) => (&**qualifier, &**index),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let limit = k_limits::MAX_TRACKED_ARRAY_ELEMENTS as i128;
+        let smashed_value = self.value_at(&Self::smashed_element_path(array_path));
+        let interval = self.get_index_interval(array_path, index);
+        let lower_bound = cmp::max(interval.lower_bound().unwrap_or(0), 0);
+        let upper_bound = match interval.upper_bound() {
+            Some(upper_bound) if upper_bound < limit => upper_bound,
+            _ => limit - 1,
+        };
+        let mut result = match interval.upper_bound() {
+            Some(upper_bound) if upper_bound < limit => None,
+            _ => Some(smashed_value?.clone()),
+        };
+        for i in (lower_bound..=upper_bound).rev() {
+            let element_value = self
+                .value_at(&Self::element_path(array_path, i))
+                .or(smashed_value)?;
+            result = Some(match result {
+                Some(other_value) => {
+                    let element_index: AbstractValue = ConstantDomain::U128(i as u128).into();
+                    element_value.join(&other_value, &index.equals(&element_index, None))
+                }
+                None => element_value.clone(),
+            });
+        }
+        result
+    }
+
+    /// Returns the interval of the given index into the array at array_path, limited to the
+    /// indices of the array if its length is known.
+    fn get_index_interval(&self, array_path: &Path, index: &AbstractValue) -> IntervalDomain {
+        let interval = index.domain.get_as_interval();
+        let length_path = Path::QualifiedPath {
+            length: array_path.path_length() + 1,
+            qualifier: box array_path.clone(),
+            selector: box PathSelector::ArrayLength,
+        };
+        match self
+            .value_at(&length_path)
+            .and_then(|length| length.domain.get_as_interval().upper_bound())
+        {
+            Some(length) => interval.intersect(&IntervalDomain::new(0, length - 1)),
+            None => interval,
+        }
+    }
+
+    /// Returns the path of the element with the given constant index of the array at array_path.
+    fn element_path(array_path: &Path, index: i128) -> Path {
+        let index_value: AbstractValue = ConstantDomain::U128(index as u128).into();
+        Path::QualifiedPath {
+            length: array_path.path_length() + 1,
+            qualifier: box array_path.clone(),
+            selector: box PathSelector::Index(box index_value),
+        }
+    }
+
+    /// Returns the path of the summary of the elements of the array at array_path that are not
+    /// tracked individually.
+    fn smashed_element_path(array_path: &Path) -> Path {
+        Path::QualifiedPath {
+            length: array_path.path_length() + 1,
+            qualifier: box array_path.clone(),
+            selector: box PathSelector::SmashedElement,
+        }
+    }
+
+    /// Removes the value at the given path, as well as the values of all paths rooted by it.
+    /// Subsequent lookups of these paths will produce fresh unknown values.
+    pub fn forget_value_at(&mut self, path: &Path) {
+        let stale_paths: Vec<Path> = self
+            .value_map
+            .keys()
+            .filter(|p| *p == path || p.is_rooted_by(path))
+            .cloned()
+            .collect();
+        for stale_path in stale_paths.iter() {
+            self.value_map = self.value_map.remove(stale_path);
+        }
+    }
+
+    /// Returns the ordinals of the heap blocks that can be reached from the return value, the
+    /// parameters or static variables. These blocks escape the function that owns this environment.
+    /// All other heap blocks are allocated locally and are not visible to callers once the
+    /// function returns, so they need not be part of its summary.
+    pub fn get_escaping_heap_addresses(&self, argument_count: usize) -> HashSet<usize> {
+        let mut escaping = HashSet::new();
+        for (path, value) in self.value_map.iter() {
+            let is_visible_to_caller = match path.get_root() {
+                Path::LocalVariable { ordinal } => *ordinal <= argument_count,
+                Path::StaticVariable { .. } => true,
+                _ => false,
+            };
+            if is_visible_to_caller {
+                value.domain.expression.record_heap_addresses(&mut escaping);
+            }
+        }
+        // Heap blocks referenced from the contents of escaping heap blocks also escape.
+        loop {
+            let mut reachable = HashSet::new();
+            for (path, value) in self.value_map.iter() {
+                if let Path::AbstractHeapAddress { ordinal } = path.get_root() {
+                    if escaping.contains(ordinal) {
+                        value
+                            .domain
+                            .expression
+                            .record_heap_addresses(&mut reachable);
+                    }
+                }
+            }
+            if reachable.is_subset(&escaping) {
+                return escaping;
+            }
+            escaping.extend(reachable);
+        }
+    }
+
     /// If the path contains an abstract value that was constructed with a join, the path is
     /// concretized into two paths where the abstract value is replaced by the consequent
     /// and alternate, respectively. These paths can then be weakly updated to reflect the
@@ -145,6 +437,35 @@ impl Environment {
         }
     }
 
+    /// Puts the values of all paths, along with the entry and exit conditions, into their
+    /// canonical forms (see AbstractDomain::canonicalize). This is a form of global value
+    /// numbering, where the canonical form of a value serves as its number: values that are
+    /// computed differently in different blocks, but are equal, end up with the same expression.
+    /// Joins then keep such values as they are, rather than turning them into conditional
+    /// expressions, and the conditions sent to the solver get smaller.
+    pub fn number_values(&mut self) {
+        let mut numbers: HashMap<AbstractDomain, AbstractDomain> = HashMap::new();
+        let mut number = |value: &AbstractValue| -> AbstractValue {
+            let domain = numbers
+                .entry(value.domain.clone())
+                .or_insert_with(|| value.domain.canonicalize())
+                .clone();
+            AbstractValue {
+                provenance: value.provenance.clone(),
+                domain,
+            }
+        };
+        let mut value_map: HashTrieMap<Path, AbstractValue> = HashTrieMap::default();
+        for (path, value) in self.value_map.iter() {
+            value_map = value_map.insert(path.clone(), number(value));
+        }
+        self.value_map = value_map;
+        self.entry_condition = number(&self.entry_condition);
+        for condition in self.exit_conditions.values_mut() {
+            *condition = number(condition);
+        }
+    }
+
     /// Returns an environment with a path for every entry in self and other and an associated
     /// value that is the join of self.value_at(path) and other.value_at(path)
     pub fn join(&self, other: &Environment, join_condition: &AbstractValue) -> Environment {
@@ -167,16 +488,33 @@ impl Environment {
     ) -> Environment {
         let value_map1 = &self.value_map;
         let value_map2 = &other.value_map;
+        let (induction_values, head_counts) = self.get_induction_values(other, join_condition);
         let mut value_map: HashTrieMap<Path, AbstractValue> = HashTrieMap::default();
         for (path, val1) in value_map1.iter() {
             let p = path.clone();
             match value_map2.get(path) {
                 Some(val2) => {
-                    value_map = value_map.insert(p, join_or_widen(&val1, &val2, &join_condition));
+                    let val = if let Some(val) = induction_values.get(path) {
+                        val.clone()
+                    } else if val1.domain != val2.domain
+                        && (Self::refers_to_any(val1, &head_counts)
+                            || Self::refers_to_any(val2, &head_counts))
+                    {
+                        // The value refers to the iteration count of a previous iteration, which
+                        // is no longer distinguishable from the count of the current iteration.
+                        abstract_value::TOP
+                    } else {
+                        join_or_widen(&val1, &val2, &join_condition)
+                    };
+                    value_map = value_map.insert(p, val);
                 }
                 None => {
                     assert!(!val1.is_bottom());
-                    let val = join_or_widen(&val1, &abstract_value::BOTTOM, &join_condition);
+                    let val = if Self::refers_to_any(val1, &head_counts) {
+                        abstract_value::TOP
+                    } else {
+                        join_or_widen(&val1, &abstract_value::BOTTOM, &join_condition)
+                    };
                     if !val.is_bottom() {
                         value_map = value_map.insert(p, val);
                     }
@@ -187,7 +525,11 @@ impl Environment {
             if !value_map1.contains_key(path) {
                 assert!(!val2.is_bottom());
                 let p = path.clone();
-                let val = join_or_widen(&abstract_value::BOTTOM, &val2, &join_condition);
+                let val = if Self::refers_to_any(val2, &head_counts) {
+                    abstract_value::TOP
+                } else {
+                    join_or_widen(&abstract_value::BOTTOM, &val2, &join_condition)
+                };
                 if !val.is_bottom() {
                     value_map = value_map.insert(p, val);
                 }
@@ -200,6 +542,324 @@ impl Environment {
         }
     }
 
+    /// Iterators over collections keep track of how many elements they have produced so far
+    /// (see PathSelector::IterationCount). At the head of a loop, the count that flows in from the
+    /// loop entry differs from the count that flows in along the back edge. Rather than joining
+    /// the two, which soon leads to widening and the loss of all information about the count,
+    /// the count at the loop head becomes the unknown, but named, value of its path. Integer values
+    /// that differ from the count by the same constant on both incoming edges, such as the number
+    /// of elements processed so far, become that value plus the constant.
+    ///
+    /// Returns the values of these induction variables, along with the paths of the counts.
+    /// Self is the state at the end of an edge taken when join_condition is true and
+    /// other is the state at the end of an edge taken when other.entry_condition is true.
+    fn get_induction_values(
+        &self,
+        other: &Environment,
+        join_condition: &AbstractValue,
+    ) -> (HashMap<Path, AbstractValue>, Vec<Path>) {
+        let mut induction_values = HashMap::new();
+        let mut head_counts = Vec::new();
+        for (count_path, count1) in self.value_map.iter() {
+            if let Path::QualifiedPath { selector, .. } = count_path {
+                if **selector != PathSelector::IterationCount {
+                    continue;
+                }
+            } else {
+                continue;
+            }
+            let count2 = match other.value_at(count_path) {
+                Some(count2) if count1.domain != count2.domain => count2,
+                _ => continue,
+            };
+            let count1 = count1.domain.refine_with(&join_condition.domain);
+            let count2 = count2.domain.refine_with(&other.entry_condition.domain);
+            let head_count: AbstractValue = Expression::Variable {
+                path: box count_path.clone(),
+                var_type: ExpressionType::Usize,
+            }
+            .into();
+            for (path, val1) in self.value_map.iter() {
+                let val2 = match other.value_at(path) {
+                    Some(val2) if val1.domain != val2.domain => val2,
+                    _ => continue,
+                };
+                let offset1 = val1
+                    .domain
+                    .refine_with(&join_condition.domain)
+                    .offset_from(&count1);
+                let offset2 = val2
+                    .domain
+                    .refine_with(&other.entry_condition.domain)
+                    .offset_from(&count2);
+                match (offset1, offset2) {
+                    (Some(0), Some(0)) => {
+                        induction_values.insert(path.clone(), head_count.clone());
+                    }
+                    (Some(offset1), Some(offset2)) if offset1 == offset2 => {
+                        let offset: AbstractValue = ConstantDomain::I128(offset1).into();
+                        induction_values.insert(path.clone(), head_count.add(&offset, None));
+                    }
+                    _ => {}
+                }
+            }
+            head_counts.push(count_path.clone());
+        }
+        (induction_values, head_counts)
+    }
+
+    /// Loops that add up values, such as `for x in xs { sum += x }`, increase an accumulator by a
+    /// bounded amount on every iteration. Joining the values of the accumulator that flow into the
+    /// loop head soon leads to widening and the loss of all information about it, so that overflows
+    /// of the accumulator cannot be ruled out. Instead, the accumulator at the loop head becomes the
+    /// unknown, but named, value of its path and the entry condition of the loop head records that
+    /// this value lies between the initial value plus the iteration count times the smallest
+    /// increment and the initial value plus the count times the largest increment. Together with a
+    /// bound on the number of iterations, this allows the solver to show, for example, that the sum
+    /// of fewer than 2^16 values, each less than 2^16, fits in a u32.
+    ///
+    /// Self is the state at the head of a loop, computed by joining entry, the state at the end of
+    /// the edge into the loop, taken when entry_condition is true, with back_edge, the state at the
+    /// end of the edge from the loop body, taken when back_edge_condition is true. The accumulators
+    /// are variables for which get_type returns an integer type.
+    pub fn bound_accumulators(
+        &mut self,
+        entry: &Environment,
+        entry_condition: &AbstractValue,
+        back_edge: &Environment,
+        back_edge_condition: &AbstractValue,
+        get_type: &dyn Fn(&Path) -> Option<ExpressionType>,
+    ) {
+        // Find the iteration count of the loop, which get_induction_values has turned into the
+        // value of its path at the loop head, along with the count on entry to the loop.
+        let mut count = None;
+        for (count_path, head_count) in self.value_map.iter() {
+            if let Path::QualifiedPath { selector, .. } = count_path {
+                if **selector != PathSelector::IterationCount {
+                    continue;
+                }
+            } else {
+                continue;
+            }
+            match &head_count.domain.expression {
+                Expression::Variable { path, .. } if **path == *count_path => {}
+                _ => continue,
+            }
+            let initial_count = entry
+                .value_at(count_path)
+                .map(|c| c.domain.refine_with(&entry_condition.domain))
+                .and_then(|c| c.as_constant_integer());
+            if let Some(initial_count) = initial_count {
+                count = Some((head_count.clone(), initial_count));
+                break;
+            }
+        }
+        let (head_count, initial_count) = match count {
+            Some(count) => count,
+            None => return,
+        };
+        let iterations = if initial_count == 0 {
+            head_count
+        } else {
+            head_count.sub(&ConstantDomain::I128(initial_count).into(), None)
+        };
+        for (path, back_edge_value) in back_edge.value_map.iter() {
+            let var_type = match get_type(path) {
+                Some(var_type) => var_type,
+                None => continue,
+            };
+            let entry_value = match entry.value_at(path) {
+                Some(entry_value) => entry_value.domain.refine_with(&entry_condition.domain),
+                None => continue,
+            };
+            if entry_value.expression.refers_to_variable(path) {
+                continue;
+            }
+            let mut head_value: AbstractValue = Expression::Variable {
+                path: box path.clone(),
+                var_type,
+            }
+            .into();
+            let back_edge_value = back_edge_value
+                .domain
+                .refine_with(&back_edge_condition.domain);
+            let increment = match &back_edge_value.expression {
+                Expression::Add { left, right }
+                    if **left == head_value.domain || **left == entry_value =>
+                {
+                    right
+                }
+                // The canonical form of the sum may have the accumulator as its second operand.
+                Expression::Add { left, right }
+                    if **right == head_value.domain || **right == entry_value =>
+                {
+                    left
+                }
+                _ => continue,
+            };
+            let initial_interval = entry_value.get_as_interval();
+            let increment_interval = Self::get_bounds(increment);
+            let (initial_lower, initial_upper, increment_lower, increment_upper) = match (
+                initial_interval.lower_bound(),
+                initial_interval.upper_bound(),
+                increment_interval.lower_bound(),
+                increment_interval.upper_bound(),
+            ) {
+                (Some(il), Some(iu), Some(el), Some(eu)) => (il, iu, el, eu),
+                _ => continue,
+            };
+            let mut lower_bound = iterations
+                .mul(&ConstantDomain::I128(increment_lower).into(), None)
+                .add(&ConstantDomain::I128(initial_lower).into(), None);
+            let mut upper_bound = iterations
+                .mul(&ConstantDomain::I128(increment_upper).into(), None)
+                .add(&ConstantDomain::I128(initial_upper).into(), None);
+            let bounds = head_value
+                .greater_or_equal(&mut lower_bound, None)
+                .and(&head_value.less_or_equal(&mut upper_bound, None), None);
+            self.entry_condition = self.entry_condition.and(&bounds, None);
+            self.update_value_at(path.clone(), head_value);
+        }
+    }
+
+    /// Returns an interval that includes all of the values of the given domain. Unknown values of
+    /// integer variables lie within the range of their types.
+    fn get_bounds(domain: &AbstractDomain) -> IntervalDomain {
+        let interval = domain.get_as_interval();
+        if !interval.is_top() {
+            return interval;
+        }
+        if let Expression::Variable { var_type, .. } = &domain.expression {
+            if var_type.is_integer() && var_type.bit_length() < 128 {
+                return Self::get_type_bounds(var_type);
+            }
+        }
+        interval
+    }
+
+    /// Returns the interval of the values of the given integer type. The bounds of 128 bit
+    /// types are treated as missing.
+    fn get_type_bounds(var_type: &ExpressionType) -> IntervalDomain {
+        if var_type.bit_length() >= 128 {
+            return if var_type.is_signed_integer() {
+                interval_domain::TOP
+            } else {
+                IntervalDomain::new(0, std::i128::MAX)
+            };
+        }
+        let bits = u32::from(var_type.bit_length());
+        if var_type.is_signed_integer() {
+            IntervalDomain::new(-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            IntervalDomain::new(0, (1i128 << bits) - 1)
+        }
+    }
+
+    /// Loops that count, such as `while i < n { i += 1 }`, change the values of their counters on
+    /// every iteration, so that joining the values at the loop head soon leads to widening and the
+    /// loss of all information about them. Instead, once the fixed point loop has joined them for
+    /// long enough, a counter at the loop head becomes the unknown, but named, value of its path and
+    /// the entry condition of the loop head records that this value lies within an interval. The
+    /// interval starts out as the interval of the value on entry to the loop. Whenever the value at
+    /// the end of the back edge, computed from a head value within the interval, does not lie within
+    /// it, the bounds that grow move out to the nearest of the given thresholds, but never beyond the
+    /// range of the type of the counter. Since there are finitely many thresholds, this reaches a
+    /// fixed point, while keeping bounds such as 0 that make the counter usable as an index.
+    ///
+    /// Self, entry and back_edge are as for bound_accumulators. The intervals of the counters of
+    /// the loop are kept in widened_intervals between calls.
+    #[allow(clippy::too_many_arguments)]
+    pub fn widen_to_thresholds(
+        &mut self,
+        entry: &Environment,
+        entry_condition: &AbstractValue,
+        back_edge: &Environment,
+        back_edge_condition: &AbstractValue,
+        thresholds: &[i128],
+        widened_intervals: &mut HashMap<Path, IntervalDomain>,
+        get_type: &dyn Fn(&Path) -> Option<ExpressionType>,
+    ) {
+        for (path, back_edge_value) in back_edge.value_map.iter() {
+            match self.value_at(path) {
+                Some(head_value) if head_value.is_top() => {}
+                _ => continue,
+            }
+            let var_type = match get_type(path) {
+                Some(var_type) => var_type,
+                None => continue,
+            };
+            let entry_interval = match entry.value_at(path) {
+                Some(entry_value) => entry_value
+                    .domain
+                    .refine_with(&entry_condition.domain)
+                    .get_as_interval(),
+                None => continue,
+            };
+            if entry_interval.is_bottom() || entry_interval.is_top() {
+                continue;
+            }
+            let head_interval = widened_intervals
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| entry_interval.clone());
+            let back_edge_interval = back_edge_value
+                .domain
+                .refine_with(&back_edge_condition.domain)
+                .get_as_interval_assuming(path, &head_interval);
+            if back_edge_interval.is_bottom() || back_edge_interval.is_top() {
+                widened_intervals.remove(path);
+                continue;
+            }
+            let type_bounds = Self::get_type_bounds(&var_type);
+            let interval = head_interval
+                .widen_with_thresholds(&entry_interval.widen(&back_edge_interval), thresholds)
+                .intersect(&type_bounds);
+            let (lower, upper) = match (interval.lower_bound(), interval.upper_bound()) {
+                (Some(lower), Some(upper)) if lower <= upper => (lower, upper),
+                _ => {
+                    widened_intervals.remove(path);
+                    continue;
+                }
+            };
+            widened_intervals.insert(path.clone(), interval);
+            let mut head_value: AbstractValue = Expression::Variable {
+                path: box path.clone(),
+                var_type,
+            }
+            .into();
+            let mut lower_bound: AbstractValue = ConstantDomain::I128(lower).into();
+            let mut upper_bound: AbstractValue = ConstantDomain::I128(upper).into();
+            let bounds = head_value
+                .greater_or_equal(&mut lower_bound, None)
+                .and(&head_value.less_or_equal(&mut upper_bound, None), None);
+            self.entry_condition = self.entry_condition.and(&bounds, None);
+            self.update_value_at(path.clone(), head_value);
+        }
+    }
+
+    /// True if the value depends on the unknown value of any of the given paths.
+    fn refers_to_any(value: &AbstractValue, paths: &[Path]) -> bool {
+        paths
+            .iter()
+            .any(|path| value.domain.expression.refers_to_variable(path))
+    }
+
+    /// Returns a hash of the entry condition and the values of self. Equal environments have the
+    /// same fingerprint, whatever the order in which their values were added, so the fingerprint
+    /// can be used to look up what is known about an environment before comparing it.
+    pub fn fingerprint(&self) -> u64 {
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+        self.value_map
+            .iter()
+            .fold(hash_of(&self.entry_condition), |fingerprint, entry| {
+                fingerprint.wrapping_add(hash_of(&entry))
+            })
+    }
+
     /// Returns true if for every path, self.value_at(path).subset(other.value_at(path))
     pub fn subset(&self, other: &Environment) -> bool {
         let value_map1 = &self.value_map;
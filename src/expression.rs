@@ -7,6 +7,8 @@ use crate::abstract_domains::AbstractDomain;
 use crate::abstract_value::Path;
 use crate::constant_domain::ConstantDomain;
 
+use std::collections::HashSet;
+
 /// Closely based on the expressions found in MIR.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum Expression {
@@ -231,6 +233,14 @@ pub enum Expression {
         result_type: ExpressionType,
     },
 
+    /// An expression that is the string left followed by the string right.
+    StringConcat {
+        // The value of the left operand.
+        left: Box<AbstractDomain>,
+        // The value of the right operand.
+        right: Box<AbstractDomain>,
+    },
+
     /// An expression that is the right subtracted from left. -
     Sub {
         // The value of the left operand.
@@ -249,6 +259,19 @@ pub enum Expression {
         result_type: ExpressionType,
     },
 
+    /// The result of calling a function whose semantics are not modeled, other than that it
+    /// deterministically computes its result from the values of its arguments.
+    /// This keeps track of how values flow into the result, without having to analyze the
+    /// function body.
+    UninterpretedCall {
+        // The value of the function that is called.
+        callee: Box<AbstractDomain>,
+        // The values of the arguments, or of what they refer to.
+        arguments: Vec<AbstractDomain>,
+        // The type of the result of the call.
+        result_type: ExpressionType,
+    },
+
     /// The unknown value of a place in memory.
     /// This is distinct from Top in that we known something: the place and the type.
     /// This is a useful distinction because it allows us to simplify some expressions
@@ -260,6 +283,634 @@ pub enum Expression {
     },
 }
 
+impl Expression {
+    /// Adds the ordinals of all of the heap blocks that are referenced by this expression
+    /// to the given set.
+    pub fn record_heap_addresses(&self, result: &mut HashSet<usize>) {
+        match self {
+            Expression::AbstractHeapAddress(ordinal) => {
+                result.insert(*ordinal);
+            }
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.record_heap_addresses(result);
+                right.expression.record_heap_addresses(result);
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.record_heap_addresses(result);
+                consequent.expression.record_heap_addresses(result);
+                alternate.expression.record_heap_addresses(result);
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                operand.expression.record_heap_addresses(result);
+            }
+            Expression::Reference(path) => {
+                if let Path::AbstractHeapAddress { ordinal } = path.get_root() {
+                    result.insert(*ordinal);
+                }
+            }
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                callee.expression.record_heap_addresses(result);
+                for argument in arguments.iter() {
+                    argument.expression.record_heap_addresses(result);
+                }
+            }
+            Expression::Top
+            | Expression::Bottom
+            | Expression::CompileTimeConstant(..)
+            | Expression::Variable { .. } => (),
+        }
+    }
+
+    /// Returns a copy of this expression in which the ordinal of every heap block, including
+    /// the roots of the paths that it refers to, is replaced by the ordinal that the given
+    /// function returns for it.
+    pub fn renumber_heap_addresses(&self, renumber: &mut dyn FnMut(usize) -> usize) -> Expression {
+        fn renumber_operand(
+            operand: &AbstractDomain,
+            renumber: &mut dyn FnMut(usize) -> usize,
+        ) -> Box<AbstractDomain> {
+            box AbstractDomain::from(operand.expression.renumber_heap_addresses(renumber))
+        }
+        match self {
+            Expression::AbstractHeapAddress(ordinal) => {
+                Expression::AbstractHeapAddress(renumber(*ordinal))
+            }
+            Expression::Add { left, right } => Expression::Add {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::And { left, right } => Expression::And {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::BitAnd { left, right } => Expression::BitAnd {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::BitOr { left, right } => Expression::BitOr {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::BitXor { left, right } => Expression::BitXor {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Div { left, right } => Expression::Div {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Equals { left, right } => Expression::Equals {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::GreaterOrEqual { left, right } => Expression::GreaterOrEqual {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::GreaterThan { left, right } => Expression::GreaterThan {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::LessOrEqual { left, right } => Expression::LessOrEqual {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::LessThan { left, right } => Expression::LessThan {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Mul { left, right } => Expression::Mul {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Ne { left, right } => Expression::Ne {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Or { left, right } => Expression::Or {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Offset { left, right } => Expression::Offset {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Rem { left, right } => Expression::Rem {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Shl { left, right } => Expression::Shl {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::StringConcat { left, right } => Expression::StringConcat {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::Sub { left, right } => Expression::Sub {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+            },
+            Expression::AddOverflows {
+                left,
+                right,
+                result_type,
+            } => Expression::AddOverflows {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+                result_type: result_type.clone(),
+            },
+            Expression::MulOverflows {
+                left,
+                right,
+                result_type,
+            } => Expression::MulOverflows {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+                result_type: result_type.clone(),
+            },
+            Expression::ShlOverflows {
+                left,
+                right,
+                result_type,
+            } => Expression::ShlOverflows {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+                result_type: result_type.clone(),
+            },
+            Expression::Shr {
+                left,
+                right,
+                result_type,
+            } => Expression::Shr {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+                result_type: result_type.clone(),
+            },
+            Expression::ShrOverflows {
+                left,
+                right,
+                result_type,
+            } => Expression::ShrOverflows {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+                result_type: result_type.clone(),
+            },
+            Expression::SubOverflows {
+                left,
+                right,
+                result_type,
+            } => Expression::SubOverflows {
+                left: renumber_operand(left, renumber),
+                right: renumber_operand(right, renumber),
+                result_type: result_type.clone(),
+            },
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => Expression::ConditionalExpression {
+                condition: renumber_operand(condition, renumber),
+                consequent: renumber_operand(consequent, renumber),
+                alternate: renumber_operand(alternate, renumber),
+            },
+            Expression::Neg { operand } => Expression::Neg {
+                operand: renumber_operand(operand, renumber),
+            },
+            Expression::Not { operand } => Expression::Not {
+                operand: renumber_operand(operand, renumber),
+            },
+            Expression::Reference(path) => {
+                Expression::Reference(path.renumber_heap_addresses(renumber))
+            }
+            Expression::UninterpretedCall {
+                callee,
+                arguments,
+                result_type,
+            } => Expression::UninterpretedCall {
+                callee: renumber_operand(callee, renumber),
+                arguments: arguments
+                    .iter()
+                    .map(|argument| *renumber_operand(argument, renumber))
+                    .collect(),
+                result_type: result_type.clone(),
+            },
+            Expression::Variable { path, var_type } => Expression::Variable {
+                path: box path.renumber_heap_addresses(renumber),
+                var_type: var_type.clone(),
+            },
+            Expression::Top | Expression::Bottom | Expression::CompileTimeConstant(..) => {
+                self.clone()
+            }
+        }
+    }
+
+    /// Adds the Expression::Variable nodes of this expression that are not already in the given
+    /// list to it, in the order in which they occur.
+    pub fn record_variables(&self, result: &mut Vec<Expression>) {
+        match self {
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.record_variables(result);
+                right.expression.record_variables(result);
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.record_variables(result);
+                consequent.expression.record_variables(result);
+                alternate.expression.record_variables(result);
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                operand.expression.record_variables(result);
+            }
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                callee.expression.record_variables(result);
+                for argument in arguments.iter() {
+                    argument.expression.record_variables(result);
+                }
+            }
+            Expression::Variable { .. } => {
+                if !result.contains(self) {
+                    result.push(self.clone());
+                }
+            }
+            Expression::Top
+            | Expression::Bottom
+            | Expression::AbstractHeapAddress(..)
+            | Expression::CompileTimeConstant(..)
+            | Expression::Reference(..) => (),
+        }
+    }
+
+    /// Returns true if this expression multiplies, divides or shifts two values that are not
+    /// compile time constants. Such expressions are outside of linear arithmetic.
+    pub fn is_nonlinear(&self) -> bool {
+        match self {
+            Expression::Div { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. } => {
+                let is_constant = |operand: &Expression| match operand {
+                    Expression::CompileTimeConstant(..) => true,
+                    _ => false,
+                };
+                (!is_constant(&left.expression) && !is_constant(&right.expression))
+                    || left.expression.is_nonlinear()
+                    || right.expression.is_nonlinear()
+            }
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.is_nonlinear() || right.expression.is_nonlinear()
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.is_nonlinear()
+                    || consequent.expression.is_nonlinear()
+                    || alternate.expression.is_nonlinear()
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                operand.expression.is_nonlinear()
+            }
+            Expression::UninterpretedCall { arguments, .. } => arguments
+                .iter()
+                .any(|argument| argument.expression.is_nonlinear()),
+            Expression::Top
+            | Expression::Bottom
+            | Expression::AbstractHeapAddress(..)
+            | Expression::CompileTimeConstant(..)
+            | Expression::Reference(..)
+            | Expression::Variable { .. } => false,
+        }
+    }
+
+    /// Returns true if this expression depends on the unknown value of the place identified by
+    /// the given path, i.e. if it contains Expression::Variable for the path.
+    pub fn refers_to_variable(&self, var_path: &Path) -> bool {
+        match self {
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.refers_to_variable(var_path)
+                    || right.expression.refers_to_variable(var_path)
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.refers_to_variable(var_path)
+                    || consequent.expression.refers_to_variable(var_path)
+                    || alternate.expression.refers_to_variable(var_path)
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                operand.expression.refers_to_variable(var_path)
+            }
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                callee.expression.refers_to_variable(var_path)
+                    || arguments
+                        .iter()
+                        .any(|argument| argument.expression.refers_to_variable(var_path))
+            }
+            Expression::Variable { path, .. } => **path == *var_path,
+            Expression::Top
+            | Expression::Bottom
+            | Expression::AbstractHeapAddress(..)
+            | Expression::CompileTimeConstant(..)
+            | Expression::Reference(..) => false,
+        }
+    }
+
+    /// Returns true if this expression depends on the unknown value of the place identified by
+    /// the given path, or of a place rooted by it, i.e. if it contains Expression::Variable for
+    /// such a path.
+    pub fn refers_to_variable_rooted_by(&self, root_path: &Path) -> bool {
+        match self {
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.refers_to_variable_rooted_by(root_path)
+                    || right.expression.refers_to_variable_rooted_by(root_path)
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.refers_to_variable_rooted_by(root_path)
+                    || consequent
+                        .expression
+                        .refers_to_variable_rooted_by(root_path)
+                    || alternate.expression.refers_to_variable_rooted_by(root_path)
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                operand.expression.refers_to_variable_rooted_by(root_path)
+            }
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                callee.expression.refers_to_variable_rooted_by(root_path)
+                    || arguments
+                        .iter()
+                        .any(|argument| argument.expression.refers_to_variable_rooted_by(root_path))
+            }
+            Expression::Variable { path, .. } => {
+                **path == *root_path || path.is_rooted_by(root_path)
+            }
+            Expression::Top
+            | Expression::Bottom
+            | Expression::AbstractHeapAddress(..)
+            | Expression::CompileTimeConstant(..)
+            | Expression::Reference(..) => false,
+        }
+    }
+
+    /// Returns the number of nodes in the tree that represents this expression.
+    pub fn size(&self) -> u64 {
+        match self {
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                1 + left.expression.size() + right.expression.size()
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                1 + condition.expression.size()
+                    + consequent.expression.size()
+                    + alternate.expression.size()
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                1 + operand.expression.size()
+            }
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                1 + callee.expression.size()
+                    + arguments
+                        .iter()
+                        .map(|argument| argument.expression.size())
+                        .sum::<u64>()
+            }
+            Expression::Top
+            | Expression::Bottom
+            | Expression::AbstractHeapAddress(..)
+            | Expression::CompileTimeConstant(..)
+            | Expression::Reference(..)
+            | Expression::Variable { .. } => 1,
+        }
+    }
+
+    /// Returns true if some part of this expression is Top, i.e. if it depends on a value
+    /// about which nothing is known.
+    pub fn contains_top(&self) -> bool {
+        match self {
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Or { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::StringConcat { left, right }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.contains_top() || right.expression.contains_top()
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.contains_top()
+                    || consequent.expression.contains_top()
+                    || alternate.expression.contains_top()
+            }
+            Expression::Neg { operand } | Expression::Not { operand } => {
+                operand.expression.contains_top()
+            }
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                callee.expression.contains_top()
+                    || arguments
+                        .iter()
+                        .any(|argument| argument.expression.contains_top())
+            }
+            Expression::Top => true,
+            Expression::Bottom
+            | Expression::AbstractHeapAddress(..)
+            | Expression::CompileTimeConstant(..)
+            | Expression::Reference(..)
+            | Expression::Variable { .. } => false,
+        }
+    }
+}
+
 /// The type of a place in memory, as understood by MIR.
 /// For now, we are only really interested to distinguish between
 /// floating point values and other values, because NaN != NaN.
@@ -286,6 +937,15 @@ pub enum ExpressionType {
 }
 
 impl ExpressionType {
+    /// Returns true if this type is one of the integer types.
+    pub fn is_integer(&self) -> bool {
+        use self::ExpressionType::*;
+        match self {
+            I8 | I16 | I32 | I64 | I128 | Isize | U8 | U16 | U32 | U64 | U128 | Usize => true,
+            _ => false,
+        }
+    }
+
     /// Returns true if this type is one of the signed integer types.
     pub fn is_signed_integer(&self) -> bool {
         use self::ExpressionType::*;
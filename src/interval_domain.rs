@@ -8,6 +8,7 @@ use crate::expression::ExpressionType::{self, *};
 
 use std::cmp;
 use std::convert::TryFrom;
+use std::fmt;
 
 /// An element of the Interval domain is a range of i128 numbers denoted by a lower bound and
 /// upper bound. A lower bound of std::i128::MIN denotes -infinity and an upper bound of
@@ -52,7 +53,32 @@ impl From<u128> for IntervalDomain {
     }
 }
 
+/// Formats the interval as [lower, upper], with -inf and +inf for missing bounds.
+impl fmt::Display for IntervalDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_bottom() {
+            return f.write_str("[]");
+        }
+        match self.lower_bound() {
+            Some(lower_bound) => write!(f, "[{}, ", lower_bound)?,
+            None => f.write_str("[-inf, ")?,
+        }
+        match self.upper_bound() {
+            Some(upper_bound) => write!(f, "{}]", upper_bound),
+            None => f.write_str("+inf]"),
+        }
+    }
+}
+
 impl IntervalDomain {
+    /// The interval that includes all integers from lower_bound to upper_bound, inclusive.
+    pub fn new(lower_bound: i128, upper_bound: i128) -> IntervalDomain {
+        IntervalDomain {
+            lower_bound,
+            upper_bound,
+        }
+    }
+
     //[x...y] + [a...b] = [x+a...y+b]
     pub fn add(&self, other: &Self) -> Self {
         if self.is_bottom() || other.is_bottom() {
@@ -163,6 +189,32 @@ impl IntervalDomain {
         }
     }
 
+    /// The smallest value in this interval, or None if the interval has no lower bound.
+    pub fn lower_bound(&self) -> Option<i128> {
+        if self.lower_bound == std::i128::MIN {
+            None
+        } else {
+            Some(self.lower_bound)
+        }
+    }
+
+    /// The largest value in this interval, or None if the interval has no upper bound.
+    pub fn upper_bound(&self) -> Option<i128> {
+        if self.upper_bound == std::i128::MAX {
+            None
+        } else {
+            Some(self.upper_bound)
+        }
+    }
+
+    // [x...y] intersect [a...b] = [max(x,a)...min(y,b)]
+    pub fn intersect(&self, other: &Self) -> Self {
+        IntervalDomain {
+            lower_bound: cmp::max(self.lower_bound, other.lower_bound),
+            upper_bound: cmp::min(self.upper_bound, other.upper_bound),
+        }
+    }
+
     // All concrete integer values belong to this interval, so we know nothing.
     pub fn is_top(&self) -> bool {
         self.lower_bound == std::i128::MIN && self.upper_bound == std::i128::MAX
@@ -251,4 +303,42 @@ impl IntervalDomain {
             upper_bound: cmp::max(self.upper_bound, other.upper_bound),
         }
     }
+
+    /// Widens self, the interval of a value at an earlier iteration of a loop, with other, the
+    /// interval of the value at a later iteration. Bounds that grow move out to the nearest of the
+    /// given thresholds that includes them, or become unbounded if there is no such threshold.
+    /// Since there are finitely many thresholds, a small number of widen calls reach a fixed point,
+    /// while bounds such as 0 and the lengths of arrays are not lost straight away.
+    pub fn widen_with_thresholds(&self, other: &Self, thresholds: &[i128]) -> Self {
+        if self.is_bottom() || other.is_bottom() {
+            return BOTTOM.clone();
+        }
+        if self.is_top() || other.is_top() {
+            return TOP.clone();
+        }
+        let lower_bound = if other.lower_bound < self.lower_bound {
+            thresholds
+                .iter()
+                .filter(|t| **t <= other.lower_bound)
+                .max()
+                .cloned()
+                .unwrap_or(std::i128::MIN)
+        } else {
+            self.lower_bound
+        };
+        let upper_bound = if other.upper_bound > self.upper_bound {
+            thresholds
+                .iter()
+                .filter(|t| **t >= other.upper_bound)
+                .min()
+                .cloned()
+                .unwrap_or(std::i128::MAX)
+        } else {
+            self.upper_bound
+        };
+        IntervalDomain {
+            lower_bound,
+            upper_bound,
+        }
+    }
 }
@@ -9,5 +9,82 @@
 /// Prevents the fixed point loop from creating ever more new abstract values of type Expression::Variable.
 pub const MAX_PATH_LENGTH: usize = 10;
 
+/// Prevents expressions from growing without bound, for example when a loop keeps combining a value with itself.
+/// Values with larger expressions are treated as unknown (Top).
+pub const MAX_EXPRESSION_SIZE: u64 = 1000;
+
 /// The point at which diverging summaries experience exponential blowup right now.
+/// This is the default for the recursion_depth in the [summaries] section of a configuration file.
 pub const MAX_OUTER_FIXPOINT_ITERATIONS: usize = 3;
+
+/// The longest time a solver process may take to answer a query, unless configured otherwise.
+pub const MAX_SOLVER_QUERY_MILLISECONDS: u64 = 10_000;
+
+/// The number of times the fixed point loop of a function body analyzes a loop, joining the states
+/// of the predecessors of its head, before it starts widening them.
+pub const MAX_JOIN_ITERATIONS: usize = 3;
+
+/// The number of times the fixed point loop of a function body analyzes a loop before it gives up
+/// on the loop converging.
+pub const MAX_LOOP_ITERATIONS: usize = 50;
+
+/// The number of basic blocks that a function body must have before the results of analyzing its
+/// blocks are cached during the fixed point loop, so that a block whose in state has not changed
+/// since it was last analyzed is not analyzed again. Smaller bodies are cheaper to analyze again
+/// than to cache.
+pub const MIN_BLOCKS_FOR_BLOCK_CACHE: usize = 100;
+
+/// The number of elements at the start of an array or slice whose values are tracked individually.
+/// The values of the other elements are summarized by a single smashed element.
+pub const MAX_TRACKED_ARRAY_ELEMENTS: usize = 32;
+
+/// The factor by which the limits above are multiplied when a function has obligations that could not
+/// be proven with the default limits and it is analyzed again with elevated precision.
+pub const PRECISION_ESCALATION_FACTOR: usize = 4;
+
+/// The number of functions in a crate that may be analyzed again with elevated precision.
+pub const MAX_PRECISION_ESCALATIONS: usize = 50;
+
+/// The number of proof obligations of a function that are checked, unless configured otherwise. If a
+/// function has more, the less important classes of obligations are skipped.
+pub const MAX_OBLIGATIONS_PER_FUNCTION: usize = 2000;
+
+/// The longest time a compiler process waits for the summary database of a crate to be released by
+/// another compiler process, for example one that compiles the tests of the same crate.
+pub const MAX_SUMMARY_STORE_WAIT_SECONDS: u64 = 600;
+
+/// The depth to which calls of const functions with constant arguments are evaluated concretely.
+pub const MAX_CONST_EVAL_DEPTH: usize = 8;
+
+/// The number of basic blocks that the concrete evaluation of a call of a const function may visit.
+pub const MAX_CONST_EVAL_STEPS: usize = 10_000;
+
+/// The largest number of implementations of a trait method whose summaries are joined to get the
+/// summary of a call of the method via a trait object. Calls with more possible implementations are
+/// summarized from the signature of the trait method.
+pub const MAX_DEVIRTUALIZED_IMPLEMENTATIONS: usize = 16;
+
+/// The limits that apply to the analysis of a function body. They default to the constants above,
+/// which can be overridden for a project, a package or a function (see options::LimitsConfig).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KLimits {
+    /// See MAX_PATH_LENGTH.
+    pub max_path_length: usize,
+    /// See MAX_EXPRESSION_SIZE.
+    pub max_expression_size: u64,
+    /// See MAX_JOIN_ITERATIONS.
+    pub max_join_iterations: usize,
+    /// See MAX_LOOP_ITERATIONS.
+    pub max_fixed_point_iterations: usize,
+}
+
+impl Default for KLimits {
+    fn default() -> Self {
+        KLimits {
+            max_path_length: MAX_PATH_LENGTH,
+            max_expression_size: MAX_EXPRESSION_SIZE,
+            max_join_iterations: MAX_JOIN_ITERATIONS,
+            max_fixed_point_iterations: MAX_LOOP_ITERATIONS,
+        }
+    }
+}
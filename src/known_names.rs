@@ -0,0 +1,433 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::constant_domain::ConstantDomain;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+/// The default table of well known functions. It is embedded into the MIRAI binary.
+const DEFAULT_KNOWN_NAMES: &str = include_str!("known_names.toml");
+
+/// The environment variable that can be used to point at a file with additional entries.
+const KNOWN_NAMES_VARIABLE: &str = "MIRAI_KNOWN_NAMES";
+
+/// The built in models that the abstract interpreter uses for well known functions.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum KnownFunction {
+    /// Calls to this function remove all elements from the collection referenced by the first
+    /// argument.
+    AllocCollectionsClear,
+    /// Calls to this function return true if the set referenced by the first argument contains
+    /// the value given by the second argument.
+    AllocCollectionsContains,
+    /// Calls to this function add the value given by the second argument to the set referenced by
+    /// the first argument. They return true if the value was not already a member of the set.
+    AllocCollectionsInsert,
+    /// Calls to this function return true if the collection referenced by the first argument
+    /// has no elements.
+    AllocCollectionsIsEmpty,
+    /// Calls to this function return the number of elements of the collection referenced by the
+    /// first argument.
+    AllocCollectionsLen,
+    /// Calls to this function return a new collection without elements.
+    AllocCollectionsNew,
+    /// Calls to this function return Some reference to an element of the collection referenced
+    /// by the first argument, or None if the collection has no elements.
+    AllocCollectionsPeek,
+    /// Calls to this function remove an element from the collection referenced by the first
+    /// argument and return Some element, or None if the collection has no elements.
+    AllocCollectionsPop,
+    /// Calls to this function add an element to the collection referenced by the first argument.
+    AllocCollectionsPush,
+    /// Calls to this function remove the value given by the second argument from the set
+    /// referenced by the first argument. They return true if the value was a member of the set.
+    AllocCollectionsRemove,
+    /// Calls to this function return Some reference to the value of the entry with the key
+    /// referenced by the second argument in the map referenced by the first argument, or None if
+    /// the map has no such entry.
+    AllocMapGet,
+    /// Calls to this function set the value of the entry with the key given by the second argument
+    /// in the map referenced by the first argument to the value given by the third argument. They
+    /// return Some previous value of the entry, or None if the map had no such entry.
+    AllocMapInsert,
+    /// Calls to this function remove the entry with the key referenced by the second argument from
+    /// the map referenced by the first argument. They return Some value of the entry, or None if
+    /// the map had no such entry.
+    AllocMapRemove,
+    /// Calls to this function return a pointer that shares the heap block of the Rc or Arc
+    /// referenced by the first argument, which now has one more pointer to it.
+    AllocRcClone,
+    /// Calls to this function return a reference to the value in the heap block of the Rc or Arc
+    /// referenced by the first argument.
+    AllocRcDeref,
+    /// Calls to this function return an Rc or Arc that points to a new heap block, which holds
+    /// the value given by the first argument.
+    AllocRcNew,
+    /// Calls to this function return the number of Rc or Arc pointers that share the heap block of
+    /// the pointer referenced by the first argument.
+    AllocRcStrongCount,
+    /// Calls to this function return a str that views the contents of the String referenced by
+    /// the first argument.
+    AllocStringAsStr,
+    /// Calls to this function return a String with the contents of the str or String referenced
+    /// by the first argument, such as String::from, to_string and to_owned.
+    AllocStringFrom,
+    /// Calls to this function return a new String without contents.
+    AllocStringNew,
+    /// Calls to this function append the str given by the second argument to the String
+    /// referenced by the first argument.
+    AllocStringPushStr,
+    /// Calls to this function return the integer encoded by the first bytes of the slice
+    /// referenced by the first argument, in the byte order of the implementing type of the
+    /// byteorder::ByteOrder trait. They panic if the slice is too short.
+    ByteorderRead,
+    /// Calls to this function encode the integer given by the second argument into the first bytes
+    /// of the slice referenced by the first argument, in the byte order of the implementing type
+    /// of the byteorder::ByteOrder trait. They panic if the slice is too short.
+    ByteorderWrite,
+    /// Calls to this function return the char or u8 referenced by the first argument, converted
+    /// to the ASCII case given by the name of the function (to_ascii_lowercase or
+    /// to_ascii_uppercase). Characters that are not ASCII letters are returned unchanged.
+    CoreAsciiCaseConversion,
+    /// Calls to this function return true if the char or u8 referenced by the first argument
+    /// belongs to the ASCII character class given by the name of the function, such as
+    /// is_ascii_digit or is_ascii_whitespace.
+    CoreAsciiPredicate,
+    /// Calls to this function convert the integer given by the first argument to a char without
+    /// checking that it is a Unicode scalar value, i.e. at most 0x10FFFF and not a surrogate.
+    CoreCharFromU32Unchecked,
+    /// Calls to this function are part of the machinery behind format! and friends.
+    /// The result is modeled as an uninterpreted function of the values referenced by the arguments.
+    CoreFmtFormat,
+    /// Calls to this function collect the elements produced by the iterator given by the first
+    /// argument into a collection.
+    CoreIterCollect,
+    /// Calls to this function return an iterator that produces the elements of the iterator
+    /// given by the first argument for which the closure given by the second argument returns
+    /// true, or some other subset of its elements.
+    CoreIterFilter,
+    /// Calls to this function apply the closure given by the third argument to the value given by
+    /// the second argument and each element produced by the iterator given by the first argument,
+    /// and return the final value.
+    CoreIterFold,
+    /// Calls to this function return the iterator given by the first argument.
+    CoreIterIntoIter,
+    /// Calls to this function return an iterator that produces an element for each element
+    /// produced by the iterator given by the first argument, such as the result of applying the
+    /// closure given by the second argument to it.
+    CoreIterMap,
+    /// Calls to this function return the sum of the elements produced by the iterator given by the
+    /// first argument.
+    CoreIterSum,
+    /// Calls to this function drop the value given by the first argument.
+    CoreMemDrop,
+    /// Calls to this function return the value wrapped by a niche type such as NonZeroU32
+    /// or NonNull. The result is never zero (null).
+    CoreNonZeroGet,
+    /// Calls to this function construct a niche type such as NonZeroU32 or NonNull without
+    /// checking the value. The first argument must not be zero (null).
+    CoreNonZeroNewUnchecked,
+    /// Calls to this function return the integer encoded by the array of bytes given by the first
+    /// argument, in the byte order given by the name of the function (from_le_bytes, from_be_bytes
+    /// or from_ne_bytes).
+    CoreNumFromBytes,
+    /// Calls to this function return the array of bytes that encodes the integer given by the
+    /// first argument, in the byte order given by the name of the function (to_le_bytes,
+    /// to_be_bytes or to_ne_bytes).
+    CoreNumToBytes,
+    /// Calls to this function index the array, slice or collection referenced by the first
+    /// argument with the value given by the second argument. If that is a range, the result is a
+    /// sub-slice, and the call panics unless the range lies within the collection.
+    CoreOpsIndex,
+    /// Calls to this function return the value wrapped by the Some variant of the Option given by
+    /// the first argument, without checking that the Option is not None.
+    CoreOptionUnwrapUnchecked,
+    /// Calls to this function start a panic with the message found in the first field of the
+    /// tuple referenced by the first argument. The compiler uses it for built in checks such as
+    /// the overflow checks, as well as for panic! without arguments.
+    CorePanickingPanic,
+    /// Calls to this function return true if the pointer given by the first argument is null.
+    CorePtrIsNull,
+    /// Calls to this function return a pointer that is offset from the pointer given by the first
+    /// argument by the number of elements given by the second argument. The resulting pointer must
+    /// stay within the allocation of the first pointer, or point just past its end.
+    CorePtrOffset,
+    /// Like CorePtrOffset, but the pointer is moved backwards.
+    CorePtrSub,
+    /// Calls to this function return the value wrapped by the Ok variant of the Result given by
+    /// the first argument. They panic if the Result is an Err.
+    CoreResultUnwrap,
+    /// Calls to this function return the value wrapped by the Ok variant of the Result given by
+    /// the first argument, without checking that the Result is not an Err.
+    CoreResultUnwrapUnchecked,
+    /// Calls to this function return a slice that starts at the pointer given by the first argument
+    /// and has the length given by the second argument. The pointer must not be null and must
+    /// point to at least that many elements.
+    CoreSliceFromRawParts,
+    /// Calls to this function index the slice given by the first argument with the index given
+    /// by the second argument, without checking that the index is less than the length of the slice.
+    CoreSliceGetUnchecked,
+    /// Calls to this function return an iterator over the elements of the array or slice
+    /// referenced by the first argument.
+    CoreSliceIter,
+    /// Calls to this function advance the iterator over an array or slice, or the adapter of
+    /// such an iterator, referenced by the first argument. The result is None once all elements
+    /// have been produced.
+    CoreSliceIterNext,
+    /// Calls to this function return the length of the slice referenced by the first argument.
+    CoreSliceLen,
+    /// Calls to this function sort the elements of the slice referenced by the first argument.
+    CoreSliceSort,
+    /// Calls to this function split the slice referenced by the first argument into two slices,
+    /// at the index given by the second argument. They panic if the index exceeds the length.
+    CoreSliceSplitAt,
+    /// Calls to this function return true if the str referenced by the first argument ends with
+    /// the pattern given by the second argument.
+    CoreStrEndsWith,
+    /// Calls to this function check that the bytes of the byte slice given by the first argument
+    /// are valid UTF-8 and return Ok if they are.
+    CoreStrFromUtf8,
+    /// Calls to this function parse the str referenced by the first argument into a value,
+    /// such as a number, and return Ok if it can be parsed.
+    CoreStrParse,
+    /// Calls to this function convert the byte slice given by the first argument to a str without
+    /// checking that the bytes are valid UTF-8.
+    CoreStrFromUtf8Unchecked,
+    /// Calls to this function return the sub-str of the str given by the first argument that the
+    /// range given by the second argument selects, without checking that the range lies within the
+    /// str or that its bounds fall on char boundaries.
+    CoreStrGetUnchecked,
+    /// Calls to this function return the length, in bytes, of the str or String referenced by the
+    /// first argument.
+    CoreStrLen,
+    /// Calls to this function return true if the str referenced by the first argument starts with
+    /// the pattern given by the second argument.
+    CoreStrStartsWith,
+    /// Calls to this function state a loop invariant: the condition given by the first argument
+    /// holds whenever control reaches the call, which is at the start of the body of a loop. The
+    /// condition is checked there and assumed for the rest of the iteration.
+    MiraiLoopInvariant,
+    /// Calls to this function block the calling thread until an operation completes, for example
+    /// file or network I/O, or acquiring a lock. Async code should not call them.
+    StdBlocking,
+    /// Calls to this function create a channel and return a tuple with its sender and receiver.
+    StdChannelNew,
+    /// Calls to this function send the value given by the second argument on the channel of the
+    /// sender referenced by the first argument. They fail if the receiver has been dropped.
+    StdChannelSend,
+    /// Calls to this function return the command line arguments or the environment variables of
+    /// the program. Nothing is known about their values or about how many of them there are.
+    StdEnvInput,
+    /// Calls to this function return an iterator over the entries, keys or values of the HashMap
+    /// or HashSet referenced by the first argument, which produces them in an order that differs
+    /// from run to run.
+    StdHashIter,
+    /// Calls to this function are never reached if the program is correct.
+    StdIntrinsicsUnreachable,
+    /// Calls to this function return a value that differs from run to run, such as a random
+    /// number, the current time or the identity of the current thread.
+    StdNondeterministic,
+    /// Calls to this function write the values referenced by the arguments to an output, such as
+    /// a file or the console, where they can be observed outside of the program.
+    StdOutput,
+    /// Calls to this function start a panic with the message given by the first argument.
+    StdPanickingBeginPanic,
+    /// Calls to this function start a panic with the message formatted by the core::fmt::Arguments
+    /// value given by the first argument.
+    StdPanickingBeginPanicFmt,
+}
+
+/// An entry in a known names table, as read from a TOML file.
+#[derive(Deserialize, Clone, Debug)]
+struct KnownNameEntry {
+    /// The complete summary cache key of the function, possibly without the disambiguators of its
+    /// impl blocks (see without_impl_disambiguators).
+    #[serde(default)]
+    key: Option<String>,
+    /// Matches any function whose summary cache key starts with this string.
+    #[serde(default)]
+    key_prefix: Option<String>,
+    /// Matches any function whose summary cache key ends with this string.
+    #[serde(default)]
+    key_suffix: Option<String>,
+    /// If true, the function must also be a Rust intrinsic for the entry to match.
+    #[serde(default)]
+    intrinsic: bool,
+    /// If given, the function must also be a method of an impl block whose Self type is identified
+    /// by this string (see utils::self_type_key_str) for the entry to match.
+    #[serde(default)]
+    self_type: Option<String>,
+    /// The model to use for calls to the function.
+    kind: KnownFunction,
+    /// The version of the model. Bump it whenever the model changes in a way that may change the
+    /// summaries of the callers of the function, so that stored summaries of these callers are
+    /// computed again rather than reused.
+    #[serde(default)]
+    version: u32,
+}
+
+impl KnownNameEntry {
+    /// True if this entry applies to a function with the given summary cache key and Self type.
+    fn matches(
+        &self,
+        summary_cache_key: &str,
+        is_intrinsic: bool,
+        self_type: Option<&str>,
+    ) -> bool {
+        if self.intrinsic && !is_intrinsic {
+            return false;
+        }
+        if self.self_type.is_some() && self.self_type.as_ref().map(String::as_str) != self_type {
+            return false;
+        }
+        if let Some(key) = &self.key {
+            if key != summary_cache_key
+                && !(key.contains(IMPL_COMPONENT)
+                    && *key == without_impl_disambiguators(summary_cache_key))
+            {
+                return false;
+            }
+        }
+        if let Some(prefix) = &self.key_prefix {
+            if !summary_cache_key.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(suffix) = &self.key_suffix {
+            if !summary_cache_key.ends_with(suffix.as_str()) {
+                return false;
+            }
+        }
+        self.key.is_some() || self.key_prefix.is_some() || self.key_suffix.is_some()
+    }
+}
+
+/// The path component of the summary cache keys of items that are defined in impl blocks.
+const IMPL_COMPONENT: &str = "{{impl}}";
+
+/// Returns the given summary cache key without the disambiguators of its impl blocks, so that
+/// "core.fmt.{{impl}}:3.new_v1" becomes "core.fmt.{{impl}}.new_v1". The disambiguators depend on
+/// the order of the impl blocks in the source of their module, which a table of known names should
+/// not have to depend on.
+fn without_impl_disambiguators(summary_cache_key: &str) -> String {
+    let mut result = String::with_capacity(summary_cache_key.len());
+    let mut rest = summary_cache_key;
+    while let Some(i) = rest.find(IMPL_COMPONENT) {
+        let end = i + IMPL_COMPONENT.len();
+        result.push_str(&rest[..end]);
+        rest = &rest[end..];
+        if rest.starts_with(':') {
+            rest = rest[1..].trim_start_matches(|c: char| c.is_ascii_digit());
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The contents of a TOML file with known names.
+#[derive(Deserialize, Debug, Default)]
+struct KnownNamesFile {
+    #[serde(default)]
+    function: Vec<KnownNameEntry>,
+}
+
+/// Maps functions to the built in models that should be used for calls to them.
+/// The table is data driven, so that models can be attached to new functions without
+/// having to recompile MIRAI. Nothing in the table depends on the crate being analyzed, so a
+/// single table can be shared by all of the crates that are analyzed in the same process.
+pub struct KnownNamesCache {
+    /// The entries in order of precedence. User supplied entries come first.
+    entries: Vec<KnownNameEntry>,
+    /// Remembers the result of looking up a summary cache key along with a Self type.
+    lookup_cache: Mutex<HashMap<(String, Option<String>), Option<KnownFunction>>>,
+}
+
+impl KnownNamesCache {
+    /// Creates a table from the embedded defaults, extended with the entries found in the file
+    /// identified by the MIRAI_KNOWN_NAMES environment variable, if it is set.
+    pub fn create_cache_from_environment() -> KnownNamesCache {
+        let mut entries = Vec::new();
+        if let Ok(path) = env::var(KNOWN_NAMES_VARIABLE) {
+            match fs::read_to_string(&path) {
+                Ok(contents) => entries.append(&mut Self::parse(&contents, &path)),
+                Err(err) => println!("unable to read known names from {}: {}", path, err),
+            }
+        }
+        entries.append(&mut Self::parse(DEFAULT_KNOWN_NAMES, "known_names.toml"));
+        KnownNamesCache {
+            entries,
+            lookup_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Parses the contents of a known names file. Reports and ignores malformed files.
+    fn parse(contents: &str, file_name: &str) -> Vec<KnownNameEntry> {
+        match toml::from_str::<KnownNamesFile>(contents) {
+            Ok(file) => file.function,
+            Err(err) => {
+                println!("unable to parse known names in {}: {}", file_name, err);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns the built in model that applies to the given function, if any.
+    pub fn get(&self, fun: &ConstantDomain) -> Option<KnownFunction> {
+        if let ConstantDomain::Function {
+            is_intrinsic,
+            summary_cache_key,
+            self_type,
+            ..
+        } = fun
+        {
+            let entries = &self.entries;
+            *self
+                .lookup_cache
+                .lock()
+                .unwrap()
+                .entry((summary_cache_key.clone(), self_type.clone()))
+                .or_insert_with(|| {
+                    let self_type = self_type.as_ref().map(String::as_str);
+                    entries
+                        .iter()
+                        .find(|e| e.matches(summary_cache_key, *is_intrinsic, self_type))
+                        .map(|e| e.kind)
+                })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a hash of the models, and their versions, of all of the entries that may apply to
+    /// the function with the given summary cache key, whatever the Self type of the function. The
+    /// hash changes if a model is attached to or detached from the function, or if the version of
+    /// its model changes.
+    pub fn get_model_hash(&self, summary_cache_key: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for entry in self.entries.iter().filter(|e| {
+            e.matches(
+                summary_cache_key,
+                true,
+                e.self_type.as_ref().map(String::as_str),
+            )
+        }) {
+            entry.kind.hash(&mut hasher);
+            entry.version.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+impl Default for KnownNamesCache {
+    fn default() -> Self {
+        Self::create_cache_from_environment()
+    }
+}
@@ -33,12 +33,27 @@ extern crate serde_derive;
 pub mod abstract_domains;
 pub mod abstract_value;
 pub mod callbacks;
+pub mod certificate;
 pub mod constant_domain;
+pub mod contracts;
+pub mod cvc5_solver;
 pub mod environment;
 pub mod expression;
 pub mod interval_domain;
 pub mod k_limits;
+pub mod known_names;
+pub mod message_templates;
+pub mod metrics;
+pub mod models;
+pub mod options;
+pub mod remote_cache;
+pub mod sarif;
+pub mod security;
+pub mod smt_lib;
 pub mod smt_solver;
+pub mod solver_process;
+pub mod string_domain;
 pub mod summaries;
 pub mod utils;
 pub mod visitors;
+pub mod weak_topological_order;
@@ -20,6 +20,10 @@ extern crate mirai;
 extern crate rustc_driver;
 
 use mirai::callbacks;
+use mirai::options::{
+    DiagnosticFormat, Options, CERTIFY_UNSAFE_FREE_OPTION, DIAGNOSTIC_FORMAT_OPTION,
+    EXPORT_CONTRACTS_OPTION, EXPORT_SUMMARIES_OPTION,
+};
 use mirai::utils;
 use std::env;
 use std::path::Path;
@@ -48,16 +52,77 @@ fn main() {
             command_line_arguments.remove(1);
         }
 
+        // The format of the diagnostics and the options of the analysis, such as the widening
+        // thresholds, the solver, the limits and the checks to make, are options of Mirai, not of
+        // the compiler, so they are removed from the arguments passed on to the compiler.
+        let mut diagnostic_format = DiagnosticFormat::default();
+        let mut options = Options::from_environment();
+        command_line_arguments.retain(|arg| {
+            if arg.starts_with(DIAGNOSTIC_FORMAT_OPTION) {
+                let name = &arg[DIAGNOSTIC_FORMAT_OPTION.len()..];
+                match DiagnosticFormat::from_name(name) {
+                    Some(format) => diagnostic_format = format,
+                    None => println!(
+                        "unknown diagnostic format {}, expected human or sarif",
+                        name
+                    ),
+                }
+                false
+            } else {
+                !options.parse_command_line_option(arg)
+            }
+        });
+
+        // The options that export inferred contracts, summaries and certificates take a path as
+        // the next argument, so they are removed.
+        if let Some(path) =
+            remove_option_with_path(&mut command_line_arguments, EXPORT_CONTRACTS_OPTION)
+        {
+            options.inferred_contracts_file = Some(path);
+        }
+        if let Some(directory) =
+            remove_option_with_path(&mut command_line_arguments, EXPORT_SUMMARIES_OPTION)
+        {
+            options.summary_export_directory = Some(directory);
+        }
+        if let Some(path) =
+            remove_option_with_path(&mut command_line_arguments, CERTIFY_UNSAFE_FREE_OPTION)
+        {
+            options.unsafe_free_certificate_file = Some(path);
+        }
+
         // Tell compiler where to find the std library and so on.
         // The compiler relies on the standard rustc driver to tell it, so we have to do likewise.
         command_line_arguments.push(String::from("--sysroot"));
         command_line_arguments.push(utils::find_sysroot());
 
+        let shared_state = callbacks::SharedState::from_environment().with_options(options);
+        let mirai_callbacks = callbacks::MiraiCallbacks::default()
+            .with_diagnostic_format(diagnostic_format)
+            .with_shared_state(shared_state);
         rustc_driver::run_compiler(
             &command_line_arguments,
-            box callbacks::MiraiCallbacks::default(),
+            box mirai_callbacks,
             None, // use default file loader
             None, // emit output to default destination
         )
     });
 }
+
+/// Removes the given option, and the path that follows it, from the command line arguments and
+/// returns the path. Reports a missing path.
+fn remove_option_with_path(
+    command_line_arguments: &mut Vec<String>,
+    option: &str,
+) -> Option<String> {
+    let i = command_line_arguments
+        .iter()
+        .position(|arg| arg == option)?;
+    command_line_arguments.remove(i);
+    if i < command_line_arguments.len() {
+        Some(command_line_arguments.remove(i))
+    } else {
+        println!("{} expects a path", option);
+        None
+    }
+}
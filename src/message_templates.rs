@@ -0,0 +1,49 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::collections::HashMap;
+
+/// What the analysis knows about the condition that a diagnostic reports, which the templates of
+/// the [messages] section of a MIRAI configuration file can interpolate into the message.
+#[derive(Debug, Default)]
+pub struct MessageFacts {
+    /// The message that MIRAI would give the diagnostic without a template.
+    pub message: String,
+    /// The confidence code of the diagnostic, such as "limited_precision".
+    pub confidence: String,
+    /// The range of values of the operand of the condition that could not be proven, such as
+    /// "[0, 10]", or "unknown" if the analysis knows nothing about it.
+    pub range: String,
+    /// The locations where untrusted input, on which the condition depends, entered the program,
+    /// or "none" if it does not depend on untrusted input.
+    pub sources: String,
+    /// The number of branch conditions on the path that leads to the location of the diagnostic.
+    pub path_length: usize,
+    /// The values of the counterexample that the solver found, or "none" if it found none.
+    pub counterexample: String,
+}
+
+/// Returns the template for a diagnostic with the given message and confidence code, if the given
+/// templates have one. A template for the exact message takes precedence over a template for the
+/// confidence code.
+pub fn get_template<'a>(
+    templates: &'a HashMap<String, String>,
+    message: &str,
+    confidence: &str,
+) -> Option<&'a String> {
+    templates.get(message).or_else(|| templates.get(confidence))
+}
+
+/// Returns the given template with its placeholders, such as {message} or {range}, replaced by the
+/// corresponding facts. Placeholders that do not name a fact are left as they are.
+pub fn interpolate(template: &str, facts: &MessageFacts) -> String {
+    template
+        .replace("{message}", &facts.message)
+        .replace("{confidence}", &facts.confidence)
+        .replace("{range}", &facts.range)
+        .replace("{sources}", &facts.sources)
+        .replace("{path_length}", &facts.path_length.to_string())
+        .replace("{counterexample}", &facts.counterexample)
+}
@@ -0,0 +1,61 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::summaries::Summary;
+use crate::utils::json_string;
+
+use std::fs;
+use std::path::Path;
+
+/// The suffix of the names of the files to which the metrics of crates are written. The files are
+/// named after their crates and placed next to the summary store, where cargo mirai collects them
+/// into a summary of the workspace.
+pub const METRICS_FILE_SUFFIX: &str = ".mirai_metrics.json";
+
+/// How much of a crate the analysis was able to verify.
+#[derive(Debug, Default)]
+pub struct CrateMetrics {
+    /// The number of functions and methods of the crate that have bodies.
+    pub functions: usize,
+    /// The number of these functions for which no diagnostics were reported.
+    pub verified_functions: usize,
+    /// The number of verified functions that also require nothing of their callers and never
+    /// unwind, so that no call to them can panic.
+    pub panic_free_functions: usize,
+    /// The number of diagnostics that were reported for the crate, including the ones that are not
+    /// about a particular function.
+    pub diagnostics: usize,
+}
+
+impl CrateMetrics {
+    /// Counts a function for which the given number of diagnostics were reported and that has the
+    /// given summary.
+    pub fn add_function(&mut self, diagnostic_count: usize, summary: &Summary) {
+        self.functions += 1;
+        if diagnostic_count > 0 {
+            return;
+        }
+        self.verified_functions += 1;
+        if !summary.may_panic() {
+            self.panic_free_functions += 1;
+        }
+    }
+}
+
+/// Writes the metrics of the crate with the given name to a JSON file at the given path, as an
+/// object with the crate name and the counts of the metrics. Reports and otherwise ignores failures.
+pub fn write_metrics(path: &Path, crate_name: &str, metrics: &CrateMetrics) {
+    let contents = format!(
+        "{{\"crate\": {}, \"functions\": {}, \"verified_functions\": {}, \"panic_free_functions\": {}, \"diagnostics\": {}}}\n",
+        json_string(crate_name),
+        metrics.functions,
+        metrics.verified_functions,
+        metrics.panic_free_functions,
+        metrics.diagnostics
+    );
+    if let Err(err) = fs::write(path, contents) {
+        println!("unable to write metrics to {}: {}", path.display(), err);
+    }
+}
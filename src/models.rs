@@ -0,0 +1,200 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::collections::HashMap;
+
+/// The contents of a models file, which declares what functions that cannot be analyzed, such as
+/// the extern "C" functions of a foreign library, require and do.
+#[derive(Deserialize, Debug, Default)]
+struct ModelsFile {
+    /// The models of functions, keyed by summary key.
+    #[serde(default)]
+    functions: HashMap<String, ModelEntry>,
+}
+
+/// The [functions."KEY"] sections of a models file, as written by the user.
+#[derive(Deserialize, Debug, Default)]
+struct ModelEntry {
+    #[serde(default)]
+    preconditions: Vec<String>,
+    #[serde(default)]
+    postconditions: Vec<String>,
+    #[serde(default)]
+    modifies: Vec<usize>,
+}
+
+/// What a function that cannot be analyzed requires of its callers and does for them, as declared
+/// by a models file. Calls to the function use the model rather than the signature of the function,
+/// which would make them modify everything that their arguments give mutable access to.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionModel {
+    /// Conditions on the arguments that must hold when the function is called.
+    pub preconditions: Vec<Condition>,
+    /// Conditions on the arguments and the result that hold when the function returns.
+    pub postconditions: Vec<Condition>,
+    /// The (zero based) positions of the arguments whose targets the function may modify.
+    /// The function modifies nothing else.
+    pub modifies: Vec<usize>,
+}
+
+/// A comparison of two operands, such as "arg0 != 0" or "result <= arg1", or a comparison that
+/// is quantified over the elements of an array or slice, such as "forall i: arg0[i] > 0".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Condition {
+    /// If the comparison is quantified, whether it must hold for all of the elements or for some.
+    pub quantifier: Option<Quantifier>,
+    pub left: Operand,
+    pub comparison: Comparison,
+    pub right: Operand,
+    /// The text of the condition, as written in the models file.
+    pub text: String,
+}
+
+/// An operand of a condition of a model.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operand {
+    /// The argument at the given (zero based) position, written as argN.
+    Argument(usize),
+    /// The value returned by the function, written as result.
+    Result,
+    /// The element at the quantified index of the array or slice that the argument at the given
+    /// position refers to, written as argN[i].
+    Element(usize),
+    /// An integer constant.
+    Constant(i128),
+}
+
+/// The quantifier of a condition of a model.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Quantifier {
+    /// The comparison holds for every index i of the collection, written as forall i:.
+    ForAll,
+    /// The comparison holds for some index i of the collection, written as exists i:.
+    Exists,
+}
+
+/// The operator of a condition of a model.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    Equals,
+    NotEquals,
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+}
+
+impl Condition {
+    /// Parses a condition of the form "OPERAND OPERATOR OPERAND", where the operator is one of
+    /// ==, !=, <, <=, > and >=, optionally preceded by "forall i:" or "exists i:". Elements argN[i]
+    /// may be used, and must all refer to the same argument, exactly when the condition is
+    /// quantified. Returns None if the text is not of this form.
+    pub fn parse(text: &str) -> Option<Condition> {
+        let mut parts: Vec<&str> = text.split_whitespace().collect();
+        let quantifier = match parts.get(0..2) {
+            Some(["forall", "i:"]) => Some(Quantifier::ForAll),
+            Some(["exists", "i:"]) => Some(Quantifier::Exists),
+            _ => None,
+        };
+        if quantifier.is_some() {
+            parts.drain(0..2);
+        }
+        if parts.len() != 3 {
+            return None;
+        }
+        let comparison = match parts[1] {
+            "==" => Comparison::Equals,
+            "!=" => Comparison::NotEquals,
+            "<" => Comparison::LessThan,
+            "<=" => Comparison::LessOrEqual,
+            ">" => Comparison::GreaterThan,
+            ">=" => Comparison::GreaterOrEqual,
+            _ => return None,
+        };
+        let condition = Condition {
+            quantifier,
+            left: Operand::parse(parts[0])?,
+            comparison,
+            right: Operand::parse(parts[2])?,
+            text: String::from(text),
+        };
+        let element_arguments_agree = match (condition.left, condition.right) {
+            (Operand::Element(i), Operand::Element(j)) => i == j,
+            _ => true,
+        };
+        if element_arguments_agree
+            && condition.quantified_argument().is_some() == quantifier.is_some()
+        {
+            Some(condition)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position of the argument whose elements the condition is about, if any.
+    pub fn quantified_argument(&self) -> Option<usize> {
+        match (self.left, self.right) {
+            (Operand::Element(i), _) | (_, Operand::Element(i)) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl Operand {
+    /// Parses argN, argN[i], result or an integer constant.
+    fn parse(text: &str) -> Option<Operand> {
+        if text == "result" {
+            Some(Operand::Result)
+        } else if text.starts_with("arg") && text.ends_with("[i]") {
+            text[3..text.len() - 3]
+                .parse::<usize>()
+                .ok()
+                .map(Operand::Element)
+        } else if text.starts_with("arg") {
+            text[3..].parse::<usize>().ok().map(Operand::Argument)
+        } else {
+            text.parse::<i128>().ok().map(Operand::Constant)
+        }
+    }
+}
+
+/// Parses the given contents of a models file into models keyed by summary key. Reports and skips
+/// malformed files and conditions.
+pub fn parse_models(contents: &str, file_name: &str) -> HashMap<String, FunctionModel> {
+    let models_file = match toml::from_str::<ModelsFile>(contents) {
+        Ok(models_file) => models_file,
+        Err(err) => {
+            println!("unable to parse function models in {}: {}", file_name, err);
+            return HashMap::new();
+        }
+    };
+    let parse_conditions = |key: &str, conditions: &[String]| -> Vec<Condition> {
+        conditions
+            .iter()
+            .filter_map(|text| {
+                let condition = Condition::parse(text);
+                if condition.is_none() {
+                    println!(
+                        "ignoring malformed condition \"{}\" of the model of {} in {}",
+                        text, key, file_name
+                    );
+                }
+                condition
+            })
+            .collect()
+    };
+    models_file
+        .functions
+        .into_iter()
+        .map(|(key, entry)| {
+            let model = FunctionModel {
+                preconditions: parse_conditions(&key, &entry.preconditions),
+                postconditions: parse_conditions(&key, &entry.postconditions),
+                modifies: entry.modifies,
+            };
+            (key, model)
+        })
+        .collect()
+}
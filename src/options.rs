@@ -0,0 +1,1023 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::k_limits::{self, KLimits};
+use crate::models::{self, FunctionModel};
+use crate::smt_lib;
+use crate::smt_solver::ObligationClass;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// The environment variable that opts into diagnostics for arithmetic that may silently wrap around.
+const LINT_WRAPPING_ARITHMETIC_VARIABLE: &str = "MIRAI_LINT_WRAPPING_ARITHMETIC";
+
+/// The environment variable that opts into a mode where nonlinear arithmetic is never sent to the solver.
+const LINEAR_ARITHMETIC_ONLY_VARIABLE: &str = "MIRAI_LINEAR_ARITHMETIC_ONLY";
+
+/// The environment variable that opts into reporting functions with equivalent summaries.
+const REPORT_EQUIVALENT_FUNCTIONS_VARIABLE: &str = "MIRAI_REPORT_EQUIVALENT_FUNCTIONS";
+
+/// The environment variable, set by cargo mirai --workspace-only, that says that only the crates of
+/// the workspace are to be analyzed, since the summaries of their dependencies are already stored.
+pub const WORKSPACE_ONLY_VARIABLE: &str = "MIRAI_WORKSPACE_ONLY";
+
+/// The environment variable that cargo sets when it compiles a crate of a package that is part of
+/// the workspace being built, rather than a dependency.
+pub const PRIMARY_PACKAGE_VARIABLE: &str = "CARGO_PRIMARY_PACKAGE";
+
+/// The environment variable that can be used to point at a configuration file.
+const CONFIG_FILE_VARIABLE: &str = "MIRAI_CONFIG";
+
+/// The configuration file that is used if MIRAI_CONFIG is not set and the file exists.
+const DEFAULT_CONFIG_FILE: &str = "mirai.toml";
+
+/// The environment variable that can be used to point at a file with models of functions that
+/// cannot be analyzed, such as extern "C" functions.
+const MODELS_FILE_VARIABLE: &str = "MIRAI_MODELS";
+
+/// The models file that is used if MIRAI_MODELS is not set and the file exists.
+const DEFAULT_MODELS_FILE: &str = "mirai-models.toml";
+
+/// The environment variable that cargo sets to the directory of the Cargo.toml file of the package
+/// that is being compiled.
+const MANIFEST_DIR_VARIABLE: &str = "CARGO_MANIFEST_DIR";
+
+/// The contents of a MIRAI configuration file.
+#[derive(Deserialize, Debug, Default)]
+struct ConfigFile {
+    #[serde(default)]
+    solver: SolverConfig,
+    #[serde(default)]
+    environment: HashMap<String, EnvironmentVariable>,
+    #[serde(default)]
+    obligations: ObligationsConfig,
+    #[serde(default)]
+    fixed_point_types: HashMap<String, FixedPointType>,
+    #[serde(default)]
+    contracts: ContractsConfig,
+    #[serde(default)]
+    summaries: SummariesConfig,
+    #[serde(default)]
+    trust: HashMap<String, TrustLevel>,
+    #[serde(default)]
+    trusted_functions: HashMap<String, TrustedFunction>,
+    #[serde(default)]
+    security: SecurityConfig,
+    #[serde(default)]
+    ffi: HashMap<String, FfiContract>,
+    #[serde(default)]
+    sanitizers: HashMap<String, Sanitizer>,
+    #[serde(default)]
+    sinks: HashMap<String, Sink>,
+    #[serde(default)]
+    precision: HashMap<String, PrecisionPreset>,
+    #[serde(default)]
+    limits: LimitsConfig,
+    #[serde(default)]
+    messages: HashMap<String, String>,
+}
+
+/// The parts of the Cargo.toml file of a package that MIRAI looks at: the limits in its
+/// [package.metadata.mirai] section.
+#[derive(Deserialize, Debug, Default)]
+struct Manifest {
+    #[serde(default)]
+    package: ManifestPackage,
+}
+
+/// The [package] section of a Cargo.toml file.
+#[derive(Deserialize, Debug, Default)]
+struct ManifestPackage {
+    #[serde(default)]
+    metadata: ManifestMetadata,
+}
+
+/// The [package.metadata] section of a Cargo.toml file.
+#[derive(Deserialize, Debug, Default)]
+struct ManifestMetadata {
+    #[serde(default)]
+    mirai: LimitsConfig,
+}
+
+/// Overrides of the k-limits (see k_limits::KLimits), as given by the [limits] section of a MIRAI
+/// configuration file, the [package.metadata.mirai] section of the Cargo.toml file of a package,
+/// command line options such as --max-path-length=20, or the #[mirai::limits(..)] attribute of a
+/// function, in increasing order of precedence. Limits that are not given keep their values.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct LimitsConfig {
+    /// The length of the longest access path that is tracked.
+    #[serde(default)]
+    pub max_path_length: Option<usize>,
+    /// The size of the largest expression that is not treated as unknown.
+    #[serde(default)]
+    pub max_expression_size: Option<u64>,
+    /// The number of times a loop is analyzed before its states are widened.
+    #[serde(default)]
+    pub max_join_iterations: Option<usize>,
+    /// The number of times a loop is analyzed before the analysis gives up on it converging.
+    #[serde(default)]
+    pub max_fixed_point_iterations: Option<usize>,
+    /// The number of times the functions of a crate are analyzed while their summaries keep
+    /// changing, which limits the depth to which recursive calls are unfolded. This limit applies
+    /// to a crate as a whole, so it cannot be given for a function.
+    #[serde(default)]
+    pub max_recursion_depth: Option<usize>,
+}
+
+impl LimitsConfig {
+    /// Overrides the given limits with the ones that are given by self.
+    pub fn apply_to(&self, limits: &mut KLimits) {
+        if let Some(max_path_length) = self.max_path_length {
+            limits.max_path_length = max_path_length;
+        }
+        if let Some(max_expression_size) = self.max_expression_size {
+            limits.max_expression_size = max_expression_size;
+        }
+        if let Some(max_join_iterations) = self.max_join_iterations {
+            limits.max_join_iterations = max_join_iterations;
+        }
+        if let Some(max_fixed_point_iterations) = self.max_fixed_point_iterations {
+            limits.max_fixed_point_iterations = max_fixed_point_iterations;
+        }
+    }
+
+    /// True if no limit is given.
+    pub fn is_empty(&self) -> bool {
+        self.max_path_length.is_none()
+            && self.max_expression_size.is_none()
+            && self.max_join_iterations.is_none()
+            && self.max_fixed_point_iterations.is_none()
+            && self.max_recursion_depth.is_none()
+    }
+
+    /// Gives the limit with the given name, as used in the configuration file, the given value.
+    /// Returns false if there is no such limit.
+    pub fn set(&mut self, name: &str, value: u64) -> bool {
+        let value_as_usize = value as usize;
+        match name {
+            "max_path_length" => self.max_path_length = Some(value_as_usize),
+            "max_expression_size" => self.max_expression_size = Some(value),
+            "max_join_iterations" => self.max_join_iterations = Some(value_as_usize),
+            "max_fixed_point_iterations" => self.max_fixed_point_iterations = Some(value_as_usize),
+            "max_recursion_depth" => self.max_recursion_depth = Some(value_as_usize),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Parses a command line option that overrides a limit, such as --max-path-length=20, and
+    /// records the limit. Reports and ignores unknown limits and invalid values.
+    pub fn parse_option(&mut self, option: &str) {
+        let mut parts = option.trim_start_matches("--").splitn(2, '=');
+        let name = parts.next().unwrap_or_default().replace('-', "_");
+        match parts.next().map(str::parse::<u64>) {
+            Some(Ok(value)) => {
+                if !self.set(&name, value) {
+                    println!("unknown limit {}", option);
+                }
+            }
+            _ => println!("invalid limit {}, expected a number", option),
+        }
+    }
+}
+
+/// The [contracts] section of a MIRAI configuration file.
+#[derive(Deserialize, Debug, Default)]
+struct ContractsConfig {
+    /// The file to which the inferred contracts of the public functions of the crate are written.
+    #[serde(default)]
+    export: Option<String>,
+}
+
+/// The [security] section of a MIRAI configuration file.
+#[derive(Deserialize, Debug, Default)]
+struct SecurityConfig {
+    /// The file to which the security findings of the analysis are written as JSON.
+    #[serde(default)]
+    export: Option<String>,
+}
+
+/// The [summaries] section of a MIRAI configuration file.
+#[derive(Deserialize, Debug, Default)]
+struct SummariesConfig {
+    /// The number of times the summaries of functions that call each other are recomputed.
+    #[serde(default)]
+    recursion_depth: Option<usize>,
+    /// The URL of a remote cache from which the summaries of published dependencies are fetched.
+    #[serde(default)]
+    remote_cache: Option<String>,
+    /// If true, the summaries of published dependencies that were not in the remote cache are
+    /// stored there once they have been computed.
+    #[serde(default)]
+    upload: bool,
+}
+
+/// The [obligations] section of a MIRAI configuration file.
+#[derive(Deserialize, Debug, Default)]
+struct ObligationsConfig {
+    /// The number of proof obligations of a function that are checked.
+    #[serde(default)]
+    budget: Option<usize>,
+    /// The directory to which the obligations that could not be proven are written as SMT-LIB2.
+    #[serde(default)]
+    export: Option<String>,
+    /// A file that lists exported obligations that were proven by other means.
+    #[serde(default)]
+    proven: Option<String>,
+    /// The file to which the obligations that were assumed to hold because of the proven file are
+    /// written.
+    #[serde(default)]
+    assumption_log: Option<String>,
+}
+
+/// What is known about the value of an environment variable, as declared in the [environment.NAME]
+/// sections of a MIRAI configuration file.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct EnvironmentVariable {
+    /// If true, the value of the variable is set by someone that the program trusts, so it is not
+    /// treated as untrusted input.
+    #[serde(default)]
+    pub trusted: bool,
+    /// If set, a number parsed from the value of the variable lies within this (inclusive) range.
+    #[serde(default)]
+    pub range: Option<(i64, i64)>,
+}
+
+/// A user defined type that represents fixed point (decimal) numbers as integers that are scaled by
+/// a constant factor, as declared in the [fixed_point_types."TYPE"] sections of a MIRAI
+/// configuration file, where TYPE is the summary key of the type, for example "my_crate.Decimal".
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct FixedPointType {
+    /// The number by which the numbers are multiplied to get the integers that represent them.
+    pub scale: u64,
+    /// The index of the field of the type that holds the scaled integer.
+    #[serde(default)]
+    pub field: usize,
+}
+
+/// What the C callers of a function that is exported to C promise about its arguments, as declared
+/// in the [ffi.NAME] sections of a MIRAI configuration file, where NAME is the name of the
+/// function. Parameters are identified by their names. Arguments that are not covered by the
+/// contract can have any value, so pointers may be null.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct FfiContract {
+    /// The pointer parameters whose arguments are never null.
+    #[serde(default)]
+    pub non_null: Vec<String>,
+    /// Pairs of a pointer parameter and a length parameter. The pointer is never null and points
+    /// to at least as many elements as given by the length.
+    #[serde(default)]
+    pub buffers: Vec<(String, String)>,
+}
+
+/// A function that establishes a named property of values, such as "matches ^[a-z]+$", as declared
+/// in the [sanitizers."KEY"] sections of a MIRAI configuration file, where KEY is the summary key of
+/// the function. Sinks can require their arguments to have the property.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct Sanitizer {
+    /// The name of the property.
+    pub tag: String,
+    /// If set, the function is a validator that checks if the argument at this (zero based)
+    /// position has the property, and returns true, or Ok, if it does. Otherwise the function is a
+    /// sanitizer, whose result has the property.
+    #[serde(default)]
+    pub validates: Option<usize>,
+}
+
+/// A function that must only be called with an argument that has a named property, as declared in
+/// the [sinks."KEY"] sections of a MIRAI configuration file, where KEY is the summary key of the
+/// function. Calls are reported unless the argument has been sanitized or validated to have it.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct Sink {
+    /// The (zero based) position of the argument that must have the property.
+    #[serde(default)]
+    pub argument: usize,
+    /// The name of the property, as given by the tag of a sanitizer.
+    pub requires: String,
+}
+
+/// How much effort the analysis spends on the functions of a module, as declared in the [precision]
+/// section of a MIRAI configuration file, which maps module paths to presets.
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrecisionPreset {
+    /// The default limits apply and functions are never analyzed again with elevated limits.
+    /// Conditions that involve nonlinear arithmetic are not sent to the solver and fewer proof
+    /// obligations are checked. Suitable for generated code.
+    Fast,
+    /// The default limits apply. Functions with obligations that cannot be proven with them are
+    /// analyzed again with elevated limits.
+    Standard,
+    /// Functions are analyzed with elevated limits, and a longer solver time limit, from the start
+    /// and more proof obligations are checked. Suitable for the core modules of a crate.
+    Thorough,
+}
+
+impl Default for PrecisionPreset {
+    fn default() -> Self {
+        PrecisionPreset::Standard
+    }
+}
+
+impl PrecisionPreset {
+    /// The factor by which the k-limits and the solver time limit are multiplied when a function
+    /// is first analyzed.
+    pub fn initial_precision_factor(self) -> usize {
+        match self {
+            PrecisionPreset::Thorough => k_limits::PRECISION_ESCALATION_FACTOR,
+            _ => 1,
+        }
+    }
+
+    /// Returns the number of proof obligations of a function that are checked, given the budget
+    /// that applies to functions with the Standard preset.
+    pub fn scale_obligation_budget(self, budget: usize) -> usize {
+        match self {
+            PrecisionPreset::Fast => budget / 4,
+            PrecisionPreset::Standard => budget,
+            PrecisionPreset::Thorough => budget.saturating_mul(4),
+        }
+    }
+}
+
+/// How far the analysis trusts the code of a dependency, as declared in the [trust] section of a
+/// MIRAI configuration file, which maps crate names to trust levels.
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustLevel {
+    /// The summaries of the functions of the crate are used, but the preconditions in them are not
+    /// checked at calls, since the crate is trusted to be used correctly.
+    Trusted,
+    /// The functions of the crate must have been analyzed. Calls to functions of the crate for
+    /// which there is no summary are reported.
+    Verified,
+    /// The summaries of the functions of the crate are not used. Calls may modify anything that
+    /// their arguments allow them to modify, as well as static variables, and their results are
+    /// untrusted input.
+    Untrusted,
+}
+
+/// A function that is trusted to never panic, as declared in the [trusted_functions."KEY"] sections
+/// of a MIRAI configuration file, where KEY is the summary key of the function. This lets the
+/// analysis rely on functions that it cannot analyze, such as those of vendored dependencies,
+/// without trusting everything else in their crates.
+#[derive(Deserialize, Clone, Debug)]
+pub struct TrustedFunction {
+    /// What the function is trusted to guarantee.
+    pub guarantee: Guarantee,
+    /// Why the function is trusted, such as who audited it and when. It is written to the
+    /// assumption log whenever the declaration is relied upon. Declarations without a reason are
+    /// ignored.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// What a trusted function guarantees to its callers.
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Guarantee {
+    /// The function never panics, whatever its arguments, so calls have no preconditions and
+    /// never unwind.
+    PanicFree,
+    /// The function is panic free and returns a meaningful result for all arguments, so the
+    /// conditions that depend on its result are not blamed on missing models.
+    Total,
+}
+
+impl Guarantee {
+    /// The name of the guarantee, as written in diagnostics and the assumption log.
+    pub fn description(self) -> &'static str {
+        match self {
+            Guarantee::PanicFree => "panic free",
+            Guarantee::Total => "total",
+        }
+    }
+}
+
+/// The command line option that selects the format in which diagnostics are reported.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const DIAGNOSTIC_FORMAT_OPTION: &str = "--diag-format=";
+
+/// The command line option that selects the SMT solver that is used to prove obligations.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const SMT_BACKEND_OPTION: &str = "--smt-backend=";
+
+/// The command line option that makes every arithmetic operation that may overflow a diagnostic.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const CHECK_OVERFLOW_OPTION: &str = "--check-overflow";
+
+/// The command line option that makes every possible panic an error, so that the absence of
+/// diagnostics certifies that the crate cannot panic.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const VERIFY_NO_PANICS_OPTION: &str = "--verify-no-panics";
+
+/// The command line option that makes the results of functions that depend on the order in which
+/// a HashMap or HashSet is iterated over a diagnostic, as well as the outputs of values that depend
+/// on other sources of nondeterminism.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const CHECK_DETERMINISM_OPTION: &str = "--check-determinism";
+
+/// The command line option that selects how casts of integers to raw pointers are treated.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const INT_TO_PTR_CASTS_OPTION: &str = "--int-to-ptr-casts=";
+
+/// The command line option that opts into diagnostics for arithmetic that may silently wrap around,
+/// like the MIRAI_LINT_WRAPPING_ARITHMETIC environment variable.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const LINT_WRAPPING_ARITHMETIC_OPTION: &str = "--lint-wrapping-arithmetic";
+
+/// The command line option that keeps conditions that involve nonlinear arithmetic away from the
+/// solver, like the MIRAI_LINEAR_ARITHMETIC_ONLY environment variable.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const LINEAR_ARITHMETIC_ONLY_OPTION: &str = "--linear-arithmetic-only";
+
+/// The command line option that stops the analysis of a crate at the first error.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const FAIL_FAST_OPTION: &str = "--fail-fast";
+
+/// The command line option that limits the analysis of a crate to its entry points and the
+/// functions that they call directly, for quick checks of pull requests.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const SMOKE_OPTION: &str = "--smoke";
+
+/// The command line option that names the summary store of an earlier version of the crates
+/// being analyzed, such as target/debug/.summary_store of a checkout of the previous release.
+/// It is removed from the command line before the arguments are passed to rustc.
+pub const DIFF_AGAINST_OPTION: &str = "--diff-against=";
+
+/// The command line option that sets the number of milliseconds after which the analysis of a
+/// function body is abandoned. It is removed from the command line before the arguments are passed
+/// to rustc.
+pub const PER_BODY_TIMEOUT_OPTION: &str = "--per-body-timeout-ms=";
+
+/// The command line option that sets the constants to which the values of loop counters are
+/// widened, as a comma separated list of integers. It is removed from the command line before the
+/// arguments are passed to rustc.
+pub const WIDENING_THRESHOLDS_OPTION: &str = "--widening-thresholds=";
+
+/// The command line option that is followed by the path of the file to which the preconditions
+/// and postconditions inferred for the public functions of the crate are written as JSON.
+/// It is removed, along with the path, from the command line before the arguments are passed to
+/// rustc.
+pub const EXPORT_CONTRACTS_OPTION: &str = "--export-contracts";
+
+/// The command line option that is followed by the path of the directory to which the summaries of
+/// the public functions of the crate are written, as a summary store that can be published along
+/// with the crate. It is removed, along with the path, from the command line before the arguments
+/// are passed to rustc.
+pub const EXPORT_SUMMARIES_OPTION: &str = "--export-summaries";
+
+/// The command line option that is followed by the path of the file to which a certificate is
+/// written that says whether unsafe code can be reached from the public functions of the crate.
+/// It is removed, along with the path, from the command line before the arguments are passed to
+/// rustc.
+pub const CERTIFY_UNSAFE_FREE_OPTION: &str = "--certify-unsafe-free";
+
+/// The prefix of the command line options that override the k-limits, such as
+/// --max-path-length=20 and --max-fixed-point-iterations=100. The rest of the name of an option is
+/// that of a limit of LimitsConfig, with dashes rather than underscores. The options are removed
+/// from the command line before the arguments are passed to rustc.
+pub const LIMIT_OPTION_PREFIX: &str = "--max-";
+
+/// The constants to which the values of loop counters are widened, unless the
+/// --widening-thresholds command line option says otherwise. The constant lengths of arrays are
+/// always used as thresholds as well.
+const DEFAULT_WIDENING_THRESHOLDS: [i128; 5] =
+    [std::i32::MIN as i128, -1, 0, 1, std::i32::MAX as i128];
+
+/// Returns the integers in the given comma separated list, or None if it has elements that are
+/// not integers.
+pub fn parse_widening_thresholds(list: &str) -> Option<Vec<i128>> {
+    list.split(',')
+        .map(|threshold| threshold.trim().parse::<i128>().ok())
+        .collect()
+}
+
+/// The format in which diagnostics are reported, as selected by the --diag-format command
+/// line option.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiagnosticFormat {
+    /// Diagnostics are only reported on the console, the way rustc reports them.
+    Human,
+    /// Diagnostics are reported on the console and are also written to a SARIF 2.1.0 file, so that
+    /// they can be ingested by code scanning tools and CI dashboards.
+    Sarif,
+}
+
+impl Default for DiagnosticFormat {
+    fn default() -> Self {
+        DiagnosticFormat::Human
+    }
+}
+
+impl DiagnosticFormat {
+    /// Returns the format with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<DiagnosticFormat> {
+        match name {
+            "human" => Some(DiagnosticFormat::Human),
+            "sarif" => Some(DiagnosticFormat::Sarif),
+            _ => None,
+        }
+    }
+}
+
+/// The SMT solver that is used to prove obligations, as selected by the --smt-backend command line
+/// option. If the option is not given, the solver process of the [solver] section is used, if
+/// there is one, and no solver otherwise.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SmtBackend {
+    /// No solver is used, so only the conditions that the abstract domains can decide are proven.
+    None,
+    /// The solver process started with the command of the [solver] section (see IsolatedSolver).
+    Process,
+    /// A cvc5 process, to which the obligations are sent as SMT-LIB2 (see Cvc5Solver).
+    Cvc5,
+}
+
+impl SmtBackend {
+    /// Returns the backend with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<SmtBackend> {
+        match name {
+            "none" => Some(SmtBackend::None),
+            "process" => Some(SmtBackend::Process),
+            "cvc5" => Some(SmtBackend::Cvc5),
+            _ => None,
+        }
+    }
+}
+
+/// How casts of integers to raw pointers, such as `addr as *const T`, are treated, as selected by
+/// the --int-to-ptr-casts command line option. Under strict provenance such a pointer has no
+/// provenance, so it may not be used to access memory, even if the integer is the address of an
+/// allocation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IntToPointerCasts {
+    /// The casts are errors, so that crates that have migrated to strict provenance stay that way.
+    Strict,
+    /// The casts are warnings, so that crates that are migrating to strict provenance can find
+    /// them.
+    Warn,
+    /// The casts are not reported and the pointer that results from a cast is assumed to point to
+    /// an allocation of its own, as if the integer came from a pointer to a valid allocation.
+    Angelic,
+}
+
+impl Default for IntToPointerCasts {
+    fn default() -> Self {
+        IntToPointerCasts::Warn
+    }
+}
+
+impl IntToPointerCasts {
+    /// Returns the treatment with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<IntToPointerCasts> {
+        match name {
+            "strict" => Some(IntToPointerCasts::Strict),
+            "warn" => Some(IntToPointerCasts::Warn),
+            "angelic" => Some(IntToPointerCasts::Angelic),
+            _ => None,
+        }
+    }
+}
+
+/// The [solver] section of a MIRAI configuration file.
+#[derive(Deserialize, Debug, Default)]
+struct SolverConfig {
+    /// The solver strategy to use for each class of proof obligation.
+    #[serde(default)]
+    strategies: HashMap<ObligationClass, String>,
+    /// The shell command that starts a separate solver process.
+    #[serde(default)]
+    command: Option<String>,
+    /// The longest time, in milliseconds, that the solver process may take to answer a query.
+    #[serde(default)]
+    time_limit_ms: Option<u64>,
+    /// The most virtual memory, in megabytes, that the solver process may use.
+    #[serde(default)]
+    memory_limit_mb: Option<u64>,
+}
+
+/// Options, supplied by the user, that control how the analysis is done and what it reports.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// If true, arithmetic operations that may overflow are reported even when the crate is
+    /// compiled without overflow checks, so that overflows silently wrap around rather than panic.
+    pub lint_wrapping_arithmetic: bool,
+    /// If true, every arithmetic operation that may overflow is reported where it occurs, whether
+    /// or not the crate is compiled with overflow checks, rather than becoming a precondition of
+    /// the function that contains it.
+    pub check_overflow: bool,
+    /// If true, the diagnostics about possible panics are errors, calls to functions that have not
+    /// been analyzed count as possible panics, unreachable!() has to be proven unreachable and no
+    /// obligations are skipped, so that a crate without errors is certified to be panic free.
+    pub verify_no_panics: bool,
+    /// If true, the functions whose results depend on the order in which a HashMap or HashSet is
+    /// iterated over are reported, since the order, and with it the result, differs from run to
+    /// run. So are the writes to files and the console of values that depend on that order or on
+    /// other sources of nondeterminism, such as random numbers, clocks and addresses.
+    pub check_determinism: bool,
+    /// How casts of integers to raw pointers are treated.
+    pub int_to_ptr_casts: IntToPointerCasts,
+    /// If true, the analysis of a crate stops as soon as the analysis of a function finds an
+    /// error, and only the diagnostics found so far are reported.
+    pub fail_fast: bool,
+    /// If true, only the entry points of a crate and the functions of the crate that they call
+    /// directly are analyzed. Calls to other functions are analyzed with the summaries stored by
+    /// earlier runs, if there are any, or with summaries derived from their signatures.
+    pub smoke: bool,
+    /// If true, conditions that involve nonlinear arithmetic are not sent to the solver, so that
+    /// analysis time stays predictable. Such conditions are treated as unproven.
+    pub linear_arithmetic_only: bool,
+    /// If true, functions whose summaries are the same as those of other functions of the crate
+    /// are reported, since they may duplicate logic that could be consolidated.
+    pub report_equivalent_functions: bool,
+    /// The solver strategy (a tactic or logic) to use for each class of proof obligation.
+    /// Obligations of a class without an entry are solved with the default strategy of the solver.
+    pub solver_strategies: HashMap<ObligationClass, String>,
+    /// If set, solver queries are answered by a separate process, started with this shell command,
+    /// so that a crash or memory blow-up of the solver does not take down the compiler.
+    pub solver_command: Option<String>,
+    /// The longest time, in milliseconds, that the solver process may take to answer a query.
+    /// If not set, k_limits::MAX_SOLVER_QUERY_MILLISECONDS applies.
+    pub solver_time_limit_ms: Option<u64>,
+    /// If set, the solver process may not use more than this many megabytes of virtual memory.
+    pub solver_memory_limit_mb: Option<u64>,
+    /// The solver that is used to prove obligations, if selected on the command line.
+    /// See get_smt_backend for what happens if it is not.
+    pub smt_backend: Option<SmtBackend>,
+    /// What is known about the values of environment variables, keyed by their names.
+    /// Variables without an entry are untrusted inputs about which nothing is known.
+    pub environment_variables: HashMap<String, EnvironmentVariable>,
+    /// The number of proof obligations of a function that are checked. If a function has more,
+    /// the less important classes of obligations are skipped and a diagnostic says which.
+    /// If not set, k_limits::MAX_OBLIGATIONS_PER_FUNCTION applies.
+    pub obligation_budget: Option<usize>,
+    /// If set, the analysis of a function body is abandoned once it has taken this many
+    /// milliseconds, after which the function gets the summary of a function without a body.
+    pub per_body_timeout_ms: Option<u64>,
+    /// If set, the summaries of the public functions are compared with their summaries in this
+    /// summary store, of an earlier version of the crate, and the differences in behavior are
+    /// reported.
+    pub baseline_summary_store: Option<String>,
+    /// If set, the obligations that could not be proven are written to this directory, as a SMT-LIB2
+    /// script per function, so that they can be attempted with other provers.
+    pub obligations_directory: Option<String>,
+    /// The checksums of the obligations that were proven by other means, along with descriptions
+    /// of their proofs, keyed by the summary keys of their functions. These obligations are
+    /// assumed to hold rather than reported. See smt_lib::obligation_checksum.
+    pub proven_obligations: HashMap<String, HashMap<String, String>>,
+    /// If set, the obligations that were assumed to hold because they are among the proven
+    /// obligations are written to this file as JSON, so that what the analysis takes on trust
+    /// can be audited.
+    pub assumption_log: Option<String>,
+    /// The types that represent fixed point numbers, keyed by their summary keys. Calls to the
+    /// arithmetic operators and comparisons of these types are modeled as integer arithmetic.
+    pub fixed_point_types: HashMap<String, FixedPointType>,
+    /// If set, the contracts inferred for the public functions of the crate are written to this
+    /// file, so that they can be published along with the crate.
+    pub contracts_file: Option<String>,
+    /// If set, the preconditions and postconditions inferred for the public functions of the
+    /// crate are written to this file as JSON, along with their source locations, so that they
+    /// can be reviewed and promoted to explicit annotations.
+    pub inferred_contracts_file: Option<String>,
+    /// If set, the summaries of the public functions of the crate, and nothing else, are written
+    /// to a summary database in this directory, so that they can be published along with the crate.
+    pub summary_export_directory: Option<String>,
+    /// If set, the public functions of the crate are checked to not reach unsafe code, other than
+    /// that of trusted crates, and a certificate that records the outcome is written to this file.
+    /// See certificate::write_unsafe_free_certificate.
+    pub unsafe_free_certificate_file: Option<String>,
+    /// The number of times the functions of the crate are analyzed while their summaries keep
+    /// changing, which limits how deeply the summaries of recursive functions are unfolded.
+    /// If not set, k_limits::MAX_OUTER_FIXPOINT_ITERATIONS applies.
+    pub summary_recursion_depth: Option<usize>,
+    /// The URL of a remote cache that holds the summary databases of the published crates that
+    /// the workspace depends on, so that they need not be computed on every machine.
+    /// See remote_cache::get_remote_key.
+    pub remote_summary_cache: Option<String>,
+    /// If true, the summary databases of published crates that are not in the remote summary cache
+    /// are stored there once they have been computed.
+    pub upload_summaries: bool,
+    /// How far the analysis trusts the functions of other crates, keyed by crate name. Crates
+    /// without an entry are analyzed as usual, which is the same as Verified, except that calls
+    /// to functions that were not analyzed are not reported.
+    pub trust_levels: HashMap<String, TrustLevel>,
+    /// The functions that are trusted to be panic free or total, keyed by summary key. Calls to
+    /// them are assumed to never panic, which is recorded in the assumption log.
+    pub trusted_functions: HashMap<String, TrustedFunction>,
+    /// If set, the diagnostics that depend on untrusted input are also written to this file as
+    /// JSON, along with the locations where the input entered the program, so that they can be
+    /// ingested by tools that track security findings.
+    pub security_findings_file: Option<String>,
+    /// The ABI contracts of the functions that are exported to C, keyed by function name.
+    /// Exported functions without a contract may be called with any arguments.
+    pub ffi_contracts: HashMap<String, FfiContract>,
+    /// The constants to which the values of loop counters are widened, once the fixed point loop
+    /// has joined them for long enough. See IntervalDomain::widen_with_thresholds.
+    pub widening_thresholds: Vec<i128>,
+    /// If true, the crate is a dependency of a workspace that is analyzed by cargo mirai
+    /// --workspace-only. If a previous run has stored summaries for the crate, they are used
+    /// as they are, rather than analyzing the crate again.
+    pub reuse_dependency_summaries: bool,
+    /// The functions that establish named properties of values, keyed by summary key.
+    pub sanitizers: HashMap<String, Sanitizer>,
+    /// The functions whose arguments must have named properties, keyed by summary key.
+    pub sinks: HashMap<String, Sink>,
+    /// What functions that cannot be analyzed require and do, keyed by summary key.
+    pub function_models: HashMap<String, FunctionModel>,
+    /// The precision presets of modules, keyed by module path, such as my_crate.generated.
+    /// Functions of modules without an entry, or an entry for an enclosing module, use
+    /// PrecisionPreset::Standard.
+    pub precision_presets: HashMap<String, PrecisionPreset>,
+    /// The limits that apply to the analysis of the function bodies of the crate, unless they are
+    /// overridden by the #[mirai::limits(..)] attribute of a function.
+    pub limits: KLimits,
+    /// Templates for the messages of the diagnostics about conditions that could not be proven,
+    /// keyed by the message they replace or by a confidence code, such as "missing_models".
+    /// See message_templates::interpolate for the facts that they can refer to.
+    pub message_templates: HashMap<String, String>,
+}
+
+impl Options {
+    /// Reads the options from the environment variables that were set when MIRAI was started
+    /// and from the configuration file, if there is one.
+    pub fn from_environment() -> Options {
+        Self::from_environment_and_config(Self::read_config_file())
+    }
+
+    /// Reads the options from the environment variables that were set when MIRAI was started, but
+    /// takes the configuration from the given contents of a configuration file, rather than from
+    /// the configuration file of the environment. This is how the test harness gives a test case
+    /// the configuration in its // MIRAI_CONFIG lines.
+    pub fn from_environment_and_configuration(contents: &str) -> Options {
+        Self::from_environment_and_config(Self::parse_config_file(contents, "the configuration"))
+    }
+
+    /// Applies the given command line argument to the options, if it is one of the options of
+    /// MIRAI, such as --check-overflow or --per-body-timeout-ms=100, and returns true if it is.
+    /// Other arguments are meant for rustc. The options that are followed by a path, such as
+    /// --export-contracts, and --diag-format, which is not about the analysis, are not handled
+    /// here. Reports and ignores options with malformed values.
+    pub fn parse_command_line_option(&mut self, arg: &str) -> bool {
+        if arg.starts_with(WIDENING_THRESHOLDS_OPTION) {
+            let list = &arg[WIDENING_THRESHOLDS_OPTION.len()..];
+            match parse_widening_thresholds(list) {
+                Some(thresholds) => self.widening_thresholds = thresholds,
+                None => println!(
+                    "invalid widening thresholds {}, expected a comma separated list of integers",
+                    list
+                ),
+            }
+        } else if arg.starts_with(SMT_BACKEND_OPTION) {
+            let name = &arg[SMT_BACKEND_OPTION.len()..];
+            match SmtBackend::from_name(name) {
+                Some(backend) => self.smt_backend = Some(backend),
+                None => println!(
+                    "unknown SMT backend {}, expected none, process or cvc5",
+                    name
+                ),
+            }
+        } else if arg.starts_with(INT_TO_PTR_CASTS_OPTION) {
+            let name = &arg[INT_TO_PTR_CASTS_OPTION.len()..];
+            match IntToPointerCasts::from_name(name) {
+                Some(treatment) => self.int_to_ptr_casts = treatment,
+                None => println!(
+                    "unknown treatment of integer to pointer casts {}, expected strict, warn or angelic",
+                    name
+                ),
+            }
+        } else if arg.starts_with(PER_BODY_TIMEOUT_OPTION) {
+            let timeout = &arg[PER_BODY_TIMEOUT_OPTION.len()..];
+            match timeout.parse::<u64>() {
+                Ok(timeout_ms) => self.per_body_timeout_ms = Some(timeout_ms),
+                Err(_) => println!(
+                    "invalid per body timeout {}, expected a number of milliseconds",
+                    timeout
+                ),
+            }
+        } else if arg.starts_with(LIMIT_OPTION_PREFIX) {
+            let mut limits = LimitsConfig::default();
+            limits.parse_option(arg);
+            self.override_limits(&limits);
+        } else if arg.starts_with(DIFF_AGAINST_OPTION) {
+            self.baseline_summary_store = Some(String::from(&arg[DIFF_AGAINST_OPTION.len()..]));
+        } else if arg == CHECK_OVERFLOW_OPTION {
+            self.check_overflow = true;
+        } else if arg == VERIFY_NO_PANICS_OPTION {
+            self.verify_no_panics = true;
+        } else if arg == CHECK_DETERMINISM_OPTION {
+            self.check_determinism = true;
+        } else if arg == LINT_WRAPPING_ARITHMETIC_OPTION {
+            self.lint_wrapping_arithmetic = true;
+        } else if arg == LINEAR_ARITHMETIC_ONLY_OPTION {
+            self.linear_arithmetic_only = true;
+        } else if arg == FAIL_FAST_OPTION {
+            self.fail_fast = true;
+        } else if arg == SMOKE_OPTION {
+            self.smoke = true;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Reads the options from the environment variables and the given configuration.
+    fn from_environment_and_config(config: ConfigFile) -> Options {
+        let manifest_limits = Self::read_manifest_limits();
+        let mut limits = KLimits::default();
+        config.limits.apply_to(&mut limits);
+        manifest_limits.apply_to(&mut limits);
+        Options {
+            lint_wrapping_arithmetic: env::var(LINT_WRAPPING_ARITHMETIC_VARIABLE).is_ok(),
+            check_overflow: false,
+            verify_no_panics: false,
+            check_determinism: false,
+            int_to_ptr_casts: IntToPointerCasts::default(),
+            fail_fast: false,
+            smoke: false,
+            linear_arithmetic_only: env::var(LINEAR_ARITHMETIC_ONLY_VARIABLE).is_ok(),
+            report_equivalent_functions: env::var(REPORT_EQUIVALENT_FUNCTIONS_VARIABLE).is_ok(),
+            solver_strategies: config.solver.strategies,
+            solver_command: config.solver.command,
+            solver_time_limit_ms: config.solver.time_limit_ms,
+            solver_memory_limit_mb: config.solver.memory_limit_mb,
+            smt_backend: None,
+            environment_variables: config.environment,
+            obligation_budget: config.obligations.budget,
+            per_body_timeout_ms: None,
+            baseline_summary_store: None,
+            obligations_directory: config.obligations.export,
+            proven_obligations: Self::read_proofs_file(&config.obligations.proven),
+            assumption_log: config.obligations.assumption_log,
+            fixed_point_types: config.fixed_point_types,
+            contracts_file: config.contracts.export,
+            inferred_contracts_file: None,
+            summary_export_directory: None,
+            unsafe_free_certificate_file: None,
+            summary_recursion_depth: manifest_limits
+                .max_recursion_depth
+                .or(config.limits.max_recursion_depth)
+                .or(config.summaries.recursion_depth),
+            remote_summary_cache: config.summaries.remote_cache,
+            upload_summaries: config.summaries.upload,
+            trust_levels: config.trust,
+            trusted_functions: Self::get_trusted_functions(config.trusted_functions),
+            security_findings_file: config.security.export,
+            ffi_contracts: config.ffi,
+            widening_thresholds: DEFAULT_WIDENING_THRESHOLDS.to_vec(),
+            reuse_dependency_summaries: env::var(WORKSPACE_ONLY_VARIABLE).is_ok()
+                && env::var(PRIMARY_PACKAGE_VARIABLE).is_err(),
+            sanitizers: config.sanitizers,
+            sinks: config.sinks,
+            function_models: Self::read_models_file(),
+            precision_presets: config.precision,
+            limits,
+            message_templates: config.messages,
+        }
+    }
+
+    /// Overrides the limits with the given ones, which take precedence over the ones of the
+    /// configuration files.
+    pub fn override_limits(&mut self, overrides: &LimitsConfig) {
+        overrides.apply_to(&mut self.limits);
+        if overrides.max_recursion_depth.is_some() {
+            self.summary_recursion_depth = overrides.max_recursion_depth;
+        }
+    }
+
+    /// Returns the solver that is used to prove obligations: the one selected on the command line,
+    /// or else the solver process of the configuration file, if there is one.
+    pub fn get_smt_backend(&self) -> SmtBackend {
+        match self.smt_backend {
+            Some(backend) => backend,
+            None if self.solver_command.is_some() => SmtBackend::Process,
+            None => SmtBackend::None,
+        }
+    }
+
+    /// Returns the precision preset of the function with the given summary key, which is that of
+    /// the innermost module that contains the function and has an entry in precision_presets.
+    pub fn get_precision_preset(&self, summary_key: &str) -> PrecisionPreset {
+        self.precision_presets
+            .iter()
+            .filter(|(module_path, _)| {
+                summary_key.starts_with(module_path.as_str())
+                    && summary_key[module_path.len()..].starts_with('.')
+            })
+            .max_by_key(|(module_path, _)| module_path.len())
+            .map(|(_, preset)| *preset)
+            .unwrap_or_default()
+    }
+
+    /// Reads the models file identified by the MIRAI_MODELS environment variable, or
+    /// mirai-models.toml in the current directory if the variable is not set.
+    /// Reports and ignores missing or malformed files.
+    fn read_models_file() -> HashMap<String, FunctionModel> {
+        let path = match env::var(MODELS_FILE_VARIABLE) {
+            Ok(path) => path,
+            Err(_) if Path::new(DEFAULT_MODELS_FILE).exists() => String::from(DEFAULT_MODELS_FILE),
+            Err(_) => return HashMap::new(),
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => models::parse_models(&contents, &path),
+            Err(err) => {
+                println!("unable to read function models from {}: {}", path, err);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Returns the given trusted functions, except for the ones whose declarations do not say why
+    /// they are trusted, which are reported and ignored, since trust has to be accounted for.
+    fn get_trusted_functions(
+        trusted_functions: HashMap<String, TrustedFunction>,
+    ) -> HashMap<String, TrustedFunction> {
+        trusted_functions
+            .into_iter()
+            .filter(|(key, trusted_function)| {
+                if trusted_function.reason.trim().is_empty() {
+                    println!(
+                        "ignoring the trusted function {}, because its declaration has no reason",
+                        key
+                    );
+                    return false;
+                }
+                true
+            })
+            .collect()
+    }
+
+    /// Reads the proven obligations from the given file, if there is one.
+    /// Reports and ignores missing or malformed files.
+    fn read_proofs_file(path: &Option<String>) -> HashMap<String, HashMap<String, String>> {
+        let path = match path {
+            Some(path) => path,
+            None => return HashMap::new(),
+        };
+        match fs::read_to_string(path) {
+            Ok(contents) => smt_lib::parse_proofs(&contents, path),
+            Err(err) => {
+                println!("unable to read proven obligations from {}: {}", path, err);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Reads the limits in the [package.metadata.mirai] section of the Cargo.toml file of the
+    /// package that is being compiled, if cargo says which package that is.
+    /// Reports and ignores malformed files.
+    fn read_manifest_limits() -> LimitsConfig {
+        let manifest_path = match env::var(MANIFEST_DIR_VARIABLE) {
+            Ok(directory) => Path::new(&directory).join("Cargo.toml"),
+            Err(_) => return LimitsConfig::default(),
+        };
+        match fs::read_to_string(&manifest_path) {
+            Ok(contents) => match toml::from_str::<Manifest>(&contents) {
+                Ok(manifest) => manifest.package.metadata.mirai,
+                Err(err) => {
+                    println!(
+                        "unable to parse limits in {}: {}",
+                        manifest_path.display(),
+                        err
+                    );
+                    LimitsConfig::default()
+                }
+            },
+            Err(_) => LimitsConfig::default(),
+        }
+    }
+
+    /// Reads the configuration file identified by the MIRAI_CONFIG environment variable, or
+    /// mirai.toml in the current directory if the variable is not set.
+    /// Reports and ignores missing or malformed files.
+    fn read_config_file() -> ConfigFile {
+        let path = match env::var(CONFIG_FILE_VARIABLE) {
+            Ok(path) => path,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => String::from(DEFAULT_CONFIG_FILE),
+            Err(_) => return ConfigFile::default(),
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse_config_file(&contents, &path),
+            Err(err) => {
+                println!("unable to read configuration from {}: {}", path, err);
+                ConfigFile::default()
+            }
+        }
+    }
+
+    /// Parses the contents of a configuration file. Reports and ignores malformed files.
+    fn parse_config_file(contents: &str, path: &str) -> ConfigFile {
+        match toml::from_str::<ConfigFile>(contents) {
+            Ok(config) => config,
+            Err(err) => {
+                println!("unable to parse configuration in {}: {}", path, err);
+                ConfigFile::default()
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::options::PRIMARY_PACKAGE_VARIABLE;
+use crate::smt_lib;
+use crate::summaries::SUMMARY_FORMAT_VERSION;
+
+use std::env;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The version of MIRAI, which is part of the keys of the remote summary cache, since the
+/// summaries computed by one version of MIRAI need not be valid for another. Builds of MIRAI
+/// between releases have the same version, so the keys also include SUMMARY_FORMAT_VERSION.
+const MIRAI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The program that transfers summary databases to and from the remote summary cache. Plain HTTP
+/// GET and PUT requests are all that is needed, so any HTTP server or S3 compatible bucket that
+/// accepts them can serve as the cache. Credentials, if any, are taken from ~/.netrc.
+const CURL: &str = "curl";
+
+/// Returns the key under which the summary database of the crate being compiled is kept in a
+/// remote summary cache. It is made of the name and version of the crate, a hash of the
+/// configuration with which it is compiled (its features, target and so on, as given by the
+/// cfg settings), the version of MIRAI and the version of the format of stored summaries. The hash
+/// is stable, so that every machine computes the same key. Only crates that cargo got from a registry qualify,
+/// since their sources are determined by their names and versions, while the crates of the
+/// workspace and path and git dependencies may change without getting a new version. Returns
+/// None for such crates.
+pub fn get_remote_key(crate_name: &str, configuration: &[String]) -> Option<String> {
+    if env::var(PRIMARY_PACKAGE_VARIABLE).is_ok() {
+        return None;
+    }
+    // Cargo unpacks the crates it gets from a registry into $CARGO_HOME/registry/src.
+    let manifest_directory = env::var("CARGO_MANIFEST_DIR").ok()?;
+    let is_from_registry = Path::new(&manifest_directory)
+        .components()
+        .any(|component| component.as_os_str() == "registry");
+    if !is_from_registry {
+        return None;
+    }
+    let version = env::var("CARGO_PKG_VERSION").ok()?;
+    let configuration_hash = smt_lib::stable_hash(configuration.join("\n").as_bytes());
+    Some(format!(
+        "{}-{}-{:016x}-mirai-{}-format-{}.summaries",
+        crate_name, version, configuration_hash, MIRAI_VERSION, SUMMARY_FORMAT_VERSION
+    ))
+}
+
+/// Returns the address of the given key in the remote summary cache at the given URL.
+fn get_address(url: &str, key: &str) -> String {
+    format!("{}/{}", url.trim_end_matches('/'), key)
+}
+
+/// Fetches the summary database with the given key from the remote summary cache at the given
+/// URL. Returns None if the cache does not have it or cannot be reached.
+pub fn download(url: &str, key: &str) -> Option<Vec<u8>> {
+    let address = get_address(url, key);
+    let output = Command::new(CURL)
+        .args(&["--silent", "--fail", "--location", "--netrc-optional"])
+        .arg(&address)
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            info!("fetched summaries from {}", address);
+            Some(output.stdout)
+        }
+        Ok(_) => {
+            info!("no summaries at {}", address);
+            None
+        }
+        Err(err) => {
+            println!("unable to run {} to fetch summaries: {}", CURL, err);
+            None
+        }
+    }
+}
+
+/// Stores the given summary database under the given key in the remote summary cache at the
+/// given URL. Reports and otherwise ignores failures.
+pub fn upload(url: &str, key: &str, blob: &[u8]) {
+    let address = get_address(url, key);
+    let mut child = match Command::new(CURL)
+        .args(&[
+            "--silent",
+            "--fail",
+            "--netrc-optional",
+            "--upload-file",
+            "-",
+        ])
+        .arg(&address)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            println!("unable to run {} to store summaries: {}", CURL, err);
+            return;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(err) = stdin.write_all(blob) {
+            println!("unable to store summaries at {}: {}", address, err);
+        }
+    }
+    match child.wait() {
+        Ok(status) if status.success() => info!("stored summaries at {}", address),
+        Ok(status) => println!(
+            "unable to store summaries at {}: {} exited with {}",
+            address, CURL, status
+        ),
+        Err(err) => println!("unable to store summaries at {}: {}", address, err),
+    }
+}
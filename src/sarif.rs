@@ -0,0 +1,137 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::utils::json_string;
+
+use rustc::ty::TyCtxt;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use syntax::errors::{Diagnostic, DiagnosticId, Level};
+use syntax_pos::Span;
+
+/// The version of the SARIF format that is written.
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The JSON schema of the SARIF format that is written.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The rule id of diagnostics that do not have a code.
+const DEFAULT_RULE_ID: &str = "mirai";
+
+/// Writes the given diagnostics to a SARIF 2.1.0 file at the given path, as the results of a
+/// single run of MIRAI. The rule id of a result is the confidence code of its diagnostic. The
+/// related locations of a diagnostic become the related locations of its result and, together
+/// with the location of the diagnostic, the steps of a code flow that traces the calls that lead
+/// to the problem. Reports and otherwise ignores failures.
+pub fn write_sarif(path: &Path, diagnostics: &[Diagnostic], tcx: &TyCtxt<'_, '_, '_>) {
+    let rule_ids: BTreeSet<String> = diagnostics.iter().map(get_rule_id).collect();
+    let rules: Vec<String> = rule_ids.iter().map(|id| rule_to_json(id)).collect();
+    let results: Vec<String> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| result_to_json(diagnostic, tcx))
+        .collect();
+    let contents = format!(
+        "{{\"$schema\":{},\"version\":{},\"runs\":[{{\"tool\":{{\"driver\":{{\"name\":\"MIRAI\",\
+         \"informationUri\":\"https://github.com/facebookexperimental/MIRAI\",\"rules\":[{}]}}}},\
+         \"results\":[\n{}\n]}}]}}\n",
+        json_string(SARIF_SCHEMA),
+        json_string(SARIF_VERSION),
+        rules.join(","),
+        results.join(",\n")
+    );
+    if let Err(err) = fs::write(path, contents) {
+        println!("unable to write diagnostics to {}: {}", path.display(), err);
+    }
+}
+
+/// Returns the confidence code of the diagnostic, which serves as the id of its SARIF rule.
+fn get_rule_id(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.code {
+        Some(DiagnosticId::Lint(code)) | Some(DiagnosticId::Error(code)) => code.clone(),
+        None => String::from(DEFAULT_RULE_ID),
+    }
+}
+
+fn rule_to_json(rule_id: &str) -> String {
+    let description = match rule_id {
+        "definite" => "The problem occurs whenever the code is reached.",
+        "limited_precision" => {
+            "The problem may occur, or may be due to the limited precision of the analysis."
+        }
+        "missing_models" => "The problem may occur, or may be due to a call that is not modeled.",
+        "specification" => "The problem is in a specification, such as a contract or invariant.",
+        _ => "A problem found by MIRAI.",
+    };
+    format!(
+        "{{\"id\":{},\"shortDescription\":{{\"text\":{}}}}}",
+        json_string(rule_id),
+        json_string(description)
+    )
+}
+
+/// Returns the SARIF result for the given diagnostic, or None if it has no location.
+fn result_to_json(diagnostic: &Diagnostic, tcx: &TyCtxt<'_, '_, '_>) -> Option<String> {
+    let span = diagnostic.span.primary_span()?;
+    let location = location_to_json(span, tcx);
+    let level = match diagnostic.level {
+        Level::Bug | Level::Fatal | Level::PhaseFatal | Level::Error => "error",
+        Level::Warning => "warning",
+        _ => "note",
+    };
+    let mut related_locations = Vec::new();
+    let mut steps = vec![format!(
+        "{{\"location\":{{\"physicalLocation\":{}}}}}",
+        location
+    )];
+    for child in diagnostic.children.iter() {
+        if let Some(child_span) = child.span.primary_span() {
+            let child_location = location_to_json(child_span, tcx);
+            let message = json_string(&child.message());
+            related_locations.push(format!(
+                "{{\"id\":{},\"physicalLocation\":{},\"message\":{{\"text\":{}}}}}",
+                related_locations.len(),
+                child_location,
+                message
+            ));
+            steps.push(format!(
+                "{{\"location\":{{\"physicalLocation\":{},\"message\":{{\"text\":{}}}}}}}",
+                child_location, message
+            ));
+        }
+    }
+    let mut result = format!(
+        "{{\"ruleId\":{},\"level\":\"{}\",\"message\":{{\"text\":{}}},\"locations\":[{{\"physicalLocation\":{}}}]",
+        json_string(&get_rule_id(diagnostic)),
+        level,
+        json_string(&diagnostic.message()),
+        location
+    );
+    if !related_locations.is_empty() {
+        result.push_str(&format!(
+            ",\"relatedLocations\":[{}],\"codeFlows\":[{{\"threadFlows\":[{{\"locations\":[{}]}}]}}]",
+            related_locations.join(","),
+            steps.join(",")
+        ));
+    }
+    result.push('}');
+    Some(result)
+}
+
+/// Returns the SARIF physical location of the given span.
+fn location_to_json(span: Span, tcx: &TyCtxt<'_, '_, '_>) -> String {
+    let source_map = tcx.sess.source_map();
+    let start = source_map.lookup_char_pos(span.lo());
+    let end = source_map.lookup_char_pos(span.hi());
+    format!(
+        "{{\"artifactLocation\":{{\"uri\":{}}},\"region\":{{\"startLine\":{},\"startColumn\":{},\
+         \"endLine\":{},\"endColumn\":{}}}}}",
+        json_string(&start.file.name.to_string()),
+        start.line,
+        start.col.0 + 1,
+        end.line,
+        end.col.0 + 1
+    )
+}
@@ -0,0 +1,132 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::utils::json_string;
+
+use rustc::ty::TyCtxt;
+use std::fs;
+use syntax::errors::{Diagnostic, DiagnosticId};
+use syntax_pos::Span;
+
+/// The message of the notes that point at the locations where untrusted input, on which the
+/// condition of a diagnostic depends, entered the program.
+pub const UNTRUSTED_INPUT_NOTE: &str = "untrusted input enters the program here";
+
+/// The location of a span in the source of the crate, as written to the findings file.
+struct Location {
+    file: String,
+    line: usize,
+    column: usize,
+}
+
+/// A diagnostic that depends on untrusted input, along with the data flow that links the
+/// input to the location of the diagnostic.
+struct SecurityFinding {
+    /// The kind of finding. Currently always "taint".
+    class: &'static str,
+    message: String,
+    /// The confidence code of the diagnostic, if it has one.
+    confidence: Option<String>,
+    /// Where the untrusted input ends up being used.
+    sink: Location,
+    /// Where the untrusted input entered the program.
+    sources: Vec<Location>,
+    /// The locations, such as call sites and related locations, through which the input
+    /// flows from the sources to the sink.
+    hops: Vec<Location>,
+}
+
+/// Writes the diagnostics that depend on untrusted input to a JSON file at the given path, as an
+/// array of findings that each have a class, message, confidence, sink, sources and hops, so that
+/// tools that track security findings can ingest them. Diagnostics depend on untrusted input if
+/// they have notes with UNTRUSTED_INPUT_NOTE as message. Reports and otherwise ignores failures.
+pub fn export_findings(path: &str, diagnostics: &[Diagnostic], tcx: &TyCtxt<'_, '_, '_>) {
+    let findings: Vec<SecurityFinding> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| get_finding(diagnostic, tcx))
+        .collect();
+    let contents = format!(
+        "[{}]\n",
+        findings
+            .iter()
+            .map(finding_to_json)
+            .collect::<Vec<String>>()
+            .join(",\n")
+    );
+    if let Err(err) = fs::write(path, contents) {
+        println!("unable to write security findings to {}: {}", path, err);
+    }
+}
+
+/// Returns a finding for the given diagnostic if it depends on untrusted input.
+fn get_finding(diagnostic: &Diagnostic, tcx: &TyCtxt<'_, '_, '_>) -> Option<SecurityFinding> {
+    let mut sources = Vec::new();
+    let mut hops = Vec::new();
+    for child in diagnostic.children.iter() {
+        if let Some(span) = child.span.primary_span() {
+            if child.message() == UNTRUSTED_INPUT_NOTE {
+                sources.push(get_location(span, tcx));
+            } else {
+                hops.push(get_location(span, tcx));
+            }
+        }
+    }
+    if sources.is_empty() {
+        return None;
+    }
+    let sink = get_location(diagnostic.span.primary_span()?, tcx);
+    let confidence = match &diagnostic.code {
+        Some(DiagnosticId::Lint(code)) | Some(DiagnosticId::Error(code)) => Some(code.clone()),
+        None => None,
+    };
+    Some(SecurityFinding {
+        class: "taint",
+        message: diagnostic.message(),
+        confidence,
+        sink,
+        sources,
+        hops,
+    })
+}
+
+/// Returns the file, line and column of the start of the given span.
+fn get_location(span: Span, tcx: &TyCtxt<'_, '_, '_>) -> Location {
+    let loc = tcx.sess.source_map().lookup_char_pos(span.lo());
+    Location {
+        file: loc.file.name.to_string(),
+        line: loc.line,
+        column: loc.col.0 + 1,
+    }
+}
+
+fn finding_to_json(finding: &SecurityFinding) -> String {
+    let confidence = match &finding.confidence {
+        Some(code) => json_string(code),
+        None => String::from("null"),
+    };
+    format!(
+        "{{\"class\":{},\"message\":{},\"confidence\":{},\"sink\":{},\"sources\":{},\"hops\":{}}}",
+        json_string(finding.class),
+        json_string(&finding.message),
+        confidence,
+        location_to_json(&finding.sink),
+        locations_to_json(&finding.sources),
+        locations_to_json(&finding.hops)
+    )
+}
+
+fn locations_to_json(locations: &[Location]) -> String {
+    let elements: Vec<String> = locations.iter().map(location_to_json).collect();
+    format!("[{}]", elements.join(","))
+}
+
+fn location_to_json(location: &Location) -> String {
+    format!(
+        "{{\"file\":{},\"line\":{},\"column\":{}}}",
+        json_string(&location.file),
+        location.line,
+        location.column
+    )
+}
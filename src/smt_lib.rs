@@ -0,0 +1,549 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::abstract_domains::AbstractDomain;
+use crate::constant_domain::ConstantDomain;
+use crate::expression::{Expression, ExpressionType};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A proof obligation that the analysis could not discharge and that was reported as a diagnostic.
+#[derive(Clone, Debug)]
+pub struct UnprovenObligation {
+    /// The message of the diagnostic that reports the obligation.
+    pub message: String,
+    /// The location of the diagnostic in the source of the crate, as file:line:column.
+    pub location: String,
+    /// The condition under which the location of the obligation is reached.
+    pub entry_condition: Expression,
+    /// The condition that must hold whenever the location is reached. False for obligations to
+    /// prove that the location cannot be reached.
+    pub goal: Expression,
+}
+
+/// An unproven obligation that a proofs file says was discharged by other means, such as an
+/// interactive prover, so that it was assumed to hold rather than reported. Calls of functions
+/// that the configuration file trusts are recorded the same way.
+#[derive(Serialize, Clone, Debug)]
+pub struct AssumedObligation {
+    /// The summary key of the function that has the obligation.
+    pub function: String,
+    /// The checksum of the obligation (see obligation_checksum), or empty if the obligation is a
+    /// call of a function that the configuration file trusts.
+    pub checksum: String,
+    /// What the proofs file says about the proof, such as where it can be found.
+    pub proof: String,
+    /// The message of the diagnostic that would otherwise have reported the obligation.
+    pub message: String,
+    /// The location of the obligation in the source of the crate, as file:line:column.
+    pub location: String,
+}
+
+/// The contents of a proofs file, which lists the exported obligations that were proven by other
+/// means.
+#[derive(Deserialize, Debug, Default)]
+struct ProofsFile {
+    #[serde(default)]
+    proven: Vec<ProvenEntry>,
+}
+
+/// The [[proven]] entries of a proofs file.
+#[derive(Deserialize, Debug)]
+struct ProvenEntry {
+    /// The summary key of the function that has the obligation.
+    function: String,
+    /// The checksum of the obligation, as given in the exported SMT-LIB2 script.
+    checksum: String,
+    /// Anything that helps an auditor find the proof, such as the name of a proof script.
+    #[serde(default)]
+    proof: String,
+}
+
+/// Parses the given contents of a proofs file into a map from summary keys to maps from the
+/// checksums of the proven obligations of the function to descriptions of their proofs.
+/// Reports and ignores malformed files.
+pub fn parse_proofs(contents: &str, file_name: &str) -> HashMap<String, HashMap<String, String>> {
+    let proofs_file = match toml::from_str::<ProofsFile>(contents) {
+        Ok(proofs_file) => proofs_file,
+        Err(err) => {
+            println!(
+                "unable to parse proven obligations in {}: {}",
+                file_name, err
+            );
+            return HashMap::new();
+        }
+    };
+    let mut proofs: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for entry in proofs_file.proven {
+        proofs
+            .entry(entry.function)
+            .or_insert_with(HashMap::new)
+            .insert(entry.checksum.to_lowercase(), entry.proof);
+    }
+    proofs
+}
+
+/// Returns a checksum of the SMT-LIB2 commands that check the given obligation, as a string of 16
+/// hexadecimal digits. The checksum does not depend on the message and location of the diagnostic,
+/// but it changes whenever a change to the code changes what has to be proven, so that a proof of
+/// an earlier version of the obligation is not mistaken for a proof of the current one.
+pub fn obligation_checksum(obligation: &UnprovenObligation) -> String {
+    let (_, commands) = obligation_to_smt_lib(obligation);
+    format!("{:016x}", stable_hash(commands.as_bytes()))
+}
+
+/// Returns the 64 bit FNV-1a hash of the given bytes, which, unlike the hashers of std, is the
+/// same for every build and platform, so that it can be part of what is written to files or
+/// shared between machines.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Writes the given assumed obligations to a JSON file at the given path, as an array of objects
+/// that each have the function, checksum, proof, message and location of an obligation, so that
+/// the results of the analysis can be audited for what they take on trust. Reports and otherwise
+/// ignores failures.
+pub fn write_assumption_log(path: &str, mut obligations: Vec<AssumedObligation>) {
+    obligations.sort_by(|o1, o2| {
+        (&o1.function, &o1.location, &o1.checksum).cmp(&(&o2.function, &o2.location, &o2.checksum))
+    });
+    match serde_json::to_string_pretty(&obligations) {
+        Ok(contents) => {
+            if let Err(err) = fs::write(path, contents) {
+                println!("unable to write assumed obligations to {}: {}", path, err);
+            }
+        }
+        Err(err) => println!("unable to serialize assumed obligations: {}", err),
+    }
+}
+
+/// Writes the given unproven obligations of the function with the given summary key to an SMT-LIB2
+/// script in the given directory, named after the function, so that users can attempt them with
+/// other provers. The file is removed if there are no obligations, so that the directory does not
+/// keep the obligations of functions that have since been fixed. Reports and otherwise ignores
+/// failures.
+pub fn write_obligations(directory: &str, summary_key: &str, obligations: &[UnprovenObligation]) {
+    let file_name: String = summary_key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = Path::new(directory).join(format!("{}.smt2", file_name));
+    if obligations.is_empty() {
+        if path.exists() {
+            let _ = fs::remove_file(&path);
+        }
+        return;
+    }
+    let result = fs::create_dir_all(directory)
+        .and_then(|_| fs::write(&path, obligations_to_smt_lib(summary_key, obligations)));
+    if let Err(err) = result {
+        println!(
+            "unable to write unproven obligations to {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+/// Returns an SMT-LIB2 script that checks each of the given obligations in a scope of its own,
+/// preceded by comments that give the message and location of the diagnostic that reports it and
+/// the places that its constants stand for, as well as its checksum, which identifies it in a
+/// proofs file. An obligation holds if its check-sat is unsat.
+pub fn obligations_to_smt_lib(summary_key: &str, obligations: &[UnprovenObligation]) -> String {
+    let mut script = format!(
+        "; unproven obligations of {}\n; an obligation holds if its check-sat answers unsat\n(set-logic ALL)\n",
+        summary_key
+    );
+    for (i, obligation) in obligations.iter().enumerate() {
+        let (comments, commands) = obligation_to_smt_lib(obligation);
+        script.push_str(&format!(
+            "\n; obligation {}: {}\n; at {}\n; checksum: {}\n(push 1)\n",
+            i + 1,
+            obligation.message.replace('\n', " "),
+            obligation.location,
+            obligation_checksum(obligation)
+        ));
+        for line in comments {
+            script.push_str(&line);
+            script.push('\n');
+        }
+        script.push_str(&commands);
+        script.push_str("(pop 1)\n");
+    }
+    script
+}
+
+/// Returns the comments that say what the constants of the given obligation stand for, along with
+/// the commands that declare the constants, assert the entry condition and the negation of the goal
+/// and check if they can be true.
+fn obligation_to_smt_lib(obligation: &UnprovenObligation) -> (Vec<String>, String) {
+    let mut translator = Translator::default();
+    let entry_condition = translator.term(&obligation.entry_condition, Sort::Bool);
+    let goal = translator.term(&obligation.goal, Sort::Bool);
+    let mut commands = String::new();
+    for line in translator
+        .declarations
+        .iter()
+        .chain(translator.axioms.iter())
+    {
+        commands.push_str(line);
+        commands.push('\n');
+    }
+    commands.push_str(&format!(
+        "(assert {})\n(assert (not {}))\n(check-sat)\n",
+        entry_condition, goal
+    ));
+    (translator.comments, commands)
+}
+
+/// Returns the SMT-LIB2 commands that declare the constants of the given assertions, assert them
+/// and check if they can all be true, using the given logic. Also returns the terms for the given
+/// model expressions, whose values can be obtained with get-value if the answer is sat.
+pub fn query_to_smt_lib(
+    logic: &str,
+    assertions: &[Expression],
+    model_expressions: &[Expression],
+) -> (String, Vec<String>) {
+    let mut translator = Translator::default();
+    let assertions: Vec<String> = assertions
+        .iter()
+        .map(|assertion| translator.term(assertion, Sort::Bool))
+        .collect();
+    let model_terms: Vec<String> = model_expressions
+        .iter()
+        .map(|expression| translator.translate(expression, Sort::Int).0)
+        .collect();
+    let mut commands = format!("(set-logic {})\n", logic);
+    for line in translator
+        .declarations
+        .iter()
+        .chain(translator.axioms.iter())
+    {
+        commands.push_str(line);
+        commands.push('\n');
+    }
+    for assertion in assertions {
+        commands.push_str(&format!("(assert {})\n", assertion));
+    }
+    commands.push_str("(check-sat)\n");
+    (commands, model_terms)
+}
+
+/// The sorts of the SMT-LIB2 terms into which expressions are translated. Integers are unbounded,
+/// since arithmetic that overflows is the subject of obligations of its own.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Sort {
+    Bool,
+    Int,
+}
+
+impl Sort {
+    fn name(self) -> &'static str {
+        match self {
+            Sort::Bool => "Bool",
+            Sort::Int => "Int",
+        }
+    }
+}
+
+/// Translates expressions into SMT-LIB2 terms. Variables become constants, which are declared as
+/// they are encountered, and so do subexpressions that cannot be expressed with integers and
+/// booleans, such as bitwise operations. Since such constants can have any value, obligations that
+/// hold for the translation hold for the expressions.
+#[derive(Default)]
+struct Translator {
+    /// Comments that say what the constants stand for.
+    comments: Vec<String>,
+    /// The declarations of the constants.
+    declarations: Vec<String>,
+    /// Facts about the constants, such as the ranges of the types of the variables they stand for.
+    axioms: Vec<String>,
+    /// The constants, and their sorts, that stand for expressions, so that an expression that
+    /// occurs more than once is translated to the same constant each time.
+    constants: HashMap<Expression, (String, Sort)>,
+}
+
+impl Translator {
+    /// Returns the translation of the expression as a term of the given sort.
+    fn term(&mut self, expression: &Expression, sort: Sort) -> String {
+        let (term, term_sort) = self.translate(expression, sort);
+        Self::coerce(term, term_sort, sort)
+    }
+
+    /// Converts a term of one sort to the other, the way Rust converts booleans to integers.
+    fn coerce(term: String, from: Sort, to: Sort) -> String {
+        match (from, to) {
+            (Sort::Bool, Sort::Int) => format!("(ite {} 1 0)", term),
+            (Sort::Int, Sort::Bool) => format!("(not (= {} 0))", term),
+            _ => term,
+        }
+    }
+
+    /// Returns the translation of the expression along with its sort. The given sort is the one
+    /// that the context expects, which is used for expressions that do not have a sort of their own.
+    fn translate(&mut self, expression: &Expression, sort: Sort) -> (String, Sort) {
+        match expression {
+            Expression::Add { left, right } => self.arithmetic("+", left, right),
+            Expression::Mul { left, right } => self.arithmetic("*", left, right),
+            Expression::Sub { left, right } => self.arithmetic("-", left, right),
+            Expression::Div { left, right } => {
+                let left = self.term(&left.expression, Sort::Int);
+                let right = self.term(&right.expression, Sort::Int);
+                (Self::truncating_division(&left, &right), Sort::Int)
+            }
+            Expression::Rem { left, right } => {
+                let left = self.term(&left.expression, Sort::Int);
+                let right = self.term(&right.expression, Sort::Int);
+                let quotient = Self::truncating_division(&left, &right);
+                (
+                    format!("(- {} (* {} {}))", left, right, quotient),
+                    Sort::Int,
+                )
+            }
+            Expression::Neg { operand } => {
+                let operand = self.term(&operand.expression, Sort::Int);
+                (format!("(- {})", operand), Sort::Int)
+            }
+            Expression::And { left, right } => self.logical("and", left, right),
+            Expression::Or { left, right } => self.logical("or", left, right),
+            Expression::Not { operand } => {
+                let operand = self.term(&operand.expression, Sort::Bool);
+                (format!("(not {})", operand), Sort::Bool)
+            }
+            Expression::Equals { left, right } => {
+                let (left, left_sort) = self.translate(&left.expression, Sort::Int);
+                let right = self.term(&right.expression, left_sort);
+                (format!("(= {} {})", left, right), Sort::Bool)
+            }
+            Expression::Ne { left, right } => {
+                let (left, left_sort) = self.translate(&left.expression, Sort::Int);
+                let right = self.term(&right.expression, left_sort);
+                (format!("(not (= {} {}))", left, right), Sort::Bool)
+            }
+            Expression::GreaterOrEqual { left, right } => self.comparison(">=", left, right),
+            Expression::GreaterThan { left, right } => self.comparison(">", left, right),
+            Expression::LessOrEqual { left, right } => self.comparison("<=", left, right),
+            Expression::LessThan { left, right } => self.comparison("<", left, right),
+            Expression::AddOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows("+", left, right, result_type, expression),
+            Expression::MulOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows("*", left, right, result_type, expression),
+            Expression::SubOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows("-", left, right, result_type, expression),
+            Expression::ShlOverflows {
+                right, result_type, ..
+            }
+            | Expression::ShrOverflows {
+                right, result_type, ..
+            } => {
+                let right = self.term(&right.expression, Sort::Int);
+                (
+                    format!(
+                        "(not (and (<= 0 {}) (< {} {})))",
+                        right,
+                        right,
+                        result_type.bit_length()
+                    ),
+                    Sort::Bool,
+                )
+            }
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                let condition = self.term(&condition.expression, Sort::Bool);
+                let (consequent, consequent_sort) = self.translate(&consequent.expression, sort);
+                let alternate = self.term(&alternate.expression, consequent_sort);
+                (
+                    format!("(ite {} {} {})", condition, consequent, alternate),
+                    consequent_sort,
+                )
+            }
+            Expression::CompileTimeConstant(ConstantDomain::True) => {
+                (String::from("true"), Sort::Bool)
+            }
+            Expression::CompileTimeConstant(ConstantDomain::False) => {
+                (String::from("false"), Sort::Bool)
+            }
+            Expression::CompileTimeConstant(ConstantDomain::I128(value)) => {
+                let magnitude = if *value < 0 {
+                    (*value as u128).wrapping_neg()
+                } else {
+                    *value as u128
+                };
+                (Self::integer(*value < 0, magnitude), Sort::Int)
+            }
+            Expression::CompileTimeConstant(ConstantDomain::U128(value)) => {
+                (Self::integer(false, *value), Sort::Int)
+            }
+            Expression::CompileTimeConstant(ConstantDomain::Char(value)) => {
+                (Self::integer(false, u128::from(*value as u32)), Sort::Int)
+            }
+            Expression::Variable { var_type, .. } => match var_type {
+                ExpressionType::Bool => self.constant_for(expression, Sort::Bool),
+                ExpressionType::F32 | ExpressionType::F64 => {
+                    // Floating point values are not equal to themselves if they are NaN.
+                    (self.fresh_constant(expression, sort), sort)
+                }
+                ExpressionType::NonPrimitive => self.constant_for(expression, sort),
+                _ => {
+                    let is_new = !self.constants.contains_key(expression);
+                    let (constant, _) = self.constant_for(expression, Sort::Int);
+                    if is_new {
+                        let (min, max) = Self::bounds(var_type);
+                        self.axioms.push(format!(
+                            "(assert (and (<= {} {}) (<= {} {})))",
+                            min, constant, constant, max
+                        ));
+                    }
+                    (constant, Sort::Int)
+                }
+            },
+            Expression::Top | Expression::Bottom => (self.fresh_constant(expression, sort), sort),
+            _ => self.constant_for(expression, sort),
+        }
+    }
+
+    fn arithmetic(
+        &mut self,
+        operator: &str,
+        left: &AbstractDomain,
+        right: &AbstractDomain,
+    ) -> (String, Sort) {
+        let left = self.term(&left.expression, Sort::Int);
+        let right = self.term(&right.expression, Sort::Int);
+        (format!("({} {} {})", operator, left, right), Sort::Int)
+    }
+
+    fn comparison(
+        &mut self,
+        operator: &str,
+        left: &AbstractDomain,
+        right: &AbstractDomain,
+    ) -> (String, Sort) {
+        let (term, _) = self.arithmetic(operator, left, right);
+        (term, Sort::Bool)
+    }
+
+    fn logical(
+        &mut self,
+        operator: &str,
+        left: &AbstractDomain,
+        right: &AbstractDomain,
+    ) -> (String, Sort) {
+        let left = self.term(&left.expression, Sort::Bool);
+        let right = self.term(&right.expression, Sort::Bool);
+        (format!("({} {} {})", operator, left, right), Sort::Bool)
+    }
+
+    /// Returns a term that is true if applying the operator to the operands gives a result that
+    /// is outside of the range of the result type.
+    fn overflows(
+        &mut self,
+        operator: &str,
+        left: &AbstractDomain,
+        right: &AbstractDomain,
+        result_type: &ExpressionType,
+        expression: &Expression,
+    ) -> (String, Sort) {
+        if !result_type.is_integer() {
+            return self.constant_for(expression, Sort::Bool);
+        }
+        let (result, _) = self.arithmetic(operator, left, right);
+        let (min, max) = Self::bounds(result_type);
+        (
+            format!(
+                "(not (and (<= {} {}) (<= {} {})))",
+                min, result, result, max
+            ),
+            Sort::Bool,
+        )
+    }
+
+    /// Returns the quotient of the division of left by right, rounded towards zero, as in Rust.
+    /// The div of SMT-LIB2 rounds down for positive divisors and up for negative ones, which is
+    /// the same as long as the dividend is not negative.
+    fn truncating_division(left: &str, right: &str) -> String {
+        format!(
+            "(ite (>= {} 0) (div {} {}) (- (div (- {}) {})))",
+            left, left, right, left, right
+        )
+    }
+
+    /// Returns the smallest and the largest values of the given integer type, or of a char.
+    fn bounds(expression_type: &ExpressionType) -> (String, String) {
+        if *expression_type == ExpressionType::Char {
+            let max = u128::from(std::char::MAX as u32);
+            return (String::from("0"), Self::integer(false, max));
+        }
+        let bits = u32::from(expression_type.bit_length());
+        if expression_type.is_signed_integer() {
+            let magnitude = 1u128 << (bits - 1);
+            (
+                Self::integer(true, magnitude),
+                Self::integer(false, magnitude - 1),
+            )
+        } else if bits >= 128 {
+            (String::from("0"), Self::integer(false, std::u128::MAX))
+        } else {
+            (String::from("0"), Self::integer(false, (1u128 << bits) - 1))
+        }
+    }
+
+    /// Returns the SMT-LIB2 numeral for the integer with the given sign and magnitude.
+    fn integer(is_negative: bool, magnitude: u128) -> String {
+        if is_negative {
+            format!("(- {})", magnitude)
+        } else {
+            format!("{}", magnitude)
+        }
+    }
+
+    /// Returns the constant that stands for the given expression, along with its sort. If there is
+    /// no such constant yet, one of the given sort is declared.
+    fn constant_for(&mut self, expression: &Expression, sort: Sort) -> (String, Sort) {
+        if let Some(constant_and_sort) = self.constants.get(expression) {
+            return constant_and_sort.clone();
+        }
+        let constant = self.fresh_constant(expression, sort);
+        self.constants
+            .insert(expression.clone(), (constant.clone(), sort));
+        (constant, sort)
+    }
+
+    /// Declares a new constant that stands for a value of the given expression.
+    fn fresh_constant(&mut self, expression: &Expression, sort: Sort) -> String {
+        let constant = format!("v{}", self.declarations.len());
+        if let Expression::Variable { path, .. } = expression {
+            self.comments.push(format!("; {} is {:?}", constant, path));
+        }
+        self.declarations
+            .push(format!("(declare-const {} {})", constant, sort.name()));
+        constant
+    }
+}
@@ -16,6 +16,44 @@ pub enum SmtResult {
     Undefined,
 }
 
+/// The kinds of proof obligations that MIRAI asks the solver to discharge. The solver can be
+/// configured to use a different strategy for each kind, since they benefit from different
+/// tactics and logics.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ObligationClass {
+    /// Checks that indices are within the bounds of arrays and slices.
+    BoundsCheck,
+    /// Checks that arithmetic does not overflow and does not divide by zero.
+    Overflow,
+    /// Checks of preconditions, postconditions, invariants and user assertions.
+    Contract,
+    /// Checks that calls to panicking functions and unreachable code cannot be reached.
+    Reachability,
+}
+
+impl ObligationClass {
+    /// All classes of obligations, most important first. Contracts and explicit assertions, which
+    /// are checked by proving that the panics they lead to cannot be reached, come before the
+    /// checks that the compiler inserts implicitly.
+    pub const BY_PRIORITY: [ObligationClass; 4] = [
+        ObligationClass::Contract,
+        ObligationClass::Reachability,
+        ObligationClass::BoundsCheck,
+        ObligationClass::Overflow,
+    ];
+
+    /// A description of the checks of this class, for use in diagnostics.
+    pub fn description(self) -> &'static str {
+        match self {
+            ObligationClass::BoundsCheck => "bounds checks",
+            ObligationClass::Overflow => "overflow checks",
+            ObligationClass::Contract => "contract checks",
+            ObligationClass::Reachability => "panic reachability checks",
+        }
+    }
+}
+
 /// The functionality that a solver must expose in order for MIRAI to use it.
 pub trait SmtSolver<SmtExpressionType> {
     /// Returns a string representation of the given expression for use in debugging.
@@ -34,10 +72,30 @@ pub trait SmtSolver<SmtExpressionType> {
     /// of the solver will be restored to what it was when this was called.
     fn set_backtrack_position(&mut self);
 
+    /// Use the given strategy (a tactic or logic understood by the solver) for the obligations
+    /// that follow, or the default strategy of the solver if None.
+    /// Solvers that do not support strategies ignore this.
+    fn set_strategy(&mut self, _strategy: Option<&str>) {}
+
+    /// Multiply the time that the solver may spend on a query by the given factor.
+    /// Solvers without a time limit ignore this.
+    fn set_time_limit_factor(&mut self, _factor: usize) {}
+
     /// Try to find an assignment of values to the free variables so that the assertions in the
     /// current context are all true.
     fn solve(&mut self) -> SmtResult;
 
+    /// Like solve, but if the assertions can all be true, also returns the values that the given
+    /// expressions have in the assignment (model) found by the solver, written the way the solver
+    /// writes them, or None for expressions that the model does not give a value.
+    /// Solvers that do not produce models return no values.
+    fn solve_for_model(
+        &mut self,
+        _expressions: &[SmtExpressionType],
+    ) -> (SmtResult, Vec<Option<String>>) {
+        (self.solve(), Vec::new())
+    }
+
     /// Establish if the given expression can be satisfied (or not) without changing the current context.
     fn solve_expression(&mut self, expression: &SmtExpressionType) -> SmtResult {
         self.set_backtrack_position();
@@ -46,22 +104,39 @@ pub trait SmtSolver<SmtExpressionType> {
         self.backtrack();
         result
     }
+
+    /// Establish if the given expression can be satisfied without changing the current context and,
+    /// if so, what values the other given expressions have in the model found by the solver.
+    fn solve_expression_for_model(
+        &mut self,
+        expression: &SmtExpressionType,
+        model_expressions: &[SmtExpressionType],
+    ) -> (SmtResult, Vec<Option<String>>) {
+        self.set_backtrack_position();
+        self.assert(expression);
+        let result = self.solve_for_model(model_expressions);
+        self.backtrack();
+        result
+    }
 }
 
 /// A dummy implementation of SmtSolver to use in configurations where a real SMT solver is not available or required.
 #[derive(Default)]
 pub struct SolverStub {}
 
-impl SmtSolver<()> for SolverStub {
-    fn as_debug_string(&self, _: &()) -> String {
+impl SmtSolver<Expression> for SolverStub {
+    fn as_debug_string(&self, _: &Expression) -> String {
         String::from("not implemented")
     }
 
-    fn assert(&mut self, _: &()) {}
+    fn assert(&mut self, _: &Expression) {}
 
     fn backtrack(&mut self) {}
 
-    fn get_as_smt_predicate(&mut self, _mirai_expression: &Expression) {}
+    fn get_as_smt_predicate(&mut self, _mirai_expression: &Expression) -> Expression {
+        // The stub never looks at the expression, so there is no point in translating it.
+        Expression::Top
+    }
 
     fn set_backtrack_position(&mut self) {}
 
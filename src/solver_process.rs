@@ -0,0 +1,242 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::expression::Expression;
+use crate::smt_solver::{SmtResult, SmtSolver};
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// A query that is sent to a solver process: can all of the assertions be true at the same time?
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SolverQuery {
+    /// The strategy that the solver should use, or None for its default strategy.
+    pub strategy: Option<String>,
+    /// The expressions that must all be true.
+    pub assertions: Vec<Expression>,
+    /// The expressions whose values in the model found by the solver should be part of the answer,
+    /// if the assertions can all be true.
+    pub model_expressions: Vec<Expression>,
+}
+
+/// A solver process that is running and waiting for queries.
+pub struct RunningSolver {
+    child: Child,
+    stdin: ChildStdin,
+    /// Receives the lines that the solver process writes to its standard output.
+    responses: Receiver<String>,
+}
+
+impl RunningSolver {
+    /// Starts the given command via the shell, applying the memory limit with ulimit.
+    pub fn start(command: &str, memory_limit_mb: Option<u64>) -> Option<RunningSolver> {
+        let script = match memory_limit_mb {
+            Some(limit) => format!("ulimit -v {} && exec {}", limit * 1024, command),
+            None => format!("exec {}", command),
+        };
+        let mut child = match Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                info!("unable to start solver process {}: {}", command, err);
+                return None;
+            }
+        };
+        let stdin = child.stdin.take()?;
+        let stdout = child.stdout.take()?;
+        let (sender, responses) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => {
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        Some(RunningSolver {
+            child,
+            stdin,
+            responses,
+        })
+    }
+
+    /// Writes the given bytes to the standard input of the process and waits, for at most the
+    /// given time, for the process to write a line to its standard output. Returns None if the
+    /// process did not.
+    pub fn ask(&mut self, bytes: &[u8], time_limit: Duration) -> Option<String> {
+        self.stdin
+            .write_all(bytes)
+            .and_then(|_| self.stdin.flush())
+            .ok()?;
+        self.responses.recv_timeout(time_limit).ok()
+    }
+
+    /// Kills the process.
+    pub fn stop(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A solver that sends its queries to a separate process, so that a crash or memory blow-up of
+/// the solver does not take down the compiler. The process is killed if it takes longer than the
+/// time limit to answer a query and it is restarted when the next query comes along.
+/// Queries that are not answered by the process are treated as undecided.
+///
+/// The process reads queries from its standard input, each one a little endian u64 length followed
+/// by that many bytes of bincode serialized SolverQuery, and writes one line with sat, unsat or
+/// unknown to its standard output for each query. A sat line continues with the values of the
+/// model expressions of the query, each one preceded by a tab and empty if the model does not give
+/// the expression a value.
+pub struct IsolatedSolver {
+    /// The shell command that starts the solver process.
+    command: String,
+    /// The longest time the process may take to answer a query.
+    time_limit: Duration,
+    /// The factor by which the time limit is multiplied for the queries that follow.
+    time_limit_factor: usize,
+    /// If set, the process may not use more than this many megabytes of virtual memory.
+    memory_limit_mb: Option<u64>,
+    /// The strategy to use for the queries that follow.
+    strategy: Option<String>,
+    /// The expressions asserted so far, in all of the nested contexts.
+    assertions: Vec<Expression>,
+    /// For each nested context, the number of assertions that were made before it was created.
+    backtrack_positions: Vec<usize>,
+    /// The current solver process, if one is running.
+    process: Option<RunningSolver>,
+}
+
+impl IsolatedSolver {
+    pub fn new(
+        command: &str,
+        time_limit: Duration,
+        memory_limit_mb: Option<u64>,
+    ) -> IsolatedSolver {
+        IsolatedSolver {
+            command: String::from(command),
+            time_limit,
+            time_limit_factor: 1,
+            memory_limit_mb,
+            strategy: None,
+            assertions: Vec::new(),
+            backtrack_positions: Vec::new(),
+            process: None,
+        }
+    }
+
+    /// Sends the query to the solver process, starting it if need be, and waits for the answer.
+    /// Returns None if the process crashed, timed out or did not understand the query, in which
+    /// case the process is killed so that the next query starts a fresh one.
+    fn send_query(&mut self, query: &SolverQuery) -> Option<String> {
+        if self.process.is_none() {
+            self.process = RunningSolver::start(&self.command, self.memory_limit_mb);
+        }
+        let time_limit = self.time_limit * self.time_limit_factor as u32;
+        let response = {
+            let process = self.process.as_mut()?;
+            let mut bytes = bincode::serialize(query).ok()?;
+            let mut message = (bytes.len() as u64).to_le_bytes().to_vec();
+            message.append(&mut bytes);
+            process.ask(&message, time_limit)
+        };
+        if response.is_none() {
+            info!("solver process {} failed to answer a query", self.command);
+            self.stop_process();
+        }
+        response
+    }
+
+    /// Kills the solver process, if it is running.
+    fn stop_process(&mut self) {
+        if let Some(process) = self.process.take() {
+            process.stop();
+        }
+    }
+}
+
+impl Drop for IsolatedSolver {
+    fn drop(&mut self) {
+        self.stop_process();
+    }
+}
+
+impl SmtSolver<Expression> for IsolatedSolver {
+    fn as_debug_string(&self, expression: &Expression) -> String {
+        format!("{:?}", expression)
+    }
+
+    fn assert(&mut self, expression: &Expression) {
+        self.assertions.push(expression.clone());
+    }
+
+    fn backtrack(&mut self) {
+        if let Some(position) = self.backtrack_positions.pop() {
+            self.assertions.truncate(position);
+        }
+    }
+
+    fn get_as_smt_predicate(&mut self, mirai_expression: &Expression) -> Expression {
+        mirai_expression.clone()
+    }
+
+    fn set_backtrack_position(&mut self) {
+        self.backtrack_positions.push(self.assertions.len());
+    }
+
+    fn set_strategy(&mut self, strategy: Option<&str>) {
+        self.strategy = strategy.map(String::from);
+    }
+
+    fn set_time_limit_factor(&mut self, factor: usize) {
+        self.time_limit_factor = factor;
+    }
+
+    fn solve(&mut self) -> SmtResult {
+        self.solve_for_model(&[]).0
+    }
+
+    fn solve_for_model(&mut self, expressions: &[Expression]) -> (SmtResult, Vec<Option<String>>) {
+        let query = SolverQuery {
+            strategy: self.strategy.clone(),
+            assertions: self.assertions.clone(),
+            model_expressions: expressions.to_vec(),
+        };
+        let response = match self.send_query(&query) {
+            Some(response) => response,
+            None => return (SmtResult::Undefined, Vec::new()),
+        };
+        let mut parts = response.split('\t');
+        match parts.next() {
+            Some("sat") => {
+                let values = parts
+                    .map(|value| {
+                        if value.is_empty() {
+                            None
+                        } else {
+                            Some(String::from(value))
+                        }
+                    })
+                    .collect();
+                (SmtResult::Satisfiable, values)
+            }
+            Some("unsat") => (SmtResult::Unsatisfiable, Vec::new()),
+            _ => (SmtResult::Undefined, Vec::new()),
+        }
+    }
+}
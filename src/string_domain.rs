@@ -0,0 +1,147 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+use crate::interval_domain::IntervalDomain;
+
+use std::i128;
+
+/// An element of the String domain is the set of strings that start with a known prefix, end
+/// with a known suffix and have a length (in bytes) that lies within an interval. If all of the
+/// string is known, the prefix and the suffix are the string itself.
+/// String domain elements are constructed on demand from AbstractDomain expressions.
+/// They are most useful for checking assertions about the contents of strings, such as
+/// s.starts_with("http") or s.len() > 0.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StringDomain {
+    /// If known, the entire string.
+    constant: Option<String>,
+    /// A string with which every string in the set starts. Empty if nothing is known.
+    prefix: String,
+    /// A string with which every string in the set ends. Empty if nothing is known.
+    suffix: String,
+    /// The lengths of the strings in the set.
+    length: IntervalDomain,
+}
+
+impl From<&str> for StringDomain {
+    fn from(s: &str) -> StringDomain {
+        StringDomain {
+            constant: Some(String::from(s)),
+            prefix: String::from(s),
+            suffix: String::from(s),
+            length: (s.len() as i128).into(),
+        }
+    }
+}
+
+impl StringDomain {
+    /// The set of all strings.
+    pub fn top() -> StringDomain {
+        StringDomain {
+            constant: None,
+            prefix: String::new(),
+            suffix: String::new(),
+            length: IntervalDomain::new(0, i128::MAX),
+        }
+    }
+
+    /// Returns the string, if all of it is known.
+    pub fn as_constant(&self) -> Option<&str> {
+        self.constant.as_ref().map(String::as_str)
+    }
+
+    /// Returns the set of strings that are a string from self followed by a string from other.
+    pub fn concat(&self, other: &Self) -> Self {
+        if let (Some(s1), Some(s2)) = (&self.constant, &other.constant) {
+            return StringDomain::from([s1.as_str(), s2.as_str()].concat().as_str());
+        }
+        let prefix = match &self.constant {
+            Some(s1) => [s1.as_str(), other.prefix.as_str()].concat(),
+            None => self.prefix.clone(),
+        };
+        let suffix = match &other.constant {
+            Some(s2) => [self.suffix.as_str(), s2.as_str()].concat(),
+            None => other.suffix.clone(),
+        };
+        StringDomain {
+            constant: None,
+            prefix,
+            suffix,
+            length: self.length.add(&other.length),
+        }
+    }
+
+    /// Returns true if every string in the set ends with the given string, false if none do, and
+    /// None if that is not known.
+    pub fn ends_with(&self, suffix: &str) -> Option<bool> {
+        if let Some(s) = &self.constant {
+            return Some(s.ends_with(suffix));
+        }
+        if self.suffix.ends_with(suffix) {
+            return Some(true);
+        }
+        if !suffix.ends_with(self.suffix.as_str()) || self.is_shorter_than(suffix) {
+            return Some(false);
+        }
+        None
+    }
+
+    /// Returns the smallest set that includes the strings of self and of other.
+    pub fn join(&self, other: &Self) -> Self {
+        if self.constant.is_some() && self.constant == other.constant {
+            return self.clone();
+        }
+        let prefix: String = self
+            .prefix
+            .chars()
+            .zip(other.prefix.chars())
+            .take_while(|(c1, c2)| c1 == c2)
+            .map(|(c, _)| c)
+            .collect();
+        let suffix_chars: Vec<char> = self
+            .suffix
+            .chars()
+            .rev()
+            .zip(other.suffix.chars().rev())
+            .take_while(|(c1, c2)| c1 == c2)
+            .map(|(c, _)| c)
+            .collect();
+        StringDomain {
+            constant: None,
+            prefix,
+            suffix: suffix_chars.into_iter().rev().collect(),
+            length: self.length.widen(&other.length),
+        }
+    }
+
+    /// Returns the lengths of the strings in the set.
+    pub fn length(&self) -> &IntervalDomain {
+        &self.length
+    }
+
+    /// Returns true if every string in the set starts with the given string, false if none do,
+    /// and None if that is not known.
+    pub fn starts_with(&self, prefix: &str) -> Option<bool> {
+        if let Some(s) = &self.constant {
+            return Some(s.starts_with(prefix));
+        }
+        if self.prefix.starts_with(prefix) {
+            return Some(true);
+        }
+        if !prefix.starts_with(self.prefix.as_str()) || self.is_shorter_than(prefix) {
+            return Some(false);
+        }
+        None
+    }
+
+    /// True if every string in the set is shorter than the given string.
+    fn is_shorter_than(&self, s: &str) -> bool {
+        match self.length.upper_bound() {
+            Some(upper_bound) => upper_bound < s.len() as i128,
+            None => false,
+        }
+    }
+}
@@ -3,22 +3,37 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::abstract_value::{AbstractValue, Path};
+use crate::abstract_value::{self, AbstractValue, Path};
 use crate::environment::Environment;
+use crate::expression::Expression;
+use crate::interval_domain::IntervalDomain;
+use crate::k_limits;
+use crate::known_names::KnownNamesCache;
 use crate::utils;
 
+use rustc::hir;
+use rustc::hir::def::Def;
 use rustc::hir::def_id::DefId;
-use rustc::ty::TyCtxt;
+use rustc::ty::{TyCtxt, TyKind, TypeAndMut};
 use sled::Db;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// A summary is a declarative abstract specification of what a function does.
 /// This is calculated once per function and is used by callers of the function.
 /// Callers will specialize this summary by replacing embedded parameter values with the corresponding
 /// argument values and then simplifying the resulting values under the current path condition.
 ///
-/// Summaries are stored in a persistent, per project database. When a crate is recompiled,
+/// Summaries are stored in a persistent, per crate database. The databases of all of the crates of a
+/// project live in the same directory, so that the summaries of a crate can be found by the crates
+/// that depend on it. When a crate is recompiled,
 /// all summaries arising from the crate are recomputed, but the database is only updated when
 /// a function summary changes. When this happens, all callers of the function need to be reanalyzed
 /// using the new summary, which could result in their summaries being updated, and so on.
@@ -84,6 +99,155 @@ pub struct Summary {
     // under the current path condition. They should then update their current state to reflect the
     // side-effects of the call for the unwind control paths, following the call.
     pub unwind_side_effects: Vec<(Path, AbstractValue)>,
+
+    // The ordinals of the parameters whose values, or the values they refer to, may be read by
+    // the function. Sorted.
+    pub parameters_read: Vec<usize>,
+
+    // The ordinals of the parameters that refer to places that the function may modify. Sorted.
+    // Callers need not invalidate what they know about places that are passed via other parameters.
+    pub parameters_modified: Vec<usize>,
+
+    // True if the function, or any function it calls, may read the value of a static variable.
+    pub reads_static_state: bool,
+
+    // True if the function, or any function it calls, may modify the value of a static variable.
+    // Callers should forget what they know about the values of static variables when this is true
+    // and then apply any side effects on static variables that are listed in side_effects.
+    pub modifies_static_state: bool,
+
+    // True if the function, or any non public function it calls, may drop the result of a call
+    // to a fallible function without checking it. Public functions that call such a function
+    // should report this.
+    pub may_drop_errors: bool,
+
+    // If the function, or any function it calls, may block the calling thread, for example by
+    // doing file or network I/O or by waiting for a lock, this is the chain of calls that leads
+    // to the blocking operation, given as summary keys and ending with the blocking function.
+    // Otherwise it is empty. Async functions that call such a function should report this.
+    pub blocking_call_chain: Vec<String>,
+
+    // If the function, or any function it calls, may call an unsafe function, or do an unsafe
+    // operation in an unsafe block, this is the chain of calls that leads to it, given as summary
+    // keys and ending with the unsafe function or the function with the unsafe block. Calls to
+    // the functions of trusted crates are not followed. Otherwise it is empty.
+    pub unsafe_call_chain: Vec<String>,
+}
+
+impl Summary {
+    /// True if calls to the function have no side effects on state that the caller can observe.
+    pub fn is_pure(&self) -> bool {
+        self.parameters_modified.is_empty() && !self.modifies_static_state
+    }
+
+    /// True if calls to the function may panic, as far as the summary says: the function has
+    /// preconditions that its callers must satisfy, or it may unwind.
+    pub fn may_panic(&self) -> bool {
+        !self.preconditions.is_empty()
+            || self.unwind_condition.as_ref().map_or(false, |condition| {
+                condition.as_bool_if_known() != Some(false)
+            })
+    }
+
+    /// True if the summary says enough about what the function does for functions with equal
+    /// summaries to be likely to do the same thing. Functions that have no conditions or effects
+    /// and that return values that are not known, such as the results of calls, all have alike
+    /// summaries that say nothing about them.
+    pub fn is_informative(&self) -> bool {
+        if !self.preconditions.is_empty()
+            || !self.post_conditions.is_empty()
+            || !self.side_effects.is_empty()
+            || self.unwind_condition.is_some()
+        {
+            return true;
+        }
+        match &self.result {
+            Some(result) => match &result.domain.expression {
+                Expression::Top => false,
+                Expression::Variable { path, .. } => match path.get_root() {
+                    Path::LocalVariable { ordinal } => self.parameters_read.contains(ordinal),
+                    _ => true,
+                },
+                _ => true,
+            },
+            None => false,
+        }
+    }
+
+    /// Returns descriptions of the ways in which the behavior that this summary describes differs
+    /// from the behavior that the given summary, of an earlier version of the same function,
+    /// describes: new reasons to panic, a changed range of the result, new effects on the state of
+    /// the caller or the environment and new calls of unsafe functions. Behavior that went away is
+    /// not described, since it does not need the attention of the users of the function.
+    pub fn differences_from(&self, baseline: &Summary) -> Vec<String> {
+        let mut differences = Vec::new();
+        for (_, message) in &self.preconditions {
+            if !baseline
+                .preconditions
+                .iter()
+                .any(|(_, baseline_message)| baseline_message == message)
+            {
+                differences.push(format!("may now panic: {}", message));
+            }
+        }
+        let may_unwind = |summary: &Summary| {
+            summary
+                .unwind_condition
+                .as_ref()
+                .map_or(false, |condition| {
+                    condition.as_bool_if_known() != Some(false)
+                })
+        };
+        if may_unwind(self) && !may_unwind(baseline) {
+            differences.push(String::from("may now unwind"));
+        }
+        if let (Some(result), Some(baseline_result)) = (&self.result, &baseline.result) {
+            let range = result.domain.get_as_interval();
+            let baseline_range = baseline_result.domain.get_as_interval();
+            if range != baseline_range && !range.is_bottom() && !baseline_range.is_bottom() {
+                differences.push(format!(
+                    "the range of the result changed from {} to {}",
+                    range_to_string(&baseline_range),
+                    range_to_string(&range)
+                ));
+            }
+        }
+        for ordinal in &self.parameters_modified {
+            if !baseline.parameters_modified.contains(ordinal) {
+                differences.push(format!(
+                    "may now modify what parameter {} refers to",
+                    ordinal
+                ));
+            }
+        }
+        if self.modifies_static_state && !baseline.modifies_static_state {
+            differences.push(String::from("may now modify static variables"));
+        }
+        if !self.blocking_call_chain.is_empty() && baseline.blocking_call_chain.is_empty() {
+            differences.push(format!(
+                "may now do file or network I/O or wait for a lock: {}",
+                self.blocking_call_chain.join(" -> ")
+            ));
+        }
+        if !self.unsafe_call_chain.is_empty() && baseline.unsafe_call_chain.is_empty() {
+            differences.push(format!(
+                "may now call unsafe code: {}",
+                self.unsafe_call_chain.join(" -> ")
+            ));
+        }
+        differences
+    }
+}
+
+/// Returns the interval as [low, high], where an unknown bound is written as an ellipsis.
+fn range_to_string(interval: &IntervalDomain) -> String {
+    let bound_to_string =
+        |bound: Option<i128>| bound.map_or(String::from("..."), |b| b.to_string());
+    format!(
+        "[{}, {}]",
+        bound_to_string(interval.lower_bound()),
+        bound_to_string(interval.upper_bound())
+    )
 }
 
 /// Constructs a summary of a function body by processing state information gathered during
@@ -95,31 +259,269 @@ pub fn summarize(
     post_conditions: &[AbstractValue],
     unwind_condition: Option<AbstractValue>,
     unwind_environment: &Environment,
+    state_accesses: &StateAccesses,
+    may_drop_errors: bool,
+    blocking_call_chain: &[String],
+    unsafe_call_chain: &[String],
 ) -> Summary {
-    let mut preconditions: Vec<(AbstractValue, String)> = preconditions.to_owned();
-    let result = exit_environment.value_at(&Path::LocalVariable { ordinal: 0 });
+    // The values are simplified before they are stored, which keeps the summaries small and
+    // drops the conditions that turn out to be trivially true.
+    let mut preconditions: Vec<(AbstractValue, String)> = preconditions
+        .iter()
+        .map(|(condition, message)| (condition.simplify(), message.clone()))
+        .filter(|(condition, _)| !condition.as_bool_if_known().unwrap_or(false))
+        .collect();
+    let result = exit_environment
+        .value_at(&Path::LocalVariable { ordinal: 0 })
+        .map(AbstractValue::simplify);
     let mut side_effects = extract_side_effects(exit_environment, argument_count);
-    let mut post_conditions: Vec<AbstractValue> = post_conditions.to_owned();
+    let mut post_conditions: Vec<AbstractValue> = post_conditions
+        .iter()
+        .map(AbstractValue::simplify)
+        .filter(|condition| !condition.as_bool_if_known().unwrap_or(false))
+        .collect();
+    let unwind_condition = unwind_condition.as_ref().map(AbstractValue::simplify);
     let mut unwind_side_effects = extract_side_effects(unwind_environment, argument_count);
+    let mut parameters_read: Vec<usize> = state_accesses.parameters_read.iter().cloned().collect();
+    let mut parameters_modified: Vec<usize> = side_effects
+        .iter()
+        .chain(unwind_side_effects.iter())
+        .filter_map(|(path, _)| match path.get_root() {
+            Path::LocalVariable { ordinal } if *ordinal > 0 => Some(*ordinal),
+            _ => None,
+        })
+        .collect();
+    let modifies_static_state = state_accesses.callees_modify_static_state
+        || side_effects
+            .iter()
+            .chain(unwind_side_effects.iter())
+            .any(|(path, _)| path.is_rooted_by_static());
 
     preconditions.sort();
     side_effects.sort();
     post_conditions.sort();
     unwind_side_effects.sort();
+    parameters_read.sort();
+    parameters_modified.sort();
+    parameters_modified.dedup();
 
     Summary {
         preconditions,
-        result: result.cloned(),
+        result,
         side_effects,
         post_conditions,
         unwind_condition,
         unwind_side_effects,
+        parameters_read,
+        parameters_modified,
+        reads_static_state: state_accesses.reads_static_state,
+        modifies_static_state,
+        may_drop_errors,
+        blocking_call_chain: blocking_call_chain.to_owned(),
+        unsafe_call_chain: unsafe_call_chain.to_owned(),
+    }
+}
+
+/// Keeps track of the ways in which a function body accesses state that is visible to its callers.
+/// Writes to such state are found in the exit environment, so only reads need to be tracked here,
+/// along with any writes made by callees that are not directly visible in the environment.
+#[derive(Clone, Debug, Default)]
+pub struct StateAccesses {
+    /// The ordinals of the parameters whose values are read by the function body.
+    pub parameters_read: HashSet<usize>,
+    /// True if the function body, or a function it calls, reads a static variable.
+    pub reads_static_state: bool,
+    /// True if a function called by the function body modifies a static variable.
+    pub callees_modify_static_state: bool,
+}
+
+impl StateAccesses {
+    /// Records that the function body reads the value at the given path.
+    pub fn record_read(&mut self, path: &Path, argument_count: usize) {
+        match path.get_root() {
+            Path::LocalVariable { ordinal } if *ordinal > 0 && *ordinal <= argument_count => {
+                self.parameters_read.insert(*ordinal);
+            }
+            Path::StaticVariable { .. } => {
+                self.reads_static_state = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the accesses to static state made by a function with the given summary
+    /// when it is called by the function body.
+    pub fn record_call(&mut self, callee_summary: &Summary) {
+        self.reads_static_state |= callee_summary.reads_static_state;
+        self.callees_modify_static_state |= callee_summary.modifies_static_state;
     }
 }
 
-/// Returns a list of (path, value) pairs where each path is rooted by an argument.
-/// Since paths are created by writes, these are side-effects when the root is a parameter
-/// rather than the return result.
+/// Constructs a summary for the function identified by def_id using only its type signature.
+/// This is used when there is no summary in the store and no body to analyze.
+/// Parameters that are shared references or that are passed by value cannot be modified by the
+/// callee in a way that is visible to the caller, so they give rise to no side effects.
+/// The places that mutable references point to, however, may be modified in arbitrary ways,
+/// so the summary records a side effect that sets each of these places to an unknown value,
+/// both for normal returns and for the case where the function unwinds.
+/// The return value is left unspecified, which makes it a fresh unknown value at the call site.
+pub fn summarize_signature(tcx: &TyCtxt<'_, '_, '_>, def_id: DefId) -> Summary {
+    match tcx.describe_def(def_id) {
+        Some(Def::Fn(..)) | Some(Def::Method(..)) => {}
+        _ => return Summary::default(),
+    }
+    let binder = tcx.fn_sig(def_id);
+    let sig = binder.skip_binder();
+    let mut side_effects = Vec::new();
+    let mut parameters_modified = Vec::new();
+    for (i, ty) in sig.inputs().iter().enumerate() {
+        match ty.sty {
+            TyKind::Ref(_, _, hir::MutMutable)
+            | TyKind::RawPtr(TypeAndMut {
+                mutbl: hir::MutMutable,
+                ..
+            }) => {
+                let parameter_path = Path::LocalVariable { ordinal: i + 1 };
+                side_effects.push((parameter_path, abstract_value::TOP));
+                parameters_modified.push(i + 1);
+            }
+            _ => {}
+        }
+    }
+    Summary {
+        unwind_side_effects: side_effects.clone(),
+        side_effects,
+        parameters_read: (1..=sig.inputs().len()).collect(),
+        parameters_modified,
+        ..Summary::default()
+    }
+}
+
+/// Constructs a summary for a call that may be dispatched to any one of the functions with the
+/// given summaries, for example a call of a trait method via a trait object. The caller has to
+/// satisfy the preconditions of all of them, and may only rely on what all of them agree on:
+/// a side effect is kept if all of the summaries set its path to the same value, and any other
+/// path that is set by one of them becomes unknown.
+pub fn join_summaries(summaries: &[Summary]) -> Summary {
+    if summaries.len() == 1 {
+        return summaries[0].clone();
+    }
+    let mut preconditions: Vec<(AbstractValue, String)> = summaries
+        .iter()
+        .flat_map(|summary| summary.preconditions.iter().cloned())
+        .collect();
+    let mut post_conditions: Vec<AbstractValue> = summaries
+        .get(0)
+        .map(|first| first.post_conditions.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|condition| {
+            summaries
+                .iter()
+                .all(|summary| summary.post_conditions.contains(condition))
+        })
+        .collect();
+    let mut parameters_read: Vec<usize> = summaries
+        .iter()
+        .flat_map(|summary| summary.parameters_read.iter().cloned())
+        .collect();
+    let mut parameters_modified: Vec<usize> = summaries
+        .iter()
+        .flat_map(|summary| summary.parameters_modified.iter().cloned())
+        .collect();
+    preconditions.sort();
+    preconditions.dedup();
+    post_conditions.sort();
+    parameters_read.sort();
+    parameters_read.dedup();
+    parameters_modified.sort();
+    parameters_modified.dedup();
+
+    Summary {
+        preconditions,
+        result: get_common_value(summaries.iter().map(|summary| &summary.result)),
+        side_effects: join_side_effects(summaries.iter().map(|summary| &summary.side_effects)),
+        post_conditions,
+        unwind_condition: get_common_value(
+            summaries.iter().map(|summary| &summary.unwind_condition),
+        ),
+        unwind_side_effects: join_side_effects(
+            summaries.iter().map(|summary| &summary.unwind_side_effects),
+        ),
+        parameters_read,
+        parameters_modified,
+        reads_static_state: summaries.iter().any(|summary| summary.reads_static_state),
+        modifies_static_state: summaries
+            .iter()
+            .any(|summary| summary.modifies_static_state),
+        may_drop_errors: summaries.iter().any(|summary| summary.may_drop_errors),
+        blocking_call_chain: summaries
+            .iter()
+            .map(|summary| &summary.blocking_call_chain)
+            .find(|chain| !chain.is_empty())
+            .cloned()
+            .unwrap_or_default(),
+        unsafe_call_chain: summaries
+            .iter()
+            .map(|summary| &summary.unsafe_call_chain)
+            .find(|chain| !chain.is_empty())
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns the value if all of the given values are the same, and None otherwise.
+fn get_common_value<'a>(
+    mut values: impl Iterator<Item = &'a Option<AbstractValue>>,
+) -> Option<AbstractValue> {
+    let first = values.next()?.clone();
+    if values.all(|value| *value == first) {
+        first
+    } else {
+        None
+    }
+}
+
+/// Returns the side effects that set a path to the same value in all of the given lists of side
+/// effects, along with side effects that set all other paths that are set in any of the lists to
+/// an unknown value.
+fn join_side_effects<'a>(
+    side_effect_lists: impl Iterator<Item = &'a Vec<(Path, AbstractValue)>>,
+) -> Vec<(Path, AbstractValue)> {
+    let side_effect_maps: Vec<HashMap<&Path, &AbstractValue>> = side_effect_lists
+        .map(|side_effects| {
+            side_effects
+                .iter()
+                .map(|(path, value)| (path, value))
+                .collect()
+        })
+        .collect();
+    let mut paths: Vec<&Path> = side_effect_maps
+        .iter()
+        .flat_map(|side_effects| side_effects.keys().cloned())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .map(|path| {
+            let value = side_effect_maps[0].get(path);
+            let agreed = value.is_some()
+                && side_effect_maps
+                    .iter()
+                    .all(|side_effects| side_effects.get(path) == value);
+            if agreed {
+                (path.clone(), (*value.unwrap()).clone())
+            } else {
+                (path.clone(), abstract_value::TOP)
+            }
+        })
+        .collect()
+}
+
+/// Returns a list of (path, value) pairs where each path is rooted by an argument, a static
+/// or an escaping heap block.
+/// Since paths are created by writes, these are side-effects when the root is not the return result.
 fn extract_side_effects(env: &Environment, argument_count: usize) -> Vec<(Path, AbstractValue)> {
     let mut result = Vec::new();
     for ordinal in 0..=argument_count {
@@ -129,39 +531,360 @@ fn extract_side_effects(env: &Environment, argument_count: usize) -> Vec<(Path,
             .iter()
             .filter(|(p, _)| (**p) == root || p.is_rooted_by(&root))
         {
-            result.push((path.clone(), value.clone()));
+            result.push((path.clone(), value.simplify()));
+        }
+    }
+    for (path, value) in env
+        .value_map
+        .iter()
+        .filter(|(p, _)| p.is_rooted_by_static())
+    {
+        result.push((path.clone(), value.simplify()));
+    }
+    // Heap blocks that are referenced by the values of the arguments, the result or statics
+    // are visible to the caller, so their contents are side effects as well.
+    // Blocks that do not escape are private to the function and are left out of the summary.
+    let escaping_heap_addresses = env.get_escaping_heap_addresses(argument_count);
+    for (path, value) in env.value_map.iter() {
+        if let Path::AbstractHeapAddress { ordinal } = path.get_root() {
+            if escaping_heap_addresses.contains(ordinal) {
+                result.push((path.clone(), value.simplify()));
+            }
         }
     }
-    //todo: what about paths rooted by heap allocated (i.e. boxed) objects that are referenced by
-    //the values of the arguments?
     result
 }
 
+/// Records what the summary of a function was computed from, so that a later run can tell if
+/// analyzing the function again would yield the same summary and diagnostics.
+/// Fingerprints are stored in the summary database along with the summaries.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+struct Fingerprint {
+    /// A hash of the MIR body of the function and of its location in the source.
+    mir_hash: u64,
+    /// The summary keys of the functions whose summaries were used to compute the summary,
+    /// along with hashes of those summaries and of the built in models of the functions.
+    /// See PersistentSummaryCache::hash_callee.
+    callee_summary_hashes: Vec<(String, u64)>,
+    /// True if analyzing the function reported diagnostics. Diagnostics are not stored, so the
+    /// function has to be analyzed again to report them again.
+    has_diagnostics: bool,
+}
+
+/// Appended to the summary key of a function to get the database key of its fingerprint.
+/// Summary keys are made of def path components, which never contain a '#'.
+pub const FINGERPRINT_KEY_SUFFIX: &str = "#fingerprint";
+
+/// The database key under which the summary keys of the functions of the crate are recorded, one
+/// per line, so that cargo mirai --gc-summaries can tell which entries belong to functions that no
+/// longer exist.
+pub const SUMMARY_KEYS_KEY: &str = "#summary_keys";
+
+/// The version of the format in which summaries and fingerprints are stored. Bump it whenever
+/// Summary, Fingerprint or any type that they contain changes, so that databases written by other
+/// builds of MIRAI, such as those in a remote summary cache, are not mistaken for current ones.
+pub const SUMMARY_FORMAT_VERSION: u64 = 1;
+
+/// The version of summarize_signature. Bump it whenever summarize_signature changes, so that the
+/// stored summaries that were computed from the signature summaries of the functions they call,
+/// such as those of the standard library, are computed again. Other stored summaries are kept.
+const SIGNATURE_SUMMARY_VERSION: u64 = 1;
+
 /// A persistent map from DefId to Summary.
 /// Also tracks which definitions depend on (use) any particular Summary.
 pub struct PersistentSummaryCache<'a, 'tcx: 'a> {
+    /// The summary database of the crate being compiled.
     db: Db,
+    /// The name of the crate being compiled, which is the first component of the keys of its summaries.
+    crate_name: String,
+    /// The directory with the summary databases of all crates.
+    summary_store_directory: PathBuf,
+    /// The serialized summaries of crates that the crate being compiled depends on, keyed by
+    /// crate name. The database of such a crate is read in its entirety when it is first needed, so
+    /// that it is not kept open while other compiler processes may want to read it as well.
+    dependency_summaries: HashMap<String, HashMap<String, Vec<u8>>>,
     cache: HashMap<DefId, Summary>,
     dependencies: HashMap<DefId, Vec<DefId>>,
+    /// The inverse of dependencies: the definitions whose summaries a definition has used.
+    callees: HashMap<DefId, HashSet<DefId>>,
+    /// The local definitions with bodies, keyed by summary key. Built when first needed.
+    local_defs: Option<HashMap<String, DefId>>,
     key_cache: HashMap<DefId, String>,
+    /// The definitions whose cached summaries were derived from their signatures alone,
+    /// because the functions have not been analyzed (yet).
+    signature_summaries: HashSet<DefId>,
     type_context: &'a TyCtxt<'a, 'tcx, 'tcx>,
+    /// The built in models of well known functions. Summaries that depend on a model are
+    /// computed again if the model changes.
+    known_names_cache: Arc<KnownNamesCache>,
 }
 
 impl<'a, 'tcx: 'a> PersistentSummaryCache<'a, 'tcx> {
-    /// Creates a new persistent summary cache, using (or creating) a Rocks data base at the given
-    /// file path.
+    /// Creates a new persistent summary cache, using (or creating) a database for the crate being
+    /// compiled in the given directory. The databases of the crates that it depends on are expected
+    /// to be in the same directory.
     pub fn new(
         type_context: &'a TyCtxt<'a, 'tcx, 'tcx>,
-        summary_store_path: String,
+        summary_store_directory: PathBuf,
+        known_names_cache: Arc<KnownNamesCache>,
     ) -> PersistentSummaryCache<'a, 'tcx> {
+        let crate_name = String::from(type_context.crate_name.as_interned_str().as_str().get());
+        if let Err(err) = fs::create_dir_all(&summary_store_directory) {
+            println!(
+                "unable to create summary store directory {}: {}",
+                summary_store_directory.display(),
+                err
+            );
+        }
+        let summary_store_path =
+            Self::get_summary_store_path(&summary_store_directory, &crate_name);
         PersistentSummaryCache {
-            db: Db::start_default(summary_store_path)
+            db: Self::open_summary_store(&summary_store_path)
                 .unwrap_or_else(|err| panic!(format!("{} ", err))),
+            crate_name,
+            summary_store_directory,
+            dependency_summaries: HashMap::new(),
             cache: HashMap::new(),
             key_cache: HashMap::new(),
             dependencies: HashMap::new(),
+            callees: HashMap::new(),
+            local_defs: None,
+            signature_summaries: HashSet::new(),
             type_context,
+            known_names_cache,
+        }
+    }
+
+    /// Returns the path of the summary database of the named crate.
+    fn get_summary_store_path(summary_store_directory: &PathBuf, crate_name: &str) -> String {
+        let mut path = summary_store_directory.clone();
+        path.push(crate_name);
+        path.set_extension("sled");
+        String::from(path.to_str().unwrap())
+    }
+
+    /// Opens the summary database at the given path. A database can only be open in one process
+    /// at a time, so if another compiler process, for example one that compiles the tests of the
+    /// same crate, has it open, this waits until it becomes available.
+    fn open_summary_store(summary_store_path: &str) -> Result<Db, String> {
+        let start = Instant::now();
+        let time_limit = Duration::from_secs(k_limits::MAX_SUMMARY_STORE_WAIT_SECONDS);
+        loop {
+            match Db::start_default(summary_store_path) {
+                Ok(db) => return Ok(db),
+                Err(err) => {
+                    if start.elapsed() > time_limit {
+                        return Err(format!(
+                            "unable to open summary store {}: {:?}",
+                            summary_store_path, err
+                        ));
+                    }
+                    debug!("waiting for summary store {}", summary_store_path);
+                    thread::sleep(Duration::from_millis(100));
+                }
+            }
+        }
+    }
+
+    /// Reads all of the summaries of the named crate, which the crate being compiled depends on,
+    /// from its database. Crates that have not been analyzed, such as the standard library, have
+    /// no database and thus no summaries.
+    fn read_dependency_summaries(&self, crate_name: &str) -> HashMap<String, Vec<u8>> {
+        Self::read_summary_store(&self.summary_store_directory, crate_name)
+    }
+
+    /// Reads all of the entries of the database of the named crate in the given summary store
+    /// directory, which are the serialized summaries and fingerprints of its functions, keyed by
+    /// summary key. Returns an empty map if there is no such database.
+    fn read_summary_store(
+        summary_store_directory: &PathBuf,
+        crate_name: &str,
+    ) -> HashMap<String, Vec<u8>> {
+        let mut summaries = HashMap::new();
+        let summary_store_path = Self::get_summary_store_path(summary_store_directory, crate_name);
+        if !std::path::Path::new(&summary_store_path).exists() {
+            return summaries;
+        }
+        match Self::open_summary_store(&summary_store_path) {
+            Ok(db) => {
+                for (key, value) in db.iter().filter_map(Result::ok) {
+                    if let Ok(key) = String::from_utf8(key.to_vec()) {
+                        summaries.insert(key, value.to_vec());
+                    }
+                }
+            }
+            Err(err) => println!("{}", err),
+        }
+        summaries
+    }
+
+    /// Returns the summaries of the functions of the crate being compiled that are stored in the
+    /// given summary store directory, such as the one of an earlier version of the crate, keyed by
+    /// summary key.
+    pub fn read_baseline_summaries(
+        &self,
+        summary_store_directory: &str,
+    ) -> HashMap<String, Summary> {
+        Self::read_summary_store(&PathBuf::from(summary_store_directory), &self.crate_name)
+            .into_iter()
+            .filter(|(key, _)| !key.contains('#'))
+            .filter_map(|(key, serialized_summary)| {
+                let summary = bincode::deserialize(&serialized_summary).ok()?;
+                Some((key, summary))
+            })
+            .collect()
+    }
+
+    /// Records, in the summary database, the summary keys of the functions of the crate being
+    /// compiled. Entries for other keys belong to functions that have been removed or renamed
+    /// since they were analyzed, and are removed by cargo mirai --gc-summaries.
+    pub fn record_summary_keys(&mut self) {
+        let tcx = self.type_context;
+        let summary_keys: Vec<String> = tcx
+            .body_owners()
+            .map(|def_id| utils::summary_key_str(tcx, def_id))
+            .collect();
+        let result = self.db.set(
+            SUMMARY_KEYS_KEY.as_bytes(),
+            summary_keys.join("\n").into_bytes(),
+        );
+        if result.is_err() {
+            println!("unable to set key in summary database: {:?}", result);
+        }
+    }
+
+    /// Writes the summaries of the given functions of the crate being compiled, which make up its
+    /// public API, to a new summary database for the crate in the given directory. The database
+    /// holds nothing else: no summaries of private functions, no fingerprints and none of the
+    /// private functions in the call chains of the summaries. It can thus be published along with
+    /// the crate and copied into the summary store of a user of the crate. Functions that have not
+    /// been analyzed are left out. Reports and otherwise ignores failures.
+    pub fn export_summaries(&mut self, directory: &str, def_ids: &[DefId]) {
+        let directory = PathBuf::from(directory);
+        if let Err(err) = fs::create_dir_all(&directory) {
+            println!(
+                "unable to create summary export directory {}: {}",
+                directory.display(),
+                err
+            );
+            return;
+        }
+        let export_path = Self::get_summary_store_path(&directory, &self.crate_name);
+        let stale_export = std::path::Path::new(&export_path);
+        let removed = if stale_export.is_dir() {
+            fs::remove_dir_all(stale_export)
+        } else if stale_export.exists() {
+            fs::remove_file(stale_export)
+        } else {
+            Ok(())
+        };
+        if let Err(err) = removed {
+            println!(
+                "unable to replace exported summaries {}: {}",
+                export_path, err
+            );
+            return;
+        }
+        let db = match Self::open_summary_store(&export_path) {
+            Ok(db) => db,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            }
+        };
+        for def_id in def_ids.iter() {
+            let mut summary = self.get_summary_for(*def_id, None).clone();
+            if self.is_signature_summary(*def_id) {
+                continue;
+            }
+            summary.blocking_call_chain =
+                self.get_published_call_chain(*def_id, &summary.blocking_call_chain);
+            summary.unsafe_call_chain =
+                self.get_published_call_chain(*def_id, &summary.unsafe_call_chain);
+            let persistent_key = self.get_summary_key_for(*def_id).clone();
+            let serialized_summary = bincode::serialize(&summary).unwrap();
+            let result = db.set(persistent_key.as_bytes(), serialized_summary);
+            if result.is_err() {
+                println!("unable to set key in summary database: {:?}", result);
+            }
+        }
+        if let Err(err) = db.flush() {
+            println!(
+                "unable to write exported summaries {}: {:?}",
+                export_path, err
+            );
+        }
+    }
+
+    /// Returns the given call chain of the summary of def_id without the private functions of the
+    /// crate being compiled. If that leaves nothing, the chain is just def_id itself, so that the
+    /// users of the exported summary still see that the chain is not empty.
+    fn get_published_call_chain(&mut self, def_id: DefId, call_chain: &[String]) -> Vec<String> {
+        let tcx = self.type_context;
+        let mut published_chain = Vec::new();
+        for key in call_chain.iter() {
+            let is_private = match self.get_local_def_for(key) {
+                Some(callee) => !utils::is_public(callee, tcx),
+                None => false,
+            };
+            if !is_private {
+                published_chain.push(key.clone());
+            }
+        }
+        if published_chain.is_empty() && !call_chain.is_empty() {
+            published_chain.push(self.get_summary_key_for(def_id).clone());
+        }
+        published_chain
+    }
+
+    /// Returns true if the summary database of the crate being compiled has any entries.
+    pub fn has_stored_entries(&self) -> bool {
+        self.db.iter().next().is_some()
+    }
+
+    /// Returns all of the entries of the summary database of the crate being compiled, serialized
+    /// as a single blob, so that the database can be stored in a remote summary cache.
+    pub fn export_database(&self) -> Vec<u8> {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = self
+            .db
+            .iter()
+            .filter_map(Result::ok)
+            .map(|(key, value)| (key.to_vec(), value.to_vec()))
+            .collect();
+        bincode::serialize(&entries).unwrap()
+    }
+
+    /// Adds the entries of a blob made by export_database, such as one fetched from a remote
+    /// summary cache, to the summary database of the crate being compiled. Returns false, and adds
+    /// nothing, if the blob is malformed or if any of its summaries or fingerprints cannot be
+    /// decoded, as when the blob was written by a build of MIRAI with another summary format, so
+    /// that such a blob is treated like a cache miss.
+    pub fn import_database(&mut self, blob: &[u8]) -> bool {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = match bincode::deserialize(blob) {
+            Ok(entries) => entries,
+            Err(_) => return false,
+        };
+        let is_decodable = |(key, value): &(Vec<u8>, Vec<u8>)| {
+            let key = String::from_utf8_lossy(key);
+            if key.ends_with(FINGERPRINT_KEY_SUFFIX) {
+                bincode::deserialize::<Fingerprint>(value).is_ok()
+            } else if key.contains('#') {
+                true
+            } else {
+                bincode::deserialize::<Summary>(value).is_ok()
+            }
+        };
+        if !entries.iter().all(is_decodable) {
+            info!("ignoring fetched summaries that cannot be decoded");
+            return false;
         }
+        for (key, value) in entries {
+            let result = self.db.set(key.as_slice(), value);
+            if result.is_err() {
+                println!("unable to set key in summary database: {:?}", result);
+            }
+        }
+        true
     }
 
     /// Returns a list of DefIds for all functions in the current crate that are known
@@ -185,6 +908,7 @@ impl<'a, 'tcx: 'a> PersistentSummaryCache<'a, 'tcx> {
     }
 
     /// Returns the cached summary corresponding to def_id, or creates a default for it.
+    /// A generic function has one summary, which is shared by all of its instantiations.
     /// The optional dependent_def_id is the definition that refers to the returned summary.
     /// The cache tracks all such dependents so that they can be retrieved and re-analyzed
     /// if the cache is updated with a new summary for def_id.
@@ -196,29 +920,64 @@ impl<'a, 'tcx: 'a> PersistentSummaryCache<'a, 'tcx> {
                 if !dependents.contains(&id) {
                     dependents.push(id);
                 }
+                self.callees
+                    .entry(id)
+                    .or_insert_with(HashSet::new)
+                    .insert(def_id);
             }
         };
-        let tcx = self.type_context;
-        let db = &self.db;
-        self.cache.entry(def_id).or_insert_with(|| {
-            let persistent_key = utils::summary_key_str(tcx, def_id);
-            Self::get_persistent_summary_for_db(db, &persistent_key)
-        })
+        if !self.cache.contains_key(&def_id) {
+            let persistent_key = self.get_summary_key_for(def_id).clone();
+            let summary = self
+                .get_persistent_summary(&persistent_key)
+                .unwrap_or_else(|| {
+                    self.signature_summaries.insert(def_id);
+                    summarize_signature(self.type_context, def_id)
+                });
+            self.cache.insert(def_id, summary);
+        }
+        &self.cache[&def_id]
+    }
+
+    /// Returns true if the cached summary for def_id was derived from the signature of the
+    /// function alone, so that nothing is known about what the function does.
+    pub fn is_signature_summary(&self, def_id: DefId) -> bool {
+        self.signature_summaries.contains(&def_id)
+    }
+
+    /// Returns true if the summary database contains a summary for def_id. Before any function has
+    /// been analyzed, this means that the summary was computed by a previous run.
+    pub fn has_persistent_summary_for(&mut self, def_id: DefId) -> bool {
+        let persistent_key = self.get_summary_key_for(def_id).clone();
+        self.get_persistent_summary(&persistent_key).is_some()
     }
 
     /// Returns the summary corresponding to the persistent_key in the the summary database.
     /// The caller is expected to cache this.
-    pub fn get_persistent_summary_for(&self, persistent_key: &str) -> Summary {
-        Self::get_persistent_summary_for_db(&self.db, persistent_key)
+    pub fn get_persistent_summary_for(&mut self, persistent_key: &str) -> Summary {
+        self.get_persistent_summary(persistent_key)
+            .unwrap_or_default()
     }
 
     /// Helper for get_summary_for and get_persistent_summary_for.
-    fn get_persistent_summary_for_db(db: &Db, persistent_key: &str) -> Summary {
-        if let Ok(Some(pinned_value)) = db.get(persistent_key.as_bytes()) {
-            bincode::deserialize(pinned_value.deref()).unwrap()
-        } else {
-            Summary::default() // todo: #33 look for a contract summary or construct from type
+    /// Looks for the summary in the database of the crate that is named by the first component of
+    /// persistent_key. Returns None if the database does not contain a summary for persistent_key.
+    fn get_persistent_summary(&mut self, persistent_key: &str) -> Option<Summary> {
+        let crate_name = persistent_key.split('.').next().unwrap_or_default();
+        if crate_name == self.crate_name {
+            if let Ok(Some(pinned_value)) = self.db.get(persistent_key.as_bytes()) {
+                return bincode::deserialize(pinned_value.deref()).ok();
+            }
+            return None; // todo: #33 look for a contract summary
         }
+        if !self.dependency_summaries.contains_key(crate_name) {
+            let summaries = self.read_dependency_summaries(crate_name);
+            self.dependency_summaries
+                .insert(String::from(crate_name), summaries);
+        }
+        self.dependency_summaries[crate_name]
+            .get(persistent_key)
+            .and_then(|serialized_summary| bincode::deserialize(serialized_summary).ok())
     }
 
     /// Sets or updates the cache so that from now on def_id maps to the given summary.
@@ -229,6 +988,157 @@ impl<'a, 'tcx: 'a> PersistentSummaryCache<'a, 'tcx> {
         if result.is_err() {
             println!("unable to set key in summary database: {:?}", result);
         }
+        self.signature_summaries.remove(&def_id);
+        self.cache.insert(def_id, summary)
+    }
+
+    /// Sets or updates the cache so that from now on def_id maps to the given summary, which was
+    /// derived from the signature of the function because its analysis did not complete. Unlike
+    /// the summaries of set_summary_for, such a summary is not stored in the database. The summary
+    /// and the fingerprint that a previous run may have stored are removed, so that the next run
+    /// analyzes the function again and other crates do not take the summary for a real one.
+    pub fn set_incomplete_summary_for(
+        &mut self,
+        def_id: DefId,
+        summary: Summary,
+    ) -> Option<Summary> {
+        let persistent_key = utils::summary_key_str(self.type_context, def_id);
+        let fingerprint_key = format!("{}{}", persistent_key, FINGERPRINT_KEY_SUFFIX);
+        for key in [persistent_key, fingerprint_key].iter() {
+            let result = self.db.del(key.as_bytes());
+            if result.is_err() {
+                println!("unable to remove key from summary database: {:?}", result);
+            }
+        }
+        self.signature_summaries.insert(def_id);
         self.cache.insert(def_id, summary)
     }
+
+    /// Records, in the summary database, what the current summary of def_id was computed from,
+    /// so that later runs can skip analyzing def_id if none of it has changed.
+    /// See is_unchanged_since_last_run.
+    pub fn set_fingerprint_for(&mut self, def_id: DefId, has_diagnostics: bool) {
+        let mut callee_summary_hashes: Vec<(String, u64)> = self
+            .callees
+            .get(&def_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|callee| {
+                let key = self.get_summary_key_for(callee).clone();
+                let summary = if self.is_signature_summary(callee) {
+                    None
+                } else {
+                    Some(&self.cache[&callee])
+                };
+                let hash = self.hash_callee(&key, summary);
+                (key, hash)
+            })
+            .collect();
+        callee_summary_hashes.sort();
+        let fingerprint = Fingerprint {
+            mir_hash: self.hash_mir(def_id),
+            callee_summary_hashes,
+            has_diagnostics,
+        };
+        let fingerprint_key = format!(
+            "{}{}",
+            self.get_summary_key_for(def_id),
+            FINGERPRINT_KEY_SUFFIX
+        );
+        let serialized_fingerprint = bincode::serialize(&fingerprint).unwrap();
+        let result = self
+            .db
+            .set(fingerprint_key.as_bytes(), serialized_fingerprint);
+        if result.is_err() {
+            println!("unable to set key in summary database: {:?}", result);
+        }
+    }
+
+    /// Returns true if the summary of def_id that is stored in the database was computed by a
+    /// previous run from the same MIR body and the same summaries of the functions it calls, and if
+    /// analyzing def_id reported no diagnostics. Analyzing def_id again would then give the same
+    /// summary and no diagnostics, so it can be skipped. The functions that def_id calls are
+    /// recorded as its dependencies, as if it had been analyzed, so that it is analyzed after all
+    /// if one of their summaries changes in this run.
+    pub fn is_unchanged_since_last_run(&mut self, def_id: DefId) -> bool {
+        let fingerprint_key = format!(
+            "{}{}",
+            self.get_summary_key_for(def_id),
+            FINGERPRINT_KEY_SUFFIX
+        );
+        let fingerprint: Fingerprint = match self.db.get(fingerprint_key.as_bytes()) {
+            Ok(Some(pinned_value)) => match bincode::deserialize(pinned_value.deref()) {
+                Ok(fingerprint) => fingerprint,
+                Err(_) => return false,
+            },
+            _ => return false,
+        };
+        if fingerprint.has_diagnostics || fingerprint.mir_hash != self.hash_mir(def_id) {
+            return false;
+        }
+        fingerprint
+            .callee_summary_hashes
+            .iter()
+            .all(|(callee_key, callee_summary_hash)| {
+                let summary = match self.get_local_def_for(callee_key) {
+                    Some(callee) => {
+                        let summary = self.get_summary_for(callee, Some(def_id)).clone();
+                        if self.is_signature_summary(callee) {
+                            None
+                        } else {
+                            Some(summary)
+                        }
+                    }
+                    None => self.get_persistent_summary(callee_key),
+                };
+                let hash = self.hash_callee(callee_key, summary.as_ref());
+                hash == *callee_summary_hash
+            })
+    }
+
+    /// Returns a hash of what the summary of a callee, as used by a caller, depends on: the
+    /// summary itself, or the version of summarize_signature if the summary was derived from the
+    /// signature of the callee alone, and the built in models that may apply to the callee.
+    /// If a new version of MIRAI changes any of these, the summaries of the callers of the callee
+    /// are computed again, and so are those of their callers if their summaries change as a result.
+    fn hash_callee(&self, callee_key: &str, summary: Option<&Summary>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match summary {
+            Some(summary) => summary.hash(&mut hasher),
+            None => SIGNATURE_SUMMARY_VERSION.hash(&mut hasher),
+        }
+        self.known_names_cache
+            .get_model_hash(callee_key)
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a hash of the MIR body of def_id and of the location of the function in the source.
+    /// The location is included because the summary refers to the source by position, so it has
+    /// to be computed again if the function moves, for example because lines were added above it.
+    fn hash_mir(&self, def_id: DefId) -> u64 {
+        let tcx = self.type_context;
+        let span = tcx.def_span(def_id);
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}", tcx.optimized_mir(def_id)).hash(&mut hasher);
+        span.lo().0.hash(&mut hasher);
+        span.hi().0.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the local definition with a body that has the given summary key, if there is one.
+    fn get_local_def_for(&mut self, summary_key: &str) -> Option<DefId> {
+        if self.local_defs.is_none() {
+            let tcx = self.type_context;
+            let local_defs = tcx
+                .body_owners()
+                .map(|def_id| (utils::summary_key_str(tcx, def_id), def_id))
+                .collect();
+            self.local_defs = Some(local_defs);
+        }
+        self.local_defs
+            .as_ref()
+            .and_then(|local_defs| local_defs.get(summary_key).cloned())
+    }
 }
@@ -3,11 +3,20 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use rustc::hir::def_id::DefId;
+use crate::options::LimitsConfig;
+
+use rustc::hir;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
+use rustc::hir::intravisit::{self, NestedVisitorMap, Visitor};
 use rustc::hir::ItemKind;
 use rustc::hir::Node;
-use rustc::ty::TyCtxt;
+use rustc::mir::TerminatorKind;
+use rustc::ty::{TyCtxt, TyKind};
 use rustc_target::spec::abi::Abi;
+use std::collections::HashSet;
+use syntax::ast::{Attribute, LitKind, MetaItemKind};
+use syntax::attr::InlineAttr;
+use syntax_pos::Span;
 
 /// Returns the location of the rust system binaries that are associated with this build of Mirai.
 /// The location is obtained by looking at the contents of the environmental variables that were
@@ -41,6 +50,88 @@ pub fn is_rust_intrinsic(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
     }
 }
 
+/// Returns true if the function identified by def_id is the entry point (main) of a binary crate.
+pub fn is_entry_point(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    match tcx.entry_fn(LOCAL_CRATE) {
+        Some((entry_def_id, _)) => entry_def_id == def_id,
+        None => false,
+    }
+}
+
+/// Returns true if the crate being compiled has an entry point (main), i.e. if it is a binary crate.
+pub fn has_entry_point(tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    tcx.entry_fn(LOCAL_CRATE).is_some()
+}
+
+/// Returns true if the body identified by def_id was generated by the harness that rustc adds to a
+/// crate that is compiled with --test: the main function that runs the tests, or the description
+/// of a #[test] function (a constant marked #[rustc_test_marker]) or the closure in it that calls
+/// the test function. Such code is not written by the user and only calls into libtest, so it is
+/// not analyzed.
+pub fn is_test_harness_code(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    if !tcx.sess.opts.test || !def_id.is_local() {
+        return false;
+    }
+    let item_def_id = tcx.closure_base_def_id(def_id);
+    is_entry_point(item_def_id, tcx) || tcx.has_attr(item_def_id, "rustc_test_marker")
+}
+
+/// Returns the #[test] functions of the crate being compiled, which are the functions of the crate
+/// that the closures in the test descriptions call. The set is empty unless the crate is compiled
+/// with --test.
+pub fn get_test_functions(tcx: &TyCtxt<'_, '_, '_>) -> HashSet<DefId> {
+    if !tcx.sess.opts.test {
+        return HashSet::new();
+    }
+    let body_owners: HashSet<DefId> = tcx.body_owners().collect();
+    tcx.body_owners()
+        .filter(|def_id| tcx.is_closure(*def_id) && is_test_harness_code(*def_id, tcx))
+        .flat_map(|def_id| get_direct_callees(def_id, tcx))
+        .filter(|callee| body_owners.contains(callee))
+        .collect()
+}
+
+/// Returns true if the function identified by def_id has the #[mirai::entry_point] attribute, or
+/// #[entry_point] (see get_safety_contract). Such a function is analyzed as a root, in addition to
+/// main, for example because it is a request handler that is called by an async runtime.
+pub fn is_annotated_entry_point(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    tcx.get_attrs(def_id)
+        .iter()
+        .any(|attribute| is_mirai_attribute(attribute, "entry_point"))
+}
+
+/// Returns true if the function identified by def_id is exported to C, i.e. if it is declared as
+/// `#[no_mangle] extern "C" fn`. Such a function can be called by C code, with any arguments, so
+/// it is analyzed as a root whose arguments are only constrained by its ABI contract (see
+/// Options::ffi_contracts).
+pub fn is_exported_to_c(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    if let Some(Node::Item(item)) = tcx.hir().get_if_local(def_id) {
+        if let ItemKind::Fn(_, header, ..) = &item.node {
+            return header.abi == Abi::C
+                && tcx
+                    .get_attrs(def_id)
+                    .iter()
+                    .any(|attribute| attribute.check_name("no_mangle"));
+        }
+    }
+    false
+}
+
+/// If def_id identifies the generator that is the body of an async fn, or of an async block,
+/// returns the function in which it is defined. Polling the future of an async fn resumes this
+/// generator, so it is analyzed as a root whenever the function is.
+pub fn get_function_of_generator(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> Option<DefId> {
+    if let TyKind::Generator(..) = tcx.type_of(def_id).sty {
+        let mut function = tcx.parent(def_id)?;
+        while tcx.is_closure(function) {
+            function = tcx.parent(function)?;
+        }
+        Some(function)
+    } else {
+        None
+    }
+}
+
 /// Returns true if the function identified by def_id is a public function.
 pub fn is_public(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
     if let Some(node) = tcx.hir().get_if_local(def_id) {
@@ -69,6 +160,227 @@ pub fn is_public(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
     }
 }
 
+/// Returns the spans of the calls in the body of the local function identified by def_id that
+/// are statements by themselves, such as `f(x);`, so that their results are discarded without
+/// being bound, not even to _.
+pub fn get_discarded_call_spans(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> HashSet<Span> {
+    let mut finder = DiscardedCallFinder {
+        spans: HashSet::new(),
+    };
+    if let Some(hir_id) = tcx.hir().as_local_hir_id(def_id) {
+        let body_id = tcx.hir().body_owned_by(hir_id);
+        finder.visit_body(tcx.hir().body(body_id));
+    }
+    finder.spans
+}
+
+/// Collects the spans of calls that are statements by themselves.
+struct DiscardedCallFinder {
+    spans: HashSet<Span>,
+}
+
+impl<'tcx> Visitor<'tcx> for DiscardedCallFinder {
+    fn nested_visit_map<'this>(&'this mut self) -> NestedVisitorMap<'this, 'tcx> {
+        NestedVisitorMap::None
+    }
+
+    fn visit_stmt(&mut self, stmt: &'tcx hir::Stmt) {
+        if let hir::StmtKind::Semi(expr) = &stmt.node {
+            match expr.node {
+                hir::ExprKind::Call(..) | hir::ExprKind::MethodCall(..) => {
+                    self.spans.insert(expr.span);
+                }
+                _ => {}
+            }
+        }
+        intravisit::walk_stmt(self, stmt);
+    }
+}
+
+/// Returns true if the function identified by def_id is a method of an impl that was
+/// generated by a derive macro, such as #[derive(Deserialize)].
+pub fn is_automatically_derived(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    if let Some(impl_def_id) = tcx.impl_of_method(def_id) {
+        tcx.has_attr(impl_def_id, "automatically_derived")
+    } else {
+        false
+    }
+}
+
+/// The methods of derived impls that call the same method on each field of self (and of the other
+/// value) and combine the results, so that their bodies only depend on the shape of the type.
+const SHAPE_DETERMINED_DERIVED_METHODS: [&str; 5] = [
+    "core.clone.Clone.clone",
+    "core.cmp.PartialEq.eq",
+    "core.cmp.PartialEq.ne",
+    "core.fmt.Debug.fmt",
+    "core.hash.Hash.hash",
+];
+
+/// If def_id identifies the clone, eq, ne, fmt or hash method of a derived impl of Clone,
+/// PartialEq, Debug or Hash, returns a description of the shape of the type that it is derived
+/// for: the method, the kind, representation and generic parameters of the type, and the
+/// discriminants and field types of its variants. Debug prints the names of the type, its
+/// variants and its fields, so for fmt they are part of the shape as well. Derived methods with
+/// the same shape have the same body, apart from the type of self, and thus the same summary, so
+/// only one of them needs to be analyzed.
+pub fn get_derived_shape(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> Option<String> {
+    if !is_automatically_derived(def_id, tcx) {
+        return None;
+    }
+    let impl_def_id = tcx.impl_of_method(def_id)?;
+    let trait_def_id = tcx.trait_id_of_impl(impl_def_id)?;
+    let method_key = format!(
+        "{}.{}",
+        summary_key_str(tcx, trait_def_id),
+        tcx.item_name(def_id)
+    );
+    if !SHAPE_DETERMINED_DERIVED_METHODS.contains(&method_key.as_str()) {
+        return None;
+    }
+    let adt_def = match tcx.type_of(impl_def_id).sty {
+        TyKind::Adt(adt_def, _) => adt_def,
+        _ => return None,
+    };
+    let with_names = method_key == "core.fmt.Debug.fmt";
+    let mut shape = format!(
+        "{} {:?} {:?} {}",
+        method_key,
+        adt_def.adt_kind(),
+        adt_def.repr,
+        tcx.generics_of(adt_def.did).count()
+    );
+    if with_names {
+        shape.push_str(&format!(" {}", tcx.item_name(adt_def.did)));
+    }
+    for variant in adt_def.variants.iter() {
+        shape.push_str(&format!(" | {:?}", variant.discr));
+        if with_names {
+            shape.push_str(&format!(" {}", variant.ident));
+        }
+        for field in variant.fields.iter() {
+            if with_names {
+                shape.push_str(&format!(" {}:", field.ident));
+            }
+            shape.push_str(&format!(" {:?}", tcx.type_of(field.did)));
+        }
+    }
+    Some(shape)
+}
+
+/// Returns true if the local function identified by def_id is a trivial wrapper: a function
+/// marked #[inline(always)], an implementation of Deref::deref or DerefMut::deref_mut, or a getter,
+/// which is a method whose only parameter is self and whose body does not call other functions.
+/// Obligations that arise inside such functions are reported at their callers.
+pub fn is_trivial_wrapper(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> bool {
+    if !def_id.is_local() {
+        return false;
+    }
+    if tcx.codegen_fn_attrs(def_id).inline == InlineAttr::Always {
+        return true;
+    }
+    if let Some(impl_def_id) = tcx.impl_of_method(def_id) {
+        if let Some(trait_def_id) = tcx.trait_id_of_impl(impl_def_id) {
+            let trait_key = summary_key_str(tcx, trait_def_id);
+            return trait_key == "core.ops.deref.Deref" || trait_key == "core.ops.deref.DerefMut";
+        }
+        if !tcx.associated_item(def_id).method_has_self_argument {
+            return false;
+        }
+        let mir = tcx.optimized_mir(def_id);
+        let calls_functions =
+            mir.basic_blocks()
+                .iter()
+                .any(|block| match block.terminator().kind {
+                    TerminatorKind::Call { .. } => true,
+                    _ => false,
+                });
+        return mir.arg_count == 1 && !calls_functions;
+    }
+    false
+}
+
+/// Returns the functions that the body of the local function identified by def_id calls directly,
+/// rather than via function pointers or trait objects, without duplicates.
+pub fn get_direct_callees(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> Vec<DefId> {
+    let mir = tcx.optimized_mir(def_id);
+    let mut callees: Vec<DefId> = Vec::new();
+    for block in mir.basic_blocks().iter() {
+        if let TerminatorKind::Call { func, .. } = &block.terminator().kind {
+            if let TyKind::FnDef(callee, _) = func.ty(mir, *tcx).sty {
+                if !callees.contains(&callee) {
+                    callees.push(callee);
+                }
+            }
+        }
+    }
+    callees
+}
+
+/// Returns the name of the predicate given by the #[mirai::safety_requires(predicate)] attribute
+/// of the function identified by def_id, if it has one. Since compilers that do not know about
+/// the mirai tool reject the attribute, #[safety_requires(predicate)] is accepted as well, which
+/// can be used with #![feature(custom_attribute)]. A malformed attribute yields an empty name.
+pub fn get_safety_contract(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> Option<String> {
+    for attribute in tcx.get_attrs(def_id).iter() {
+        if is_mirai_attribute(attribute, "safety_requires") {
+            let predicate_name = attribute
+                .meta_item_list()
+                .and_then(|items| items.first().and_then(|item| item.ident()))
+                .map(|ident| ident.to_string());
+            return Some(predicate_name.unwrap_or_default());
+        }
+    }
+    None
+}
+
+/// Returns the limits given by the #[mirai::limits(name = value, ..)] attribute of the function
+/// identified by def_id, such as #[mirai::limits(max_fixed_point_iterations = 200)], if it has
+/// one. The names are those of the [limits] section of a MIRAI configuration file. Like
+/// #[mirai::safety_requires], the attribute may also be written as #[limits(..)].
+/// Reports and ignores unknown limits and values that are not integers.
+pub fn get_limits_attribute(def_id: DefId, tcx: &TyCtxt<'_, '_, '_>) -> Option<LimitsConfig> {
+    for attribute in tcx.get_attrs(def_id).iter() {
+        if !is_mirai_attribute(attribute, "limits") {
+            continue;
+        }
+        let mut limits = LimitsConfig::default();
+        for item in attribute.meta_item_list().unwrap_or_default() {
+            let name = item
+                .ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            let value = match item.meta_item().map(|meta_item| &meta_item.node) {
+                Some(MetaItemKind::NameValue(literal)) => match literal.node {
+                    LitKind::Int(value, _) => Some(value as u64),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if !value.map_or(false, |value| limits.set(&name, value)) {
+                println!(
+                    "ignoring the invalid limit {} of {}",
+                    name,
+                    summary_key_str(tcx, def_id)
+                );
+            }
+        }
+        return Some(limits);
+    }
+    None
+}
+
+/// Returns true if the attribute is #[mirai::name] or #[name].
+fn is_mirai_attribute(attribute: &Attribute, name: &str) -> bool {
+    let path: Vec<String> = attribute
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    path == ["mirai", name] || path == [name]
+}
+
 /// Constructs a string that uniquely identifies a definition to serve as a key to
 /// the summary cache, which is a key value store. The string will always be the same as
 /// long as the definition does not change its name or location, so it can be used to
@@ -105,3 +417,43 @@ pub fn summary_key_str(tcx: &TyCtxt<'_, '_, '_>, def_id: DefId) -> String {
     }
     name
 }
+
+/// Returns a string that identifies the Self type of the impl block that defines the method
+/// identified by def_id, if it is a method of an impl block. The string is the summary cache key
+/// of a struct, enum or union, or slice, array or str for methods of these types, so that a known
+/// names entry can tell apart the methods with the same name of different types in a module, such
+/// as the next methods of core::slice::Iter and core::slice::Chunks. The Self types of impls for
+/// references are those of the types they refer to.
+pub fn self_type_key_str(tcx: &TyCtxt<'_, '_, '_>, def_id: DefId) -> Option<String> {
+    let impl_def_id = tcx.impl_of_method(def_id)?;
+    let mut self_type = tcx.type_of(impl_def_id);
+    while let TyKind::Ref(_, referenced_type, _) = self_type.sty {
+        self_type = referenced_type;
+    }
+    match self_type.sty {
+        TyKind::Adt(adt_def, _) => Some(summary_key_str(tcx, adt_def.did)),
+        TyKind::Array(..) => Some(String::from("array")),
+        TyKind::Slice(..) => Some(String::from("slice")),
+        TyKind::Str => Some(String::from("str")),
+        _ => None,
+    }
+}
+
+/// Returns the given string as a JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
@@ -3,27 +3,123 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::abstract_domains::AbstractDomain;
-use crate::abstract_value::{self, AbstractValue, Path, PathSelector};
+use crate::abstract_domains::{self, AbstractDomain};
+use crate::abstract_value::{self, AbstractValue, Path, PathSelector, Tag};
 use crate::constant_domain::{ConstantDomain, ConstantValueCache};
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType};
-use crate::k_limits;
-use crate::smt_solver::{SmtResult, SmtSolver};
+use crate::interval_domain::{self, IntervalDomain};
+use crate::k_limits::{self, KLimits};
+use crate::known_names::KnownFunction;
+use crate::message_templates::{self, MessageFacts};
+use crate::models::{Comparison, Condition, FunctionModel, Operand, Quantifier};
+use crate::options::{
+    FfiContract, Guarantee, IntToPointerCasts, Options, PrecisionPreset, Sanitizer, Sink,
+    TrustLevel, TrustedFunction,
+};
+use crate::security;
+use crate::smt_lib::{self, AssumedObligation, UnprovenObligation};
+use crate::smt_solver::{ObligationClass, SmtResult, SmtSolver};
 use crate::summaries;
-use crate::summaries::{PersistentSummaryCache, Summary};
+use crate::summaries::{PersistentSummaryCache, StateAccesses, Summary};
 use crate::utils::{self, is_public};
+use crate::weak_topological_order::{weak_topological_order, WtoComponent};
 
 use rustc::session::Session;
-use rustc::ty::{Const, LazyConst, Ty, TyCtxt, TyKind, UserTypeAnnotationIndex};
+use rustc::ty::subst::SubstsRef;
+use rustc::ty::{
+    self, Const, LazyConst, Ty, TyCtxt, TyKind, TypeFoldable, UserTypeAnnotationIndex,
+};
 use rustc::{hir, mir};
+use rustc_target::abi::Endian;
 use std::borrow::Borrow;
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
-use std::iter::FromIterator;
-use syntax::errors::{Diagnostic, DiagnosticBuilder};
+use std::iter::{self, FromIterator};
+use std::time::{Duration, Instant};
+use syntax::errors::{Diagnostic, DiagnosticBuilder, DiagnosticId};
 use syntax_pos;
 
+/// The summary cache key of the serde trait that is implemented by types that can be deserialized.
+/// A copy of serde that is vendored as a module of another crate has a trait whose key ends with
+/// this key, after a dot.
+const SERDE_DESERIALIZE_TRAIT: &str = "serde.de.Deserialize";
+
+/// The summary keys of the std collections whose operations are modeled by the interpreter.
+const MODELED_COLLECTIONS: [&str; 6] = [
+    "alloc.vec.Vec",
+    "alloc.collections.vec_deque.VecDeque",
+    "alloc.collections.binary_heap.BinaryHeap",
+    "alloc.collections.btree.set.BTreeSet",
+    "std.collections.hash.map.HashMap",
+    "alloc.collections.btree.map.BTreeMap",
+];
+
+/// The summary keys of the modeled collections that keep every element that is added to them, so
+/// that collecting an iterator into them yields as many elements as the iterator produces.
+const SEQUENCE_COLLECTIONS: [&str; 3] = [
+    "alloc.vec.Vec",
+    "alloc.collections.vec_deque.VecDeque",
+    "alloc.collections.binary_heap.BinaryHeap",
+];
+
+/// The summary key of the type of the ranges that iterate over the integers from their start up
+/// to, but not including, their end.
+const RANGE_TYPE: &str = "core.ops.range.Range";
+
+/// The tags of the values that differ from run to run. See track_nondeterminism.
+const NONDETERMINISM_TAGS: [Tag; 2] = [Tag::UnorderedIteration, Tag::Nondeterministic];
+
+/// The summary keys of the types of the pointers that share heap blocks, whose operations are
+/// modeled by the interpreter.
+const SHARED_POINTERS: [&str; 2] = ["alloc.rc.Rc", "alloc.sync.Arc"];
+
+/// The summary keys of the types of the receiving endpoints of channels.
+const CHANNEL_RECEIVERS: [&str; 2] = [
+    "std.sync.mpsc.Receiver",
+    "crossbeam_channel.channel.Receiver",
+];
+
+/// The summary cache key of the type of values returned by fallible functions.
+const RESULT_TYPE: &str = "core.result.Result";
+
+/// How much trust can be placed in a diagnostic. This is given as the code of the diagnostic,
+/// which is part of the JSON output of the compiler (--error-format=json), so that findings can
+/// be triaged by tools.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Confidence {
+    /// The problem definitely occurs for some input: the abstract domains show the condition to be
+    /// false, or the solver found a model in which it is false.
+    Definite,
+    /// The condition could not be proven, but this may be because of the limited precision of
+    /// the analysis.
+    LimitedPrecision,
+    /// The condition depends on the results of calls to functions for which there are no
+    /// summaries or models, so nothing is known about what they return.
+    MissingModels,
+    /// The contracts of the code contradict each other, so that they can never be satisfied.
+    /// This is a problem with the specification, rather than with the code.
+    Specification,
+}
+
+impl Confidence {
+    /// The name of this confidence, as used in diagnostic codes and configuration files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Confidence::Definite => "definite",
+            Confidence::LimitedPrecision => "limited_precision",
+            Confidence::MissingModels => "missing_models",
+            Confidence::Specification => "specification",
+        }
+    }
+
+    /// The code that is given to diagnostics with this confidence.
+    pub fn diagnostic_code(self) -> DiagnosticId {
+        DiagnosticId::Lint(String::from(self.name()))
+    }
+}
+
 pub struct MirVisitorCrateContext<'a, 'b: 'a, 'tcx: 'b, E> {
     /// A place where diagnostic messages can be buffered by the test harness.
     pub buffered_diagnostics: &'a mut Vec<Diagnostic>,
@@ -34,9 +130,31 @@ pub struct MirVisitorCrateContext<'a, 'b: 'a, 'tcx: 'b, E> {
     pub tcx: TyCtxt<'b, 'tcx, 'tcx>,
     pub def_id: hir::def_id::DefId,
     pub mir: &'a mir::Mir<'tcx>,
+    pub options: &'a Options,
     pub constant_value_cache: &'a mut ConstantValueCache,
     pub summary_cache: &'a mut PersistentSummaryCache<'b, 'tcx>,
     pub smt_solver: &'a mut dyn SmtSolver<E>,
+    /// The functions that are analyzed with elevated limits because some of their obligations
+    /// could not be proven with the default limits.
+    pub escalated_defs: &'a mut HashSet<hir::def_id::DefId>,
+    /// A place where the obligations that were assumed to hold, because they were proven by other
+    /// means, are recorded once the body has been analyzed.
+    pub assumption_log: &'a mut Vec<AssumedObligation>,
+    /// The #[test] functions of the crate, which are analyzed as roots when the crate is compiled
+    /// with --test. See utils::get_test_functions.
+    pub test_functions: &'a HashSet<hir::def_id::DefId>,
+}
+
+/// The state that resulted from analyzing a basic block during the fixed point loop, given an in
+/// state, which is kept so that the block need not be analyzed again if it has the same in state
+/// in a later iteration of the loop.
+struct CachedBlock {
+    /// See Environment::fingerprint.
+    in_state_fingerprint: u64,
+    in_state: Environment,
+    out_state: Environment,
+    /// The span of the terminator of the block.
+    span: syntax_pos::Span,
 }
 
 /// Holds the state for the MIR test visitor.
@@ -47,20 +165,54 @@ pub struct MirVisitor<'a, 'b: 'a, 'tcx: 'b, E> {
     tcx: TyCtxt<'b, 'tcx, 'tcx>,
     def_id: hir::def_id::DefId,
     mir: &'a mir::Mir<'tcx>,
+    options: &'a Options,
     constant_value_cache: &'a mut ConstantValueCache,
     summary_cache: &'a mut PersistentSummaryCache<'b, 'tcx>,
     smt_solver: &'a mut dyn SmtSolver<E>,
+    escalated_defs: &'a mut HashSet<hir::def_id::DefId>,
+    assumption_log: &'a mut Vec<AssumedObligation>,
+    test_functions: &'a HashSet<hir::def_id::DefId>,
 
+    assumed_obligations: Vec<AssumedObligation>,
+    block_cache: HashMap<mir::BasicBlock, CachedBlock>,
+    blocking_call_chain: Vec<String>,
+    callee_heap_addresses: HashMap<(mir::Location, usize), usize>,
     check_for_errors: bool,
+    const_eval_depth: usize,
+    counterexample: Option<String>,
+    counterexample_found: bool,
     current_environment: Environment,
     current_location: mir::Location,
     current_span: syntax_pos::Span,
+    deadline: Option<Instant>,
     exit_environment: Environment,
+    fallible_call_results: HashMap<mir::Local, (syntax_pos::Span, mir::BasicBlock)>,
     heap_addresses: HashMap<mir::Location, AbstractValue>,
+    limits: KLimits,
+    loop_invariant_thresholds: Vec<i128>,
+    may_drop_errors: bool,
+    obligation_count: usize,
+    obligation_goal: AbstractValue,
     post_conditions: Vec<AbstractValue>,
+    precision_factor: usize,
+    precision_limited_condition: bool,
+    precision_limits: Vec<(syntax_pos::Span, &'static str)>,
+    precision_preset: PrecisionPreset,
     preconditions: Vec<(AbstractValue, String)>,
+    pure_call_results: HashMap<mir::Local, syntax_pos::Span>,
+    skipped_obligations: Vec<(ObligationClass, usize)>,
+    state_accesses: StateAccesses,
+    timed_out: bool,
+    unmodeled_call_results: HashSet<Path>,
+    unmodeled_condition: bool,
+    unproven_obligations: Vec<UnprovenObligation>,
+    unsafe_call_chain: Vec<String>,
     unwind_condition: Option<AbstractValue>,
+    unsolved_nonlinear_condition: bool,
+    untrusted_input_spans: Vec<syntax_pos::Span>,
     unwind_environment: Environment,
+    unwind_states: HashMap<mir::BasicBlock, Environment>,
+    used_locals: HashMap<mir::Local, HashSet<mir::BasicBlock>>,
 }
 
 /// A visitor that simply traverses enough of the MIR associated with a particular code body
@@ -69,6 +221,14 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
     pub fn new(
         crate_context: MirVisitorCrateContext<'a, 'b, 'tcx, E>,
     ) -> MirVisitor<'a, 'b, 'tcx, E> {
+        let summary_key = utils::summary_key_str(&crate_context.tcx, crate_context.def_id);
+        let precision_preset = crate_context.options.get_precision_preset(&summary_key);
+        let mut limits = crate_context.options.limits;
+        if let Some(overrides) =
+            utils::get_limits_attribute(crate_context.def_id, &crate_context.tcx)
+        {
+            overrides.apply_to(&mut limits);
+        }
         MirVisitor {
             buffered_diagnostics: crate_context.buffered_diagnostics,
             emit_diagnostic: crate_context.emit_diagnostic,
@@ -76,35 +236,122 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             tcx: crate_context.tcx,
             def_id: crate_context.def_id,
             mir: crate_context.mir,
+            options: crate_context.options,
             constant_value_cache: crate_context.constant_value_cache,
             summary_cache: crate_context.summary_cache,
             smt_solver: crate_context.smt_solver,
+            escalated_defs: crate_context.escalated_defs,
+            assumption_log: crate_context.assumption_log,
+            test_functions: crate_context.test_functions,
 
+            assumed_obligations: Vec::new(),
+            block_cache: HashMap::default(),
+            blocking_call_chain: Vec::new(),
+            callee_heap_addresses: HashMap::default(),
             check_for_errors: false,
+            const_eval_depth: 0,
+            counterexample: None,
+            counterexample_found: false,
             current_environment: Environment::default(),
             current_location: mir::Location::START,
             current_span: syntax_pos::DUMMY_SP,
+            deadline: None,
             exit_environment: Environment::default(),
+            fallible_call_results: HashMap::default(),
             heap_addresses: HashMap::default(),
+            limits,
+            loop_invariant_thresholds: Vec::new(),
+            may_drop_errors: false,
+            obligation_count: 0,
+            obligation_goal: abstract_value::TRUE,
             post_conditions: Vec::new(),
+            precision_factor: 1,
+            precision_limited_condition: false,
+            precision_limits: Vec::new(),
+            precision_preset,
             preconditions: Vec::new(),
+            pure_call_results: HashMap::default(),
+            skipped_obligations: Vec::new(),
+            state_accesses: StateAccesses::default(),
+            timed_out: false,
+            unmodeled_call_results: HashSet::default(),
+            unmodeled_condition: false,
+            unproven_obligations: Vec::new(),
+            unsafe_call_chain: Vec::new(),
+            unsolved_nonlinear_condition: false,
+            untrusted_input_spans: Vec::new(),
             unwind_condition: None,
             unwind_environment: Environment::default(),
+            unwind_states: HashMap::default(),
+            used_locals: HashMap::default(),
+        }
+    }
+
+    /// Returns true if diagnostics are reported in the function being analyzed, rather than being
+    /// turned into preconditions that its callers must satisfy. In a library crate, this is the
+    /// case for public functions, since their callers are not seen by the analysis. A binary crate
+    /// is analyzed as a whole program, starting from main, so only main reports diagnostics and
+    /// the findings in other functions surface at the calls that lead to them. When the crate is
+    /// compiled with --test, main is generated by the test harness and is not analyzed, so the
+    /// #[test] functions are the roots instead.
+    fn is_analysis_root(&self) -> bool {
+        if utils::is_trivial_wrapper(self.def_id, &self.tcx) {
+            // The obligations of a trivial wrapper are reported at its callers, where they arise.
+            return false;
+        }
+        // The body of an async fn is a generator that runs when the future returned by the
+        // function is polled. Since the future may be polled by any executor, the body is
+        // analyzed as a root if the function is, with no assumptions about when it resumes.
+        let def_id =
+            utils::get_function_of_generator(self.def_id, &self.tcx).unwrap_or(self.def_id);
+        if utils::is_annotated_entry_point(def_id, &self.tcx)
+            || utils::is_exported_to_c(def_id, &self.tcx)
+            || self.test_functions.contains(&def_id)
+        {
+            return true;
+        }
+        if utils::has_entry_point(&self.tcx) {
+            utils::is_entry_point(def_id, &self.tcx)
+        } else {
+            is_public(def_id, &self.tcx)
         }
     }
 
     /// Restores the method only state to its initial state.
     fn reset_visitor_state(&mut self) {
+        self.assumed_obligations = Vec::new();
+        self.block_cache = HashMap::default();
+        self.blocking_call_chain = Vec::new();
+        self.callee_heap_addresses = HashMap::default();
         self.check_for_errors = false;
+        self.counterexample = None;
+        self.counterexample_found = false;
         self.current_environment = Environment::default();
         self.current_location = mir::Location::START;
         self.current_span = syntax_pos::DUMMY_SP;
         self.exit_environment = Environment::default();
+        self.fallible_call_results = HashMap::default();
         self.heap_addresses = HashMap::default();
+        self.loop_invariant_thresholds = Vec::new();
+        self.may_drop_errors = false;
+        self.obligation_count = 0;
+        self.obligation_goal = abstract_value::TRUE;
         self.post_conditions = Vec::new();
+        self.precision_limited_condition = false;
+        self.precision_limits = Vec::new();
         self.preconditions = Vec::new();
+        self.pure_call_results = HashMap::default();
+        self.skipped_obligations = Vec::new();
+        self.state_accesses = StateAccesses::default();
+        self.unmodeled_call_results = HashSet::default();
+        self.unmodeled_condition = false;
+        self.unproven_obligations = Vec::new();
+        self.unsafe_call_chain = Vec::new();
+        self.untrusted_input_spans = Vec::new();
         self.unwind_condition = None;
         self.unwind_environment = Environment::default();
+        self.unwind_states = HashMap::default();
+        self.used_locals = HashMap::default();
     }
 
     /// Use the local and global environments to resolve Path to an abstract value.
@@ -114,9 +361,18 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         path: Path,
         result_type: ExpressionType,
     ) -> AbstractValue {
+        self.state_accesses.record_read(&path, self.mir.arg_count);
         let refined_val = {
             let bottom = abstract_value::BOTTOM;
-            let local_val = self.current_environment.value_at(&path).unwrap_or(&bottom);
+            let element_val;
+            let local_val = match self.current_environment.value_at(&path) {
+                Some(local_val) => local_val,
+                None => {
+                    // The path may select an array element that is summarized by other paths.
+                    element_val = self.current_environment.element_value_at(&path);
+                    element_val.as_ref().unwrap_or(&bottom)
+                }
+            };
             local_val.refine_with(&self.current_environment.entry_condition, self.current_span)
         };
         if refined_val.is_bottom() {
@@ -144,20 +400,35 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                     }
                     .into()
                 })
-            } else if path.path_length() < k_limits::MAX_PATH_LENGTH {
+            } else if path.path_length() < self.limits.max_path_length * self.precision_factor {
                 Expression::Variable {
                     path: box path.clone(),
                     var_type: result_type,
                 }
                 .into()
             } else {
+                self.record_precision_limit("the path is too long to track");
                 abstract_value::TOP
             }
+        } else if refined_val.domain.expression.size()
+            > self.limits.max_expression_size * self.precision_factor as u64
+        {
+            self.record_precision_limit("the expression is too large to track");
+            abstract_value::TOP
         } else {
             refined_val
         }
     }
 
+    /// Remembers that a value was replaced with Top at the current location because it exceeded
+    /// one of the k-limits, so that diagnostics that are caused by the loss of precision can say so.
+    fn record_precision_limit(&mut self, reason: &'static str) {
+        let span = self.current_span;
+        if !self.precision_limits.iter().any(|(s, _)| *s == span) {
+            self.precision_limits.push((span, reason));
+        }
+    }
+
     // Path is required to be a temporary used to track an operation result.
     fn get_target_path_type(&mut self, path: &Path) -> ExpressionType {
         match path {
@@ -184,6 +455,109 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
     /// that is already in the cache.
     pub fn visit_body(&mut self) -> Option<Summary> {
         debug!("visit_body({:?})", self.def_id);
+        self.deadline = self
+            .options
+            .per_body_timeout_ms
+            .map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms));
+        self.precision_factor = self.precision_preset.initial_precision_factor();
+        if self.escalated_defs.contains(&self.def_id) {
+            self.precision_factor = k_limits::PRECISION_ESCALATION_FACTOR;
+        }
+        // All diagnostics are held back until the body has been visited, so that the fingerprint
+        // of the summary can record whether there were any.
+        let emit_diagnostic = self.emit_diagnostic;
+        self.emit_diagnostic = Self::hold_back_diagnostic;
+        let diagnostics_start = self.buffered_diagnostics.len();
+        let mut diagnostics = self.analyze_body();
+        if !diagnostics.is_empty()
+            && self.precision_factor == 1
+            && self.precision_preset == PrecisionPreset::Standard
+            && self.escalated_defs.len() < k_limits::MAX_PRECISION_ESCALATIONS
+        {
+            // Some obligations could not be proven with the default limits. Before reporting them,
+            // see if they can be proven with elevated limits. The function keeps being analyzed with
+            // the elevated limits, so that its summary does not flip flop between the two.
+            debug!("reanalyzing {:?} with elevated precision", self.def_id);
+            self.escalated_defs.insert(self.def_id);
+            self.reset_visitor_state();
+            self.precision_factor = k_limits::PRECISION_ESCALATION_FACTOR;
+            diagnostics = self.analyze_body();
+        }
+        self.buffered_diagnostics.append(&mut diagnostics);
+        if self.timed_out {
+            self.report_incomplete_analysis();
+        } else {
+            self.report_skipped_obligations();
+            self.report_conflicting_contracts();
+            self.check_implementation_preconditions();
+            self.check_safety_contract_is_declared();
+            if self.options.check_determinism {
+                self.check_result_determinism();
+            }
+        }
+        self.emit_diagnostic = emit_diagnostic;
+        if let Some(obligations_directory) = &self.options.obligations_directory {
+            let summary_key = utils::summary_key_str(&self.tcx, self.def_id);
+            smt_lib::write_obligations(
+                obligations_directory,
+                &summary_key,
+                &self.unproven_obligations,
+            );
+        }
+        let diagnostics = self.buffered_diagnostics.split_off(diagnostics_start);
+        // A function whose obligations were assumed to hold is always analyzed again, rather than
+        // reusing its summary from a previous run, so that the assumptions are logged every time.
+        let has_diagnostics = !diagnostics.is_empty() || !self.assumed_obligations.is_empty();
+        self.assumption_log.append(&mut self.assumed_obligations);
+        for diagnostic in diagnostics {
+            let mut db = DiagnosticBuilder::new_diagnostic(self.session.diagnostic(), diagnostic);
+            (self.emit_diagnostic)(&mut db, &mut self.buffered_diagnostics);
+        }
+
+        // Now create a summary of the body that can be in-lined into call sites. If the analysis
+        // did not complete, nothing more is known about the function than its signature. Such a
+        // summary is neither persisted nor fingerprinted, so the next run analyzes the function
+        // again, perhaps with a larger budget, rather than reusing what little is known now.
+        let summary = if self.timed_out {
+            summaries::summarize_signature(&self.tcx, self.def_id)
+        } else {
+            summaries::summarize(
+                self.mir.arg_count,
+                &self.exit_environment,
+                &self.preconditions,
+                &self.post_conditions,
+                self.unwind_condition.clone(),
+                &self.unwind_environment,
+                &self.state_accesses,
+                self.may_drop_errors,
+                &self.blocking_call_chain,
+                &self.unsafe_call_chain,
+            )
+        };
+        let changed = {
+            let old_summary = self.summary_cache.get_summary_for(self.def_id, None);
+            summary != *old_summary
+        };
+        if self.timed_out {
+            let old_summary = self
+                .summary_cache
+                .set_incomplete_summary_for(self.def_id, summary);
+            return if changed { old_summary } else { None };
+        }
+        let old_summary_if_changed = if changed {
+            self.summary_cache.set_summary_for(self.def_id, summary)
+        } else {
+            None
+        };
+        self.summary_cache
+            .set_fingerprint_for(self.def_id, has_diagnostics);
+        old_summary_if_changed
+    }
+
+    /// Computes a fixed point for the body and then traverses it once more, checking for errors.
+    /// Returns the diagnostics that were found. They have not been emitted yet.
+    fn analyze_body(&mut self) -> Vec<Diagnostic> {
+        self.smt_solver.set_time_limit_factor(self.precision_factor);
         // in_state[bb] is the join (or widening) of the out_state values of each predecessor of bb
         let mut in_state: HashMap<mir::BasicBlock, Environment> = HashMap::new();
         // out_state[bb] is the environment that results from analyzing block bb, given in_state[bb]
@@ -194,509 +568,5575 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         }
         // The entry block has no predecessors and its initial state is the function parameters
         // as well any promoted constants.
-        let first_state = self.promote_constants();
+        let mut first_state = self.promote_constants();
+        self.assume_parameter_invariants(&mut first_state);
+        self.assume_safety_contract(&mut first_state);
+        self.assume_ffi_contract(&mut first_state);
 
         // Compute a fixed point, which is a value of out_state that will not grow with more iterations.
-        let mut changed = true;
-        let mut iteration_count = 0;
-        while changed {
-            changed = false;
-            for bb in self.mir.basic_blocks().indices() {
-                // Merge output states of predecessors of bb
-                let i_state = if bb.index() == 0 {
-                    first_state.clone()
-                } else {
-                    let mut predecessor_states_and_conditions: Vec<(
-                        &Environment,
-                        Option<&AbstractValue>,
-                    )> = self
-                        .mir
-                        .predecessors_for(bb)
-                        .iter()
-                        .map(|pred_bb| {
-                            let pred_state = &out_state[pred_bb];
-                            let pred_exit_condition = pred_state.exit_conditions.get(&bb);
-                            (pred_state, pred_exit_condition)
-                        })
-                        .filter(|(_, pred_exit_condition)| pred_exit_condition.is_some())
-                        .collect();
-                    if predecessor_states_and_conditions.is_empty() {
-                        // unreachable block
-                        let mut i_state = in_state[&bb].clone();
-                        i_state.entry_condition = abstract_value::FALSE;
-                        i_state
-                    } else {
-                        // We want to do right associative operations and that is easier if we reverse.
-                        predecessor_states_and_conditions.reverse();
-                        let (p_state, pred_exit_condition) = predecessor_states_and_conditions[0];
-                        let mut i_state = p_state.clone();
-                        i_state.entry_condition = pred_exit_condition
-                            .unwrap()
-                            .with_provenance(self.current_span);
-                        for (p_state, pred_exit_condition) in
-                            predecessor_states_and_conditions.iter().skip(1)
-                        {
-                            let join_condition = pred_exit_condition.unwrap();
-                            // Once all paths have already been analyzed for a second time (iteration_count >= 3)
-                            // we to abstract more aggressively in order to ensure reaching a fixed point.
-                            let mut j_state = if iteration_count < 3 {
-                                p_state.join(&i_state, join_condition)
-                            } else {
-                                p_state.widen(&i_state, join_condition)
-                            };
-                            j_state.entry_condition = join_condition
-                                .or(&i_state.entry_condition, Some(self.current_span));
-                            i_state = j_state;
-                        }
-                        i_state
-                    }
-                };
-                // Analyze the basic block.
-                in_state.insert(bb, i_state.clone());
-                self.current_environment = i_state;
-                self.visit_basic_block(bb);
-
-                // Check for a fixed point.
-                if !self.current_environment.subset(&out_state[&bb]) {
-                    // There is some path for which self.current_environment.value_at(path) includes
-                    // a value this is not present in out_state[bb].value_at(path), so any block
-                    // that used out_state[bb] as part of its input state now needs to get reanalyzed.
-                    out_state.insert(bb, self.current_environment.clone());
-                    changed = true;
-                } else {
-                    // If the environment at the end of this block does not have any new values,
-                    // we have reached a fixed point for this block.
-                }
-            }
-            iteration_count += 1;
-            if iteration_count > 50 {
-                println!("fixed point loop diverged");
-                break;
-            }
+        // The blocks are analyzed in a weak topological order, so that every loop is stabilized,
+        // innermost loops first, before the blocks that follow it are analyzed.
+        let components = weak_topological_order(self.mir);
+        // The intervals to which the counters of loops have been widened, by loop head.
+        let mut widened_intervals: HashMap<mir::BasicBlock, HashMap<Path, IntervalDomain>> =
+            HashMap::new();
+        self.visit_components(
+            &components,
+            &first_state,
+            &mut in_state,
+            &mut out_state,
+            &mut widened_intervals,
+        );
+        if self.timed_out {
+            return Vec::new();
         }
 
         // Now traverse the blocks again, doing checks and emitting diagnostics.
         // in_state[bb] is now complete for every basic block bb in the body.
-        debug!(
-            "Fixed point loop took {} iterations, now checking for errors.",
-            iteration_count
-        );
+        debug!("Fixed point loop is done, now checking for errors.");
+        self.plan_obligations();
         self.check_for_errors = true;
+        // Diagnostics are held back until it is known whether they should be reported.
+        let emit_diagnostic = self.emit_diagnostic;
+        self.emit_diagnostic = Self::hold_back_diagnostic;
+        let diagnostics_start = self.buffered_diagnostics.len();
         for bb in self.mir.basic_blocks().indices() {
+            if self.is_out_of_time() {
+                // The diagnostics found so far are dropped along with the rest of the analysis.
+                self.emit_diagnostic = emit_diagnostic;
+                self.buffered_diagnostics.truncate(diagnostics_start);
+                return Vec::new();
+            }
             let i_state = (&in_state[&bb]).clone();
             if i_state.entry_condition.as_bool_if_known().unwrap_or(true) {
                 self.current_environment = i_state;
                 self.visit_basic_block(bb);
             }
         }
-
-        // Now create a summary of the body that can be in-lined into call sites.
-        let summary = summaries::summarize(
-            self.mir.arg_count,
-            &self.exit_environment,
-            &self.preconditions,
-            &self.post_conditions,
-            self.unwind_condition.clone(),
-            &self.unwind_environment,
-        );
-        let changed = {
-            let old_summary = self.summary_cache.get_summary_for(self.def_id, None);
-            summary != *old_summary
-        };
-        if changed {
-            self.summary_cache.set_summary_for(self.def_id, summary)
+        self.report_unchecked_results();
+        self.report_unused_pure_results();
+        if self.is_derived_deserialize_method() {
+            // The code generated by #[derive(Deserialize)] is not something the programmer can
+            // fix, so diagnostics about it are suppressed. Its summaries are still useful.
+            self.buffered_diagnostics.truncate(diagnostics_start);
+        }
+        if let Some(invariant_def_id) = self.get_invariant_of_deserialized_type() {
+            self.check_deserialized_invariant(invariant_def_id);
         } else {
-            None
+            self.check_invariants_of_constructed_values();
         }
+        self.emit_diagnostic = emit_diagnostic;
+        self.buffered_diagnostics.split_off(diagnostics_start)
     }
 
-    /// Use the visitor to compute the state corresponding to promoted constants.
-    fn promote_constants(&mut self) -> Environment {
-        let mut state_with_parameters = Environment::default();
-        let saved_mir = self.mir;
-        let result_root = Path::LocalVariable { ordinal: 0 };
-        for (ordinal, constant_mir) in self.mir.promoted.iter().enumerate() {
-            self.mir = constant_mir;
-            let result_type = self.get_type_for_local(0);
-            self.visit_body();
-
-            let promoted_root = Path::PromotedConstant { ordinal };
-            let value = self.lookup_path_and_refine_result(result_root.clone(), result_type);
-            state_with_parameters.update_value_at(promoted_root.clone(), value);
-            for (path, value) in self
-                .exit_environment
-                .value_map
-                .iter()
-                .filter(|(p, _)| p.is_rooted_by(&result_root))
-            {
-                let promoted_path = path.replace_root(&result_root, promoted_root.clone());
-                state_with_parameters.update_value_at(promoted_path, value.clone());
+    /// Analyzes the blocks of the given components of the weak topological order of the body, in
+    /// order. The blocks of a loop are analyzed again and again, until their out states stop
+    /// changing, before the components that follow the loop are analyzed. The head of a loop is
+    /// where its states are widened, once the loop has been analyzed a few times.
+    /// Returns true if the out state of any of the blocks changed.
+    fn visit_components(
+        &mut self,
+        components: &[WtoComponent],
+        first_state: &Environment,
+        in_state: &mut HashMap<mir::BasicBlock, Environment>,
+        out_state: &mut HashMap<mir::BasicBlock, Environment>,
+        widened_intervals: &mut HashMap<mir::BasicBlock, HashMap<Path, IntervalDomain>>,
+    ) -> bool {
+        let mut changed = false;
+        for component in components.iter() {
+            if self.is_out_of_time() {
+                break;
+            }
+            match component {
+                WtoComponent::Vertex(bb) => {
+                    changed |= self.visit_block_for_fixed_point(
+                        *bb,
+                        false,
+                        first_state,
+                        in_state,
+                        out_state,
+                        widened_intervals,
+                    );
+                }
+                WtoComponent::Component { head, body } => {
+                    let max_join_iterations =
+                        self.limits.max_join_iterations * self.precision_factor;
+                    let mut iteration_count = 0;
+                    loop {
+                        if self.is_out_of_time() {
+                            break;
+                        }
+                        let widen = iteration_count >= max_join_iterations;
+                        let mut loop_changed = self.visit_block_for_fixed_point(
+                            *head,
+                            widen,
+                            first_state,
+                            in_state,
+                            out_state,
+                            widened_intervals,
+                        );
+                        loop_changed |= self.visit_components(
+                            body,
+                            first_state,
+                            in_state,
+                            out_state,
+                            widened_intervals,
+                        );
+                        if !loop_changed {
+                            break;
+                        }
+                        changed = true;
+                        iteration_count += 1;
+                        if iteration_count > self.limits.max_fixed_point_iterations {
+                            println!("fixed point loop diverged");
+                            break;
+                        }
+                    }
+                }
             }
-
-            self.reset_visitor_state();
         }
-        self.mir = saved_mir;
-        state_with_parameters
+        changed
     }
 
-    /// Visits each statement in order and then visits the terminator.
-    fn visit_basic_block(&mut self, bb: mir::BasicBlock) {
-        debug!("visit_basic_block({:?})", bb);
-
-        let mir::BasicBlockData {
-            ref statements,
-            ref terminator,
-            ..
-        } = &self.mir[bb];
-        let mut location = bb.start_location();
-        let terminator_index = statements.len();
-
-        while location.statement_index < terminator_index {
-            self.visit_statement(location, &statements[location.statement_index]);
-            location.statement_index += 1;
+    /// Joins the out states of the predecessors of bb into its in state and analyzes bb.
+    /// If widen is true, the out states are widened rather than joined, which makes the
+    /// fixed point loop converge when bb is the head of a loop.
+    /// Returns true if the out state of bb changed.
+    fn visit_block_for_fixed_point(
+        &mut self,
+        bb: mir::BasicBlock,
+        widen: bool,
+        first_state: &Environment,
+        in_state: &mut HashMap<mir::BasicBlock, Environment>,
+        out_state: &mut HashMap<mir::BasicBlock, Environment>,
+        widened_intervals: &mut HashMap<mir::BasicBlock, HashMap<Path, IntervalDomain>>,
+    ) -> bool {
+        // Merge output states of predecessors of bb
+        let i_state = if bb.index() == 0 {
+            first_state.clone()
+        } else {
+            let mut predecessor_states_and_conditions: Vec<(&Environment, Option<&AbstractValue>)> =
+                self.mir
+                    .predecessors_for(bb)
+                    .iter()
+                    .map(|pred_bb| {
+                        let pred_exit_condition = out_state[pred_bb].exit_conditions.get(&bb);
+                        // A cleanup block that is entered from a call that unwinds starts out
+                        // with the state of the caller at the time the callee unwound.
+                        let pred_state = if self.mir[bb].is_cleanup {
+                            self.unwind_states
+                                .get(pred_bb)
+                                .unwrap_or(&out_state[pred_bb])
+                        } else {
+                            &out_state[pred_bb]
+                        };
+                        (pred_state, pred_exit_condition)
+                    })
+                    .filter(|(_, pred_exit_condition)| pred_exit_condition.is_some())
+                    .collect();
+            if predecessor_states_and_conditions.is_empty() {
+                // unreachable block
+                let mut i_state = in_state[&bb].clone();
+                i_state.entry_condition = abstract_value::FALSE;
+                i_state
+            } else {
+                // We want to do right associative operations and that is easier if we reverse.
+                predecessor_states_and_conditions.reverse();
+                let (p_state, pred_exit_condition) = predecessor_states_and_conditions[0];
+                let mut i_state = p_state.clone();
+                i_state.entry_condition = pred_exit_condition
+                    .unwrap()
+                    .with_provenance(self.current_span);
+                for (p_state, pred_exit_condition) in
+                    predecessor_states_and_conditions.iter().skip(1)
+                {
+                    let join_condition = pred_exit_condition.unwrap();
+                    let mut j_state = if widen {
+                        p_state.widen(&i_state, join_condition)
+                    } else {
+                        p_state.join(&i_state, join_condition)
+                    };
+                    j_state.entry_condition =
+                        join_condition.or(&i_state.entry_condition, Some(self.current_span));
+                    i_state = j_state;
+                }
+                // The entry condition is simplified once, here, rather than whenever the solver
+                // is asked about it. It is made of the exit conditions of the predecessors, whose
+                // entry conditions have already been simplified, so only the rest is traversed.
+                i_state.entry_condition = i_state.entry_condition.simplify();
+                self.bound_accumulators(bb, &mut i_state, out_state);
+                if widen {
+                    self.widen_to_thresholds(
+                        bb,
+                        &mut i_state,
+                        out_state,
+                        widened_intervals.entry(bb).or_insert_with(HashMap::new),
+                    );
+                }
+                i_state
+            }
+        };
+        // Analyze the basic block, unless it has been analyzed with the same in state before.
+        // Large bodies, such as generated state machines, have many blocks that are analyzed again
+        // in every iteration of the loops that contain them, while their in states stay the same.
+        in_state.insert(bb, i_state.clone());
+        let in_state_fingerprint =
+            if self.mir.basic_blocks().len() >= k_limits::MIN_BLOCKS_FOR_BLOCK_CACHE {
+                Some(i_state.fingerprint())
+            } else {
+                None
+            };
+        let block_cache = &self.block_cache;
+        let cached_block = in_state_fingerprint.and_then(|fingerprint| {
+            block_cache.get(&bb).filter(|cached_block| {
+                cached_block.in_state_fingerprint == fingerprint && cached_block.in_state == i_state
+            })
+        });
+        if let Some(cached_block) = cached_block {
+            debug!("reusing the out state of {:?}", bb);
+            self.current_environment = cached_block.out_state.clone();
+            self.current_span = cached_block.span;
+        } else {
+            self.current_environment = i_state.clone();
+            self.visit_basic_block(bb);
+            self.current_environment.number_values();
+            if let Some(in_state_fingerprint) = in_state_fingerprint {
+                let cached_block = CachedBlock {
+                    in_state_fingerprint,
+                    in_state: i_state,
+                    out_state: self.current_environment.clone(),
+                    span: self.current_span,
+                };
+                self.block_cache.insert(bb, cached_block);
+            }
         }
 
-        if let Some(mir::Terminator {
-            ref source_info,
-            ref kind,
-        }) = *terminator
-        {
-            self.visit_terminator(*source_info, kind);
+        // Check for a fixed point.
+        if !self.current_environment.subset(&out_state[&bb]) {
+            // There is some path for which self.current_environment.value_at(path) includes
+            // a value this is not present in out_state[bb].value_at(path), so any block
+            // that used out_state[bb] as part of its input state now needs to get reanalyzed.
+            out_state.insert(bb, self.current_environment.clone());
+            true
+        } else {
+            // If the environment at the end of this block does not have any new values,
+            // we have reached a fixed point for this block.
+            false
         }
     }
 
-    /// Calls a specialized visitor for each kind of statement.
-    fn visit_statement(&mut self, location: mir::Location, statement: &mir::Statement<'tcx>) {
-        self.current_location = location;
-        let mir::Statement { kind, source_info } = statement;
-        debug!("{:?}", source_info);
-        self.current_span = source_info.span;
-        match kind {
-            mir::StatementKind::Assign(place, rvalue) => self.visit_assign(place, rvalue.borrow()),
-            mir::StatementKind::FakeRead(..) => unreachable!(),
-            mir::StatementKind::SetDiscriminant {
-                place,
-                variant_index,
-            } => self.visit_set_discriminant(place, *variant_index),
-            mir::StatementKind::StorageLive(local) => self.visit_storage_live(*local),
-            mir::StatementKind::StorageDead(local) => self.visit_storage_dead(*local),
-            mir::StatementKind::InlineAsm {
-                asm,
-                outputs,
-                inputs,
-            } => self.visit_inline_asm(asm, outputs, inputs),
-            mir::StatementKind::Retag(retag_kind, place) => self.visit_retag(*retag_kind, place),
-            mir::StatementKind::AscribeUserType(..) => unreachable!(),
-            mir::StatementKind::Nop => return,
+    /// If bb is the head of a loop that is entered along one edge and repeated along another,
+    /// bounds the values of local integer variables that accumulate values in the loop.
+    /// See Environment::bound_accumulators.
+    fn bound_accumulators(
+        &self,
+        bb: mir::BasicBlock,
+        i_state: &mut Environment,
+        out_state: &HashMap<mir::BasicBlock, Environment>,
+    ) {
+        if let Some((entry, entry_condition, back_edge, back_edge_condition)) =
+            self.get_loop_edges(bb, out_state)
+        {
+            let mir = self.mir;
+            i_state.bound_accumulators(
+                entry,
+                entry_condition,
+                back_edge,
+                back_edge_condition,
+                &|path| Self::get_integer_local_type(mir, path),
+            );
         }
     }
 
-    /// Write the RHS Rvalue to the LHS Place.
-    fn visit_assign(&mut self, place: &mir::Place<'tcx>, rvalue: &mir::Rvalue<'tcx>) {
-        debug!(
-            "default visit_assign(place: {:?}, rvalue: {:?})",
-            place, rvalue
-        );
-        let path = self.visit_place(place);
-        self.visit_rvalue(path, rvalue);
-    }
-
-    /// Write the discriminant for a variant to the enum Place.
-    fn visit_set_discriminant(
-        &mut self,
-        place: &mir::Place<'tcx>,
-        variant_index: rustc::ty::layout::VariantIdx,
+    /// If bb is the head of a loop that is entered along one edge and repeated along another,
+    /// widens the values of local integer variables that count in the loop to the nearest of the
+    /// widening thresholds of the options, or of the constant lengths of arrays.
+    /// See Environment::widen_to_thresholds.
+    fn widen_to_thresholds(
+        &self,
+        bb: mir::BasicBlock,
+        i_state: &mut Environment,
+        out_state: &HashMap<mir::BasicBlock, Environment>,
+        widened_intervals: &mut HashMap<Path, IntervalDomain>,
     ) {
-        debug!(
-            "default visit_set_discriminant(place: {:?}, variant_index: {:?})",
-            place, variant_index
-        );
-        let target_path = self.visit_place(place);
-        let index_val = self
-            .constant_value_cache
-            .get_u128_for(variant_index.as_usize() as u128)
-            .clone()
-            .into();
-        self.current_environment
-            .update_value_at(target_path, index_val);
+        if let Some((entry, entry_condition, back_edge, back_edge_condition)) =
+            self.get_loop_edges(bb, out_state)
+        {
+            let mut thresholds = self.options.widening_thresholds.clone();
+            thresholds.extend_from_slice(&self.loop_invariant_thresholds);
+            for (path, value) in i_state.value_map.iter() {
+                if let Path::QualifiedPath { selector, .. } = path {
+                    if **selector == PathSelector::ArrayLength {
+                        if let Some(length) = value.domain.as_constant_integer() {
+                            thresholds.push(length);
+                        }
+                    }
+                }
+            }
+            let mir = self.mir;
+            i_state.widen_to_thresholds(
+                entry,
+                entry_condition,
+                back_edge,
+                back_edge_condition,
+                &thresholds,
+                widened_intervals,
+                &|path| Self::get_integer_local_type(mir, path),
+            );
+        }
     }
 
-    /// Start a live range for the storage of the local.
-    fn visit_storage_live(&mut self, local: mir::Local) {
-        debug!("default visit_storage_live(local: {:?})", local);
-        let path = Path::LocalVariable {
-            ordinal: local.as_usize(),
+    /// If bb has two predecessors, one of which is dominated by bb, bb is the head of a loop.
+    /// Returns the states at the end of the edge that enters the loop and at the end of the back
+    /// edge, along with the conditions under which the edges are taken.
+    fn get_loop_edges<'s>(
+        &self,
+        bb: mir::BasicBlock,
+        out_state: &'s HashMap<mir::BasicBlock, Environment>,
+    ) -> Option<(
+        &'s Environment,
+        &'s AbstractValue,
+        &'s Environment,
+        &'s AbstractValue,
+    )> {
+        let predecessors = self.mir.predecessors_for(bb);
+        if predecessors.len() != 2 {
+            return None;
+        }
+        let dominators = self.mir.dominators();
+        let (entry_bb, back_edge_bb) = if dominators.is_dominated_by(predecessors[1], bb) {
+            (predecessors[0], predecessors[1])
+        } else if dominators.is_dominated_by(predecessors[0], bb) {
+            (predecessors[1], predecessors[0])
+        } else {
+            return None;
         };
-        self.current_environment
-            .update_value_at(path, abstract_value::TOP.clone());
+        let entry = &out_state[&entry_bb];
+        let back_edge = &out_state[&back_edge_bb];
+        match (
+            entry.exit_conditions.get(&bb),
+            back_edge.exit_conditions.get(&bb),
+        ) {
+            (Some(entry_condition), Some(back_edge_condition)) => {
+                Some((entry, entry_condition, back_edge, back_edge_condition))
+            }
+            _ => None,
+        }
     }
 
-    /// End the current live range for the storage of the local.
-    fn visit_storage_dead(&mut self, local: mir::Local) {
-        debug!("default visit_storage_dead(local: {:?})", local);
-        let path = Path::LocalVariable {
-            ordinal: local.as_usize(),
-        };
-        self.current_environment
-            .update_value_at(path, abstract_value::BOTTOM.clone());
+    /// Returns the type of the local variable at the given path, if it is a local variable, other
+    /// than a parameter, of an integer type.
+    fn get_integer_local_type(mir: &mir::Mir<'tcx>, path: &Path) -> Option<ExpressionType> {
+        if let Path::LocalVariable { ordinal } = path {
+            if *ordinal > mir.arg_count && *ordinal < mir.local_decls.len() {
+                let loc = &mir.local_decls[mir::Local::from(*ordinal)];
+                let var_type: ExpressionType = (&loc.ty.sty).into();
+                if var_type.is_integer() {
+                    return Some(var_type);
+                }
+            }
+        }
+        None
     }
 
-    /// Execute a piece of inline Assembly.
-    fn visit_inline_asm(
-        &mut self,
-        asm: &hir::InlineAsm,
-        outputs: &[mir::Place<'tcx>],
-        inputs: &[(syntax_pos::Span, mir::Operand<'tcx>)],
-    ) {
-        debug!(
-            "default visit_inline_asm(asm: {:?}, outputs: {:?}, inputs: {:?})",
-            asm, outputs, inputs
+    /// Counts the proof obligations of the body, by class. If there are more than the budget allows,
+    /// decides which classes of obligations to skip, so that the budget is spent on the most
+    /// important classes. Machine generated code and code that comes from macro expansions can
+    /// have thousands of obligations, which would otherwise starve the analysis of everything else.
+    fn plan_obligations(&mut self) {
+        let mut counts: HashMap<ObligationClass, usize> = HashMap::new();
+        for bb in self.mir.basic_blocks().indices() {
+            let mir::BasicBlockData {
+                ref statements,
+                ref terminator,
+                ..
+            } = &self.mir[bb];
+            if self.options.lint_wrapping_arithmetic || self.options.check_overflow {
+                for statement in statements.iter() {
+                    if let mir::StatementKind::Assign(_, rvalue) = &statement.kind {
+                        if let mir::Rvalue::BinaryOp(bin_op, ..) = rvalue.borrow() {
+                            match bin_op {
+                                mir::BinOp::Add
+                                | mir::BinOp::Mul
+                                | mir::BinOp::Shl
+                                | mir::BinOp::Shr
+                                | mir::BinOp::Sub => {
+                                    *counts.entry(ObligationClass::Overflow).or_insert(0) += 1;
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+            }
+            let obligation_class = match terminator.as_ref().map(|t| &t.kind) {
+                Some(mir::TerminatorKind::Assert { cond, msg, .. }) => {
+                    if let mir::Operand::Constant(..) = cond {
+                        continue;
+                    }
+                    Self::get_obligation_class(msg)
+                }
+                Some(mir::TerminatorKind::Call { func, .. }) => {
+                    let func_to_call = self.visit_operand(func);
+                    match self.get_known_function(&func_to_call) {
+                        Some(KnownFunction::CorePanickingPanic)
+                        | Some(KnownFunction::StdPanickingBeginPanic)
+                        | Some(KnownFunction::StdPanickingBeginPanicFmt) => {
+                            ObligationClass::Reachability
+                        }
+                        _ => ObligationClass::Contract,
+                    }
+                }
+                Some(mir::TerminatorKind::Unreachable) => ObligationClass::Reachability,
+                _ => continue,
+            };
+            *counts.entry(obligation_class).or_insert(0) += 1;
+        }
+        self.obligation_count = counts.values().sum();
+        let budget = self.get_obligation_budget();
+        if self.obligation_count <= budget || self.options.verify_no_panics {
+            // Skipped obligations are not proven, which would void the certification.
+            return;
+        }
+        let mut spent = 0;
+        for obligation_class in ObligationClass::BY_PRIORITY.iter() {
+            let count = counts.get(obligation_class).cloned().unwrap_or(0);
+            if spent + count <= budget {
+                spent += count;
+            } else {
+                self.skipped_obligations.push((*obligation_class, count));
+            }
+        }
+    }
+
+    /// The number of proof obligations of the function that are checked, as configured and scaled
+    /// by the precision preset of its module.
+    fn get_obligation_budget(&self) -> usize {
+        let budget = self
+            .options
+            .obligation_budget
+            .unwrap_or(k_limits::MAX_OBLIGATIONS_PER_FUNCTION);
+        self.precision_preset.scale_obligation_budget(budget)
+    }
+
+    /// True if obligations of the given class are not checked, because the function has more
+    /// obligations than the budget allows. Skipped obligations that would otherwise become
+    /// preconditions of the function still do (see push_skipped_obligation), so that the callers of
+    /// the function are held to them. The others are not checked at all.
+    fn is_skipped_obligation(&self, obligation_class: ObligationClass) -> bool {
+        self.skipped_obligations
+            .iter()
+            .any(|(skipped_class, _)| *skipped_class == obligation_class)
+    }
+
+    /// Makes the given condition, which must hold at the current location but is not checked
+    /// because its class of obligations is skipped, a precondition of the function.
+    fn push_skipped_obligation(&mut self, condition: &AbstractValue, message: &str) {
+        let precondition = self
+            .current_environment
+            .entry_condition
+            .not(None)
+            .or(condition, Some(self.current_span));
+        self.preconditions
+            .push((precondition, String::from(message)));
+    }
+
+    /// Says which classes of obligations were not checked because the function has more
+    /// obligations than the budget allows.
+    fn report_skipped_obligations(&mut self) {
+        if self.skipped_obligations.is_empty() || self.is_derived_deserialize_method() {
+            return;
+        }
+        let skipped: Vec<String> = self
+            .skipped_obligations
+            .iter()
+            .map(|(obligation_class, count)| {
+                format!("{} {}", count, obligation_class.description())
+            })
+            .collect();
+        let span = self.tcx.def_span(self.def_id);
+        let message = format!(
+            "this function has {} proof obligations, more than the budget of {}, so {} were skipped",
+            self.obligation_count,
+            self.get_obligation_budget(),
+            skipped.join(", ")
         );
-        if self.check_for_errors {
-            let span = self.current_span;
-            let mut err = self.session.struct_span_warn(
-                span,
-                "Inline assembly code cannot be analyzed by MIRAI. Unsoundly ignoring this.",
-            );
-            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        let mut warning = self.session.struct_span_warn(span, message.as_str());
+        warning.code(Confidence::LimitedPrecision.diagnostic_code());
+        warning.note(
+            "errors in this function may go unreported, since skipped obligations become preconditions at best",
+        );
+        (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+    }
+
+    /// Returns true if the analysis of the body has taken longer than the per body timeout of the
+    /// options. Once this happens, the analysis is abandoned (see report_incomplete_analysis).
+    fn is_out_of_time(&mut self) -> bool {
+        if !self.timed_out {
+            if let Some(deadline) = self.deadline {
+                self.timed_out = Instant::now() >= deadline;
+            }
         }
+        self.timed_out
     }
 
-    /// Retag references in the given place, ensuring they got fresh tags.  This is
-    /// part of the Stacked Borrows model. These statements are currently only interpreted
-    /// by miri and only generated when "-Z mir-emit-retag" is passed.
-    /// See <https://internals.rust-lang.org/t/stacked-borrows-an-aliasing-model-for-rust/8153/>
-    /// for more details.
-    fn visit_retag(&self, retag_kind: mir::RetagKind, place: &mir::Place<'tcx>) {
-        debug!(
-            "default visit_retag(retag_kind: {:?}, place: {:?})",
-            retag_kind, place
+    /// Reports that the analysis of the body was abandoned because it took longer than the per
+    /// body timeout. The function gets a summary derived from its signature, as if it had no body,
+    /// so that the functions that call it can still be analyzed.
+    fn report_incomplete_analysis(&mut self) {
+        self.assumed_obligations = Vec::new();
+        self.unproven_obligations = Vec::new();
+        let span = self.tcx.def_span(self.def_id);
+        let message = format!(
+            "analysis incomplete: this function could not be analyzed within {} ms, so nothing is known about what it does",
+            self.options.per_body_timeout_ms.unwrap_or_default()
         );
-        // This seems to be an intermediate artifact of MIR generation and is related to aliasing.
-        // We assume (and will attempt to enforce) that no aliasing of mutable pointers are present
-        // in the programs we check.
-        //
-        // Therefore we simply ignore this.
+        let mut warning = self.session.struct_span_warn(span, message.as_str());
+        warning.code(Confidence::LimitedPrecision.diagnostic_code());
+        (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
     }
 
-    /// Calls a specialized visitor for each kind of terminator.
-    fn visit_terminator(&mut self, source_info: mir::SourceInfo, kind: &mir::TerminatorKind<'tcx>) {
-        debug!("{:?}", source_info);
-        self.current_span = source_info.span;
-        match kind {
-            mir::TerminatorKind::Goto { target } => self.visit_goto(*target),
-            mir::TerminatorKind::SwitchInt {
-                discr,
-                switch_ty,
-                values,
-                targets,
-            } => self.visit_switch_int(discr, switch_ty, values, targets),
-            mir::TerminatorKind::Resume => self.visit_resume(),
-            mir::TerminatorKind::Abort => self.visit_abort(),
-            mir::TerminatorKind::Return => self.visit_return(),
-            mir::TerminatorKind::Unreachable => self.visit_unreachable(),
-            mir::TerminatorKind::Drop {
-                location,
-                target,
-                unwind,
-            } => self.visit_drop(location, *target, *unwind),
-            mir::TerminatorKind::DropAndReplace { .. } => unreachable!(),
-            mir::TerminatorKind::Call {
-                func,
-                args,
-                destination,
-                cleanup,
-                from_hir_call,
-            } => self.visit_call(func, args, destination, *cleanup, *from_hir_call),
-            mir::TerminatorKind::Assert {
-                cond,
-                expected,
-                msg,
-                target,
-                cleanup,
-            } => self.visit_assert(cond, *expected, msg, *target, *cleanup),
-            mir::TerminatorKind::Yield { .. } => unreachable!(),
-            mir::TerminatorKind::GeneratorDrop => unreachable!(),
-            mir::TerminatorKind::FalseEdges { .. } => unreachable!(),
-            mir::TerminatorKind::FalseUnwind { .. } => unreachable!(),
+    /// Buffers a copy of the diagnostic and cancels the original, so that it can be emitted later.
+    fn hold_back_diagnostic(db: &mut DiagnosticBuilder<'_>, buf: &mut Vec<Diagnostic>) {
+        db.clone().buffer(buf);
+        db.cancel();
+    }
+
+    /// If the given type is, or refers to, a struct that declares an invariant and whose fields
+    /// are not public, returns the def_id of the invariant. An invariant is declared by an
+    /// inherent method of the type that is named invariant, takes &self and returns a bool.
+    /// Since values of such a type can only be created and modified by the code of the module
+    /// that defines it, the invariant can be checked there and relied upon everywhere else.
+    fn get_invariant_of_type(&self, ty: Ty<'tcx>) -> Option<hir::def_id::DefId> {
+        match &ty.sty {
+            TyKind::Ref(_, referenced_type, _) => self.get_invariant_of_type(referenced_type),
+            TyKind::Adt(adt_def, _) if adt_def.is_struct() => {
+                if adt_def
+                    .non_enum_variant()
+                    .fields
+                    .iter()
+                    .any(|field| field.vis == ty::Visibility::Public)
+                {
+                    return None;
+                }
+                self.get_declared_invariant(adt_def.did)
+            }
+            _ => None,
         }
     }
 
-    /// block should have one successor in the graph; we jump there
-    fn visit_goto(&mut self, target: mir::BasicBlock) {
-        debug!("default visit_goto(local: {:?})", target);
-        // Propagate the entry condition to the successor block.
-        self.current_environment
-            .exit_conditions
-            .insert(target, self.current_environment.entry_condition.clone());
+    /// Returns the def_id of the inherent method named invariant of the given type, if any.
+    fn get_declared_invariant(
+        &self,
+        type_def_id: hir::def_id::DefId,
+    ) -> Option<hir::def_id::DefId> {
+        for inherent_impl_def_id in self.tcx.inherent_impls(type_def_id).iter() {
+            for item_def_id in self
+                .tcx
+                .associated_item_def_ids(*inherent_impl_def_id)
+                .iter()
+            {
+                if &*self.tcx.item_name(*item_def_id).as_str() == "invariant" {
+                    return Some(*item_def_id);
+                }
+            }
+        }
+        None
     }
 
-    /// `discr` evaluates to an integer; jump depending on its value
-    /// to one of the targets, and otherwise fallback to last element of `targets`.
-    ///
-    /// # Arguments
-    /// * `discr` - Discriminant value being tested
-    /// * `switch_ty` - type of value being tested
-    /// * `values` - Possible values. The locations to branch to in each case
-    /// are found in the corresponding indices from the `targets` vector.
-    /// * `targets` - Possible branch sites. The last element of this vector is used
-    /// for the otherwise branch, so targets.len() == values.len() + 1 should hold.
-    fn visit_switch_int(
+    /// Returns the def_id of the predicate that the #[mirai::safety_requires(predicate)] attribute
+    /// of the given function names, if the function has such a safety contract and the predicate
+    /// is a function of the same module or impl. The predicate takes the same parameters as the
+    /// function and returns true if it is safe to call the function with them.
+    fn get_safety_predicate(&self, def_id: hir::def_id::DefId) -> Option<hir::def_id::DefId> {
+        let predicate_name = utils::get_safety_contract(def_id, &self.tcx)?;
+        let parent = self.tcx.parent(def_id);
+        self.tcx.body_owners().find(|owner_def_id| {
+            self.tcx.parent(*owner_def_id) == parent
+                && *self.tcx.item_name(*owner_def_id).as_str() == *predicate_name
+        })
+    }
+
+    /// Returns the result of the safety predicate of the function to call, in terms of the
+    /// parameters of that function, if the function is an unsafe function of the current crate
+    /// with a safety contract.
+    fn get_safety_contract_of_callee(
         &mut self,
-        discr: &mir::Operand<'tcx>,
-        switch_ty: rustc::ty::Ty<'tcx>,
-        values: &[u128],
-        targets: &[mir::BasicBlock],
-    ) {
-        debug!(
-            "default visit_switch_int(discr: {:?}, switch_ty: {:?}, values: {:?}, targets: {:?})",
-            discr, switch_ty, values, targets
-        );
-        let mut default_exit_condition = self.current_environment.entry_condition.clone();
-        let discr = self.visit_operand(discr);
-        let discr = discr.as_int_if_known().unwrap_or(discr);
-        for i in 0..values.len() {
-            let val: AbstractValue = ConstantDomain::U128(values[i]).into();
-            let cond = discr.equals(&val, None);
-            let not_cond = cond.not(None);
-            default_exit_condition = default_exit_condition.and(&not_cond, None);
-            let target = targets[i];
-            self.current_environment
-                .exit_conditions
-                .insert(target, cond);
+        func_to_call: &AbstractValue,
+    ) -> Option<AbstractValue> {
+        let def_id = match func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id: Some(def_id),
+                ..
+            }) if def_id.is_local() => def_id,
+            _ => return None,
+        };
+        let predicate_def_id = self.get_safety_predicate(def_id)?;
+        self.summary_cache
+            .get_summary_for(predicate_def_id, Some(self.def_id))
+            .result
+            .clone()
+    }
+
+    /// The callers of an unsafe function with a safety contract have to satisfy it (see
+    /// get_safety_contract_of_callee), so the body of the function can assume it on entry.
+    fn assume_safety_contract(&mut self, first_state: &mut Environment) {
+        let predicate_def_id = match self.get_safety_predicate(self.def_id) {
+            Some(predicate_def_id) if predicate_def_id != self.def_id => predicate_def_id,
+            _ => return,
+        };
+        if let Some(result) = &self
+            .summary_cache
+            .get_summary_for(predicate_def_id, Some(self.def_id))
+            .result
+        {
+            // The predicate has the same parameters as the function being analyzed.
+            let contract = result.refine_paths(first_state);
+            first_state.entry_condition = first_state.entry_condition.and(&contract, None);
         }
-        self.current_environment
-            .exit_conditions
-            .insert(targets[values.len()], default_exit_condition);
     }
 
-    /// Indicates that the landing pad is finished and unwinding should
-    /// continue. Emitted by build::scope::diverge_cleanup.
-    fn visit_resume(&self) {
-        debug!("default visit_resume()");
+    /// A function that is exported to C can be called by any C code, so its arguments are untrusted
+    /// input and only the ABI contract of the function, if the options declare one, limits their
+    /// values: the pointers that the contract declares to be non null, or to point to buffers, are
+    /// assumed to be non null on entry.
+    fn assume_ffi_contract(&mut self, first_state: &mut Environment) {
+        if !utils::is_exported_to_c(self.def_id, &self.tcx) {
+            return;
+        }
+        let span = self.tcx.def_span(self.def_id);
+        for ordinal in 1..=self.mir.arg_count {
+            let tag_path = Self::get_tag_path(Path::LocalVariable { ordinal }, Tag::Untrusted);
+            first_state.update_value_at(tag_path, abstract_value::TRUE.with_provenance(span));
+        }
+        let contract = match self.get_ffi_contract() {
+            Some(contract) => contract.clone(),
+            None => return,
+        };
+        let non_null_pointers = contract
+            .non_null
+            .iter()
+            .chain(contract.buffers.iter().map(|(pointer, _)| pointer));
+        for name in non_null_pointers {
+            if let Some(ordinal) = self.get_parameter_ordinal(name) {
+                let pointer: AbstractValue = Expression::Variable {
+                    path: box Path::LocalVariable { ordinal },
+                    var_type: ExpressionType::NonPrimitive,
+                }
+                .into();
+                let zero: AbstractValue = ConstantDomain::U128(0).into();
+                let non_null = pointer.equals(&zero, None).not(None);
+                first_state.entry_condition = first_state.entry_condition.and(&non_null, None);
+            }
+        }
     }
 
-    /// Indicates that the landing pad is finished and that the process
-    /// should abort. Used to prevent unwinding for foreign items.
-    fn visit_abort(&self) {
-        debug!("default visit_abort()");
+    /// Returns the ABI contract that the options declare for the function being analyzed, if any.
+    fn get_ffi_contract(&self) -> Option<&FfiContract> {
+        let name = self.tcx.item_name(self.def_id).to_string();
+        self.options.ffi_contracts.get(&name)
     }
 
-    /// Indicates a normal return. The return place should have been filled in by now.
-    fn visit_return(&mut self) {
-        debug!("default visit_return()");
-        if self.check_for_errors {
-            // Done with fixed point, so prepare to summarize.
-            let return_guard = self.current_environment.entry_condition.as_bool_if_known();
-            if return_guard.unwrap_or(false) {
-                self.exit_environment = self.current_environment.clone();
-            } else if return_guard.unwrap_or(true) {
-                self.exit_environment = self.current_environment.join(
-                    &self.exit_environment,
-                    &self.current_environment.entry_condition,
-                );
-            }
+    /// Returns the ordinal of the parameter with the given name, if there is one.
+    fn get_parameter_ordinal(&self, name: &str) -> Option<usize> {
+        (1..=self.mir.arg_count).find(|ordinal| {
+            self.mir.local_decls[mir::Local::from(*ordinal)]
+                .name
+                .map_or(false, |local_name| local_name.to_string() == name)
+        })
+    }
+
+    /// Checks that a pointer, which the function being analyzed dereferences, is not null, if the
+    /// function is exported to C and the pointer is derived from its arguments. C callers may pass null pointers, so the function must check for them, unless
+    /// its ABI contract rules them out. Dereferencing a null pointer is undefined behavior.
+    fn check_ffi_pointer_is_not_null(&mut self, pointer: &AbstractValue, message: &str) {
+        debug_assert!(self.check_for_errors);
+        if !utils::is_exported_to_c(self.def_id, &self.tcx) {
+            return;
+        }
+        let is_derived_from_argument = (1..=self.mir.arg_count).any(|ordinal| {
+            pointer
+                .domain
+                .expression
+                .refers_to_variable_rooted_by(&Path::LocalVariable { ordinal })
+        });
+        if !is_derived_from_argument {
+            return;
         }
+        let zero: AbstractValue = ConstantDomain::U128(0).into();
+        let non_null = pointer.equals(&zero, None).not(None);
+        let refined_condition = non_null
+            .refine_paths(&mut self.current_environment)
+            .refine_with(&self.current_environment.entry_condition, self.current_span);
+        self.check_required_condition(&refined_condition, message);
     }
 
-    /// Indicates a terminator that can never be reached.
-    fn visit_unreachable(&mut self) {
-        debug!("default visit_unreachable()");
-        // Complain if we are quite sure control gets here.
-        if self.check_for_errors {
-            let mut entry_cond_as_bool =
-                self.current_environment.entry_condition.as_bool_if_known();
-            if entry_cond_as_bool.is_none() {
-                let entry_cond_val = &self.current_environment.entry_condition.clone();
-                entry_cond_as_bool = self.solve_condition(entry_cond_val);
+    /// Checks that the length given to slice::from_raw_parts does not exceed the length of the
+    /// buffer that the pointer points to, if the pointer is a parameter of a function that is
+    /// exported to C and the ABI contract of the function declares the length of the buffer.
+    fn check_ffi_buffer_length(&mut self, pointer: &AbstractValue, length: &AbstractValue) {
+        debug_assert!(self.check_for_errors);
+        let pointer_ordinal = match &pointer.domain.expression {
+            Expression::Variable { path, .. } => match **path {
+                Path::LocalVariable { ordinal } => ordinal,
+                _ => return,
+            },
+            _ => return,
+        };
+        let contract = match self.get_ffi_contract() {
+            Some(contract) if utils::is_exported_to_c(self.def_id, &self.tcx) => contract.clone(),
+            _ => return,
+        };
+        for (pointer_name, length_name) in contract.buffers.iter() {
+            if self.get_parameter_ordinal(pointer_name) != Some(pointer_ordinal) {
+                continue;
             }
-            if entry_cond_as_bool.unwrap_or(false) {
-                let span = self.current_span;
-                let mut err = self
-                    .session
-                    .struct_span_warn(span, "Execution might panic.");
-                (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
-            } else {
-                self.preconditions.push((
-                    self.current_environment
-                        .entry_condition
-                        .not(Some(self.current_span)),
-                    String::from("Execution might panic."),
-                ));
+            if let Some(ordinal) = self.get_parameter_ordinal(length_name) {
+                let mut buffer_length: AbstractValue = Expression::Variable {
+                    path: box Path::LocalVariable { ordinal },
+                    var_type: ExpressionType::Usize,
+                }
+                .into();
+                let refined_condition = length
+                    .clone()
+                    .less_or_equal(&mut buffer_length, None)
+                    .refine_paths(&mut self.current_environment)
+                    .refine_with(&self.current_environment.entry_condition, self.current_span);
+                self.check_required_condition(
+                    &refined_condition,
+                    "the slice extends beyond the buffer that the pointer points to",
+                );
             }
         }
     }
 
-    /// Drop the Place
-    fn visit_drop(
-        &mut self,
-        location: &mir::Place<'tcx>,
-        target: mir::BasicBlock,
-        unwind: Option<mir::BasicBlock>,
-    ) {
-        debug!(
-            "default visit_drop(location: {:?}, target: {:?}, unwind: {:?})",
-            location, target, unwind
-        );
-        // Propagate the entry condition to the successor blocks.
-        self.current_environment
-            .exit_conditions
-            .insert(target, self.current_environment.entry_condition.clone());
-        if let Some(unwind_target) = unwind {
-            self.current_environment.exit_conditions.insert(
-                unwind_target,
-                self.current_environment.entry_condition.clone(),
-            );
+    /// The "# Safety" section of the documentation of a public unsafe function says what its
+    /// callers must ensure, but cannot be checked. Complains about public unsafe functions without
+    /// a machine readable safety contract, as well as about contracts whose predicates cannot be
+    /// found, so that the contracts of all of the public unsafe functions can be checked.
+    fn check_safety_contract_is_declared(&mut self) {
+        if !is_public(self.def_id, &self.tcx)
+            || self.tcx.fn_sig(self.def_id).unsafety() != hir::Unsafety::Unsafe
+        {
+            return;
         }
+        let message = match utils::get_safety_contract(self.def_id, &self.tcx) {
+            None => "public unsafe function without a safety contract, declare one with #[mirai::safety_requires(predicate)]",
+            Some(_) if self.get_safety_predicate(self.def_id).is_none() => {
+                "the predicate of the safety contract is not a function of the same module or impl"
+            }
+            Some(_) => return,
+        };
+        let span = self.tcx.def_span(self.def_id);
+        let mut warning = self.session.struct_span_warn(span, message);
+        warning.code(Confidence::Specification.diagnostic_code());
+        (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
     }
 
-    /// Block ends with the call of a function.
-    ///
-    /// #Arguments
-    /// * `func` - The function that’s being called
-    /// * `args` - Arguments the function is called with.
-    /// These are owned by the callee, which is free to modify them.
-    /// This allows the memory occupied by "by-value" arguments to be
-    /// reused across function calls without duplicating the contents.
-    /// * `destination` - Destination for the return value. If some, the call returns a value.
-    /// * `cleanup` - Cleanups to be done if the call unwinds.
-    /// * `from_hir_call` - Whether this is from a call in HIR, rather than from an overloaded
-    /// operator. True for overloaded function call.
-    fn visit_call(
+    /// If the function being analyzed is the deserialize method of an implementation of
+    /// serde::Deserialize and the type being deserialized declares an invariant, return
+    /// the def_id of the invariant.
+    fn get_invariant_of_deserialized_type(&self) -> Option<hir::def_id::DefId> {
+        if !self.is_deserialize_method() {
+            return None;
+        }
+        let impl_def_id = self.tcx.impl_of_method(self.def_id)?;
+        if let TyKind::Adt(adt_def, _) = self.tcx.type_of(impl_def_id).sty {
+            return self.get_declared_invariant(adt_def.did);
+        }
+        None
+    }
+
+    /// True if the function being analyzed is the deserialize method of an implementation of
+    /// serde::Deserialize.
+    fn is_deserialize_method(&self) -> bool {
+        let trait_def_id = match self.tcx.impl_of_method(self.def_id) {
+            Some(impl_def_id) => match self.tcx.trait_id_of_impl(impl_def_id) {
+                Some(trait_def_id) => trait_def_id,
+                None => return false,
+            },
+            None => return false,
+        };
+        let trait_key = utils::summary_key_str(&self.tcx, trait_def_id);
+        (trait_key == SERDE_DESERIALIZE_TRAIT
+            || trait_key.ends_with(&format!(".{}", SERDE_DESERIALIZE_TRAIT)))
+            && &*self.tcx.item_name(self.def_id).as_str() == "deserialize"
+    }
+
+    /// True if the function being analyzed is the deserialize method of an implementation of
+    /// serde::Deserialize that was generated by #[derive(Deserialize)].
+    fn is_derived_deserialize_method(&self) -> bool {
+        self.is_deserialize_method() && utils::is_automatically_derived(self.def_id, &self.tcx)
+    }
+
+    /// The values of types with invariants (see get_invariant_of_type) are created and modified
+    /// by the inherent methods of the types. If the function being analyzed is such a method,
+    /// checks that the values it returns, or modifies via a &mut parameter, satisfy the invariant.
+    fn check_invariants_of_constructed_values(&mut self) {
+        let impl_def_id = match self.tcx.impl_of_method(self.def_id) {
+            Some(impl_def_id) if self.tcx.trait_id_of_impl(impl_def_id).is_none() => impl_def_id,
+            _ => return,
+        };
+        let self_type = self.tcx.type_of(impl_def_id);
+        let invariant_def_id = match self.get_invariant_of_type(self_type) {
+            Some(invariant_def_id) if invariant_def_id != self.def_id => invariant_def_id,
+            _ => return,
+        };
+        let result_root = Path::LocalVariable { ordinal: 0 };
+        let return_type = self.mir.local_decls[mir::RETURN_PLACE].ty;
+        let (value_path, variant) = match &return_type.sty {
+            _ if return_type == self_type => (Some(result_root), None),
+            TyKind::Adt(def, substs) if substs.types().next() == Some(self_type) => {
+                // Some(value) and Ok(value) carry the constructed value.
+                let variant = match utils::summary_key_str(&self.tcx, def.did).as_str() {
+                    "core.option.Option" => Some(1),
+                    "core.result.Result" => Some(0),
+                    _ => None,
+                };
+                let value_path = variant.map(|variant| {
+                    let variant_path = Path::QualifiedPath {
+                        length: 2,
+                        qualifier: box result_root,
+                        selector: box PathSelector::Downcast(variant),
+                    };
+                    Path::QualifiedPath {
+                        length: 3,
+                        qualifier: box variant_path,
+                        selector: box PathSelector::Field(0),
+                    }
+                });
+                (value_path, variant.map(|variant| variant as u128))
+            }
+            _ => (None, None),
+        };
+        if let Some(value_path) = value_path {
+            self.check_invariant_on_exit(
+                invariant_def_id,
+                value_path,
+                variant,
+                "constructed value might not satisfy the invariant of its type",
+            );
+        }
+        for ordinal in 1..=self.mir.arg_count {
+            let parameter_type = self.mir.local_decls[mir::Local::from(ordinal)].ty;
+            if let TyKind::Ref(_, referenced_type, hir::MutMutable) = parameter_type.sty {
+                if referenced_type == self_type {
+                    self.check_invariant_on_exit(
+                        invariant_def_id,
+                        Path::LocalVariable { ordinal },
+                        None,
+                        "modified value might not satisfy the invariant of its type",
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that every normal return from a deserialize method that returns Ok(value) is
+    /// guaranteed to return a value that satisfies the invariant of its type. Complains if this
+    /// cannot be proved. Either way, the invariant becomes a post condition of the method, so that
+    /// callers may assume that a successfully deserialized value satisfies its invariant.
+    fn check_deserialized_invariant(&mut self, invariant_def_id: hir::def_id::DefId) {
+        let ok_variant = Path::QualifiedPath {
+            length: 2,
+            qualifier: box Path::LocalVariable { ordinal: 0 },
+            selector: box PathSelector::Downcast(0),
+        };
+        let ok_value = Path::QualifiedPath {
+            length: 3,
+            qualifier: box ok_variant,
+            selector: box PathSelector::Field(0),
+        };
+        self.check_invariant_on_exit(
+            invariant_def_id,
+            ok_value,
+            Some(0),
+            "deserialized value might not satisfy the invariant of its type",
+        );
+    }
+
+    /// Checks that every normal return from the function being analyzed leaves a value at the
+    /// given path that satisfies the given invariant, provided that the result of the function is
+    /// the given variant, if any. Complains with the given message if this cannot be proved.
+    /// Either way, this becomes a post condition of the function, so that callers may rely on it.
+    fn check_invariant_on_exit(
+        &mut self,
+        invariant_def_id: hir::def_id::DefId,
+        value_path: Path,
+        variant: Option<u128>,
+        message: &str,
+    ) {
+        if self.is_skipped_obligation(ObligationClass::Contract) {
+            return;
+        }
+        let invariant = if let Some(result) = &self
+            .summary_cache
+            .get_summary_for(invariant_def_id, Some(self.def_id))
+            .result
+        {
+            result.clone()
+        } else {
+            // The summary of the invariant is not (yet) known.
+            return;
+        };
+        self.current_environment = self.exit_environment.clone();
+        let other_variant = if let Some(variant) = variant {
+            let result_root = Path::LocalVariable { ordinal: 0 };
+            let discriminant =
+                self.lookup_path_and_refine_result(result_root, ExpressionType::NonPrimitive);
+            let discriminant = discriminant.as_int_if_known().unwrap_or(discriminant);
+            let variant_discriminant: AbstractValue = ConstantDomain::U128(variant).into();
+            discriminant.equals(&variant_discriminant, None).not(None)
+        } else {
+            abstract_value::FALSE
+        };
+        let refined_invariant = invariant
+            .refine_parameters(&[Expression::Reference(value_path).into()])
+            .refine_paths(&mut self.current_environment)
+            .refine_with(&self.current_environment.entry_condition, self.current_span);
+        let post_condition = other_variant.or(&refined_invariant, None);
+        let (post_condition_as_bool, entry_cond_as_bool) =
+            self.check_condition_value_and_reachability(&post_condition, ObligationClass::Contract);
+        if !post_condition_as_bool.unwrap_or(false) && entry_cond_as_bool.unwrap_or(true) {
+            let span = self.tcx.def_span(self.def_id);
+            let mut err = self.session.struct_span_warn(span, message);
+            let related_spans: HashSet<&syntax_pos::Span> =
+                HashSet::from_iter(refined_invariant.provenance.iter());
+            for related_span in related_spans.iter() {
+                err.span_note(**related_span, "related location");
+            }
+            let definite = post_condition_as_bool.is_some() && entry_cond_as_bool.unwrap_or(false);
+            self.set_confidence(&mut err, definite);
+            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        }
+        self.post_conditions.push(post_condition);
+    }
+
+    /// Complains about contracts that can never be satisfied: preconditions of the function being
+    /// analyzed that are each satisfiable but contradict each other, so that no caller can call
+    /// the function without a failure, and declared invariants that no value satisfies, so that
+    /// no value of the type can be constructed. Such diagnostics are about the specification
+    /// rather than the code, since otherwise they show up as failures to verify the callers.
+    fn report_conflicting_contracts(&mut self) {
+        if self.preconditions.len() > 1 {
+            let preconditions = self.preconditions.clone();
+            let each_satisfiable = preconditions
+                .iter()
+                .all(|(precondition, _)| !self.is_unsatisfiable(precondition));
+            let conjunction = preconditions
+                .iter()
+                .fold(abstract_value::TRUE, |conjunction, (precondition, _)| {
+                    conjunction.and(precondition, None)
+                });
+            if each_satisfiable && self.is_unsatisfiable(&conjunction) {
+                let span = self.tcx.def_span(self.def_id);
+                let mut err = self.session.struct_span_warn(
+                    span,
+                    "the preconditions of this function contradict each other, so no caller can satisfy them",
+                );
+                let mut related_spans: Vec<syntax_pos::Span> = conjunction
+                    .provenance
+                    .iter()
+                    .map(|related_span| related_span.source_callsite())
+                    .filter(|related_span| !self.is_in_library_code(*related_span))
+                    .collect::<HashSet<syntax_pos::Span>>()
+                    .into_iter()
+                    .collect();
+                related_spans.sort();
+                for related_span in related_spans.into_iter() {
+                    err.span_note(related_span, "related location");
+                }
+                err.code(Confidence::Specification.diagnostic_code());
+                (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+            }
+        }
+        if self.is_declared_invariant() {
+            let result_path = Path::LocalVariable { ordinal: 0 };
+            if let Some(result) = self.exit_environment.value_at(&result_path).cloned() {
+                let entry_condition = self.exit_environment.entry_condition.clone();
+                let exit_condition = entry_condition.and(&result, None);
+                if !self.is_unsatisfiable(&entry_condition)
+                    && self.is_unsatisfiable(&exit_condition)
+                {
+                    let span = self.tcx.def_span(self.def_id);
+                    let mut err = self.session.struct_span_warn(
+                        span,
+                        "no value satisfies this invariant, so no value of its type can be constructed",
+                    );
+                    err.code(Confidence::Specification.diagnostic_code());
+                    (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+                }
+            }
+        }
+    }
+
+    /// Generic functions call trait methods with the summaries of the trait methods (see
+    /// resolve_trait_method), so if the function being analyzed implements a method of a trait
+    /// of the current crate, its preconditions must follow from those of the trait method.
+    /// Complains about the preconditions that do not, since generic callers have no reason to
+    /// satisfy them. Only trait methods with default bodies declare contracts, by way of the
+    /// preconditions of the bodies. A default body without preconditions accepts every argument,
+    /// so every precondition of an implementation of it is complained about.
+    fn check_implementation_preconditions(&mut self) {
+        let trait_method_def_id = match self.get_implemented_trait_method() {
+            Some(def_id) => def_id,
+            None => return,
+        };
+        if self.preconditions.is_empty() || self.is_skipped_obligation(ObligationClass::Contract) {
+            return;
+        }
+        if !self.tcx.is_mir_available(trait_method_def_id) {
+            // Without a default body, the trait method declares no contract to check against.
+            return;
+        }
+        let trait_method_summary = self
+            .summary_cache
+            .get_summary_for(trait_method_def_id, Some(self.def_id));
+        let trait_preconditions = trait_method_summary
+            .preconditions
+            .iter()
+            .fold(abstract_value::TRUE, |conjunction, (precondition, _)| {
+                conjunction.and(precondition, None)
+            });
+        for (precondition, message) in self.preconditions.clone().iter() {
+            let counter_example = trait_preconditions.and(&precondition.not(None), None);
+            if self.is_unsatisfiable(&counter_example) {
+                continue;
+            }
+            let span = self.tcx.def_span(self.def_id);
+            let warning = format!(
+                "the implementation requires more than the trait method it implements: {}",
+                message
+            );
+            let mut err = self.session.struct_span_warn(span, warning.as_str());
+            let mut related_spans: Vec<syntax_pos::Span> = precondition
+                .provenance
+                .iter()
+                .map(|related_span| related_span.source_callsite())
+                .filter(|related_span| !self.is_in_library_code(*related_span))
+                .collect::<HashSet<syntax_pos::Span>>()
+                .into_iter()
+                .collect();
+            related_spans.sort();
+            for related_span in related_spans.into_iter() {
+                err.span_note(related_span, "related location");
+            }
+            let confidence = if trait_preconditions.as_bool_if_known() == Some(true)
+                && precondition.as_bool_if_known() == Some(false)
+            {
+                Confidence::Definite
+            } else {
+                Confidence::LimitedPrecision
+            };
+            err.code(confidence.diagnostic_code());
+            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        }
+    }
+
+    /// If the function being analyzed is a method of an implementation of a trait of the current
+    /// crate, returns the def_id of the trait method that it implements.
+    fn get_implemented_trait_method(&self) -> Option<hir::def_id::DefId> {
+        let impl_def_id = self.tcx.impl_of_method(self.def_id)?;
+        let trait_def_id = self.tcx.trait_id_of_impl(impl_def_id)?;
+        if !trait_def_id.is_local() {
+            return None;
+        }
+        let name = self.tcx.item_name(self.def_id);
+        self.tcx
+            .associated_item_def_ids(trait_def_id)
+            .iter()
+            .find(|item_def_id| self.tcx.item_name(**item_def_id) == name)
+            .cloned()
+    }
+
+    /// Returns true if the function being analyzed is the declared invariant of its type
+    /// (see get_declared_invariant).
+    fn is_declared_invariant(&self) -> bool {
+        if let Some(impl_def_id) = self.tcx.impl_of_method(self.def_id) {
+            if let TyKind::Adt(adt_def, _) = self.tcx.type_of(impl_def_id).sty {
+                return self.get_declared_invariant(adt_def.did) == Some(self.def_id);
+            }
+        }
+        false
+    }
+
+    /// Returns true if the condition is known to be false, either because the abstract domains
+    /// say so or because the solver finds that it cannot be satisfied.
+    fn is_unsatisfiable(&mut self, condition: &AbstractValue) -> bool {
+        if let Some(value) = condition.as_bool_if_known() {
+            return !value;
+        }
+        if !self.may_send_to_solver(condition) {
+            return false;
+        }
+        self.use_solver_strategy(ObligationClass::Contract);
+        let smt_expr = self
+            .smt_solver
+            .get_as_smt_predicate(&condition.domain.expression);
+        self.smt_solver.solve_expression(&smt_expr) == SmtResult::Unsatisfiable
+    }
+
+    /// Since the values of types with invariants (see get_invariant_of_type) are checked to
+    /// satisfy their invariants when they are created or modified, the invariants of the types of
+    /// the parameters of the function being analyzed can be assumed on entry to the function.
+    /// This way the property checked by the constructor of a type such as `struct Idx(usize)` need
+    /// not be proved again wherever an Idx is used.
+    fn assume_parameter_invariants(&mut self, first_state: &mut Environment) {
+        for ordinal in 1..=self.mir.arg_count {
+            let parameter_type = self.mir.local_decls[mir::Local::from(ordinal)].ty;
+            let invariant_def_id = match self.get_invariant_of_type(parameter_type) {
+                Some(invariant_def_id) if invariant_def_id != self.def_id => invariant_def_id,
+                _ => continue,
+            };
+            let invariant = if let Some(result) = &self
+                .summary_cache
+                .get_summary_for(invariant_def_id, Some(self.def_id))
+                .result
+            {
+                result.clone()
+            } else {
+                continue;
+            };
+            let parameter: AbstractValue =
+                Expression::Reference(Path::LocalVariable { ordinal }).into();
+            let refined_invariant = invariant
+                .refine_parameters(&[parameter])
+                .refine_paths(first_state);
+            first_state.entry_condition = first_state.entry_condition.and(&refined_invariant, None);
+        }
+    }
+
+    /// Complains about the results of calls to fallible functions that are not looked at on every
+    /// path from the call to the return of the function, for example because the calls are
+    /// statements by themselves or because their results are bound to _. Public functions get a
+    /// diagnostic for each such call. Other functions record the fact in their summaries, so that
+    /// the diagnostic can be given at the call sites in public functions that can reach them.
+    fn report_unchecked_results(&mut self) {
+        debug_assert!(self.check_for_errors);
+        let mut unchecked_results: Vec<(mir::Local, syntax_pos::Span)> = self
+            .fallible_call_results
+            .iter()
+            .filter(|(local, (_, target))| self.is_unused_on_some_path(**local, *target))
+            .map(|(local, (span, _))| (*local, *span))
+            .collect();
+        if unchecked_results.is_empty() {
+            return;
+        }
+        if !self.is_analysis_root() {
+            self.may_drop_errors = true;
+            return;
+        }
+        unchecked_results.sort_by_key(|(local, _)| *local);
+        for (_, span) in unchecked_results.into_iter() {
+            let mut err = self.struct_span_warn_for_user(
+                span,
+                "the result of this call is dropped without being checked",
+            );
+            err.code(Confidence::Definite.diagnostic_code());
+            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        }
+    }
+
+    /// True if a path that does not unwind leads from the start of the given block to a return
+    /// from the function without passing through a block in which the value of the given local
+    /// variable is used.
+    fn is_unused_on_some_path(&self, local: mir::Local, start: mir::BasicBlock) -> bool {
+        let used_in = self.used_locals.get(&local);
+        let mut visited: HashSet<mir::BasicBlock> = HashSet::new();
+        let mut blocks_to_visit = vec![start];
+        while let Some(bb) = blocks_to_visit.pop() {
+            if !visited.insert(bb) || used_in.map_or(false, |blocks| blocks.contains(&bb)) {
+                continue;
+            }
+            let terminator = self.mir[bb].terminator();
+            match &terminator.kind {
+                mir::TerminatorKind::Return => return true,
+                mir::TerminatorKind::Call { destination, .. } => {
+                    if let Some((_, target)) = destination {
+                        blocks_to_visit.push(*target);
+                    }
+                }
+                mir::TerminatorKind::Drop { target, .. }
+                | mir::TerminatorKind::Assert { target, .. } => blocks_to_visit.push(*target),
+                mir::TerminatorKind::Yield { resume, .. } => blocks_to_visit.push(*resume),
+                mir::TerminatorKind::Resume | mir::TerminatorKind::Abort => {}
+                _ => blocks_to_visit.extend(terminator.successors().cloned()),
+            }
+        }
+        false
+    }
+
+    /// Use the visitor to compute the state corresponding to promoted constants.
+    fn promote_constants(&mut self) -> Environment {
+        let mut state_with_parameters = Environment::default();
+        let saved_mir = self.mir;
+        let result_root = Path::LocalVariable { ordinal: 0 };
+        for (ordinal, constant_mir) in self.mir.promoted.iter().enumerate() {
+            self.mir = constant_mir;
+            let result_type = self.get_type_for_local(0);
+            self.visit_body();
+
+            let promoted_root = Path::PromotedConstant { ordinal };
+            let value = self.lookup_path_and_refine_result(result_root.clone(), result_type);
+            state_with_parameters.update_value_at(promoted_root.clone(), value);
+            for (path, value) in self
+                .exit_environment
+                .value_map
+                .iter()
+                .filter(|(p, _)| p.is_rooted_by(&result_root))
+            {
+                let promoted_path = path.replace_root(&result_root, promoted_root.clone());
+                state_with_parameters.update_value_at(promoted_path, value.clone());
+            }
+
+            self.reset_visitor_state();
+        }
+        self.mir = saved_mir;
+        state_with_parameters
+    }
+
+    /// Visits each statement in order and then visits the terminator.
+    fn visit_basic_block(&mut self, bb: mir::BasicBlock) {
+        debug!("visit_basic_block({:?})", bb);
+
+        let mir::BasicBlockData {
+            ref statements,
+            ref terminator,
+            ..
+        } = &self.mir[bb];
+        let mut location = bb.start_location();
+        let terminator_index = statements.len();
+
+        while location.statement_index < terminator_index {
+            self.visit_statement(location, &statements[location.statement_index]);
+            location.statement_index += 1;
+        }
+        self.current_location = location;
+
+        if let Some(mir::Terminator {
+            ref source_info,
+            ref kind,
+        }) = *terminator
+        {
+            self.visit_terminator(*source_info, kind);
+        }
+    }
+
+    /// Calls a specialized visitor for each kind of statement.
+    fn visit_statement(&mut self, location: mir::Location, statement: &mir::Statement<'tcx>) {
+        self.current_location = location;
+        let mir::Statement { kind, source_info } = statement;
+        debug!("{:?}", source_info);
+        self.current_span = source_info.span;
+        match kind {
+            // Unsafe operations, other than calls, show up as assignments or inline assembly.
+            mir::StatementKind::Assign(..) | mir::StatementKind::InlineAsm { .. }
+                if self.check_for_errors =>
+            {
+                self.record_unsafe_operation(source_info.scope)
+            }
+            _ => {}
+        }
+        match kind {
+            mir::StatementKind::Assign(place, rvalue) => self.visit_assign(place, rvalue.borrow()),
+            mir::StatementKind::FakeRead(..) => unreachable!(),
+            mir::StatementKind::SetDiscriminant {
+                place,
+                variant_index,
+            } => self.visit_set_discriminant(place, *variant_index),
+            mir::StatementKind::StorageLive(local) => self.visit_storage_live(*local),
+            mir::StatementKind::StorageDead(local) => self.visit_storage_dead(*local),
+            mir::StatementKind::InlineAsm {
+                asm,
+                outputs,
+                inputs,
+            } => self.visit_inline_asm(asm, outputs, inputs),
+            mir::StatementKind::Retag(retag_kind, place) => self.visit_retag(*retag_kind, place),
+            mir::StatementKind::AscribeUserType(..) => unreachable!(),
+            mir::StatementKind::Nop => return,
+        }
+    }
+
+    /// Write the RHS Rvalue to the LHS Place.
+    fn visit_assign(&mut self, place: &mir::Place<'tcx>, rvalue: &mir::Rvalue<'tcx>) {
+        debug!(
+            "default visit_assign(place: {:?}, rvalue: {:?})",
+            place, rvalue
+        );
+        let path = self.visit_place(place);
+        self.visit_rvalue(path.clone(), rvalue);
+        self.forget_strong_counts_of_escaping_pointers(&path);
+    }
+
+    /// Write the discriminant for a variant to the enum Place.
+    fn visit_set_discriminant(
+        &mut self,
+        place: &mir::Place<'tcx>,
+        variant_index: rustc::ty::layout::VariantIdx,
+    ) {
+        debug!(
+            "default visit_set_discriminant(place: {:?}, variant_index: {:?})",
+            place, variant_index
+        );
+        let target_path = self.visit_place(place);
+        let index_val = self
+            .constant_value_cache
+            .get_u128_for(variant_index.as_usize() as u128)
+            .clone()
+            .into();
+        self.current_environment
+            .update_value_at(target_path, index_val);
+    }
+
+    /// Start a live range for the storage of the local.
+    fn visit_storage_live(&mut self, local: mir::Local) {
+        debug!("default visit_storage_live(local: {:?})", local);
+        let path = Path::LocalVariable {
+            ordinal: local.as_usize(),
+        };
+        self.current_environment
+            .update_value_at(path, abstract_value::TOP.clone());
+    }
+
+    /// End the current live range for the storage of the local.
+    fn visit_storage_dead(&mut self, local: mir::Local) {
+        debug!("default visit_storage_dead(local: {:?})", local);
+        let path = Path::LocalVariable {
+            ordinal: local.as_usize(),
+        };
+        self.current_environment
+            .update_value_at(path, abstract_value::BOTTOM.clone());
+    }
+
+    /// Execute a piece of inline Assembly.
+    fn visit_inline_asm(
+        &mut self,
+        asm: &hir::InlineAsm,
+        outputs: &[mir::Place<'tcx>],
+        inputs: &[(syntax_pos::Span, mir::Operand<'tcx>)],
+    ) {
+        debug!(
+            "default visit_inline_asm(asm: {:?}, outputs: {:?}, inputs: {:?})",
+            asm, outputs, inputs
+        );
+        if self.check_for_errors {
+            let span = self.current_span;
+            let mut err = self.struct_span_warn_for_user(
+                span,
+                "Inline assembly code cannot be analyzed by MIRAI. Unsoundly ignoring this.",
+            );
+            err.code(Confidence::LimitedPrecision.diagnostic_code());
+            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        }
+    }
+
+    /// Retag references in the given place, ensuring they got fresh tags.  This is
+    /// part of the Stacked Borrows model. These statements are currently only interpreted
+    /// by miri and only generated when "-Z mir-emit-retag" is passed.
+    /// See <https://internals.rust-lang.org/t/stacked-borrows-an-aliasing-model-for-rust/8153/>
+    /// for more details.
+    fn visit_retag(&self, retag_kind: mir::RetagKind, place: &mir::Place<'tcx>) {
+        debug!(
+            "default visit_retag(retag_kind: {:?}, place: {:?})",
+            retag_kind, place
+        );
+        // This seems to be an intermediate artifact of MIR generation and is related to aliasing.
+        // We assume (and will attempt to enforce) that no aliasing of mutable pointers are present
+        // in the programs we check.
+        //
+        // Therefore we simply ignore this.
+    }
+
+    /// Calls a specialized visitor for each kind of terminator.
+    fn visit_terminator(&mut self, source_info: mir::SourceInfo, kind: &mir::TerminatorKind<'tcx>) {
+        debug!("{:?}", source_info);
+        self.current_span = source_info.span;
+        match kind {
+            mir::TerminatorKind::Goto { target } => self.visit_goto(*target),
+            mir::TerminatorKind::SwitchInt {
+                discr,
+                switch_ty,
+                values,
+                targets,
+            } => self.visit_switch_int(discr, switch_ty, values, targets),
+            mir::TerminatorKind::Resume => self.visit_resume(),
+            mir::TerminatorKind::Abort => self.visit_abort(),
+            mir::TerminatorKind::Return => self.visit_return(),
+            mir::TerminatorKind::Unreachable => self.visit_unreachable(),
+            mir::TerminatorKind::Drop {
+                location,
+                target,
+                unwind,
+            } => self.visit_drop(location, *target, *unwind),
+            mir::TerminatorKind::DropAndReplace { .. } => unreachable!(),
+            mir::TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                cleanup,
+                from_hir_call,
+            } => self.visit_call(func, args, destination, *cleanup, *from_hir_call),
+            mir::TerminatorKind::Assert {
+                cond,
+                expected,
+                msg,
+                target,
+                cleanup,
+            } => self.visit_assert(cond, *expected, msg, *target, *cleanup),
+            mir::TerminatorKind::Yield {
+                value,
+                resume,
+                drop,
+            } => self.visit_yield(value, *resume, *drop),
+            mir::TerminatorKind::GeneratorDrop => unreachable!(),
+            mir::TerminatorKind::FalseEdges { .. } => unreachable!(),
+            mir::TerminatorKind::FalseUnwind { .. } => unreachable!(),
+        }
+    }
+
+    /// block should have one successor in the graph; we jump there
+    fn visit_goto(&mut self, target: mir::BasicBlock) {
+        debug!("default visit_goto(local: {:?})", target);
+        // Propagate the entry condition to the successor block.
+        self.current_environment
+            .exit_conditions
+            .insert(target, self.current_environment.entry_condition.clone());
+    }
+
+    /// `discr` evaluates to an integer; jump depending on its value
+    /// to one of the targets, and otherwise fallback to last element of `targets`.
+    ///
+    /// # Arguments
+    /// * `discr` - Discriminant value being tested
+    /// * `switch_ty` - type of value being tested
+    /// * `values` - Possible values. The locations to branch to in each case
+    /// are found in the corresponding indices from the `targets` vector.
+    /// * `targets` - Possible branch sites. The last element of this vector is used
+    /// for the otherwise branch, so targets.len() == values.len() + 1 should hold.
+    fn visit_switch_int(
+        &mut self,
+        discr: &mir::Operand<'tcx>,
+        switch_ty: rustc::ty::Ty<'tcx>,
+        values: &[u128],
+        targets: &[mir::BasicBlock],
+    ) {
+        debug!(
+            "default visit_switch_int(discr: {:?}, switch_ty: {:?}, values: {:?}, targets: {:?})",
+            discr, switch_ty, values, targets
+        );
+        let mut default_exit_condition = self.current_environment.entry_condition.clone();
+        let discr = self.visit_operand(discr);
+        let discr = discr.as_int_if_known().unwrap_or(discr);
+        for i in 0..values.len() {
+            let val: AbstractValue = ConstantDomain::U128(values[i]).into();
+            let cond = discr.equals(&val, None);
+            let not_cond = cond.not(None);
+            default_exit_condition = default_exit_condition.and(&not_cond, None);
+            let target = targets[i];
+            self.current_environment
+                .exit_conditions
+                .insert(target, cond);
+        }
+        self.current_environment
+            .exit_conditions
+            .insert(targets[values.len()], default_exit_condition);
+    }
+
+    /// Indicates that the landing pad is finished and unwinding should
+    /// continue. Emitted by build::scope::diverge_cleanup.
+    fn visit_resume(&mut self) {
+        debug!("default visit_resume()");
+        if self.check_for_errors {
+            // Done with fixed point, so the state at this point is what the caller sees
+            // when this function unwinds.
+            let resume_guard = self.current_environment.entry_condition.as_bool_if_known();
+            if resume_guard.unwrap_or(false) {
+                self.unwind_environment = self.current_environment.clone();
+            } else if resume_guard.unwrap_or(true) {
+                self.unwind_environment = self.current_environment.join(
+                    &self.unwind_environment,
+                    &self.current_environment.entry_condition,
+                );
+            }
+        }
+    }
+
+    /// Indicates that the landing pad is finished and that the process
+    /// should abort. Used to prevent unwinding for foreign items.
+    fn visit_abort(&self) {
+        debug!("default visit_abort()");
+    }
+
+    /// Indicates a normal return. The return place should have been filled in by now.
+    fn visit_return(&mut self) {
+        debug!("default visit_return()");
+        if self.check_for_errors {
+            // Done with fixed point, so prepare to summarize.
+            let return_guard = self.current_environment.entry_condition.as_bool_if_known();
+            if return_guard.unwrap_or(false) {
+                self.exit_environment = self.current_environment.clone();
+            } else if return_guard.unwrap_or(true) {
+                self.exit_environment = self.current_environment.join(
+                    &self.exit_environment,
+                    &self.current_environment.entry_condition,
+                );
+            }
+        }
+    }
+
+    /// Indicates a terminator that can never be reached.
+    fn visit_unreachable(&mut self) {
+        debug!("default visit_unreachable()");
+        // Complain if we are quite sure control gets here.
+        if self.check_for_errors && !self.is_skipped_obligation(ObligationClass::Reachability) {
+            self.start_reachability_obligation();
+            let mut entry_cond_as_bool =
+                self.current_environment.entry_condition.as_bool_if_known();
+            if entry_cond_as_bool.is_none() {
+                let entry_cond_val = &self.current_environment.entry_condition.clone();
+                self.use_solver_strategy(ObligationClass::Reachability);
+                entry_cond_as_bool = self.solve_condition(entry_cond_val);
+            }
+            if entry_cond_as_bool.unwrap_or(false) {
+                let span = self.current_span;
+                let mut err = self.struct_span_warn_for_user(span, "Execution might panic.");
+                self.set_confidence(&mut err, false);
+                self.emit_unproven_condition_diagnostic(&mut err);
+            } else {
+                self.preconditions.push((
+                    self.current_environment
+                        .entry_condition
+                        .not(Some(self.current_span)),
+                    String::from("Execution might panic."),
+                ));
+            }
+        }
+    }
+
+    /// Drop the Place
+    fn visit_drop(
+        &mut self,
+        location: &mir::Place<'tcx>,
+        target: mir::BasicBlock,
+        unwind: Option<mir::BasicBlock>,
+    ) {
+        debug!(
+            "default visit_drop(location: {:?}, target: {:?}, unwind: {:?})",
+            location, target, unwind
+        );
+        self.drop_channel_receiver(location);
+        self.drop_shared_pointer(location);
+        // Propagate the entry condition to the successor blocks.
+        self.current_environment
+            .exit_conditions
+            .insert(target, self.current_environment.entry_condition.clone());
+        if let Some(unwind_target) = unwind {
+            self.current_environment.exit_conditions.insert(
+                unwind_target,
+                self.current_environment.entry_condition.clone(),
+            );
+        }
+    }
+
+    /// Block ends with the call of a function.
+    ///
+    /// #Arguments
+    /// * `func` - The function that’s being called
+    /// * `args` - Arguments the function is called with.
+    /// These are owned by the callee, which is free to modify them.
+    /// This allows the memory occupied by "by-value" arguments to be
+    /// reused across function calls without duplicating the contents.
+    /// * `destination` - Destination for the return value. If some, the call returns a value.
+    /// * `cleanup` - Cleanups to be done if the call unwinds.
+    /// * `from_hir_call` - Whether this is from a call in HIR, rather than from an overloaded
+    /// operator. True for overloaded function call.
+    fn visit_call(
+        &mut self,
+        func: &mir::Operand<'tcx>,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+        cleanup: Option<mir::BasicBlock>,
+        from_hir_call: bool,
+    ) {
+        debug!("default visit_call(func: {:?}, args: {:?}, destination: {:?}, cleanup: {:?}, from_hir_call: {:?})", func, args, destination, cleanup, from_hir_call);
+        let func_to_call = self.visit_operand(func);
+        let actual_args: Vec<AbstractValue> =
+            args.iter().map(|arg| self.visit_argument(arg)).collect();
+        let known_function = self.get_known_function(&func_to_call);
+        let trust_level = self.get_trust_level(&func_to_call);
+        let trusted_function = self.get_trusted_function(&func_to_call);
+        let mut function_summary = self.get_function_summary(&func_to_call, args, trust_level);
+        self.renumber_callee_heap_addresses(&mut function_summary);
+        let callee_is_closure = match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id: Some(def_id),
+                ..
+            }) => self.tcx.is_closure(*def_id),
+            _ => false,
+        };
+        if trusted_function.is_some() {
+            // The configuration vouches for the function, whatever its summary says.
+            function_summary.preconditions.clear();
+            function_summary.unwind_condition = Some(abstract_value::FALSE);
+        }
+        self.state_accesses.record_call(&function_summary);
+        if self.check_for_errors {
+            if let Some(trusted_function) = trusted_function {
+                self.record_trusted_call(&func_to_call, trusted_function);
+            }
+            if trust_level != Some(TrustLevel::Trusted) {
+                self.check_function_preconditions(&func_to_call, &actual_args, &function_summary);
+            }
+            if self.options.verify_no_panics
+                && trust_level != Some(TrustLevel::Trusted)
+                && trusted_function.is_none()
+                && known_function.is_none()
+                && self.is_unmodeled_function(&func_to_call)
+            {
+                self.check_unanalyzed_call(&func_to_call);
+            } else if trust_level == Some(TrustLevel::Verified)
+                && trusted_function.is_none()
+                && known_function.is_none()
+                && self.is_unmodeled_function(&func_to_call)
+            {
+                let span = self.current_span;
+                let mut warning = self.struct_span_warn_for_user(
+                    span,
+                    "the called function belongs to a crate that must be verified, but it has not been analyzed",
+                );
+                warning.code(Confidence::MissingModels.diagnostic_code());
+                (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+            }
+            if let Some((precondition, message)) =
+                known_function.and_then(Self::get_unchecked_precondition)
+            {
+                self.check_unchecked_precondition(&precondition, message, &actual_args);
+            }
+            if let Some(safety_contract) = self.get_safety_contract_of_callee(&func_to_call) {
+                self.check_unchecked_precondition(
+                    &safety_contract,
+                    "the safety contract of the unsafe function is not satisfied",
+                    &actual_args,
+                );
+            }
+            if self.options.check_determinism && Self::is_output(&func_to_call, known_function) {
+                self.check_output_determinism(&actual_args);
+            }
+            if let Some(sink) = self.get_sink(&func_to_call) {
+                self.check_sink_argument(sink, &actual_args);
+            }
+            if let Some(model) = self.get_function_model(&func_to_call) {
+                self.check_model_preconditions(model, args, &actual_args);
+            }
+            self.check_for_dropped_errors(destination, &function_summary);
+            self.check_for_blocking_call(&func_to_call, known_function, &function_summary);
+            self.record_unsafe_call(&func_to_call, trust_level, &function_summary);
+            self.record_pure_call_result(&func_to_call, destination, &function_summary);
+            if let Some(unwind_condition) = &function_summary.unwind_condition {
+                self.record_unwind_condition(&unwind_condition.refine_parameters(&actual_args));
+            }
+        }
+        let pre_call_environment = if cleanup.is_some() {
+            Some(self.current_environment.clone())
+        } else {
+            None
+        };
+        self.transfer_and_refine_normal_return_state(
+            destination,
+            args,
+            &actual_args,
+            &function_summary,
+            callee_is_closure,
+        );
+        if let (Some(TrustLevel::Untrusted), Some((place, _))) = (trust_level, destination) {
+            // The result of an untrusted function is untrusted input.
+            let target_path = self.visit_place(place);
+            let tag_path = Self::get_tag_path(target_path, Tag::Untrusted);
+            self.current_environment.update_value_at(
+                tag_path,
+                abstract_value::TRUE.with_provenance(self.current_span),
+            );
+        }
+        if let Some(sanitizer) = self.get_sanitizer(&func_to_call) {
+            self.model_sanitizer(sanitizer, &actual_args, destination);
+        }
+        if let Some(model) = self.get_function_model(&func_to_call) {
+            self.assume_model_postconditions(model, args, &actual_args, destination);
+        }
+        if let Some((place, _)) = destination {
+            let mut modeled = known_function.is_some();
+            if !modeled {
+                if let Some(result) = self.evaluate_const_fn_call(&func_to_call, &actual_args) {
+                    let target_path = self.visit_place(place);
+                    self.current_environment
+                        .update_value_at(target_path, result.with_provenance(self.current_span));
+                    modeled = true;
+                }
+            }
+            if !modeled {
+                modeled = self.model_fixed_point_operation(&func_to_call, args, place);
+            }
+            if !modeled {
+                // The results of total functions are meaningful even if they are not modeled.
+                modeled =
+                    trusted_function.map_or(false, |trusted| trusted.guarantee == Guarantee::Total);
+            }
+            if !modeled && self.is_unmodeled_function(&func_to_call) {
+                let target_path = self.visit_place(place);
+                self.unmodeled_call_results.insert(target_path);
+            }
+        }
+        let nondeterminism_tags = self.get_nondeterminism_tags(&actual_args);
+        match known_function {
+            Some(known_function @ KnownFunction::AllocCollectionsClear)
+            | Some(known_function @ KnownFunction::AllocCollectionsContains)
+            | Some(known_function @ KnownFunction::AllocCollectionsInsert)
+            | Some(known_function @ KnownFunction::AllocCollectionsIsEmpty)
+            | Some(known_function @ KnownFunction::AllocCollectionsLen)
+            | Some(known_function @ KnownFunction::AllocCollectionsNew)
+            | Some(known_function @ KnownFunction::AllocCollectionsPeek)
+            | Some(known_function @ KnownFunction::AllocCollectionsPop)
+            | Some(known_function @ KnownFunction::AllocCollectionsPush)
+            | Some(known_function @ KnownFunction::AllocCollectionsRemove)
+            | Some(known_function @ KnownFunction::AllocMapGet)
+            | Some(known_function @ KnownFunction::AllocMapInsert)
+            | Some(known_function @ KnownFunction::AllocMapRemove) => {
+                self.model_collection_operation(known_function, args, &actual_args, destination);
+            }
+            Some(KnownFunction::ByteorderRead) => {
+                self.model_byteorder_read(func, args, &actual_args, destination);
+            }
+            Some(KnownFunction::ByteorderWrite) => {
+                self.model_byteorder_write(func, args, &actual_args);
+            }
+            Some(KnownFunction::CoreAsciiCaseConversion) => {
+                self.model_ascii_case_conversion(&func_to_call, args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreAsciiPredicate) => {
+                self.model_ascii_predicate(&func_to_call, args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreFmtFormat) => {
+                self.model_formatting_call(&func_to_call, &actual_args, destination);
+            }
+            Some(known_function @ KnownFunction::CoreIterCollect)
+            | Some(known_function @ KnownFunction::CoreIterFold)
+            | Some(known_function @ KnownFunction::CoreIterSum) => {
+                self.model_iterator_consumer(known_function, args, &actual_args, destination);
+            }
+            Some(known_function @ KnownFunction::CoreIterFilter)
+            | Some(known_function @ KnownFunction::CoreIterMap) => {
+                self.model_iterator_adapter(known_function, args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreIterIntoIter) => {
+                self.model_into_iter(args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreMemDrop) => {
+                if let Some(mir::Operand::Move(place)) = args.get(0) {
+                    self.drop_channel_receiver(place);
+                    self.drop_shared_pointer(place);
+                }
+            }
+            Some(KnownFunction::CoreNonZeroGet) => {
+                self.assume_non_zero_result(destination);
+            }
+            Some(KnownFunction::CoreSliceIter) => {
+                self.model_collection_iterator(args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreSliceIterNext) => {
+                self.model_iterator_next(&actual_args, destination);
+            }
+            Some(KnownFunction::CoreNumFromBytes) => {
+                self.model_from_bytes(&func_to_call, args, destination);
+            }
+            Some(KnownFunction::CoreNumToBytes) => {
+                self.model_to_bytes(&func_to_call, args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreOpsIndex) => {
+                self.model_range_index(args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreSliceLen) => {
+                self.model_collection_length(args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreSliceSplitAt) => {
+                self.model_split_at(args, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreStrFromUtf8) => {
+                self.tag_validated_bytes(&actual_args, destination);
+            }
+            Some(KnownFunction::CorePtrIsNull) => {
+                self.model_pointer_is_null(&actual_args, destination);
+            }
+            Some(KnownFunction::CoreSliceFromRawParts) => {
+                // The pointer is checked to be non null along with the other unchecked functions.
+                if self.check_for_errors && actual_args.len() == 2 {
+                    self.check_ffi_buffer_length(&actual_args[0], &actual_args[1]);
+                }
+            }
+            Some(KnownFunction::CoreStrGetUnchecked) => {
+                if self.check_for_errors {
+                    self.check_str_get_unchecked(args, &actual_args);
+                }
+            }
+            Some(KnownFunction::CorePtrOffset) => {
+                self.model_pointer_offset(false, &actual_args, destination);
+            }
+            Some(KnownFunction::CorePtrSub) => {
+                self.model_pointer_offset(true, &actual_args, destination);
+            }
+            Some(KnownFunction::AllocRcClone) => {
+                self.model_shared_pointer_clone(args, &actual_args, destination);
+            }
+            Some(KnownFunction::AllocRcDeref) => {
+                self.model_shared_pointer_deref(args, &actual_args, destination);
+            }
+            Some(KnownFunction::AllocRcNew) => {
+                self.model_shared_pointer_new(args, destination);
+            }
+            Some(KnownFunction::AllocRcStrongCount) => {
+                self.model_shared_pointer_strong_count(args, &actual_args, destination);
+            }
+            Some(KnownFunction::AllocStringAsStr) => {
+                self.model_string_as_str(&actual_args, destination);
+            }
+            Some(known_function @ KnownFunction::AllocStringFrom)
+            | Some(known_function @ KnownFunction::AllocStringNew)
+            | Some(known_function @ KnownFunction::AllocStringPushStr)
+            | Some(known_function @ KnownFunction::CoreStrEndsWith)
+            | Some(known_function @ KnownFunction::CoreStrLen)
+            | Some(known_function @ KnownFunction::CoreStrStartsWith) => {
+                self.model_string_operation(known_function, &actual_args, destination);
+            }
+            Some(KnownFunction::CoreResultUnwrap) => {
+                self.model_result_unwrap(args, destination);
+            }
+            Some(KnownFunction::CoreStrParse) => {
+                self.model_str_parse(&actual_args, destination);
+            }
+            Some(KnownFunction::MiraiLoopInvariant) => {
+                self.model_loop_invariant(&actual_args, destination);
+            }
+            Some(KnownFunction::StdChannelNew) => {
+                self.model_channel_new(destination);
+            }
+            Some(KnownFunction::StdChannelSend) => {
+                self.model_channel_send(&actual_args);
+            }
+            Some(KnownFunction::StdEnvInput) => {
+                self.model_environment_input(&actual_args, destination);
+            }
+            None => {
+                self.forget_strong_counts_of_moved_arguments(args);
+            }
+            _ => {}
+        }
+        if self.options.check_determinism {
+            self.track_nondeterminism(
+                known_function,
+                &func_to_call,
+                nondeterminism_tags,
+                destination,
+            );
+        }
+        self.transfer_and_refine_cleanup_state(
+            cleanup,
+            pre_call_environment,
+            &actual_args,
+            &function_summary,
+            callee_is_closure,
+        );
+        if self.check_for_errors {
+            if let Some(KnownFunction::CorePanickingPanic)
+            | Some(KnownFunction::StdPanickingBeginPanic)
+            | Some(KnownFunction::StdPanickingBeginPanicFmt) = known_function
+            {
+                self.record_unwind_condition(&abstract_value::TRUE);
+            }
+            self.report_calls_to_special_functions(known_function, actual_args)
+        }
+    }
+
+    /// With --verify-no-panics, a call to a function that has not been analyzed is a possible
+    /// panic, since nothing is known about what the function does. It is reported if the function
+    /// being analyzed is an analysis root and, like other possible panics, it becomes a
+    /// precondition of the function, so that its callers inherit the obligation.
+    fn check_unanalyzed_call(&mut self, func_to_call: &AbstractValue) {
+        debug_assert!(self.check_for_errors);
+        if self.current_environment.entry_condition.as_bool_if_known() == Some(false) {
+            return;
+        }
+        let message = match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => format!(
+                "possible panic: the called function {} has not been analyzed",
+                summary_cache_key
+            ),
+            _ => String::from("possible panic: the called function is not known"),
+        };
+        if self.is_analysis_root() {
+            let span = self.current_span;
+            let mut error = self.struct_span_err_for_user(span, message.as_str());
+            error.code(Confidence::MissingModels.diagnostic_code());
+            (self.emit_diagnostic)(&mut error, &mut self.buffered_diagnostics);
+        }
+        self.preconditions.push((
+            self.current_environment
+                .entry_condition
+                .not(Some(self.current_span)),
+            message,
+        ));
+    }
+
+    /// Reports calls that may block the calling thread, directly or via the functions they call,
+    /// if the function being analyzed is the body of an async fn, since such calls block the
+    /// executor that polls it. Otherwise the chain of calls that leads to the blocking operation
+    /// is recorded in the summary, so that it can be reported in async code that calls this.
+    fn check_for_blocking_call(
+        &mut self,
+        func_to_call: &AbstractValue,
+        known_function: Option<KnownFunction>,
+        function_summary: &Summary,
+    ) {
+        debug_assert!(self.check_for_errors);
+        let callee_key = match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => summary_cache_key.clone(),
+            _ => return,
+        };
+        let mut call_chain = vec![callee_key];
+        if known_function != Some(KnownFunction::StdBlocking) {
+            if function_summary.blocking_call_chain.is_empty() {
+                return;
+            }
+            call_chain.extend_from_slice(&function_summary.blocking_call_chain);
+        }
+        if self.current_environment.entry_condition.as_bool_if_known() == Some(false) {
+            return;
+        }
+        if utils::get_function_of_generator(self.def_id, &self.tcx).is_some() {
+            let message = format!(
+                "this call may block the thread of the async executor: {}",
+                call_chain.join(" -> ")
+            );
+            let span = self.current_span;
+            let mut warning = self.struct_span_warn_for_user(span, message.as_str());
+            warning.code(Confidence::Definite.diagnostic_code());
+            (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+        } else if self.blocking_call_chain.is_empty() {
+            self.blocking_call_chain = call_chain;
+        }
+    }
+
+    /// Records, in the summary, the chain of calls that leads to a call of an unsafe function, if
+    /// the called function is unsafe or its summary has such a chain. Only the first chain that is
+    /// found is kept, which is enough to tell if unsafe code can be reached from the function.
+    /// The code of trusted crates, unsafe code included, is vouched for by the configuration, so
+    /// calls to them never start a chain. Since nothing is known about the functions of untrusted
+    /// crates, calls to them are treated like calls to unsafe functions.
+    fn record_unsafe_call(
+        &mut self,
+        func_to_call: &AbstractValue,
+        trust_level: Option<TrustLevel>,
+        function_summary: &Summary,
+    ) {
+        if !self.unsafe_call_chain.is_empty() || trust_level == Some(TrustLevel::Trusted) {
+            return;
+        }
+        let (def_id, callee_key) = match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id,
+                summary_cache_key,
+                ..
+            }) => (*def_id, summary_cache_key.clone()),
+            _ => return,
+        };
+        let is_unsafe = trust_level == Some(TrustLevel::Untrusted)
+            || def_id.map_or(false, |def_id| {
+                self.tcx.fn_sig(def_id).unsafety() == hir::Unsafety::Unsafe
+            });
+        if !is_unsafe && function_summary.unsafe_call_chain.is_empty() {
+            return;
+        }
+        if self.current_environment.entry_condition.as_bool_if_known() == Some(false) {
+            return;
+        }
+        let mut call_chain = vec![callee_key];
+        // The chain of a function that does unsafe operations itself is just that function.
+        if !is_unsafe && function_summary.unsafe_call_chain != call_chain {
+            call_chain.extend_from_slice(&function_summary.unsafe_call_chain);
+        }
+        self.unsafe_call_chain = call_chain;
+    }
+
+    /// Records, in the summary, that the function does an unsafe operation itself, such as
+    /// dereferencing a raw pointer or accessing a mutable static, if the current statement is in
+    /// an unsafe block, or in the body of an unsafe function, and may be reached. The chain of
+    /// calls that leads to unsafe code is then just the function itself.
+    fn record_unsafe_operation(&mut self, scope: mir::SourceScope) {
+        if !self.unsafe_call_chain.is_empty() {
+            return;
+        }
+        let is_unsafe = match &self.mir.source_scope_local_data {
+            mir::ClearCrossCrate::Set(scope_data) => match scope_data[scope].safety {
+                mir::Safety::ExplicitUnsafe(..) | mir::Safety::FnUnsafe => true,
+                _ => false,
+            },
+            mir::ClearCrossCrate::Clear => false,
+        };
+        if !is_unsafe || self.current_environment.entry_condition.as_bool_if_known() == Some(false)
+        {
+            return;
+        }
+        self.unsafe_call_chain = vec![utils::summary_key_str(&self.tcx, self.def_id)];
+    }
+
+    /// Keeps track of calls that return a Result, so that a diagnostic can be given if the
+    /// result is never looked at. Also deals with calls to functions that may themselves drop
+    /// the results of fallible calls.
+    fn check_for_dropped_errors(
+        &mut self,
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+        function_summary: &Summary,
+    ) {
+        debug_assert!(self.check_for_errors);
+        if function_summary.may_drop_errors {
+            if self.is_analysis_root() {
+                let span = self.current_span;
+                let mut err = self.struct_span_warn_for_user(
+                    span,
+                    "the called function may drop the result of a fallible call without checking it",
+                );
+                err.code(Confidence::Definite.diagnostic_code());
+                (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+            } else {
+                self.may_drop_errors = true;
+            }
+        }
+        if let Some((mir::Place::Base(mir::PlaceBase::Local(local)), target)) = destination {
+            if local.as_usize() == 0 {
+                // The result is returned to the caller, which is responsible for checking it.
+                return;
+            }
+            let place = mir::Place::Base(mir::PlaceBase::Local(*local));
+            if let TyKind::Adt(def, _) = self.get_rustc_place_type(&place) {
+                if utils::summary_key_str(&self.tcx, def.did) == RESULT_TYPE {
+                    self.fallible_call_results
+                        .insert(*local, (self.current_span, *target));
+                }
+            }
+        }
+    }
+
+    /// Keeps track of calls to functions whose summaries show that the result is all there is to
+    /// the call: the function does not modify anything that the caller can observe and it does not
+    /// panic. Calling such a function and then dropping the result is pointless and probably a
+    /// mistake, such as calling a builder method or a checked arithmetic function and ignoring
+    /// what it returns. Only functions of the crate being analyzed are considered, since the
+    /// standard library marks its functions with #[must_use] where it matters. Functions marked
+    /// #[must_use] are left to the compiler.
+    fn record_pure_call_result(
+        &mut self,
+        func_to_call: &AbstractValue,
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+        function_summary: &Summary,
+    ) {
+        debug_assert!(self.check_for_errors);
+        let def_id = match func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id: Some(def_id),
+                ..
+            }) => def_id,
+            _ => return,
+        };
+        if !def_id.is_local()
+            || function_summary.result.is_none()
+            || !function_summary.is_pure()
+            || !function_summary.preconditions.is_empty()
+            || function_summary.unwind_condition.is_some()
+            || self.summary_cache.is_signature_summary(def_id)
+            || self.tcx.has_attr(def_id, "must_use")
+        {
+            return;
+        }
+        if let Some((mir::Place::Base(mir::PlaceBase::Local(local)), _)) = destination {
+            if local.as_usize() == 0 {
+                // The result is returned to the caller.
+                return;
+            }
+            let result_type = self.mir.local_decls[*local].ty;
+            if result_type.is_unit() || result_type.is_never() {
+                return;
+            }
+            if let TyKind::Adt(def, _) = result_type.sty {
+                if utils::summary_key_str(&self.tcx, def.did) == RESULT_TYPE {
+                    // Dropped results are reported by report_unchecked_results.
+                    return;
+                }
+            }
+            self.pure_call_results.insert(*local, self.current_span);
+        }
+    }
+
+    /// Complains about the results of calls to pure functions (see record_pure_call_result) that
+    /// are never looked at. As with #[must_use], only calls that are statements by themselves
+    /// count, since `let _ = f(x);` says that the result is deliberately ignored. Unlike dropped
+    /// errors, this says nothing about the callers of the function being analyzed, so the
+    /// diagnostic is given in every function.
+    fn report_unused_pure_results(&mut self) {
+        debug_assert!(self.check_for_errors);
+        if self.pure_call_results.is_empty() {
+            return;
+        }
+        let discarded_call_spans = utils::get_discarded_call_spans(self.def_id, &self.tcx);
+        let mut unused_results: Vec<(mir::Local, syntax_pos::Span)> = self
+            .pure_call_results
+            .iter()
+            .filter(|(local, span)| {
+                !self.used_locals.contains_key(local) && discarded_call_spans.contains(span)
+            })
+            .map(|(local, span)| (*local, *span))
+            .collect();
+        unused_results.sort_by_key(|(local, _)| *local);
+        for (_, span) in unused_results.into_iter() {
+            let mut err = self.struct_span_warn_for_user(
+                span,
+                "the result of this call is dropped, but computing it is all that the call does",
+            );
+            err.code(Confidence::Definite.diagnostic_code());
+            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        }
+    }
+
+    /// Records that the value stored in the local variable at the root of the given place is used
+    /// in the current block.
+    fn record_use_of(&mut self, place: &mir::Place<'tcx>) {
+        match place {
+            mir::Place::Base(mir::PlaceBase::Local(local)) => {
+                self.used_locals
+                    .entry(*local)
+                    .or_insert_with(HashSet::new)
+                    .insert(self.current_location.block);
+            }
+            mir::Place::Projection(boxed_place_projection) => {
+                self.record_use_of(&boxed_place_projection.base);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the built in model that applies to the function to call, if any.
+    fn get_known_function(&mut self, func_to_call: &AbstractValue) -> Option<KnownFunction> {
+        if let Expression::CompileTimeConstant(fun) = &func_to_call.domain.expression {
+            self.constant_value_cache.get_known_function_for(fun)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the implicit precondition of an unchecked function, along with the message to use
+    /// if it does not hold. The precondition is expressed in terms of the parameters of the function.
+    fn get_unchecked_precondition(
+        known_function: KnownFunction,
+    ) -> Option<(AbstractValue, &'static str)> {
+        let first_argument = Path::LocalVariable { ordinal: 1 };
+        match known_function {
+            KnownFunction::CoreNonZeroNewUnchecked => {
+                let value: AbstractValue = Expression::Variable {
+                    path: box first_argument,
+                    var_type: ExpressionType::U128,
+                }
+                .into();
+                let zero: AbstractValue = ConstantDomain::U128(0).into();
+                Some((
+                    value.equals(&zero, None).not(None),
+                    "value of niche type must not be zero",
+                ))
+            }
+            KnownFunction::CoreStrFromUtf8Unchecked => {
+                let utf8_valid: AbstractValue = Expression::Variable {
+                    path: box Path::QualifiedPath {
+                        length: 2,
+                        qualifier: box first_argument,
+                        selector: box PathSelector::Tag(Tag::Utf8Valid),
+                    },
+                    var_type: ExpressionType::Bool,
+                }
+                .into();
+                Some((utf8_valid, "bytes have not been validated as UTF-8"))
+            }
+            KnownFunction::CoreSliceGetUnchecked => {
+                let mut index: AbstractValue = Expression::Variable {
+                    path: box Path::LocalVariable { ordinal: 2 },
+                    var_type: ExpressionType::Usize,
+                }
+                .into();
+                let mut length: AbstractValue = Expression::Variable {
+                    path: box Path::QualifiedPath {
+                        length: 2,
+                        qualifier: box first_argument,
+                        selector: box PathSelector::ArrayLength,
+                    },
+                    var_type: ExpressionType::Usize,
+                }
+                .into();
+                Some((
+                    index.less_than(&mut length, None),
+                    "unchecked index out of bounds",
+                ))
+            }
+            KnownFunction::CoreOptionUnwrapUnchecked | KnownFunction::CoreResultUnwrapUnchecked => {
+                // The discriminant of an enum is the value at the path of the enum.
+                let discriminant: AbstractValue = Expression::Variable {
+                    path: box first_argument,
+                    var_type: ExpressionType::U128,
+                }
+                .into();
+                let (variant, message) =
+                    if known_function == KnownFunction::CoreOptionUnwrapUnchecked {
+                        (1, "unchecked unwrap of None")
+                    } else {
+                        (0, "unchecked unwrap of an Err")
+                    };
+                let variant: AbstractValue = ConstantDomain::U128(variant).into();
+                Some((discriminant.equals(&variant, None), message))
+            }
+            KnownFunction::CoreCharFromU32Unchecked => {
+                let mut value: AbstractValue = Expression::Variable {
+                    path: box first_argument,
+                    var_type: ExpressionType::U32,
+                }
+                .into();
+                let mut max: AbstractValue = ConstantDomain::U128(0x0010_FFFF).into();
+                let mut first_surrogate: AbstractValue = ConstantDomain::U128(0xD800).into();
+                let mut last_surrogate: AbstractValue = ConstantDomain::U128(0xDFFF).into();
+                let is_not_surrogate = value
+                    .less_than(&mut first_surrogate, None)
+                    .or(&value.greater_than(&mut last_surrogate, None), None);
+                Some((
+                    value
+                        .less_or_equal(&mut max, None)
+                        .and(&is_not_surrogate, None),
+                    "value is not a valid char",
+                ))
+            }
+            KnownFunction::CoreSliceFromRawParts => {
+                let pointer: AbstractValue = Expression::Variable {
+                    path: box first_argument,
+                    var_type: ExpressionType::NonPrimitive,
+                }
+                .into();
+                let zero: AbstractValue = ConstantDomain::U128(0).into();
+                Some((
+                    pointer.equals(&zero, None).not(None),
+                    "the pointer given to slice::from_raw_parts is null",
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks the implicit precondition of a call to an unchecked function. By calling such a
+    /// function, the caller promises that the precondition holds, so unlike inferred preconditions,
+    /// a precondition that cannot be proved is reported at the call site, even if the calling
+    /// function is not public.
+    fn check_unchecked_precondition(
+        &mut self,
+        precondition: &AbstractValue,
+        message: &str,
+        actual_args: &[AbstractValue],
+    ) {
+        debug_assert!(self.check_for_errors);
+        let refined_precondition = precondition
+            .refine_parameters(actual_args)
+            .refine_paths(&mut self.current_environment)
+            .refine_with(&self.current_environment.entry_condition, self.current_span);
+        self.check_required_condition(&refined_precondition, message);
+    }
+
+    /// Reports the given condition, which must hold at the current location, if it cannot be
+    /// shown to hold. The condition must already be refined with the current environment.
+    fn check_required_condition(&mut self, condition: &AbstractValue, message: &str) {
+        if self.is_skipped_obligation(ObligationClass::Contract) {
+            return;
+        }
+        let (condition_as_bool, entry_cond_as_bool) =
+            self.check_condition_value_and_reachability(condition, ObligationClass::Contract);
+        if condition_as_bool.unwrap_or(false) || !entry_cond_as_bool.unwrap_or(true) {
+            return;
+        }
+        let span = self.current_span;
+        let definite = !condition_as_bool.unwrap_or(true) && entry_cond_as_bool.unwrap_or(false);
+        let mut warning = if definite {
+            self.struct_span_warn_for_user(span, message)
+        } else {
+            let message = format!("possible error: {}", message);
+            self.struct_span_warn_for_user(span, message.as_str())
+        };
+        self.set_confidence(&mut warning, definite);
+        self.emit_unproven_condition_diagnostic(&mut warning);
+    }
+
+    /// Calls to a sink require the argument that the options declare for it to have the property
+    /// that the options declare for it. This is a precondition on the tag of the argument for the
+    /// property, which sanitizers and validators establish (see model_sanitizer).
+    fn check_sink_argument(&mut self, sink: &Sink, actual_args: &[AbstractValue]) {
+        let has_property: AbstractValue = Expression::Variable {
+            path: box Self::get_tag_path(
+                Path::LocalVariable {
+                    ordinal: sink.argument + 1,
+                },
+                Tag::sanitized(&sink.requires),
+            ),
+            var_type: ExpressionType::Bool,
+        }
+        .into();
+        let message = format!(
+            "the argument has not been sanitized or validated to have the property \"{}\"",
+            sink.requires
+        );
+        self.check_unchecked_precondition(&has_property, message.as_str(), actual_args);
+    }
+
+    /// Checks that the arguments of a call to a function with a model satisfy the preconditions
+    /// of the model. Quantified preconditions that cannot be unrolled are reported, since they
+    /// would otherwise go unchecked.
+    fn check_model_preconditions(
+        &mut self,
+        model: &FunctionModel,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+    ) {
+        for precondition in model.preconditions.iter() {
+            if let Some(condition) = self.get_model_condition(precondition, args, actual_args, None)
+            {
+                let refined_condition = condition
+                    .refine_with(&self.current_environment.entry_condition, self.current_span);
+                let message = format!(
+                    "the precondition \"{}\" of the model of the called function is not satisfied",
+                    precondition.text
+                );
+                self.check_required_condition(&refined_condition, message.as_str());
+            } else if precondition.quantifier.is_some()
+                && self.check_for_errors
+                && self.current_environment.entry_condition.as_bool_if_known() != Some(false)
+            {
+                let message = format!(
+                    "the precondition \"{}\" of the model of the called function could not be checked, because the length of the collection is not known or exceeds {}",
+                    precondition.text,
+                    k_limits::MAX_TRACKED_ARRAY_ELEMENTS
+                );
+                let span = self.current_span;
+                let mut warning = self.struct_span_warn_for_user(span, message.as_str());
+                warning.code(Confidence::LimitedPrecision.diagnostic_code());
+                (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+            }
+        }
+    }
+
+    /// The postconditions of the model of the called function hold when the call returns, so
+    /// they are added to the condition under which control reaches the normal destination.
+    fn assume_model_postconditions(
+        &mut self,
+        model: &FunctionModel,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (place, target) = match destination {
+            Some(destination) => destination,
+            None => return,
+        };
+        let target_path = self.visit_place(place);
+        let target_type = self.get_place_type(place);
+        let result = self.lookup_path_and_refine_result(target_path, target_type);
+        for postcondition in model.postconditions.iter() {
+            if let Some(condition) =
+                self.get_model_condition(postcondition, args, actual_args, Some(&result))
+            {
+                let exit_condition = self
+                    .current_environment
+                    .exit_conditions
+                    .get(target)
+                    .unwrap_or(&self.current_environment.entry_condition)
+                    .and(&condition, None);
+                self.current_environment
+                    .exit_conditions
+                    .insert(*target, exit_condition);
+            }
+        }
+    }
+
+    /// Returns the value of the given condition of a model, given the arguments of the call and,
+    /// for postconditions, its result. Returns None if the condition refers to an argument that
+    /// the call does not have, or to the result of a precondition, or if it is quantified and
+    /// cannot be unrolled.
+    fn get_model_condition(
+        &mut self,
+        condition: &Condition,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        result: Option<&AbstractValue>,
+    ) -> Option<AbstractValue> {
+        if let Some(quantifier) = condition.quantifier {
+            return self.get_quantified_model_condition(
+                condition,
+                quantifier,
+                args,
+                actual_args,
+                result,
+            );
+        }
+        let get_operand = |operand: Operand| -> Option<AbstractValue> {
+            match operand {
+                Operand::Argument(i) => actual_args.get(i).cloned(),
+                Operand::Result => result.cloned(),
+                Operand::Constant(c) => Some(ConstantDomain::I128(c).into()),
+                Operand::Element(..) => None,
+            }
+        };
+        let left = get_operand(condition.left)?;
+        let right = get_operand(condition.right)?;
+        Some(self.compare_model_operands(left, condition.comparison, right))
+    }
+
+    /// A quantified condition of a model is unrolled into the conjunction (forall) or disjunction
+    /// (exists) of the comparison for each element of the array or slice that the quantified
+    /// argument refers to. This needs the length of the collection to be known and no greater
+    /// than k_limits::MAX_TRACKED_ARRAY_ELEMENTS, since the elements beyond that are not tracked
+    /// individually. Returns None otherwise.
+    fn get_quantified_model_condition(
+        &mut self,
+        condition: &Condition,
+        quantifier: Quantifier,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        result: Option<&AbstractValue>,
+    ) -> Option<AbstractValue> {
+        let argument = condition.quantified_argument()?;
+        let collection_path =
+            self.get_collection_path(args.get(argument)?, actual_args.get(argument)?)?;
+        let argument_ty = args[argument].ty(&self.mir.local_decls, self.tcx);
+        let (element_ty, array_length) = match argument_ty.sty {
+            TyKind::Ref(_, ty, _) => match ty.sty {
+                TyKind::Array(element_ty, length) => (element_ty, Some(length)),
+                TyKind::Slice(element_ty) => (element_ty, None),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let length = match array_length {
+            Some(length) => {
+                let usize_type = self.tcx.types.usize;
+                self.visit_constant(usize_type, None, length)
+            }
+            None => self.get_len(collection_path.clone()),
+        };
+        let length = length.domain.as_constant_integer()?;
+        if length < 0 || length > k_limits::MAX_TRACKED_ARRAY_ELEMENTS as i128 {
+            return None;
+        }
+        let element_type = ExpressionType::from(&element_ty.sty);
+        let mut unrolled_condition = match quantifier {
+            Quantifier::ForAll => abstract_value::TRUE,
+            Quantifier::Exists => abstract_value::FALSE,
+        };
+        for index in 0..length as usize {
+            let element_path = Self::get_byte_path(&collection_path, index);
+            let element = self.lookup_path_and_refine_result(element_path, element_type.clone());
+            let get_operand = |operand: Operand| -> Option<AbstractValue> {
+                match operand {
+                    Operand::Argument(i) => actual_args.get(i).cloned(),
+                    Operand::Result => result.cloned(),
+                    Operand::Constant(c) => Some(ConstantDomain::I128(c).into()),
+                    Operand::Element(..) => Some(element.clone()),
+                }
+            };
+            let left = get_operand(condition.left)?;
+            let right = get_operand(condition.right)?;
+            let comparison = self.compare_model_operands(left, condition.comparison, right);
+            unrolled_condition = match quantifier {
+                Quantifier::ForAll => unrolled_condition.and(&comparison, None),
+                Quantifier::Exists => unrolled_condition.or(&comparison, None),
+            };
+        }
+        Some(unrolled_condition)
+    }
+
+    /// Returns the value of the comparison of the given operands of a condition of a model.
+    fn compare_model_operands(
+        &self,
+        mut left: AbstractValue,
+        comparison: Comparison,
+        mut right: AbstractValue,
+    ) -> AbstractValue {
+        let span = Some(self.current_span);
+        match comparison {
+            Comparison::Equals => left.equals(&right, span),
+            Comparison::NotEquals => left.not_equals(&right, span),
+            Comparison::LessThan => left.less_than(&mut right, span),
+            Comparison::LessOrEqual => left.less_or_equal(&mut right, span),
+            Comparison::GreaterThan => left.greater_than(&mut right, span),
+            Comparison::GreaterOrEqual => left.greater_or_equal(&mut right, span),
+        }
+    }
+
+    /// Calls to a sanitizer return a value that has the property that the options declare for it.
+    /// Calls to a validator check if the argument that the options declare for it has the property.
+    /// This is recorded by giving the argument a tag for the property, whose value is the condition
+    /// under which the result is true, or Ok. Code that is only reached when the validation
+    /// succeeds has this condition as part of its entry condition, so for such code the tag
+    /// refines to true.
+    fn model_sanitizer(
+        &mut self,
+        sanitizer: &Sanitizer,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (place, _) = match destination {
+            Some(destination) => destination,
+            None => return,
+        };
+        let target_path = self.visit_place(place);
+        let tag = Tag::sanitized(&sanitizer.tag);
+        match sanitizer.validates {
+            None => {
+                self.current_environment.update_value_at(
+                    Self::get_tag_path(target_path, tag),
+                    abstract_value::TRUE.with_provenance(self.current_span),
+                );
+            }
+            Some(argument) => {
+                let validated_path = match actual_args
+                    .get(argument)
+                    .and_then(Self::get_referenced_path)
+                {
+                    Some(path) => path,
+                    None => return,
+                };
+                let target_type = self.get_place_type(place);
+                let is_valid = if target_type == ExpressionType::Bool {
+                    self.lookup_path_and_refine_result(target_path, target_type)
+                } else {
+                    let discriminant = self.lookup_path_and_refine_result(target_path, target_type);
+                    let ok_discriminant: AbstractValue = ConstantDomain::U128(0).into();
+                    discriminant.equals(&ok_discriminant, Some(self.current_span))
+                };
+                self.current_environment
+                    .update_value_at(Self::get_tag_path(validated_path, tag), is_valid);
+            }
+        }
+    }
+
+    /// A call to a function such as mirai_loop_invariant states that the condition given by the
+    /// first argument holds whenever control reaches the call, which is meant to be at the start of
+    /// the body of a loop. The state at the call joins the state on entry to the loop with the
+    /// states at the ends of earlier iterations, which assumed the invariant, so proving the
+    /// condition there shows that it holds on entry and that the body preserves it. The condition
+    /// is then assumed for the rest of the iteration, and the bounds it sets for integers become
+    /// widening thresholds, so that loop counters are widened up to them rather than past them.
+    fn model_loop_invariant(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let condition = match actual_args.get(0) {
+            Some(condition) => condition.clone(),
+            None => return,
+        };
+        if self.check_for_errors {
+            let refined_condition =
+                condition.refine_with(&self.current_environment.entry_condition, self.current_span);
+            self.check_required_condition(&refined_condition, "loop invariant does not hold");
+        } else {
+            Self::record_invariant_thresholds(
+                &condition.domain.expression,
+                &mut self.loop_invariant_thresholds,
+            );
+        }
+        if let Some((_, target)) = destination {
+            let exit_condition = self
+                .current_environment
+                .exit_conditions
+                .get(target)
+                .unwrap_or(&self.current_environment.entry_condition)
+                .and(&condition, None);
+            self.current_environment
+                .exit_conditions
+                .insert(*target, exit_condition);
+        }
+    }
+
+    /// Adds the bounds that the given loop invariant sets for integers, by comparing them with
+    /// constants, to the given thresholds, if they are not already there.
+    fn record_invariant_thresholds(condition: &Expression, thresholds: &mut Vec<i128>) {
+        let (bound, adjustment) = match condition {
+            Expression::And { left, right } => {
+                Self::record_invariant_thresholds(&left.expression, thresholds);
+                Self::record_invariant_thresholds(&right.expression, thresholds);
+                return;
+            }
+            Expression::LessThan { left, right } => {
+                match (left.as_constant_integer(), right.as_constant_integer()) {
+                    (_, Some(bound)) => (bound, -1),
+                    (Some(bound), None) => (bound, 1),
+                    (None, None) => return,
+                }
+            }
+            Expression::GreaterThan { left, right } => {
+                match (left.as_constant_integer(), right.as_constant_integer()) {
+                    (_, Some(bound)) => (bound, 1),
+                    (Some(bound), None) => (bound, -1),
+                    (None, None) => return,
+                }
+            }
+            Expression::LessOrEqual { left, right }
+            | Expression::GreaterOrEqual { left, right } => {
+                match right
+                    .as_constant_integer()
+                    .or_else(|| left.as_constant_integer())
+                {
+                    Some(bound) => (bound, 0),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        if let Some(threshold) = bound.checked_add(adjustment) {
+            if !thresholds.contains(&threshold) {
+                thresholds.push(threshold);
+            }
+        }
+    }
+
+    /// The command line arguments and environment variables of a program come from outside of it.
+    /// Whatever the summary of the called function says about them is forgotten, so that their
+    /// values and lengths are unknown, and the result is tagged as untrusted, unless the options
+    /// declare the environment variable to be trusted. The value of an environment variable is
+    /// tagged as well, so that the tags stay with the value when the result is unwrapped, and it is
+    /// tagged with the name of the variable, so that parsing it can take the options into account.
+    fn model_environment_input(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            self.current_environment.forget_value_at(&target_path);
+            let variable_name = match actual_args.get(0).map(|arg| &arg.domain.expression) {
+                Some(Expression::CompileTimeConstant(ConstantDomain::Str(name))) => Some(name),
+                _ => None,
+            };
+            let is_trusted = variable_name
+                .and_then(|name| self.options.environment_variables.get(name.as_str()))
+                .map_or(false, |variable| variable.trusted);
+            if !is_trusted {
+                let untrusted = abstract_value::TRUE.with_provenance(self.current_span);
+                let tag_path = Self::get_tag_path(target_path.clone(), Tag::Untrusted);
+                self.current_environment
+                    .update_value_at(tag_path, untrusted.clone());
+                if variable_name.is_some() {
+                    let value_path = Self::get_ok_value_path(target_path.clone());
+                    let tag_path = Self::get_tag_path(value_path, Tag::Untrusted);
+                    self.current_environment
+                        .update_value_at(tag_path, untrusted);
+                }
+            }
+            if let Some(name) = variable_name {
+                let value_path = Self::get_ok_value_path(target_path);
+                let tag_path = Self::get_tag_path(value_path, Tag::EnvironmentVariable);
+                let name_value: AbstractValue = ConstantDomain::Str(name.clone()).into();
+                self.current_environment
+                    .update_value_at(tag_path, name_value);
+            }
+        }
+    }
+
+    /// Calls to unwrap() and expect() on a Result return its Ok value. (If the Result is an Err,
+    /// they panic instead.) The elements of the Ok value are moved to the destination.
+    fn model_result_unwrap(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(mir::Operand::Move(result)), Some((place, _))) = (args.get(0), destination) {
+            let result_path = self.visit_place(result);
+            let target_path = self.visit_place(place);
+            let target_type = self.get_place_type(place);
+            self.copy_or_move_elements(
+                target_path,
+                Self::get_ok_value_path(result_path),
+                target_type,
+                true,
+            );
+        }
+    }
+
+    /// Calls to as_str() and deref() on a String return a str that views the contents of the
+    /// String. The str is modeled as a reference to the String, so that what is known about the
+    /// contents of the String is also known about the str.
+    fn model_string_as_str(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let string_path = actual_args.get(0).and_then(Self::get_referenced_path);
+        if let (Some(string_path), Some((place, _))) = (string_path, destination) {
+            let target_path = self.visit_place(place);
+            self.current_environment
+                .update_value_at(target_path, Expression::Reference(string_path).into());
+        }
+    }
+
+    /// Strings are modeled by their contents, as far as they are known: the value of a String,
+    /// or of the str that a &str refers to, is a string constant, or the concatenation of the
+    /// strings that were appended to it. The String domain of that value says what is known about
+    /// the length, prefix and suffix of the string, which decides calls to len, starts_with and
+    /// ends_with where it can.
+    fn model_string_operation(
+        &mut self,
+        known_function: KnownFunction,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (place, target) = match destination {
+            Some(destination) => destination,
+            None => return,
+        };
+        let target_path = self.visit_place(place);
+        let string = match actual_args.get(0) {
+            Some(value) => self.get_string_value(value),
+            None => {
+                if known_function == KnownFunction::AllocStringNew {
+                    let empty: AbstractValue = ConstantDomain::Str(String::new()).into();
+                    self.current_environment
+                        .update_value_at(target_path, empty.with_provenance(self.current_span));
+                }
+                return;
+            }
+        };
+        match known_function {
+            KnownFunction::AllocStringFrom => {
+                if Self::is_string_expression(&string) {
+                    self.current_environment
+                        .update_value_at(target_path, string.with_provenance(self.current_span));
+                }
+            }
+            KnownFunction::AllocStringPushStr => {
+                let string_path = match Self::get_referenced_path(&actual_args[0]) {
+                    Some(string_path) => string_path,
+                    None => return,
+                };
+                let old_string = self.lookup_path_and_refine_result(
+                    string_path.clone(),
+                    ExpressionType::NonPrimitive,
+                );
+                let appended_string = match actual_args.get(1) {
+                    Some(value) => self.get_string_value(value),
+                    None => return,
+                };
+                let new_string =
+                    old_string.string_concat(&appended_string, Some(self.current_span));
+                self.current_environment
+                    .update_value_at(string_path, new_string);
+            }
+            KnownFunction::CoreStrEndsWith | KnownFunction::CoreStrStartsWith => {
+                let pattern = match actual_args.get(1) {
+                    Some(pattern) => pattern.domain.as_constant_string(),
+                    None => None,
+                };
+                if let Some(pattern) = pattern {
+                    let string_domain = string.domain.get_as_string();
+                    let result = if known_function == KnownFunction::CoreStrStartsWith {
+                        string_domain.starts_with(pattern)
+                    } else {
+                        string_domain.ends_with(pattern)
+                    };
+                    if let Some(result) = result {
+                        let result = if result {
+                            abstract_value::TRUE
+                        } else {
+                            abstract_value::FALSE
+                        };
+                        self.current_environment.update_value_at(
+                            target_path,
+                            result.with_provenance(self.current_span),
+                        );
+                    }
+                }
+            }
+            KnownFunction::CoreStrLen => {
+                let length = string.domain.get_as_string().length().clone();
+                match (length.lower_bound(), length.upper_bound()) {
+                    (Some(lower), Some(upper)) if lower == upper => {
+                        let length: AbstractValue = ConstantDomain::U128(lower as u128).into();
+                        self.current_environment.update_value_at(
+                            target_path,
+                            length.with_provenance(self.current_span),
+                        );
+                    }
+                    (Some(lower), _) if lower > 0 => {
+                        // The length is not known, but the known prefix and suffix bound it.
+                        let mut result =
+                            self.lookup_path_and_refine_result(target_path, ExpressionType::Usize);
+                        let mut lower: AbstractValue = ConstantDomain::U128(lower as u128).into();
+                        let exit_condition = self
+                            .current_environment
+                            .exit_conditions
+                            .get(target)
+                            .unwrap_or(&self.current_environment.entry_condition)
+                            .and(&result.greater_or_equal(&mut lower, None), None);
+                        self.current_environment
+                            .exit_conditions
+                            .insert(*target, exit_condition);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the value of the string that the given &str or &String refers to. A &str that
+    /// is a string literal is its own value.
+    fn get_string_value(&mut self, value: &AbstractValue) -> AbstractValue {
+        match &value.domain.expression {
+            Expression::Reference(path) => {
+                self.lookup_path_and_refine_result(path.clone(), ExpressionType::NonPrimitive)
+            }
+            _ => value.clone(),
+        }
+    }
+
+    /// True if the value is known to be a string, i.e. a string constant or a concatenation of
+    /// strings.
+    fn is_string_expression(value: &AbstractValue) -> bool {
+        match &value.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Str(..))
+            | Expression::StringConcat { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Calls to parse() on a str, and calls to from_str_radix, produce a number that lies within
+    /// the range of its type and, if the str holds the value of an environment variable, within
+    /// the range that the options declare for the variable. This adds that fact to the condition
+    /// under which control reaches the normal destination of the call, so that code that only uses
+    /// the number if it parsed can be verified. The number is untrusted if the str is.
+    fn model_str_parse(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let string_path = actual_args.get(0).and_then(Self::get_referenced_path);
+        let (string_path, (place, target)) = match (string_path, destination) {
+            (Some(string_path), Some(destination)) => (string_path, destination),
+            _ => return,
+        };
+        let target_path = self.visit_place(place);
+        let untrusted_tag_path = Self::get_tag_path(string_path.clone(), Tag::Untrusted);
+        if let Some(untrusted) = self
+            .current_environment
+            .value_at(&untrusted_tag_path)
+            .cloned()
+        {
+            let tag_path = Self::get_tag_path(target_path.clone(), Tag::Untrusted);
+            self.current_environment
+                .update_value_at(tag_path, untrusted);
+        }
+        let value_type: ExpressionType = match self.get_rustc_place_type(place) {
+            TyKind::Adt(_, substs) => (&substs.type_at(0).sty).into(),
+            _ => return,
+        };
+        if !value_type.is_integer() {
+            return;
+        }
+        let tag_path = Self::get_tag_path(string_path, Tag::EnvironmentVariable);
+        let declared_range = match self.current_environment.value_at(&tag_path) {
+            Some(AbstractValue {
+                domain:
+                    AbstractDomain {
+                        expression: Expression::CompileTimeConstant(ConstantDomain::Str(name)),
+                        ..
+                    },
+                ..
+            }) => self
+                .options
+                .environment_variables
+                .get(name.as_str())
+                .and_then(|variable| variable.range),
+            _ => None,
+        };
+        let (mut lower_bound, mut upper_bound) =
+            Self::get_integer_range(&value_type, declared_range);
+        let mut value = self.lookup_path_and_refine_result(
+            Self::get_ok_value_path(target_path.clone()),
+            value_type.clone(),
+        );
+        let in_range = lower_bound
+            .less_or_equal(&mut value, None)
+            .and(&value.less_or_equal(&mut upper_bound, None), None);
+        let discriminant =
+            self.lookup_path_and_refine_result(target_path, ExpressionType::NonPrimitive);
+        let ok_discriminant: AbstractValue = ConstantDomain::U128(0).into();
+        let is_ok = discriminant.equals(&ok_discriminant, None);
+        let exit_condition = self
+            .current_environment
+            .exit_conditions
+            .get(target)
+            .unwrap_or(&self.current_environment.entry_condition)
+            .and(&is_ok.not(None).or(&in_range, None), None);
+        self.current_environment
+            .exit_conditions
+            .insert(*target, exit_condition);
+    }
+
+    /// Returns the smallest and largest values of the given integer type, narrowed to the given
+    /// range, if any.
+    fn get_integer_range(
+        value_type: &ExpressionType,
+        range: Option<(i64, i64)>,
+    ) -> (AbstractValue, AbstractValue) {
+        let shift = 128 - u32::from(value_type.bit_length());
+        if value_type.is_signed_integer() {
+            let (mut lower, mut upper) = (i128::min_value() >> shift, i128::max_value() >> shift);
+            if let Some((range_lower, range_upper)) = range {
+                lower = lower.max(i128::from(range_lower));
+                upper = upper.min(i128::from(range_upper));
+            }
+            (
+                ConstantDomain::I128(lower).into(),
+                ConstantDomain::I128(upper).into(),
+            )
+        } else {
+            let (mut lower, mut upper) = (0, u128::max_value() >> shift);
+            if let Some((range_lower, range_upper)) = range {
+                lower = range_lower.max(0) as u128;
+                upper = upper.min(range_upper.max(0) as u128);
+            }
+            (
+                ConstantDomain::U128(lower).into(),
+                ConstantDomain::U128(upper).into(),
+            )
+        }
+    }
+
+    /// Returns the path of the value wrapped by the Ok variant of the Result at the given path.
+    fn get_ok_value_path(result_path: Path) -> Path {
+        let ok_variant = Path::QualifiedPath {
+            length: result_path.path_length() + 1,
+            qualifier: box result_path,
+            selector: box PathSelector::Downcast(0),
+        };
+        Path::QualifiedPath {
+            length: ok_variant.path_length() + 1,
+            qualifier: box ok_variant,
+            selector: box PathSelector::Field(0),
+        }
+    }
+
+    /// Returns the path of the given tag of the value at the given path.
+    fn get_tag_path(path: Path, tag: Tag) -> Path {
+        Path::QualifiedPath {
+            length: path.path_length() + 1,
+            qualifier: box path,
+            selector: box PathSelector::Tag(tag),
+        }
+    }
+
+    /// With --check-determinism, returns the UnorderedIteration and Nondeterministic tags of the
+    /// values that the given arguments refer to, which are those of the arguments that are
+    /// obtained by iterating over a HashMap or HashSet, or from another source of nondeterminism.
+    fn get_nondeterminism_tags(&self, actual_args: &[AbstractValue]) -> Vec<(Tag, AbstractValue)> {
+        let mut tags = Vec::new();
+        if !self.options.check_determinism {
+            return tags;
+        }
+        for path in actual_args.iter().filter_map(Self::get_referenced_path) {
+            for tag in NONDETERMINISM_TAGS.iter() {
+                let tag_path = Self::get_tag_path(path.clone(), *tag);
+                if let Some(tag_value) = self.current_environment.value_at(&tag_path) {
+                    if tag_value.as_bool_if_known() == Some(true) {
+                        tags.push((*tag, tag_value.clone()));
+                    }
+                }
+            }
+        }
+        tags
+    }
+
+    /// Iterating over a HashMap or HashSet produces the elements in an order that differs from run
+    /// to run. With --check-determinism, the iterators, and the values obtained from them, are
+    /// tagged with Tag::UnorderedIteration, whose provenance is the location of the iteration.
+    /// Likewise, the results of other sources of nondeterminism, such as random number generators
+    /// and clocks, are tagged with Tag::Nondeterministic. The result of a call gets the tags of
+    /// the arguments if the callee is an iterator adapter, or any other function without a summary.
+    /// (The summaries of analyzed functions carry the tags of their results. Sums do not depend on
+    /// the order of their elements.) Sorting a slice removes the UnorderedIteration tags that stem
+    /// from the same iterations as the tag of the slice, since the order of its elements no longer
+    /// depends on them.
+    fn track_nondeterminism(
+        &mut self,
+        known_function: Option<KnownFunction>,
+        func_to_call: &AbstractValue,
+        nondeterminism_tags: Vec<(Tag, AbstractValue)>,
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let tags: Vec<(Tag, AbstractValue)> = match known_function {
+            Some(KnownFunction::StdHashIter) => vec![(
+                Tag::UnorderedIteration,
+                abstract_value::TRUE.with_provenance(self.current_span),
+            )],
+            Some(KnownFunction::StdNondeterministic) => vec![(
+                Tag::Nondeterministic,
+                abstract_value::TRUE.with_provenance(self.current_span),
+            )],
+            Some(KnownFunction::CoreSliceSort) => {
+                let spans: HashSet<syntax_pos::Span> = nondeterminism_tags
+                    .iter()
+                    .filter(|(tag, _)| *tag == Tag::UnorderedIteration)
+                    .filter_map(|(_, tag_value)| tag_value.provenance.last().cloned())
+                    .collect();
+                self.forget_unordered_iteration_tags(&spans);
+                return;
+            }
+            Some(KnownFunction::CoreIterSum) => nondeterminism_tags
+                .into_iter()
+                .filter(|(tag, _)| *tag != Tag::UnorderedIteration)
+                .collect(),
+            Some(KnownFunction::CoreIterCollect)
+            | Some(KnownFunction::CoreIterFilter)
+            | Some(KnownFunction::CoreIterFold)
+            | Some(KnownFunction::CoreIterIntoIter)
+            | Some(KnownFunction::CoreIterMap)
+            | Some(KnownFunction::CoreSliceIterNext) => nondeterminism_tags,
+            None if self.is_unmodeled_function(func_to_call) => nondeterminism_tags,
+            _ => return,
+        };
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            for (tag, tag_value) in tags.into_iter() {
+                let tag_path = Self::get_tag_path(target_path.clone(), tag);
+                self.current_environment
+                    .update_value_at(tag_path, tag_value);
+            }
+        }
+    }
+
+    /// Removes the UnorderedIteration tags of the values that are obtained from the iterations at
+    /// the given locations.
+    fn forget_unordered_iteration_tags(&mut self, spans: &HashSet<syntax_pos::Span>) {
+        let tag_paths: Vec<Path> = self
+            .current_environment
+            .value_map
+            .iter()
+            .filter_map(|(path, tag)| match path {
+                Path::QualifiedPath { selector, .. }
+                    if **selector == PathSelector::Tag(Tag::UnorderedIteration)
+                        && tag
+                            .provenance
+                            .last()
+                            .map_or(false, |span| spans.contains(span)) =>
+                {
+                    Some(path.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        for tag_path in tag_paths.into_iter() {
+            self.current_environment
+                .update_value_at(tag_path, abstract_value::FALSE);
+        }
+    }
+
+    /// True if the function writes the values referenced by its arguments to an output, such as a
+    /// file or the console. Functions that write to files are known as blocking functions, which
+    /// they also are, so they are recognized by their names.
+    fn is_output(func_to_call: &AbstractValue, known_function: Option<KnownFunction>) -> bool {
+        match known_function {
+            Some(KnownFunction::StdOutput) => true,
+            Some(KnownFunction::StdBlocking) => match &func_to_call.domain.expression {
+                Expression::CompileTimeConstant(ConstantDomain::Function {
+                    summary_cache_key,
+                    ..
+                }) => {
+                    summary_cache_key.starts_with("std.fs.")
+                        && (summary_cache_key.ends_with(".write")
+                            || summary_cache_key.ends_with(".write_all"))
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// With --check-determinism, reports a call that writes a value that depends on a source of
+    /// nondeterminism to an output, such as a file or the console, i.e. a call to which one of the
+    /// arguments refers to a value with an UnorderedIteration or Nondeterministic tag, or to a
+    /// value computed from such a value. The diagnostic has a note for each source, so that the
+    /// diagnostics add up to an inventory of the nondeterminism that reaches the outputs.
+    fn check_output_determinism(&mut self, actual_args: &[AbstractValue]) {
+        debug_assert!(self.check_for_errors);
+        let mut values: Vec<AbstractDomain> = Vec::new();
+        for arg in actual_args.iter() {
+            self.collect_referenced_values(arg, &mut values);
+        }
+        let referenced_paths: Vec<Path> = actual_args
+            .iter()
+            .filter_map(Self::get_referenced_path)
+            .collect();
+        let mut depends_on_nondeterminism = false;
+        let mut spans: Vec<syntax_pos::Span> = Vec::new();
+        for (path, tag_value) in self.current_environment.value_map.iter() {
+            if let Path::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } = path
+            {
+                let is_nondeterminism_tag = NONDETERMINISM_TAGS
+                    .iter()
+                    .any(|tag| **selector == PathSelector::Tag(*tag));
+                if !is_nondeterminism_tag || tag_value.as_bool_if_known() != Some(true) {
+                    continue;
+                }
+                if referenced_paths
+                    .iter()
+                    .any(|p| *p == **qualifier || p.is_rooted_by(qualifier))
+                    || values
+                        .iter()
+                        .any(|value| value.expression.refers_to_variable_rooted_by(qualifier))
+                {
+                    depends_on_nondeterminism = true;
+                    spans.extend(tag_value.provenance.last().cloned());
+                }
+            }
+        }
+        if !depends_on_nondeterminism {
+            return;
+        }
+        spans.sort();
+        spans.dedup();
+        let span = self.current_span;
+        let mut warning = self.struct_span_warn_for_user(
+            span,
+            "a value that depends on a source of nondeterminism is written to an output",
+        );
+        for source_span in spans.into_iter() {
+            warning.span_note(source_span, "source of nondeterminism");
+        }
+        warning.code(Confidence::Definite.diagnostic_code());
+        (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+    }
+
+    /// With --check-determinism, reports the iterations over a HashMap or HashSet in the body of
+    /// the function on which its result depends, i.e. the locations of the UnorderedIteration
+    /// tags of the result and of the values that it refers to. Iterations in the bodies of called
+    /// functions are reported where they occur, so the tags that come from their summaries, which
+    /// do not record locations, are not reported again.
+    fn check_result_determinism(&mut self) {
+        let result_path = Path::LocalVariable { ordinal: 0 };
+        let result = self.exit_environment.value_at(&result_path);
+        let body_span = self.mir.span;
+        let mut spans: Vec<syntax_pos::Span> = self
+            .exit_environment
+            .value_map
+            .iter()
+            .filter_map(|(path, tag)| match path {
+                Path::QualifiedPath {
+                    qualifier,
+                    selector,
+                    ..
+                } if **selector == PathSelector::Tag(Tag::UnorderedIteration)
+                    && tag.as_bool_if_known() == Some(true)
+                    && (**qualifier == result_path
+                        || qualifier.is_rooted_by(&result_path)
+                        || result.map_or(false, |result| {
+                            result
+                                .domain
+                                .expression
+                                .refers_to_variable_rooted_by(qualifier)
+                        })) =>
+                {
+                    tag.provenance.last().cloned()
+                }
+                _ => None,
+            })
+            .filter(|span| body_span.contains(*span))
+            .collect();
+        spans.sort();
+        spans.dedup();
+        for span in spans.into_iter() {
+            let mut warning = self.struct_span_warn_for_user(
+                span,
+                "the result of the function depends on the iteration order of a hash map or set",
+            );
+            warning.code(Confidence::Definite.diagnostic_code());
+            (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+        }
+    }
+
+    /// Calls to channel() and friends return a pair of endpoints, a sender and a receiver, of a new
+    /// channel. The channel is identified by a fresh heap address, which is the value of the
+    /// Channel tag of both endpoints. The ReceiverDropped tag of the channel is initially false.
+    fn model_channel_new(&mut self, destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>) {
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            let channel = self.get_new_heap_address();
+            if let Expression::AbstractHeapAddress(ordinal) = channel.domain.expression {
+                for endpoint in 0..2 {
+                    let endpoint_path = Self::get_field_path(target_path.clone(), endpoint);
+                    let tag_path = Self::get_tag_path(endpoint_path, Tag::Channel);
+                    self.current_environment
+                        .update_value_at(tag_path, channel.clone());
+                }
+                let channel_path = Path::AbstractHeapAddress { ordinal };
+                let tag_path = Self::get_tag_path(channel_path, Tag::ReceiverDropped);
+                self.current_environment
+                    .update_value_at(tag_path, abstract_value::FALSE);
+            }
+        }
+    }
+
+    /// Calls to send() on the sender of a channel fail if the receiver of the channel has been
+    /// dropped. This is reported if it is the case on every path that reaches the call.
+    fn model_channel_send(&mut self, actual_args: &[AbstractValue]) {
+        if !self.check_for_errors {
+            return;
+        }
+        let channel_path = match actual_args
+            .get(0)
+            .and_then(Self::get_referenced_path)
+            .and_then(|sender_path| self.get_channel_path(sender_path))
+        {
+            Some(channel_path) => channel_path,
+            None => return,
+        };
+        let tag_path = Self::get_tag_path(channel_path, Tag::ReceiverDropped);
+        let receiver_dropped = self.lookup_path_and_refine_result(tag_path, ExpressionType::Bool);
+        if receiver_dropped.as_bool_if_known() != Some(true)
+            || self.current_environment.entry_condition.as_bool_if_known() == Some(false)
+        {
+            return;
+        }
+        let span = self.current_span;
+        let mut warning = self.struct_span_warn_for_user(
+            span,
+            "the receiver of this channel has been dropped, so sending on it fails",
+        );
+        warning.code(Confidence::Definite.diagnostic_code());
+        (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+    }
+
+    /// If the place holds the receiver of a channel, records that it has been dropped, so that
+    /// later sends on the channel are known to fail.
+    fn drop_channel_receiver(&mut self, place: &mir::Place<'tcx>) {
+        let is_receiver = match self.get_rustc_place_type(place) {
+            TyKind::Adt(def, _) => {
+                CHANNEL_RECEIVERS.contains(&utils::summary_key_str(&self.tcx, def.did).as_str())
+            }
+            _ => false,
+        };
+        if !is_receiver {
+            return;
+        }
+        let receiver_path = self.visit_place(place);
+        if let Some(channel_path) = self.get_channel_path(receiver_path) {
+            let tag_path = Self::get_tag_path(channel_path, Tag::ReceiverDropped);
+            self.current_environment
+                .update_value_at(tag_path, abstract_value::TRUE);
+        }
+    }
+
+    /// Returns the path of the heap block that identifies the channel of the given endpoint,
+    /// if it is known.
+    fn get_channel_path(&self, endpoint_path: Path) -> Option<Path> {
+        self.get_tagged_heap_block_path(endpoint_path, Tag::Channel)
+    }
+
+    /// Returns the path of the heap block that is the value of the given tag of the value at the
+    /// given path, if it is known.
+    fn get_tagged_heap_block_path(&self, path: Path, tag: Tag) -> Option<Path> {
+        let tag_path = Self::get_tag_path(path, tag);
+        match self.current_environment.value_at(&tag_path) {
+            Some(AbstractValue {
+                domain:
+                    AbstractDomain {
+                        expression: Expression::AbstractHeapAddress(ordinal),
+                        ..
+                    },
+                ..
+            }) => Some(Path::AbstractHeapAddress { ordinal: *ordinal }),
+            _ => None,
+        }
+    }
+
+    /// Calls to Rc::new and Arc::new return a pointer to a new heap block, which is identified by
+    /// a fresh heap address. The address is the value of the SharedBlock tag of the pointer, and
+    /// the value of the block is the value given to the call. The StrongCount tag of the block is
+    /// initially 1. See Environment::update_value_at for how the count limits updates of the block.
+    fn model_shared_pointer_new(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let place = match destination {
+            Some((place, _)) if self.is_shared_pointer(self.get_rustc_place_type(place)) => place,
+            _ => return,
+        };
+        let target_path = self.visit_place(place);
+        let block = self.get_new_heap_address();
+        if let Expression::AbstractHeapAddress(ordinal) = block.domain.expression {
+            let block_path = Path::AbstractHeapAddress { ordinal };
+            if let Some(value) = args.get(0) {
+                self.visit_use(block_path.clone(), value);
+            }
+            let count_path = Self::get_tag_path(block_path, Tag::StrongCount);
+            self.current_environment
+                .update_value_at(count_path, ConstantDomain::U128(1).into());
+            let tag_path = Self::get_tag_path(target_path, Tag::SharedBlock);
+            self.current_environment.update_value_at(tag_path, block);
+        }
+    }
+
+    /// Calls to clone() on an Rc or Arc return a pointer to the same heap block, which now has one
+    /// more pointer to it.
+    fn model_shared_pointer_clone(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let block_path = match self.get_shared_block_of_argument(args, actual_args) {
+            Some(block_path) => block_path,
+            None => return,
+        };
+        let count_path = Self::get_tag_path(block_path.clone(), Tag::StrongCount);
+        let count = self.lookup_path_and_refine_result(count_path.clone(), ExpressionType::Usize);
+        let one: AbstractValue = ConstantDomain::U128(1).into();
+        self.current_environment
+            .update_value_at(count_path, count.add(&one, None));
+        if let (Some((place, _)), Path::AbstractHeapAddress { ordinal }) = (destination, block_path)
+        {
+            let target_path = self.visit_place(place);
+            let tag_path = Self::get_tag_path(target_path, Tag::SharedBlock);
+            self.current_environment
+                .update_value_at(tag_path, Expression::AbstractHeapAddress(ordinal).into());
+        }
+    }
+
+    /// Dereferencing an Rc or Arc gives a reference to the value in its heap block, so that all
+    /// clones of the pointer see the same value.
+    fn model_shared_pointer_deref(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let block_path = self.get_shared_block_of_argument(args, actual_args);
+        if let (Some(block_path), Some((place, _))) = (block_path, destination) {
+            let target_path = self.visit_place(place);
+            self.current_environment
+                .update_value_at(target_path, Expression::Reference(block_path).into());
+        }
+    }
+
+    /// Calls to Rc::strong_count and Arc::strong_count return the StrongCount tag of the heap
+    /// block of the pointer.
+    fn model_shared_pointer_strong_count(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let block_path = self.get_shared_block_of_argument(args, actual_args);
+        if let (Some(block_path), Some((place, _))) = (block_path, destination) {
+            let count_path = Self::get_tag_path(block_path, Tag::StrongCount);
+            let count = self.lookup_path_and_refine_result(count_path, ExpressionType::Usize);
+            let target_path = self.visit_place(place);
+            self.current_environment.update_value_at(target_path, count);
+        }
+    }
+
+    /// Returns the path of the heap block of the Rc or Arc that is referenced by the first of the
+    /// given arguments, if it is known.
+    fn get_shared_block_of_argument(
+        &self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+    ) -> Option<Path> {
+        match (args.get(0), actual_args.get(0)) {
+            (Some(arg), Some(value)) if self.is_shared_pointer(&arg.ty(self.mir, self.tcx).sty) => {
+                let pointer_path = Self::get_referenced_path(value)?;
+                self.get_tagged_heap_block_path(pointer_path, Tag::SharedBlock)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the given type is Rc or Arc, or a reference to one of them.
+    fn is_shared_pointer(&self, ty: &TyKind<'tcx>) -> bool {
+        match ty {
+            TyKind::Ref(_, referenced_type, _) => self.is_shared_pointer(&referenced_type.sty),
+            TyKind::Adt(def, _) => {
+                SHARED_POINTERS.contains(&utils::summary_key_str(&self.tcx, def.did).as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// If the place holds an Rc or Arc, its heap block has one pointer less once it is dropped.
+    /// If the place holds a value that contains such pointers, it is no longer known how many
+    /// pointers their heap blocks have, since the value may not have owned them all.
+    fn drop_shared_pointer(&mut self, place: &mir::Place<'tcx>) {
+        let path = self.visit_place(place);
+        let one: AbstractValue = ConstantDomain::U128(1).into();
+        for (block_path, is_pointer) in self.get_shared_blocks_of(&path) {
+            let count_path = Self::get_tag_path(block_path, Tag::StrongCount);
+            let count = if is_pointer {
+                self.lookup_path_and_refine_result(count_path.clone(), ExpressionType::Usize)
+                    .sub(&one, None)
+            } else {
+                abstract_value::TOP
+            };
+            self.current_environment.update_value_at(count_path, count);
+        }
+    }
+
+    /// Arguments that are moved into a function that is not modeled may be dropped or cloned by
+    /// the function, so it is no longer known how many pointers the heap blocks of the Rc and Arc
+    /// pointers that they contain have.
+    fn forget_strong_counts_of_moved_arguments(&mut self, args: &[mir::Operand<'tcx>]) {
+        for arg in args.iter() {
+            if let mir::Operand::Move(place) = arg {
+                let path = self.visit_place(place);
+                for (block_path, _) in self.get_shared_blocks_of(&path) {
+                    let count_path = Self::get_tag_path(block_path, Tag::StrongCount);
+                    self.current_environment
+                        .update_value_at(count_path, abstract_value::TOP);
+                }
+            }
+        }
+    }
+
+    /// Rc and Arc pointers that are stored in a place that is reachable from a parameter or a
+    /// static variable escape from the function, so it is no longer known how many pointers their
+    /// heap blocks have. See Environment::is_in_shared_block for how this limits their updates.
+    fn forget_strong_counts_of_escaping_pointers(&mut self, path: &Path) {
+        let escapes = match path.get_root() {
+            Path::LocalVariable { ordinal } => 0 < *ordinal && *ordinal <= self.mir.arg_count,
+            Path::StaticVariable { .. } => true,
+            _ => false,
+        };
+        if !escapes {
+            return;
+        }
+        for (block_path, _) in self.get_shared_blocks_of(path) {
+            let count_path = Self::get_tag_path(block_path, Tag::StrongCount);
+            self.current_environment
+                .update_value_at(count_path, abstract_value::TOP);
+        }
+    }
+
+    /// Returns the paths of the heap blocks of the Rc and Arc pointers that are part of the value
+    /// at the given path, along with true if the pointer is the value itself.
+    fn get_shared_blocks_of(&self, path: &Path) -> Vec<(Path, bool)> {
+        self.current_environment
+            .value_map
+            .iter()
+            .filter_map(
+                |(tag_path, value)| match (tag_path, &value.domain.expression) {
+                    (
+                        Path::QualifiedPath {
+                            qualifier,
+                            selector,
+                            ..
+                        },
+                        Expression::AbstractHeapAddress(ordinal),
+                    ) if **selector == PathSelector::Tag(Tag::SharedBlock)
+                        && (**qualifier == *path || qualifier.is_rooted_by(path)) =>
+                    {
+                        Some((
+                            Path::AbstractHeapAddress { ordinal: *ordinal },
+                            **qualifier == *path,
+                        ))
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    /// The accessors of niche types like NonZeroU32 and NonNull return values that are
+    /// known to be non zero (non null). This adds that fact to the condition under which
+    /// control reaches the normal destination of the call.
+    fn assume_non_zero_result(
+        &mut self,
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let Some((place, target)) = destination {
+            let target_path = self.visit_place(place);
+            let result_type = self.get_place_type(place);
+            let result = self.lookup_path_and_refine_result(target_path, result_type);
+            let zero: AbstractValue = ConstantDomain::U128(0).into();
+            let non_zero = result.equals(&zero, None).not(None);
+            let exit_condition = self
+                .current_environment
+                .exit_conditions
+                .get(target)
+                .unwrap_or(&self.current_environment.entry_condition)
+                .and(&non_zero, None);
+            self.current_environment
+                .exit_conditions
+                .insert(*target, exit_condition);
+        }
+    }
+
+    /// Calls to str::from_utf8 validate the bytes referenced by the first argument. If the result
+    /// is Ok, the bytes are valid UTF-8. This is recorded by giving the bytes a Utf8Valid tag whose
+    /// value is the condition under which the result is Ok. Code that is only reached when the
+    /// result is Ok has this condition as part of its entry condition, so for such code the tag
+    /// refines to true.
+    fn tag_validated_bytes(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(bytes), Some((place, _))) = (actual_args.get(0), destination) {
+            if let Expression::Reference(bytes_path) = &bytes.domain.expression {
+                let target_path = self.visit_place(place);
+                let target_type = self.get_place_type(place);
+                let discriminant = self.lookup_path_and_refine_result(target_path, target_type);
+                let ok_discriminant: AbstractValue = ConstantDomain::U128(0).into();
+                let tag_path = Path::QualifiedPath {
+                    length: bytes_path.path_length() + 1,
+                    qualifier: box bytes_path.clone(),
+                    selector: box PathSelector::Tag(Tag::Utf8Valid),
+                };
+                self.current_environment.update_value_at(
+                    tag_path,
+                    discriminant.equals(&ok_discriminant, Some(self.current_span)),
+                );
+            }
+        }
+    }
+
+    /// Calls to iter() on an array or slice, and the equivalent calls to into_iter() made by for
+    /// loops, return an iterator whose length is the length of the collection and which has not
+    /// produced any elements yet.
+    fn model_collection_iterator(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(arg), Some(value), Some((place, _))) =
+            (args.get(0), actual_args.get(0), destination)
+        {
+            if let Some(collection_path) = self.get_collection_path(arg, value) {
+                let length = self.get_len(collection_path);
+                let target_path = self.visit_place(place);
+                let length_path = Path::QualifiedPath {
+                    length: target_path.path_length() + 1,
+                    qualifier: box target_path.clone(),
+                    selector: box PathSelector::ArrayLength,
+                };
+                let count_path = Path::QualifiedPath {
+                    length: target_path.path_length() + 1,
+                    qualifier: box target_path,
+                    selector: box PathSelector::IterationCount,
+                };
+                self.current_environment
+                    .update_value_at(length_path, length);
+                self.current_environment
+                    .update_value_at(count_path, ConstantDomain::U128(0).into());
+            }
+        }
+    }
+
+    /// Calls to IntoIterator::into_iter, as made by for loops, return an iterator over the
+    /// elements of a referenced array or slice, or return the given iterator itself.
+    /// Only iterators whose count is being tracked are modeled.
+    fn model_into_iter(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(arg), Some(value)) = (args.get(0), actual_args.get(0)) {
+            if self.get_collection_path(arg, value).is_some() {
+                self.model_collection_iterator(args, actual_args, destination);
+                return;
+            }
+        }
+        if let (Some(mir::Operand::Move(iterator)), Some((place, _))) = (args.get(0), destination) {
+            let iterator_path = self.visit_place(iterator);
+            let count_path = Path::QualifiedPath {
+                length: iterator_path.path_length() + 1,
+                qualifier: box iterator_path,
+                selector: box PathSelector::IterationCount,
+            };
+            if self.current_environment.value_at(&count_path).is_some() {
+                let target_path = self.visit_place(place);
+                self.visit_used_move(target_path, iterator);
+            }
+        }
+    }
+
+    /// Calls to next() on an iterator over an array or slice produce Some element as long as the
+    /// number of elements produced so far is less than the length of the collection, and None once
+    /// it is equal to the length. The count never exceeds the length, so the discriminant of the
+    /// result is modeled as count < length ? Some : (count == length ? None : impossible).
+    /// This way the condition under which the result is None includes count == length, which
+    /// allows values that have been expressed in terms of the count to be refined after a loop.
+    fn model_iterator_next(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let iterator_path = match actual_args.get(0).and_then(Self::get_referenced_path) {
+            Some(iterator_path) => iterator_path,
+            None => return,
+        };
+        let count_path = Path::QualifiedPath {
+            length: iterator_path.path_length() + 1,
+            qualifier: box iterator_path.clone(),
+            selector: box PathSelector::IterationCount,
+        };
+        if self.current_environment.value_at(&count_path).is_none() {
+            // Not an iterator whose count is being tracked.
+            return;
+        }
+        if let Some((place, _)) = destination {
+            let mut count =
+                self.lookup_path_and_refine_result(count_path.clone(), ExpressionType::Usize);
+            let mut length = self.get_len(iterator_path);
+            let has_next = count.less_than(&mut length, None);
+            let at_end = count.equals(&length, None);
+            let none_or_impossible: AbstractDomain = Expression::ConditionalExpression {
+                condition: box at_end.domain,
+                consequent: box ConstantDomain::U128(0).into(),
+                alternate: box abstract_domains::BOTTOM,
+            }
+            .into();
+            let discriminant: AbstractValue = Expression::ConditionalExpression {
+                condition: box has_next.domain.clone(),
+                consequent: box ConstantDomain::U128(1).into(),
+                alternate: box none_or_impossible,
+            }
+            .into();
+            let target_path = self.visit_place(place);
+            self.current_environment
+                .update_value_at(target_path, discriminant.with_provenance(self.current_span));
+            let one: AbstractValue = ConstantDomain::U128(1).into();
+            let next_count = count.add(&one, None).join(&count, &has_next);
+            self.current_environment
+                .update_value_at(count_path, next_count);
+        }
+    }
+
+    /// Returns the number of elements that the iterator given by the argument is yet to produce,
+    /// if it is known. This is the case for iterators whose count is being tracked, such as
+    /// iterators over arrays and slices and the adapters of such iterators, and for ranges, which
+    /// produce end - start elements if start < end and none otherwise.
+    fn get_remaining_length(
+        &mut self,
+        arg: &mir::Operand<'tcx>,
+        value: &AbstractValue,
+    ) -> Option<AbstractValue> {
+        let iterator_path = Self::get_referenced_path(value)?;
+        let count_path = Path::QualifiedPath {
+            length: iterator_path.path_length() + 1,
+            qualifier: box iterator_path.clone(),
+            selector: box PathSelector::IterationCount,
+        };
+        if self.current_environment.value_at(&count_path).is_some() {
+            let count = self.lookup_path_and_refine_result(count_path, ExpressionType::Usize);
+            let length = self.get_len(iterator_path);
+            return Some(length.sub(&count, None));
+        }
+        let (mut start, mut end) = self.get_range_bounds(arg, iterator_path)?;
+        let has_elements = start.less_than(&mut end, None);
+        let zero: AbstractValue = ConstantDomain::U128(0).into();
+        Some(end.sub(&start, None).join(&zero, &has_elements))
+    }
+
+    /// Returns the start and end of the range given by the argument, which is at the given path,
+    /// or None if the argument is not a range.
+    fn get_range_bounds(
+        &mut self,
+        arg: &mir::Operand<'tcx>,
+        range_path: Path,
+    ) -> Option<(AbstractValue, AbstractValue)> {
+        let element_type: ExpressionType = match arg.ty(&self.mir.local_decls, self.tcx).sty {
+            TyKind::Adt(def, substs)
+                if utils::summary_key_str(&self.tcx, def.did).as_str() == RANGE_TYPE =>
+            {
+                (&substs.type_at(0).sty).into()
+            }
+            _ => return None,
+        };
+        let start = self.lookup_path_and_refine_result(
+            Self::get_field_path(range_path.clone(), 0),
+            element_type.clone(),
+        );
+        let end =
+            self.lookup_path_and_refine_result(Self::get_field_path(range_path, 1), element_type);
+        Some((start, end))
+    }
+
+    /// Calls to map(), filter() and friends return an iterator that produces an element for each
+    /// element of the iterator given by the first argument, or for some of them. If the number of
+    /// elements of that iterator is known, the result is an iterator whose count is tracked and
+    /// whose length is the same (for map) or at most the same (for filter). This way loops over
+    /// chains of adapters, and the consumers of such chains, know how many elements there are.
+    fn model_iterator_adapter(
+        &mut self,
+        known_function: KnownFunction,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(arg), Some(value), Some((place, target))) =
+            (args.get(0), actual_args.get(0), destination)
+        {
+            let remaining_length = match self.get_remaining_length(arg, value) {
+                Some(length) => length,
+                None => return,
+            };
+            let target_path = self.visit_place(place);
+            let length_path = Path::QualifiedPath {
+                length: target_path.path_length() + 1,
+                qualifier: box target_path.clone(),
+                selector: box PathSelector::ArrayLength,
+            };
+            let count_path = Path::QualifiedPath {
+                length: target_path.path_length() + 1,
+                qualifier: box target_path,
+                selector: box PathSelector::IterationCount,
+            };
+            if known_function == KnownFunction::CoreIterFilter {
+                // The length is not known, but it does not exceed that of the iterator.
+                let mut length =
+                    self.lookup_path_and_refine_result(length_path, ExpressionType::Usize);
+                let at_most_remaining = length.less_or_equal(&mut remaining_length.clone(), None);
+                let exit_condition = self
+                    .current_environment
+                    .exit_conditions
+                    .get(target)
+                    .unwrap_or(&self.current_environment.entry_condition)
+                    .and(&at_most_remaining, None);
+                self.current_environment
+                    .exit_conditions
+                    .insert(*target, exit_condition);
+            } else {
+                self.current_environment
+                    .update_value_at(length_path, remaining_length);
+            }
+            self.current_environment
+                .update_value_at(count_path, ConstantDomain::U128(0).into());
+        }
+    }
+
+    /// Calls to collect(), fold() and sum() consume the iterator given by the first argument.
+    /// If the number of elements that it produces is known, their results are summarized without
+    /// analyzing a loop: collecting into a Vec, VecDeque or BinaryHeap yields a collection with
+    /// that many elements and folding no elements yields the initial value. The sum of a range
+    /// is given in closed form.
+    fn model_iterator_consumer(
+        &mut self,
+        known_function: KnownFunction,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(arg), Some(value), Some((place, _))) =
+            (args.get(0), actual_args.get(0), destination)
+        {
+            let target_path = self.visit_place(place);
+            match known_function {
+                KnownFunction::CoreIterCollect => {
+                    let is_sequence = match self.get_rustc_place_type(place) {
+                        TyKind::Adt(def, _) => SEQUENCE_COLLECTIONS
+                            .contains(&utils::summary_key_str(&self.tcx, def.did).as_str()),
+                        _ => false,
+                    };
+                    if !is_sequence {
+                        return;
+                    }
+                    if let Some(remaining_length) = self.get_remaining_length(arg, value) {
+                        let length_path = Path::QualifiedPath {
+                            length: target_path.path_length() + 1,
+                            qualifier: box target_path,
+                            selector: box PathSelector::ArrayLength,
+                        };
+                        self.current_environment
+                            .update_value_at(length_path, remaining_length);
+                    }
+                }
+                KnownFunction::CoreIterFold => {
+                    if let (Some(remaining_length), Some(initial_value)) =
+                        (self.get_remaining_length(arg, value), actual_args.get(1))
+                    {
+                        let zero: AbstractValue = ConstantDomain::U128(0).into();
+                        let is_empty = remaining_length.equals(&zero, None);
+                        let result = initial_value.join(&abstract_value::TOP, &is_empty);
+                        self.current_environment
+                            .update_value_at(target_path, result);
+                    }
+                }
+                KnownFunction::CoreIterSum => {
+                    let range_path = match Self::get_referenced_path(value) {
+                        Some(path) => path,
+                        None => return,
+                    };
+                    if let Some((mut start, mut end)) = self.get_range_bounds(arg, range_path) {
+                        // start + (start + 1) + ... + (end - 1) = (end - start) * (start + end - 1) / 2
+                        let has_elements = start.less_than(&mut end, None);
+                        let one: AbstractValue = ConstantDomain::U128(1).into();
+                        let two: AbstractValue = ConstantDomain::U128(2).into();
+                        let zero: AbstractValue = ConstantDomain::U128(0).into();
+                        let sum = end
+                            .sub(&start, None)
+                            .mul(&start.add(&end, None).sub(&one, None), None)
+                            .div(&two, None);
+                        self.current_environment.update_value_at(
+                            target_path,
+                            sum.join(&zero, &has_elements)
+                                .with_provenance(self.current_span),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The std collections (Vec, VecDeque, BinaryHeap, BTreeSet, HashMap and BTreeMap) keep track
+    /// of their lengths with the ArrayLength selector of the collection, as arrays and slices do.
+    /// Sets also keep track of the values that are known to be members, or known not to be
+    /// members, with the Index selector of the set, where the value at set.Index(value) is true for
+    /// members. Maps keep track of their keys in the same way (see model_map_operation).
+    fn model_collection_operation(
+        &mut self,
+        known_function: KnownFunction,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let place = match destination {
+            Some((place, _)) => place,
+            None => return,
+        };
+        let zero: AbstractValue = ConstantDomain::U128(0).into();
+        let one: AbstractValue = ConstantDomain::U128(1).into();
+        if known_function == KnownFunction::AllocCollectionsNew {
+            if self.is_modeled_collection(self.get_rustc_place_type(place)) {
+                let target_path = self.visit_place(place);
+                let length_path = Self::get_length_path(target_path);
+                self.current_environment.update_value_at(length_path, zero);
+            }
+            return;
+        }
+        let collection_path = match (args.get(0), actual_args.get(0)) {
+            (Some(arg), Some(value))
+                if self.is_modeled_collection(&arg.ty(self.mir, self.tcx).sty) =>
+            {
+                match Self::get_referenced_path(value) {
+                    Some(collection_path) => collection_path,
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let length_path = Self::get_length_path(collection_path.clone());
+        let mut length = self.get_len(collection_path.clone());
+        let target_path = self.visit_place(place);
+        match known_function {
+            KnownFunction::AllocCollectionsClear => {
+                self.forget_set_members(&collection_path, false);
+                self.current_environment
+                    .forget_value_at(&Self::get_default_entry_path(collection_path));
+                self.current_environment.update_value_at(length_path, zero);
+            }
+            KnownFunction::AllocCollectionsIsEmpty => {
+                self.current_environment
+                    .update_value_at(target_path, length.equals(&zero, None));
+            }
+            KnownFunction::AllocCollectionsLen => {
+                self.current_environment
+                    .update_value_at(target_path, length);
+            }
+            KnownFunction::AllocCollectionsPeek | KnownFunction::AllocCollectionsPop => {
+                let mut zero = zero;
+                let non_empty = zero.less_than(&mut length, None);
+                let discriminant: AbstractValue = Expression::ConditionalExpression {
+                    condition: box non_empty.domain.clone(),
+                    consequent: box ConstantDomain::U128(1).into(),
+                    alternate: box ConstantDomain::U128(0).into(),
+                }
+                .into();
+                self.current_environment
+                    .update_value_at(target_path, discriminant);
+                if known_function == KnownFunction::AllocCollectionsPop {
+                    let new_length = length.sub(&one, None).join(&length, &non_empty);
+                    self.current_environment
+                        .update_value_at(length_path, new_length);
+                }
+            }
+            KnownFunction::AllocCollectionsPush => {
+                self.current_environment
+                    .update_value_at(length_path, length.add(&one, None));
+            }
+            KnownFunction::AllocCollectionsContains
+            | KnownFunction::AllocCollectionsInsert
+            | KnownFunction::AllocCollectionsRemove => {
+                let member = match (args.get(1), actual_args.get(1)) {
+                    (Some(arg), Some(value)) => self.get_set_member(arg, value),
+                    _ => return,
+                };
+                let member_path = Path::QualifiedPath {
+                    length: collection_path.path_length() + 1,
+                    qualifier: box collection_path.clone(),
+                    selector: box PathSelector::Index(box member),
+                };
+                let was_member =
+                    self.lookup_path_and_refine_result(member_path.clone(), ExpressionType::Bool);
+                match known_function {
+                    KnownFunction::AllocCollectionsContains => {
+                        self.current_environment
+                            .update_value_at(target_path, was_member);
+                    }
+                    KnownFunction::AllocCollectionsInsert => {
+                        // Values that are known not to be members may be equal to the new member.
+                        self.forget_set_members(&collection_path, true);
+                        let inserted = was_member.not(None);
+                        let new_length = length.add(&one, None).join(&length, &inserted);
+                        self.current_environment
+                            .update_value_at(target_path, inserted);
+                        self.current_environment
+                            .update_value_at(member_path, abstract_value::TRUE);
+                        self.current_environment
+                            .update_value_at(length_path, new_length);
+                    }
+                    _ => {
+                        // Values that are known to be members may be equal to the removed value.
+                        self.forget_set_members(&collection_path, false);
+                        let new_length = length.sub(&one, None).join(&length, &was_member);
+                        self.current_environment
+                            .update_value_at(target_path, was_member);
+                        self.current_environment
+                            .update_value_at(member_path, abstract_value::FALSE);
+                        self.current_environment
+                            .update_value_at(length_path, new_length);
+                    }
+                }
+            }
+            KnownFunction::AllocMapGet
+            | KnownFunction::AllocMapInsert
+            | KnownFunction::AllocMapRemove => {
+                self.model_map_operation(
+                    known_function,
+                    collection_path,
+                    args,
+                    actual_args,
+                    target_path,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Maps keep track of the keys that are known to be present, or known to be absent, like sets
+    /// do with their members, and of the values of the entries with these keys, which are at
+    /// map.Index(key).Field(0). Keys that are not constants may be equal to any other key, so
+    /// their entries do not keep the values of the entries with other keys. Instead, the values
+    /// of entries with such keys are summarized by the default entry of the map, as long as they
+    /// are all the same. The default entry provides the value of entries that have no value of
+    /// their own, so that, for example, a map that is only ever given the value true has the
+    /// value true for whatever key is present.
+    fn model_map_operation(
+        &mut self,
+        known_function: KnownFunction,
+        map_path: Path,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        target_path: Path,
+    ) {
+        let key = match (args.get(1), actual_args.get(1)) {
+            (Some(arg), Some(value)) => self.get_set_member(arg, value),
+            _ => return,
+        };
+        let key_is_constant = match key.domain.expression {
+            Expression::CompileTimeConstant(..) => true,
+            _ => false,
+        };
+        let value_type = match args.get(0).map(|arg| &arg.ty(self.mir, self.tcx).sty) {
+            Some(TyKind::Ref(_, map_type, _)) => match map_type.sty {
+                TyKind::Adt(_, substs) => (&substs.type_at(1).sty).into(),
+                _ => ExpressionType::NonPrimitive,
+            },
+            _ => ExpressionType::NonPrimitive,
+        };
+        let member_path = Path::QualifiedPath {
+            length: map_path.path_length() + 1,
+            qualifier: box map_path.clone(),
+            selector: box PathSelector::Index(box key),
+        };
+        let entry_path = Self::get_field_path(member_path.clone(), 0);
+        let default_entry_path = Self::get_default_entry_path(map_path.clone());
+        let length_path = Self::get_length_path(map_path.clone());
+        let length = self.get_len(map_path.clone());
+        let one: AbstractValue = ConstantDomain::U128(1).into();
+        let was_member =
+            self.lookup_path_and_refine_result(member_path.clone(), ExpressionType::Bool);
+        let old_value = match (
+            self.current_environment.value_at(&entry_path),
+            self.current_environment.value_at(&default_entry_path),
+        ) {
+            (Some(value), _) | (None, Some(value)) => value.clone(),
+            (None, None) => self.lookup_path_and_refine_result(entry_path.clone(), value_type),
+        };
+        let discriminant: AbstractValue = Expression::ConditionalExpression {
+            condition: box was_member.domain.clone(),
+            consequent: box ConstantDomain::U128(1).into(),
+            alternate: box ConstantDomain::U128(0).into(),
+        }
+        .into();
+        let some_value_path = Self::get_field_path(
+            Path::QualifiedPath {
+                length: target_path.path_length() + 1,
+                qualifier: box target_path.clone(),
+                selector: box PathSelector::Downcast(1),
+            },
+            0,
+        );
+        self.current_environment
+            .update_value_at(target_path, discriminant);
+        match known_function {
+            KnownFunction::AllocMapGet => {
+                self.current_environment
+                    .update_value_at(entry_path.clone(), old_value);
+                self.current_environment
+                    .update_value_at(some_value_path, Expression::Reference(entry_path).into());
+            }
+            KnownFunction::AllocMapInsert => {
+                let value = match actual_args.get(2) {
+                    Some(value) => value.clone(),
+                    None => return,
+                };
+                // Keys that are known to be absent may be equal to the new key, and so may the
+                // keys of the entries whose values are kept, unless both keys are constants.
+                self.forget_set_members(&map_path, true);
+                self.forget_map_entries(&map_path, key_is_constant);
+                if !key_is_constant {
+                    let is_same_default = self
+                        .current_environment
+                        .value_at(&default_entry_path)
+                        .map_or(true, |default_value| default_value.domain == value.domain);
+                    if is_same_default {
+                        self.current_environment
+                            .update_value_at(default_entry_path, value.clone());
+                    } else {
+                        self.current_environment
+                            .forget_value_at(&default_entry_path);
+                    }
+                }
+                let new_length = length.join(&length.add(&one, None), &was_member);
+                self.current_environment
+                    .update_value_at(some_value_path, old_value);
+                self.current_environment
+                    .update_value_at(member_path, abstract_value::TRUE);
+                self.current_environment.update_value_at(entry_path, value);
+                self.current_environment
+                    .update_value_at(length_path, new_length);
+            }
+            _ => {
+                // Keys that are known to be present may be equal to the removed key.
+                self.forget_set_members(&map_path, false);
+                let new_length = length.sub(&one, None).join(&length, &was_member);
+                self.current_environment
+                    .update_value_at(some_value_path, old_value);
+                self.current_environment
+                    .update_value_at(member_path, abstract_value::FALSE);
+                self.current_environment
+                    .update_value_at(length_path, new_length);
+            }
+        }
+    }
+
+    /// Removes the values of the entries of the map at the given path, except, if
+    /// keep_constant_keys is true, for the entries whose keys are constants.
+    fn forget_map_entries(&mut self, map_path: &Path, keep_constant_keys: bool) {
+        let stale_paths: Vec<Path> = self
+            .current_environment
+            .value_map
+            .keys()
+            .filter(|path| match path {
+                Path::QualifiedPath { qualifier, .. } => match &**qualifier {
+                    Path::QualifiedPath {
+                        qualifier: member_qualifier,
+                        selector: member_selector,
+                        ..
+                    } if **member_qualifier == *map_path => match &**member_selector {
+                        PathSelector::Index(key) => match key.domain.expression {
+                            Expression::CompileTimeConstant(..) => !keep_constant_keys,
+                            _ => true,
+                        },
+                        _ => false,
+                    },
+                    _ => false,
+                },
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        for path in stale_paths.iter() {
+            self.current_environment.forget_value_at(path);
+        }
+    }
+
+    /// Returns the path of the default entry of the map at the given path, whose value is the
+    /// value of the entries that have no value of their own.
+    fn get_default_entry_path(map_path: Path) -> Path {
+        Path::QualifiedPath {
+            length: map_path.path_length() + 1,
+            qualifier: box map_path,
+            selector: box PathSelector::SmashedElement,
+        }
+    }
+
+    /// True if the given type is, or refers to, one of the std collections that are modeled by
+    /// model_collection_operation.
+    fn is_modeled_collection(&self, ty: &TyKind<'tcx>) -> bool {
+        match ty {
+            TyKind::Ref(_, referenced_type, _) => self.is_modeled_collection(&referenced_type.sty),
+            TyKind::Adt(def, _) => {
+                MODELED_COLLECTIONS.contains(&utils::summary_key_str(&self.tcx, def.did).as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the value that is given to a set operation by the given argument, either directly
+    /// or by reference.
+    fn get_set_member(&mut self, arg: &mir::Operand<'tcx>, value: &AbstractValue) -> AbstractValue {
+        match (&arg.ty(self.mir, self.tcx).sty, &value.domain.expression) {
+            (TyKind::Ref(_, referenced_type, _), Expression::Reference(path)) => {
+                let member_type: ExpressionType = (&referenced_type.sty).into();
+                self.lookup_path_and_refine_result(path.clone(), member_type)
+            }
+            _ => value.clone(),
+        }
+    }
+
+    /// Removes what is known about the members of the set at the given path, except, if
+    /// keep_members is true, for the values that are known to be members.
+    fn forget_set_members(&mut self, set_path: &Path, keep_members: bool) {
+        let stale_paths: Vec<Path> = self
+            .current_environment
+            .value_map
+            .iter()
+            .filter(|(path, value)| match path {
+                Path::QualifiedPath {
+                    qualifier,
+                    selector,
+                    ..
+                } if **qualifier == *set_path => match **selector {
+                    PathSelector::Index(..) => {
+                        !keep_members || value.as_bool_if_known() != Some(true)
+                    }
+                    _ => false,
+                },
+                _ => false,
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in stale_paths.iter() {
+            self.current_environment.forget_value_at(path);
+        }
+    }
+
+    /// Returns the path of the length of the array, slice or collection at the given path.
+    fn get_length_path(path: Path) -> Path {
+        Path::QualifiedPath {
+            length: path.path_length() + 1,
+            qualifier: box path,
+            selector: box PathSelector::ArrayLength,
+        }
+    }
+
+    /// Calls to len() on a slice return the length of the slice.
+    fn model_collection_length(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(arg), Some(value), Some((place, _))) =
+            (args.get(0), actual_args.get(0), destination)
+        {
+            if let Some(collection_path) = self.get_collection_path(arg, value) {
+                let length = self.get_len(collection_path);
+                let target_path = self.visit_place(place);
+                self.current_environment
+                    .update_value_at(target_path, length);
+            }
+        }
+    }
+
+    /// Calls to Index::index and IndexMut::index_mut with a range, such as `&buf[offset..offset + n]`,
+    /// on an array, slice or Vec, return a slice of length end - start and panic unless
+    /// start <= end <= length. The length of the resulting slice is tracked, so that reads from it,
+    /// and further slicing of it, can be checked in terms of the offsets and the length of the buffer.
+    fn model_range_index(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (collection_path, range_arg) = match (args.get(0), actual_args.get(0), args.get(1)) {
+            (Some(arg), Some(value), Some(range_arg)) => {
+                let collection_path = if self.is_modeled_collection(&arg.ty(self.mir, self.tcx).sty)
+                {
+                    Self::get_referenced_path(value)
+                } else {
+                    self.get_collection_path(arg, value)
+                };
+                match collection_path {
+                    Some(collection_path) => (collection_path, range_arg),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let length = self.get_len(collection_path);
+        let (start, end) = match self.get_start_and_end_of_range(range_arg, &length) {
+            Some(start_and_end) => start_and_end,
+            None => return,
+        };
+        if self.check_for_errors {
+            let mut start_bound = start.clone();
+            let mut end_bound = end.clone();
+            let mut length_bound = length.clone();
+            let in_range = start_bound
+                .less_or_equal(&mut end_bound, None)
+                .and(&end_bound.less_or_equal(&mut length_bound, None), None);
+            self.check_slice_bounds(&in_range, "slice range out of bounds");
+        }
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            self.current_environment.update_value_at(
+                Self::get_length_path(target_path),
+                end.sub(&start, Some(self.current_span)),
+            );
+        }
+    }
+
+    /// Returns the start and end of the range given by the argument, which is used to index a
+    /// collection of the given length, or None if the argument is not a range of a known type.
+    fn get_start_and_end_of_range(
+        &mut self,
+        range_arg: &mir::Operand<'tcx>,
+        length: &AbstractValue,
+    ) -> Option<(AbstractValue, AbstractValue)> {
+        let range_type = match range_arg.ty(self.mir, self.tcx).sty {
+            TyKind::Adt(def, _) => utils::summary_key_str(&self.tcx, def.did),
+            _ => return None,
+        };
+        let range_path = match range_arg {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => self.visit_place(place),
+            mir::Operand::Constant(..) => return None,
+        };
+        let zero: AbstractValue = ConstantDomain::U128(0).into();
+        let mut range_bound = |ordinal: usize| {
+            let bound_path = Self::get_field_path(range_path.clone(), ordinal);
+            self.lookup_path_and_refine_result(bound_path, ExpressionType::Usize)
+        };
+        match range_type.as_str() {
+            "core.ops.range.Range" => Some((range_bound(0), range_bound(1))),
+            "core.ops.range.RangeFrom" => Some((range_bound(0), length.clone())),
+            "core.ops.range.RangeTo" => Some((zero, range_bound(0))),
+            "core.ops.range.RangeFull" => Some((zero, length.clone())),
+            _ => None,
+        }
+    }
+
+    /// Calls to get_unchecked(range) on a str require start <= end <= length, where the length
+    /// is known if the contents of the str are. That the bounds fall on char boundaries is not
+    /// checked, since the contents of the str are not tracked char by char.
+    fn check_str_get_unchecked(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+    ) {
+        debug_assert!(self.check_for_errors);
+        let (str_value, range_arg) = match (actual_args.get(0), args.get(1)) {
+            (Some(str_value), Some(range_arg)) => (str_value, range_arg),
+            _ => return,
+        };
+        let string = self.get_string_value(str_value);
+        let length = string.domain.get_as_string().length().clone();
+        let length = match (length.lower_bound(), length.upper_bound()) {
+            (Some(lower), Some(upper)) if lower == upper => {
+                ConstantDomain::U128(lower as u128).into()
+            }
+            _ => match Self::get_referenced_path(str_value) {
+                Some(str_path) => self.get_len(str_path),
+                None => return,
+            },
+        };
+        let (mut start, mut end) = match self.get_start_and_end_of_range(range_arg, &length) {
+            Some(start_and_end) => start_and_end,
+            None => return,
+        };
+        let mut length = length;
+        let in_range = start
+            .less_or_equal(&mut end, None)
+            .and(&end.less_or_equal(&mut length, None), None)
+            .refine_with(&self.current_environment.entry_condition, self.current_span);
+        self.check_required_condition(&in_range, "unchecked range out of bounds");
+    }
+
+    /// Calls to split_at(mid) on a slice return a pair of slices of lengths mid and length - mid
+    /// and panic unless mid <= length.
+    fn model_split_at(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let collection_path = match (args.get(0), actual_args.get(0)) {
+            (Some(arg), Some(value)) => match self.get_collection_path(arg, value) {
+                Some(collection_path) => collection_path,
+                None => return,
+            },
+            _ => return,
+        };
+        let mid = match actual_args.get(1) {
+            Some(mid) => mid.clone(),
+            None => return,
+        };
+        let length = self.get_len(collection_path);
+        if self.check_for_errors {
+            let mut mid_bound = mid.clone();
+            let mut length_bound = length.clone();
+            let in_range = mid_bound.less_or_equal(&mut length_bound, None);
+            self.check_slice_bounds(&in_range, "split index out of bounds");
+        }
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            let remainder = length.sub(&mid, Some(self.current_span));
+            self.current_environment.update_value_at(
+                Self::get_length_path(Self::get_field_path(target_path.clone(), 0)),
+                mid,
+            );
+            self.current_environment.update_value_at(
+                Self::get_length_path(Self::get_field_path(target_path, 1)),
+                remainder,
+            );
+        }
+    }
+
+    /// Calls to to_le_bytes, to_be_bytes and to_ne_bytes on an integer return an array with the
+    /// bytes of the integer, in the order given by the name of the function (see encode_bytes).
+    fn model_to_bytes(
+        &mut self,
+        func_to_call: &AbstractValue,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (arg, value, (place, _)) = match (args.get(0), actual_args.get(0), destination) {
+            (Some(arg), Some(value), Some(destination)) => (arg, value, destination),
+            _ => return,
+        };
+        let value_type: ExpressionType = (&arg.ty(self.mir, self.tcx).sty).into();
+        if !value_type.is_integer() {
+            return;
+        }
+        let little_endian = self.is_little_endian(&Self::get_function_name(func_to_call));
+        let target_path = self.visit_place(place);
+        let bytes = Self::encode_bytes(value, &value_type, little_endian);
+        self.current_environment.update_value_at(
+            Self::get_length_path(target_path.clone()),
+            ConstantDomain::U128(bytes.len() as u128).into(),
+        );
+        for (i, byte) in bytes.into_iter().enumerate() {
+            self.current_environment
+                .update_value_at(Self::get_byte_path(&target_path, i), byte);
+        }
+    }
+
+    /// Calls to from_le_bytes, from_be_bytes and from_ne_bytes return the integer whose bytes
+    /// are the elements of the given array (see decode_bytes).
+    fn model_from_bytes(
+        &mut self,
+        func_to_call: &AbstractValue,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (bytes_path, (place, _)) = match (args.get(0), destination) {
+            (Some(mir::Operand::Copy(bytes)), Some(destination))
+            | (Some(mir::Operand::Move(bytes)), Some(destination)) => {
+                (self.visit_place(bytes), destination)
+            }
+            _ => return,
+        };
+        let result_type = self.get_place_type(place);
+        let little_endian = self.is_little_endian(&Self::get_function_name(func_to_call));
+        if let Some(result) = self.decode_bytes(&bytes_path, &result_type, little_endian) {
+            let target_path = self.visit_place(place);
+            self.current_environment
+                .update_value_at(target_path, result.with_provenance(self.current_span));
+        }
+    }
+
+    /// Calls to the read_u16, read_i32 and similar methods of the byteorder::ByteOrder trait
+    /// return the integer encoded by the first bytes of the given slice, in the byte order of
+    /// the type that implements the trait, such as LittleEndian or BigEndian.
+    fn model_byteorder_read(
+        &mut self,
+        func: &mir::Operand<'tcx>,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let (buffer_path, (place, _)) = match (args, actual_args, destination) {
+            ([arg], [value], Some(destination)) => match self.get_collection_path(arg, value) {
+                Some(buffer_path) => (buffer_path, destination),
+                None => return,
+            },
+            _ => return,
+        };
+        let result_type = self.get_place_type(place);
+        let little_endian = match self.get_byteorder(func) {
+            Some(little_endian) if result_type.is_integer() => little_endian,
+            _ => return,
+        };
+        self.check_buffer_length(&buffer_path, &result_type);
+        if let Some(result) = self.decode_bytes(&buffer_path, &result_type, little_endian) {
+            let target_path = self.visit_place(place);
+            self.current_environment
+                .update_value_at(target_path, result.with_provenance(self.current_span));
+        }
+    }
+
+    /// Calls to the write_u16, write_i32 and similar methods of the byteorder::ByteOrder trait
+    /// store the bytes of the given integer in the first elements of the given slice, in the
+    /// byte order of the type that implements the trait.
+    fn model_byteorder_write(
+        &mut self,
+        func: &mir::Operand<'tcx>,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+    ) {
+        let (buffer_path, value_arg, value) = match (args, actual_args) {
+            ([buffer_arg, value_arg], [buffer, value]) => {
+                match self.get_collection_path(buffer_arg, buffer) {
+                    Some(buffer_path) => (buffer_path, value_arg, value),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let value_type: ExpressionType = (&value_arg.ty(self.mir, self.tcx).sty).into();
+        let little_endian = match self.get_byteorder(func) {
+            Some(little_endian) if value_type.is_integer() => little_endian,
+            _ => return,
+        };
+        self.check_buffer_length(&buffer_path, &value_type);
+        for (i, byte) in Self::encode_bytes(value, &value_type, little_endian)
+            .into_iter()
+            .enumerate()
+        {
+            self.current_environment
+                .update_value_at(Self::get_byte_path(&buffer_path, i), byte);
+        }
+    }
+
+    /// Returns true if the type that implements byteorder::ByteOrder for the given call is
+    /// LittleEndian, false if it is BigEndian, and None if it is neither.
+    fn get_byteorder(&self, func: &mir::Operand<'tcx>) -> Option<bool> {
+        if let TyKind::FnDef(_, substs) = func.ty(self.mir, self.tcx).sty {
+            if let TyKind::Adt(def, _) = substs.type_at(0).sty {
+                return match utils::summary_key_str(&self.tcx, def.did).as_str() {
+                    "byteorder.LittleEndian" => Some(true),
+                    "byteorder.BigEndian" => Some(false),
+                    _ => None,
+                };
+            }
+        }
+        None
+    }
+
+    /// Checks that the buffer at the given path is long enough to hold a value of the given type.
+    fn check_buffer_length(&mut self, buffer_path: &Path, value_type: &ExpressionType) {
+        if !self.check_for_errors {
+            return;
+        }
+        let mut size: AbstractValue =
+            ConstantDomain::U128(u128::from(value_type.bit_length() / 8)).into();
+        let mut length = self.get_len(buffer_path.clone());
+        let long_enough = size.less_or_equal(&mut length, None);
+        self.check_slice_bounds(&long_enough, "buffer too short for the value");
+    }
+
+    /// Returns the last component of the summary key of the given function.
+    fn get_function_name(func_to_call: &AbstractValue) -> String {
+        if let Expression::CompileTimeConstant(ConstantDomain::Function {
+            summary_cache_key, ..
+        }) = &func_to_call.domain.expression
+        {
+            let name = summary_cache_key.rsplit('.').next().unwrap_or_default();
+            String::from(name.split(':').next().unwrap_or_default())
+        } else {
+            String::new()
+        }
+    }
+
+    /// Returns true if the byte conversion function with the given name, such as to_le_bytes
+    /// or from_ne_bytes, uses little endian byte order.
+    fn is_little_endian(&self, function_name: &str) -> bool {
+        if function_name.contains("_le_") {
+            true
+        } else if function_name.contains("_be_") {
+            false
+        } else {
+            self.tcx.data_layout.endian == Endian::Little
+        }
+    }
+
+    /// Returns the path of the element at the given index of the array or slice at the given path.
+    fn get_byte_path(path: &Path, index: usize) -> Path {
+        Path::QualifiedPath {
+            length: path.path_length() + 1,
+            qualifier: box path.clone(),
+            selector: box PathSelector::Index(box ConstantDomain::U128(index as u128).into()),
+        }
+    }
+
+    /// Returns the bytes of the given integer value, in the given byte order. The byte with index
+    /// i is modeled as (value >> shift(i)) & 0xff, so that decode_bytes can recognize the bytes
+    /// of an integer that are decoded in the same order and return the integer itself.
+    fn encode_bytes(
+        value: &AbstractValue,
+        value_type: &ExpressionType,
+        little_endian: bool,
+    ) -> Vec<AbstractValue> {
+        let byte_count = usize::from(value_type.bit_length() / 8);
+        let mask: AbstractValue = ConstantDomain::U128(0xff).into();
+        (0..byte_count)
+            .map(|i| {
+                let shift = Self::get_byte_shift(i, byte_count, little_endian);
+                let shifted = if shift == 0 {
+                    value.clone()
+                } else {
+                    let shift_amount: AbstractValue = ConstantDomain::U128(shift).into();
+                    value.shr(&shift_amount, value_type.clone(), None)
+                };
+                shifted.bit_and(&mask, None)
+            })
+            .collect()
+    }
+
+    /// Returns the integer of the given type whose bytes, in the given byte order, are the
+    /// elements of the array or slice at the given path. If the bytes are constants, so is the
+    /// result. If they are the bytes of some value, as encoded by encode_bytes in the same order,
+    /// the result is that value. Otherwise nothing is known about the result and None is returned.
+    fn decode_bytes(
+        &mut self,
+        bytes_path: &Path,
+        result_type: &ExpressionType,
+        little_endian: bool,
+    ) -> Option<AbstractValue> {
+        if !result_type.is_integer() {
+            return None;
+        }
+        let byte_count = usize::from(result_type.bit_length() / 8);
+        let bytes: Vec<AbstractValue> = (0..byte_count)
+            .map(|i| {
+                let byte_path = Self::get_byte_path(bytes_path, i);
+                self.lookup_path_and_refine_result(byte_path, ExpressionType::U8)
+            })
+            .collect();
+        let mut constant: u128 = 0;
+        let mut encoded_value: Option<&AbstractDomain> = None;
+        let mut all_constants = true;
+        let mut all_encoded = true;
+        for (i, byte) in bytes.iter().enumerate() {
+            let shift = Self::get_byte_shift(i, byte_count, little_endian);
+            match &byte.domain.expression {
+                Expression::CompileTimeConstant(ConstantDomain::U128(byte)) => {
+                    constant |= (byte & 0xff) << shift;
+                }
+                Expression::CompileTimeConstant(ConstantDomain::I128(byte)) => {
+                    constant |= ((*byte as u128) & 0xff) << shift;
+                }
+                _ => all_constants = false,
+            }
+            match Self::get_encoded_value(&byte.domain, shift) {
+                Some(value) if encoded_value.map_or(true, |encoded| *encoded == *value) => {
+                    encoded_value = Some(value);
+                }
+                _ => all_encoded = false,
+            }
+        }
+        if all_constants {
+            return Some(if result_type.is_signed_integer() {
+                // Sign extend the bytes to the full width of an i128.
+                let unused_bits = 128 - u32::from(result_type.bit_length());
+                let value = ((constant << unused_bits) as i128) >> unused_bits;
+                ConstantDomain::I128(value).into()
+            } else {
+                ConstantDomain::U128(constant).into()
+            });
+        }
+        if all_encoded {
+            return encoded_value.map(|value| value.expression.clone().into());
+        }
+        None
+    }
+
+    /// If the given byte has the form (value >> shift) & 0xff, or value & 0xff if shift is zero,
+    /// returns value.
+    fn get_encoded_value(byte: &AbstractDomain, shift: u128) -> Option<&AbstractDomain> {
+        if let Expression::BitAnd { left, right } = &byte.expression {
+            if right.expression != Expression::CompileTimeConstant(ConstantDomain::U128(0xff)) {
+                return None;
+            }
+            if shift == 0 {
+                return Some(left);
+            }
+            if let Expression::Shr {
+                left: value,
+                right: shift_amount,
+                ..
+            } = &left.expression
+            {
+                if shift_amount.expression
+                    == Expression::CompileTimeConstant(ConstantDomain::U128(shift))
+                {
+                    return Some(value);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of bits by which the integer is shifted to get its byte with the given
+    /// index, if it has byte_count bytes in the given byte order.
+    fn get_byte_shift(index: usize, byte_count: usize, little_endian: bool) -> u128 {
+        let position = if little_endian {
+            index
+        } else {
+            byte_count - 1 - index
+        };
+        (position * 8) as u128
+    }
+
+    /// Calls to is_ascii_digit, is_ascii_alphabetic and the other ASCII predicates of char and u8
+    /// return true if the referenced character lies in one of the code point ranges of the class,
+    /// so that branching on the result refines the range of the character.
+    fn model_ascii_predicate(
+        &mut self,
+        func_to_call: &AbstractValue,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let ranges = match Self::get_ascii_class(&Self::get_function_name(func_to_call)) {
+            Some(ranges) => ranges,
+            None => return,
+        };
+        let (character, is_char, (place, _)) =
+            match self.get_ascii_character(args, actual_args, destination) {
+                Some(operands) => operands,
+                None => return,
+            };
+        let mut result: AbstractValue = false.into();
+        for (first, last) in ranges {
+            let mut first_code = Self::get_ascii_constant(*first, is_char);
+            let mut last_code = Self::get_ascii_constant(*last, is_char);
+            let mut code = character.clone();
+            let in_range = if first == last {
+                code.equals(&first_code, None)
+            } else {
+                code.greater_or_equal(&mut first_code, None)
+                    .and(&code.clone().less_or_equal(&mut last_code, None), None)
+            };
+            result = result.or(&in_range, None);
+        }
+        let target_path = self.visit_place(place);
+        self.current_environment
+            .update_value_at(target_path, result.with_provenance(self.current_span));
+    }
+
+    /// Calls to to_ascii_lowercase and to_ascii_uppercase on a char or u8 return the referenced
+    /// character with its case changed if it is an ASCII letter, and unchanged otherwise.
+    fn model_ascii_case_conversion(
+        &mut self,
+        func_to_call: &AbstractValue,
+        args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        let to_lowercase = match Self::get_function_name(func_to_call).as_str() {
+            "to_ascii_lowercase" => true,
+            "to_ascii_uppercase" => false,
+            _ => return,
+        };
+        let (character, is_char, (place, _)) =
+            match self.get_ascii_character(args, actual_args, destination) {
+                Some(operands) => operands,
+                None => return,
+            };
+        let result = match &character.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Char(c)) => {
+                let converted = if to_lowercase {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                };
+                ConstantDomain::Char(converted).into()
+            }
+            Expression::CompileTimeConstant(ConstantDomain::U128(b)) if *b <= 0xff => {
+                let b = *b as u8;
+                let converted = if to_lowercase {
+                    b.to_ascii_lowercase()
+                } else {
+                    b.to_ascii_uppercase()
+                };
+                ConstantDomain::U128(u128::from(converted)).into()
+            }
+            _ => {
+                let (first, last) = if to_lowercase {
+                    (b'A', b'Z')
+                } else {
+                    (b'a', b'z')
+                };
+                let mut first_code = Self::get_ascii_constant(first, is_char);
+                let mut last_code = Self::get_ascii_constant(last, is_char);
+                let mut code = character.clone();
+                let is_letter = code
+                    .greater_or_equal(&mut first_code, None)
+                    .and(&code.clone().less_or_equal(&mut last_code, None), None);
+                // The cases of an ASCII letter differ only in the bit with value 32.
+                let case_bit: AbstractValue = ConstantDomain::U128(32).into();
+                let converted = if to_lowercase {
+                    character.add(&case_bit, None)
+                } else {
+                    character.sub(&case_bit, None)
+                };
+                converted.join(&character, &is_letter)
+            }
+        };
+        let target_path = self.visit_place(place);
+        self.current_environment
+            .update_value_at(target_path, result.with_provenance(self.current_span));
+    }
+
+    /// Returns the value of the char or u8 referenced by the first argument of an ASCII method,
+    /// along with true if it is a char, and the destination of the call.
+    fn get_ascii_character<'a>(
         &mut self,
-        func: &mir::Operand<'tcx>,
         args: &[mir::Operand<'tcx>],
+        actual_args: &[AbstractValue],
+        destination: &'a Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) -> Option<(AbstractValue, bool, &'a (mir::Place<'tcx>, mir::BasicBlock))> {
+        let (arg, value, destination) = match (args.get(0), actual_args.get(0), destination) {
+            (Some(arg), Some(value), Some(destination)) => (arg, value, destination),
+            _ => return None,
+        };
+        let character_type: ExpressionType = match arg.ty(self.mir, self.tcx).sty {
+            TyKind::Ref(_, ty, _) => (&ty.sty).into(),
+            _ => return None,
+        };
+        let is_char = match character_type {
+            ExpressionType::Char => true,
+            ExpressionType::U8 => false,
+            _ => return None,
+        };
+        let path = Self::get_referenced_path(value)?;
+        let character = self.lookup_path_and_refine_result(path, character_type);
+        Some((character, is_char, destination))
+    }
+
+    /// Returns the ASCII code as a char constant if is_char is true, and as a u8 constant otherwise.
+    fn get_ascii_constant(code: u8, is_char: bool) -> AbstractValue {
+        if is_char {
+            ConstantDomain::Char(char::from(code)).into()
+        } else {
+            ConstantDomain::U128(u128::from(code)).into()
+        }
+    }
+
+    /// Returns the inclusive code point ranges of the ASCII class tested by the predicate with
+    /// the given name, as documented for the predicates of u8 in the standard library.
+    fn get_ascii_class(function_name: &str) -> Option<&'static [(u8, u8)]> {
+        let ranges: &'static [(u8, u8)] = match function_name {
+            "is_ascii" => &[(0x00, 0x7f)],
+            "is_ascii_alphabetic" => &[(b'A', b'Z'), (b'a', b'z')],
+            "is_ascii_uppercase" => &[(b'A', b'Z')],
+            "is_ascii_lowercase" => &[(b'a', b'z')],
+            "is_ascii_alphanumeric" => &[(b'0', b'9'), (b'A', b'Z'), (b'a', b'z')],
+            "is_ascii_digit" => &[(b'0', b'9')],
+            "is_ascii_hexdigit" => &[(b'0', b'9'), (b'A', b'F'), (b'a', b'f')],
+            "is_ascii_punctuation" => &[(0x21, 0x2f), (0x3a, 0x40), (0x5b, 0x60), (0x7b, 0x7e)],
+            "is_ascii_graphic" => &[(0x21, 0x7e)],
+            "is_ascii_whitespace" => &[
+                (b'\t', b'\t'),
+                (b'\n', b'\n'),
+                (0x0c, 0x0c),
+                (b'\r', b'\r'),
+                (b' ', b' '),
+            ],
+            "is_ascii_control" => &[(0x00, 0x1f), (0x7f, 0x7f)],
+            _ => return None,
+        };
+        Some(ranges)
+    }
+
+    /// Checks that a range used to slice a buffer lies within the buffer. Like the bounds checks
+    /// that the compiler inserts for indexing, a failure is reported in public functions and
+    /// otherwise becomes a precondition of the function being analyzed.
+    fn check_slice_bounds(&mut self, in_range: &AbstractValue, message: &str) {
+        debug_assert!(self.check_for_errors);
+        if self.is_skipped_obligation(ObligationClass::BoundsCheck) {
+            self.push_skipped_obligation(in_range, message);
+            return;
+        }
+        let (in_range_as_bool, entry_cond_as_bool) =
+            self.check_condition_value_and_reachability(in_range, ObligationClass::BoundsCheck);
+        if in_range_as_bool.unwrap_or(false) || !entry_cond_as_bool.unwrap_or(true) {
+            return;
+        }
+        let definite = in_range_as_bool == Some(false) && entry_cond_as_bool.unwrap_or(false);
+        if definite || self.is_analysis_root() {
+            let span = self.current_span;
+            let mut warning = if definite {
+                self.struct_span_warn_for_user(span, message)
+            } else {
+                let message = format!("possible {}", message);
+                self.struct_span_warn_for_user(span, message.as_str())
+            };
+            self.set_confidence(&mut warning, definite);
+            self.emit_unproven_condition_diagnostic(&mut warning);
+            if definite {
+                // The callers cannot possibly satisfy a precondition.
+                return;
+            }
+        }
+        let precondition = self
+            .current_environment
+            .entry_condition
+            .not(None)
+            .or(in_range, Some(self.current_span));
+        self.preconditions
+            .push((precondition, String::from(message)));
+    }
+
+    /// If the given argument is a reference to an array or slice, returns the path of the array
+    /// or slice, as obtained from the value of the argument.
+    fn get_collection_path(&self, arg: &mir::Operand<'tcx>, value: &AbstractValue) -> Option<Path> {
+        if let TyKind::Ref(_, ty, _) = arg.ty(&self.mir.local_decls, self.tcx).sty {
+            match ty.sty {
+                TyKind::Array(..) | TyKind::Slice(..) => Self::get_referenced_path(value),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the path of the place that the given reference value points to. If the value
+    /// of the reference is not known, its path is used, since the paths of de-referenced
+    /// parameters and locals do not include the Deref selector.
+    fn get_referenced_path(value: &AbstractValue) -> Option<Path> {
+        match &value.domain.expression {
+            Expression::Reference(path) => Some(path.clone()),
+            Expression::Variable { path, .. } => Some((**path).clone()),
+            _ => None,
+        }
+    }
+
+    /// Calls to functions like ptr.offset(count), ptr.add(count) and ptr.sub(count) (negate == true)
+    /// return a pointer into the same allocation as ptr. The result is modeled as an offset
+    /// pointer, so that later offsets can be checked against the same allocation.
+    fn model_pointer_offset(
+        &mut self,
+        negate: bool,
+        actual_args: &[AbstractValue],
         destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
-        cleanup: Option<mir::BasicBlock>,
-        from_hir_call: bool,
     ) {
-        debug!("default visit_call(func: {:?}, args: {:?}, destination: {:?}, cleanup: {:?}, from_hir_call: {:?})", func, args, destination, cleanup, from_hir_call);
-        let func_to_call = self.visit_operand(func);
-        let actual_args: Vec<AbstractValue> =
-            args.iter().map(|arg| self.visit_operand(arg)).collect();
-        let function_summary = self.get_function_summary(&func_to_call);
+        if actual_args.len() != 2 {
+            return;
+        }
+        let pointer = &actual_args[0];
+        let count = if negate {
+            actual_args[1].neg(Some(self.current_span))
+        } else {
+            actual_args[1].clone()
+        };
         if self.check_for_errors {
-            self.check_function_preconditions(&actual_args, &function_summary);
+            self.check_pointer_offset(pointer, &count);
+        }
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            let result = pointer.offset(&count, Some(self.current_span));
+            self.current_environment
+                .update_value_at(target_path, result);
+        }
+    }
+
+    /// Calls to ptr.is_null() return true if the pointer given by the first argument is zero.
+    fn model_pointer_is_null(
+        &mut self,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let (Some(pointer), Some((place, _))) = (actual_args.get(0), destination) {
+            let target_path = self.visit_place(place);
+            let zero: AbstractValue = ConstantDomain::U128(0).into();
+            let result = pointer.equals(&zero, Some(self.current_span));
+            self.current_environment
+                .update_value_at(target_path, result);
+        }
+    }
+
+    /// If pointer points into an allocation whose extent is known, checks that offsetting
+    /// the pointer by count elements results in a pointer that is still in the allocation,
+    /// or that points just past its end. Complains if this cannot be shown.
+    fn check_pointer_offset(&mut self, pointer: &AbstractValue, count: &AbstractValue) {
+        debug_assert!(self.check_for_errors);
+        if self.is_skipped_obligation(ObligationClass::BoundsCheck) {
+            return;
+        }
+        if !self
+            .current_environment
+            .entry_condition
+            .as_bool_if_known()
+            .unwrap_or(true)
+        {
+            return;
+        }
+        let (base_path, index) = match Self::get_pointer_base_and_index(&pointer.domain) {
+            Some(base_and_index) => base_and_index,
+            None => return,
+        };
+        let length = self.get_len(base_path).domain.get_as_interval();
+        let (length_lower_bound, length_upper_bound) =
+            match (length.lower_bound(), length.upper_bound()) {
+                (Some(lower_bound), Some(upper_bound)) if !length.is_bottom() => {
+                    (lower_bound, upper_bound)
+                }
+                _ => {
+                    // The extent of the allocation is not known.
+                    return;
+                }
+            };
+        let new_index = index.add(&count.domain.get_as_interval());
+        let (message, definite) = match (new_index.lower_bound(), new_index.upper_bound()) {
+            (Some(lower_bound), Some(upper_bound)) if !new_index.is_bottom() => {
+                if lower_bound >= 0 && upper_bound <= length_lower_bound {
+                    // The offset is always in bounds.
+                    return;
+                }
+                if upper_bound < 0 || lower_bound > length_upper_bound {
+                    ("pointer offset is out of bounds", true)
+                } else {
+                    ("possible pointer offset out of bounds", false)
+                }
+            }
+            _ => ("possible pointer offset out of bounds", false),
+        };
+        let span = self.current_span;
+        let mut warning = self.struct_span_warn_for_user(span, message);
+        self.set_confidence(&mut warning, definite);
+        warning.note(
+            format!(
+                "the allocation has {} elements",
+                if length_lower_bound == length_upper_bound {
+                    length_lower_bound.to_string()
+                } else {
+                    format!("{} to {}", length_lower_bound, length_upper_bound)
+                }
+            )
+            .as_str(),
+        );
+        (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
+    }
+
+    /// Decomposes a pointer value into the path of the allocation (array) that it points into
+    /// and the interval of the index of the element that it points to.
+    fn get_pointer_base_and_index(pointer: &AbstractDomain) -> Option<(Path, IntervalDomain)> {
+        match &pointer.expression {
+            Expression::Reference(path) => {
+                if let Path::QualifiedPath {
+                    qualifier,
+                    selector,
+                    ..
+                } = path
+                {
+                    if let PathSelector::Index(index) = &**selector {
+                        return Some(((**qualifier).clone(), index.domain.get_as_interval()));
+                    }
+                }
+                Some((path.clone(), IntervalDomain::from(0i128)))
+            }
+            Expression::Offset { left, right } => {
+                let (base_path, index) = Self::get_pointer_base_and_index(left)?;
+                Some((base_path, index.add(&right.get_as_interval())))
+            }
+            _ => None,
+        }
+    }
+
+    /// If the function to call is a const fn and all of the arguments are known constants, the call
+    /// is evaluated by interpreting the body of the function concretely, which gives an exact result
+    /// where the summary of the function may only give an approximation. Lookup tables and
+    /// compile time configuration are typically computed this way.
+    /// Returns None if the call cannot be evaluated, in which case the summary applies.
+    fn evaluate_const_fn_call(
+        &mut self,
+        func_to_call: &AbstractValue,
+        actual_args: &[AbstractValue],
+    ) -> Option<AbstractValue> {
+        if self.const_eval_depth >= k_limits::MAX_CONST_EVAL_DEPTH {
+            return None;
+        }
+        let def_id = match func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id: Some(def_id),
+                ..
+            }) => def_id,
+            _ => return None,
+        };
+        if !self.tcx.is_const_fn(def_id) || !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        let all_arguments_are_constants = actual_args.iter().all(|arg| {
+            if let Expression::CompileTimeConstant(..) = arg.domain.expression {
+                true
+            } else {
+                false
+            }
+        });
+        if !all_arguments_are_constants {
+            return None;
+        }
+        let mir = self.tcx.optimized_mir(def_id);
+        if !mir.promoted.is_empty() {
+            // Promoting constants would update the summary of the function.
+            return None;
+        }
+        // The evaluation happens in a visitor of its own, so that it cannot affect the state of
+        // this one. It does not check for errors and thus does not give diagnostics.
+        let mut buffered_diagnostics: Vec<Diagnostic> = vec![];
+        let mut assumption_log: Vec<AssumedObligation> = vec![];
+        let mut evaluator = MirVisitor::new(MirVisitorCrateContext {
+            buffered_diagnostics: &mut buffered_diagnostics,
+            emit_diagnostic: self.emit_diagnostic,
+            session: self.session,
+            tcx: self.tcx,
+            def_id,
+            mir,
+            options: self.options,
+            constant_value_cache: self.constant_value_cache,
+            summary_cache: self.summary_cache,
+            smt_solver: self.smt_solver,
+            escalated_defs: self.escalated_defs,
+            assumption_log: &mut assumption_log,
+            test_functions: self.test_functions,
+        });
+        evaluator.const_eval_depth = self.const_eval_depth + 1;
+        evaluator.evaluate_concretely(actual_args)
+    }
+
+    /// If the function to call is an arithmetic operator or a comparison of a type that the options
+    /// declare to be a fixed point number, the call is modeled as the corresponding operation on the
+    /// scaled integers that represent the numbers. Returns true if this is the case.
+    fn model_fixed_point_operation(
+        &mut self,
+        func_to_call: &AbstractValue,
+        args: &[mir::Operand<'tcx>],
+        place: &mir::Place<'tcx>,
+    ) -> bool {
+        if self.options.fixed_point_types.is_empty() {
+            return false;
+        }
+        let operation = match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => {
+                let name = summary_cache_key.rsplit('.').next().unwrap_or_default();
+                String::from(name.split(':').next().unwrap_or_default())
+            }
+            _ => return false,
+        };
+        let (type_key, scaled_integer_type) = match args.get(0).and_then(|arg| {
+            let arg_type = arg.ty(self.mir, self.tcx);
+            self.get_fixed_point_type(arg_type)
+        }) {
+            Some(fixed_point_type) => fixed_point_type,
+            None => return false,
+        };
+        let fixed_point_type = &self.options.fixed_point_types[&type_key];
+        let field = fixed_point_type.field;
+        let scale: AbstractValue = if scaled_integer_type.is_signed_integer() {
+            ConstantDomain::I128(i128::from(fixed_point_type.scale)).into()
+        } else {
+            ConstantDomain::U128(u128::from(fixed_point_type.scale)).into()
+        };
+        let mut operands: Vec<AbstractValue> = Vec::with_capacity(args.len());
+        for arg in args.iter() {
+            let number_path = match self.get_fixed_point_number_path(arg) {
+                Some(path) => path,
+                None => return false,
+            };
+            let scaled_integer_path = Self::get_field_path(number_path, field);
+            operands.push(
+                self.lookup_path_and_refine_result(
+                    scaled_integer_path,
+                    scaled_integer_type.clone(),
+                ),
+            );
+        }
+        let span = Some(self.current_span);
+        let (result, target_path) = match (operation.as_str(), operands.as_slice()) {
+            ("neg", [a]) => (a.neg(span), self.visit_place(place)),
+            ("add", [a, b]) | ("add_assign", [a, b]) => (a.add(b, span), self.visit_place(place)),
+            ("sub", [a, b]) | ("sub_assign", [a, b]) => (a.sub(b, span), self.visit_place(place)),
+            ("mul", [a, b]) | ("mul_assign", [a, b]) => {
+                (a.mul(b, span).div(&scale, span), self.visit_place(place))
+            }
+            ("div", [a, b]) | ("div_assign", [a, b]) => {
+                (a.mul(&scale, span).div(b, span), self.visit_place(place))
+            }
+            ("rem", [a, b]) | ("rem_assign", [a, b]) => (a.rem(b, span), self.visit_place(place)),
+            ("eq", [a, b]) => (a.equals(b, span), self.visit_place(place)),
+            ("ne", [a, b]) => (a.not_equals(b, span), self.visit_place(place)),
+            ("lt", [a, b]) => (a.less_than(b, span), self.visit_place(place)),
+            ("le", [a, b]) => (a.less_or_equal(b, span), self.visit_place(place)),
+            ("gt", [a, b]) => (a.greater_than(b, span), self.visit_place(place)),
+            ("ge", [a, b]) => (a.greater_or_equal(b, span), self.visit_place(place)),
+            _ => return false,
+        };
+        match operation.as_str() {
+            "eq" | "ne" | "lt" | "le" | "gt" | "ge" => {
+                self.current_environment
+                    .update_value_at(target_path, result);
+            }
+            _ => {
+                // The result is a fixed point number, or, for the assignment operators, the
+                // number referenced by the first argument is updated.
+                let number_path = if operation.ends_with("_assign") {
+                    match self.get_fixed_point_number_path(&args[0]) {
+                        Some(path) => path,
+                        None => return false,
+                    }
+                } else {
+                    target_path
+                };
+                let scaled_integer_path = Self::get_field_path(number_path, field);
+                self.current_environment
+                    .update_value_at(scaled_integer_path, result);
+            }
+        }
+        true
+    }
+
+    /// If the given type is, or refers to, a type that the options declare to be a fixed point
+    /// number, returns the key of the type and the type of the scaled integer that represents it.
+    fn get_fixed_point_type(&self, ty: Ty<'tcx>) -> Option<(String, ExpressionType)> {
+        match &ty.sty {
+            TyKind::Ref(_, referenced_type, _) => self.get_fixed_point_type(referenced_type),
+            TyKind::Adt(def, substs) if def.is_struct() => {
+                let type_key = utils::summary_key_str(&self.tcx, def.did);
+                let fixed_point_type = self.options.fixed_point_types.get(&type_key)?;
+                let field = def.non_enum_variant().fields.get(fixed_point_type.field)?;
+                let field_type: ExpressionType = (&field.ty(self.tcx, substs).sty).into();
+                if field_type.is_integer() {
+                    Some((type_key, field_type))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the fixed point number that is passed as the given argument, either
+    /// by value or by reference.
+    fn get_fixed_point_number_path(&mut self, arg: &mir::Operand<'tcx>) -> Option<Path> {
+        let arg_path = match arg {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => self.visit_place(place),
+            mir::Operand::Constant(..) => return None,
+        };
+        if let TyKind::Ref(..) = arg.ty(self.mir, self.tcx).sty {
+            let reference = self.current_environment.value_at(&arg_path)?;
+            Self::get_referenced_path(reference)
+        } else {
+            Some(arg_path)
+        }
+    }
+
+    /// Returns the path of the given field of the struct at the given path.
+    fn get_field_path(path: Path, field: usize) -> Path {
+        Path::QualifiedPath {
+            length: path.path_length() + 1,
+            qualifier: box path,
+            selector: box PathSelector::Field(field),
+        }
+    }
+
+    /// Interprets the body concretely, with the given constants as the values of the parameters.
+    /// Since all values are known, every branch condition is known and only the path that is
+    /// actually taken is followed, one block at a time. Returns the result of the function if it
+    /// is a constant. Returns None if it is not, or if the path leads to a panic, or if it is
+    /// longer than k_limits::MAX_CONST_EVAL_STEPS blocks.
+    fn evaluate_concretely(&mut self, actual_args: &[AbstractValue]) -> Option<AbstractValue> {
+        let mut state = Environment::default();
+        for (i, arg) in actual_args.iter().enumerate() {
+            state.update_value_at(Path::LocalVariable { ordinal: i + 1 }, arg.clone());
+        }
+        let mir = self.mir;
+        let mut bb = mir::START_BLOCK;
+        for _ in 0..k_limits::MAX_CONST_EVAL_STEPS {
+            self.current_environment = state;
+            self.visit_basic_block(bb);
+            let terminator = mir[bb].terminator();
+            if let mir::TerminatorKind::Return = terminator.kind {
+                let result = self
+                    .current_environment
+                    .value_at(&Path::LocalVariable { ordinal: 0 })?;
+                return if let Expression::CompileTimeConstant(..) = result.domain.expression {
+                    Some(result.clone())
+                } else {
+                    None
+                };
+            }
+            let exit_conditions = &self.current_environment.exit_conditions;
+            bb = *terminator.successors().find(|successor| {
+                exit_conditions
+                    .get(*successor)
+                    .and_then(AbstractValue::as_bool_if_known)
+                    .unwrap_or(false)
+            })?;
+            state = self.current_environment.clone();
+            state.entry_condition = abstract_value::TRUE;
+            state.exit_conditions = HashMap::default();
+        }
+        None
+    }
+
+    /// Calls to the functions behind format! and its relatives are modeled as uninterpreted
+    /// functions of the values that are referenced by their arguments. This keeps track of the
+    /// values that flow into formatted strings without having to analyze the internals of core::fmt.
+    /// The literal pieces of a format string are known, however, so the formatted string is known
+    /// to start with the first piece and to end with the last piece that follows an argument.
+    /// If there are no arguments, all of the formatted string is known.
+    fn model_formatting_call(
+        &mut self,
+        func_to_call: &AbstractValue,
+        actual_args: &[AbstractValue],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+    ) {
+        if let Some((place, _)) = destination {
+            let target_path = self.visit_place(place);
+            let callee_key = match &func_to_call.domain.expression {
+                Expression::CompileTimeConstant(ConstantDomain::Function {
+                    summary_cache_key,
+                    ..
+                }) => summary_cache_key.as_str(),
+                _ => "",
+            };
+            if callee_key == "alloc.fmt.format" {
+                // The String has the contents of the Arguments value that it formats.
+                if let Some(arguments) = actual_args.get(0) {
+                    if Self::is_string_expression(arguments) {
+                        self.current_environment.update_value_at(
+                            target_path,
+                            arguments.with_provenance(self.current_span),
+                        );
+                        return;
+                    }
+                }
+            }
+            let mut arguments = Vec::new();
+            for arg in actual_args.iter() {
+                self.collect_referenced_values(arg, &mut arguments);
+            }
+            let result_type = self.get_place_type(place);
+            let mut result: AbstractValue = Expression::UninterpretedCall {
+                callee: box func_to_call.domain.clone(),
+                arguments,
+                result_type,
+            }
+            .into();
+            if callee_key.ends_with(".new_v1") && actual_args.len() == 2 {
+                if let Some(formatted_string) =
+                    self.get_formatted_string(&actual_args[0], &actual_args[1], &result)
+                {
+                    result = formatted_string;
+                }
+            }
+            self.current_environment
+                .update_value_at(target_path, result.with_provenance(self.current_span));
+        }
+    }
+
+    /// Returns the string that Arguments::new_v1 formats from the given pieces and arguments, as
+    /// far as it is known, with the given uninterpreted result standing in for the part of the
+    /// string that the arguments produce. The string is piece 0, argument 0, piece 1, argument 1
+    /// and so on. Returns None if the first piece is not known.
+    fn get_formatted_string(
+        &self,
+        pieces: &AbstractValue,
+        args: &AbstractValue,
+        uninterpreted_result: &AbstractValue,
+    ) -> Option<AbstractValue> {
+        let pieces_path = Self::get_referenced_path(pieces)?;
+        let number_of_args = Self::get_referenced_path(args).and_then(|args_path| {
+            self.current_environment
+                .value_at(&Self::get_length_path(args_path))
+                .and_then(|length| length.domain.as_constant_integer())
+        });
+        let mut known_pieces = Vec::new();
+        loop {
+            let piece_path = Self::get_byte_path(&pieces_path, known_pieces.len());
+            match self
+                .current_environment
+                .value_at(&piece_path)
+                .and_then(|piece| piece.domain.as_constant_string())
+            {
+                Some(piece) => known_pieces.push(String::from(piece)),
+                None => break,
+            }
+        }
+        let first_piece: AbstractValue = ConstantDomain::Str(known_pieces.first()?.clone()).into();
+        if number_of_args == Some(0) {
+            return Some(ConstantDomain::Str(known_pieces.concat()).into());
+        }
+        let mut result = first_piece.string_concat(uninterpreted_result, None);
+        if let Some(number_of_args) = number_of_args {
+            if known_pieces.len() as i128 > number_of_args {
+                let last_piece: AbstractValue =
+                    ConstantDomain::Str(known_pieces.last()?.clone()).into();
+                result = result.string_concat(&last_piece, None);
+            }
+        }
+        Some(result)
+    }
+
+    /// If value is a reference, adds the values stored at the place it refers to, and at all
+    /// of the places rooted by that place, to the given list. Otherwise adds value itself.
+    fn collect_referenced_values(&self, value: &AbstractValue, result: &mut Vec<AbstractDomain>) {
+        if let Expression::Reference(path) = &value.domain.expression {
+            let mut referenced_values: Vec<(&Path, &AbstractValue)> = self
+                .current_environment
+                .value_map
+                .iter()
+                .filter(|(p, _)| *p == path || p.is_rooted_by(path))
+                .collect();
+            referenced_values.sort();
+            for (_, referenced_value) in referenced_values.into_iter() {
+                result.push(referenced_value.domain.clone());
+            }
+        } else {
+            result.push(value.domain.clone());
+        }
+    }
+
+    /// Returns a summary of the function to call, obtained from the summary cache.
+    fn get_function_summary(
+        &mut self,
+        func_to_call: &AbstractValue,
+        args: &[mir::Operand<'tcx>],
+        trust_level: Option<TrustLevel>,
+    ) -> Summary {
+        let def_id = match func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function { def_id, .. }) => def_id,
+            _ => None,
+        };
+        if let Some(model) = self.get_function_model(func_to_call) {
+            // The model says what the function modifies, so it modifies nothing else.
+            let mut parameters_modified: Vec<usize> =
+                model.modifies.iter().map(|i| *i + 1).collect();
+            parameters_modified.sort();
+            parameters_modified.dedup();
+            return Summary {
+                side_effects: parameters_modified
+                    .iter()
+                    .map(|ordinal| {
+                        (
+                            Path::LocalVariable { ordinal: *ordinal },
+                            abstract_value::TOP,
+                        )
+                    })
+                    .collect(),
+                parameters_read: (1..=args.len()).collect(),
+                parameters_modified,
+                ..Summary::default()
+            };
+        }
+        if trust_level == Some(TrustLevel::Untrusted) {
+            // Assume the worst: only the signature of the function limits what it does.
+            let signature_summary = def_id
+                .map(|def_id| summaries::summarize_signature(&self.tcx, def_id))
+                .unwrap_or_default();
+            return Summary {
+                modifies_static_state: true,
+                ..signature_summary
+            };
+        }
+        if let Some(def_id) = def_id {
+            if let Some(summary) = self.get_summary_of_dynamic_call(def_id, args) {
+                return summary;
+            }
+            self.summary_cache
+                .get_summary_for(def_id, Some(self.def_id))
+                .clone()
+        } else {
+            Summary::default()
+        }
+    }
+
+    /// If def_id is a trait method and the receiver of the call is a trait object, such as a
+    /// &dyn Trait or a Box<dyn Trait>, the call is dispatched dynamically to the implementation of
+    /// the method for the type of the object. Returns the join of the summaries of all of the
+    /// implementations that the call may be dispatched to, taken from the impls of the trait in
+    /// the current crate and in the crates it depends on, so that what all implementations agree on,
+    /// such as tags of the result, survives the dynamic dispatch.
+    fn get_summary_of_dynamic_call(
+        &mut self,
+        def_id: hir::def_id::DefId,
+        args: &[mir::Operand<'tcx>],
+    ) -> Option<Summary> {
+        let trait_def_id = self.tcx.trait_of_item(def_id)?;
+        let receiver_type = args.get(0)?.ty(self.mir, self.tcx);
+        match receiver_type.builtin_deref(true)?.ty.sty {
+            TyKind::Dynamic(..) => {}
+            _ => return None,
+        }
+        let tcx = self.tcx;
+        let name = tcx.item_name(def_id);
+        let mut implementations = Vec::new();
+        tcx.for_each_impl(trait_def_id, |impl_def_id| {
+            // An impl that does not implement the method uses the default method of the trait.
+            let implementation = tcx
+                .associated_item_def_ids(impl_def_id)
+                .iter()
+                .find(|item_def_id| tcx.item_name(**item_def_id) == name)
+                .cloned()
+                .unwrap_or(def_id);
+            implementations.push(implementation);
+        });
+        if implementations.is_empty()
+            || implementations.len() > k_limits::MAX_DEVIRTUALIZED_IMPLEMENTATIONS
+        {
+            return None;
+        }
+        let summaries: Vec<Summary> = implementations
+            .into_iter()
+            .map(|implementation| {
+                self.summary_cache
+                    .get_summary_for(implementation, Some(self.def_id))
+                    .clone()
+            })
+            .collect();
+        Some(summaries::join_summaries(&summaries))
+    }
+
+    /// Returns the trust level that the options assign to the crate of the function to call, if
+    /// it is a function of another crate. The crate name is the first part of the summary key.
+    fn get_trust_level(&self, func_to_call: &AbstractValue) -> Option<TrustLevel> {
+        if let Expression::CompileTimeConstant(ConstantDomain::Function {
+            def_id,
+            summary_cache_key,
+            ..
+        }) = &func_to_call.domain.expression
+        {
+            if def_id.map_or(false, |def_id| def_id.is_local()) {
+                return None;
+            }
+            let crate_name = summary_cache_key.split('.').next().unwrap_or_default();
+            self.options.trust_levels.get(crate_name).cloned()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the declaration of the called function as a sanitizer or validator, if the
+    /// options have one.
+    fn get_sanitizer(&self, func_to_call: &AbstractValue) -> Option<&'a Sanitizer> {
+        let options = self.options;
+        match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => options.sanitizers.get(summary_cache_key.as_str()),
+            _ => None,
         }
-        self.transfer_and_refine_normal_return_state(destination, &actual_args, &function_summary);
-        self.transfer_and_refine_cleanup_state(cleanup);
-        if self.check_for_errors {
-            self.report_calls_to_special_functions(func_to_call, actual_args)
+    }
+
+    /// Returns the declaration of the called function as a trusted function, if the options have
+    /// one.
+    fn get_trusted_function(&self, func_to_call: &AbstractValue) -> Option<&'a TrustedFunction> {
+        let options = self.options;
+        match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => options.trusted_functions.get(summary_cache_key.as_str()),
+            _ => None,
         }
     }
 
-    /// Returns a summary of the function to call, obtained from the summary cache.
-    fn get_function_summary(&mut self, func_to_call: &AbstractValue) -> Summary {
-        if let Expression::CompileTimeConstant(ConstantDomain::Function {
-            def_id: Some(def_id),
-            ..
-        }) = func_to_call.domain.expression
-        {
-            self.summary_cache
-                .get_summary_for(def_id, Some(self.def_id))
-                .clone()
-        } else {
-            Summary::default()
+    /// Adds the current call of a function that the options trust to the assumed obligations,
+    /// so that the assumption log accounts for every place where the trust was relied upon.
+    fn record_trusted_call(
+        &mut self,
+        func_to_call: &AbstractValue,
+        trusted_function: &TrustedFunction,
+    ) {
+        let callee_key = match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => summary_cache_key.to_string(),
+            _ => return,
+        };
+        let span = self.current_span.source_callsite();
+        self.assumed_obligations.push(AssumedObligation {
+            function: utils::summary_key_str(&self.tcx, self.def_id),
+            checksum: String::new(),
+            proof: trusted_function.reason.clone(),
+            message: format!(
+                "calls of {} are trusted to be {}",
+                callee_key,
+                trusted_function.guarantee.description()
+            ),
+            location: self.get_location(Some(span)),
+        });
+    }
+
+    /// Returns the model of the called function, if the options have one.
+    fn get_function_model(&self, func_to_call: &AbstractValue) -> Option<&'a FunctionModel> {
+        let options = self.options;
+        match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => options.function_models.get(summary_cache_key.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the declaration of the called function as a sink, if the options have one.
+    fn get_sink(&self, func_to_call: &AbstractValue) -> Option<&'a Sink> {
+        let options = self.options;
+        match &func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                summary_cache_key, ..
+            }) => options.sinks.get(summary_cache_key.as_str()),
+            _ => None,
         }
     }
 
@@ -706,10 +6146,31 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
     /// Preconditions that are maybe false become preconditions of the calling function.
     fn check_function_preconditions(
         &mut self,
+        func_to_call: &AbstractValue,
         actual_args: &[AbstractValue],
         function_summary: &Summary,
     ) {
         debug_assert!(self.check_for_errors);
+        if self.is_skipped_obligation(ObligationClass::Contract) {
+            for (precondition, message) in &function_summary.preconditions {
+                let refined_precondition = precondition
+                    .refine_parameters(actual_args)
+                    .refine_paths(&mut self.current_environment);
+                self.push_skipped_obligation(&refined_precondition, message);
+            }
+            return;
+        }
+        // If the function being called is a trivial wrapper, its obligations are reported here,
+        // with the locations inside the wrapper marked as such.
+        let wrapper_span = match func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id: Some(def_id),
+                ..
+            }) if utils::is_trivial_wrapper(def_id, &self.tcx) => {
+                self.tcx.hir().span_if_local(def_id)
+            }
+            _ => None,
+        };
         for (precondition, message) in &function_summary.preconditions {
             let refined_precondition = precondition
                 .refine_parameters(actual_args)
@@ -717,8 +6178,11 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 .refine_with(&self.current_environment.entry_condition, self.current_span);
             //todo: if refined_precondition is a Variable, look it up
             // or perhaps pass in &mut self.current_environment
-            let (refined_precondition_as_bool, entry_cond_as_bool) =
-                self.check_condition_value_and_reachability(&refined_precondition);
+            let (refined_precondition_as_bool, entry_cond_as_bool) = self
+                .check_condition_value_and_reachability(
+                    &refined_precondition,
+                    ObligationClass::Contract,
+                );
 
             if refined_precondition_as_bool.unwrap_or(false) {
                 // The precondition is definitely true.
@@ -729,16 +6193,26 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 if entry_cond_as_bool.unwrap_or(false) {
                     // We always get here if the function is called, and the precondition is always
                     // false, so complain loudly.
-                    self.emit_diagnostic_for_precondition(precondition, &message);
+                    self.emit_diagnostic_for_precondition(
+                        precondition,
+                        &message,
+                        wrapper_span,
+                        true,
+                    );
                     // Don't promote a false precondition. The callers cannot possibly satisfy it,
                     // so there is no point in complaining at call sites.
                     continue;
                 } else {
                     // We might never get here since it depends on the parameter values used to call
                     // this function. If the function is public, let's warn that we might get here.
-                    if is_public(self.def_id, &self.tcx) {
+                    if self.is_analysis_root() {
                         let warning = format!("possible error: {}", message.as_str());
-                        self.emit_diagnostic_for_precondition(precondition, &warning);
+                        self.emit_diagnostic_for_precondition(
+                            precondition,
+                            &warning,
+                            wrapper_span,
+                            false,
+                        );
                     } else {
                         // Since the function is not public, we assume that we get to see
                         // every call to this function, so just rely on the inferred precondition.
@@ -762,29 +6236,54 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
 
     /// Emit a diagnostic to the effect that the current call might violate a the given precondition
     /// of the called function. Use the provenance of the precondition to point out related locations.
-    fn emit_diagnostic_for_precondition(&mut self, precondition: &AbstractValue, diagnostic: &str) {
+    /// Related locations inside the given wrapper span, if any, are noted as wrapper machinery.
+    fn emit_diagnostic_for_precondition(
+        &mut self,
+        precondition: &AbstractValue,
+        diagnostic: &str,
+        wrapper_span: Option<syntax_pos::Span>,
+        definite: bool,
+    ) {
         // This call is definitely going to be reached
-        let span = self.current_span;
-        let mut err = self.session.struct_span_warn(span, diagnostic);
-        let related_spans: HashSet<&syntax_pos::Span> =
-            HashSet::from_iter(precondition.provenance.iter());
-        for related_span in related_spans.iter() {
-            err.span_note(**related_span, "related location");
+        let span = self.current_span.source_callsite();
+        let mut err = self.struct_span_panic_for_user(span, diagnostic);
+        // Point at the code of the user, rather than into the expansions of macros from the
+        // standard library or into the standard library itself.
+        let related_spans: HashSet<syntax_pos::Span> = precondition
+            .provenance
+            .iter()
+            .map(|related_span| related_span.source_callsite())
+            .filter(|related_span| *related_span != span && !self.is_in_library_code(*related_span))
+            .collect();
+        let mut related_spans: Vec<syntax_pos::Span> = related_spans.into_iter().collect();
+        related_spans.sort();
+        for related_span in related_spans.into_iter() {
+            if wrapper_span.map_or(false, |wrapper_span| wrapper_span.contains(related_span)) {
+                err.span_note(related_span, "related location, inside a trivial wrapper");
+            } else {
+                err.span_note(related_span, "related location");
+            }
         }
-        (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        self.set_confidence(&mut err, definite);
+        self.emit_unproven_condition_diagnostic(&mut err);
     }
 
     /// Updates the current state to reflect the effects of a normal return from the function call.
     fn transfer_and_refine_normal_return_state(
         &mut self,
         destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+        args: &[mir::Operand<'tcx>],
         actual_args: &[AbstractValue],
         function_summary: &Summary,
+        callee_is_closure: bool,
     ) {
         if let Some((place, target)) = destination {
             // Assign function result to place
             let target_path = self.visit_place(place);
             let return_value_path = Path::LocalVariable { ordinal: 0 };
+            // The call overwrites whatever was known about the destination. If the summary does
+            // not say anything about the result, the destination will end up with a fresh value.
+            self.current_environment.forget_value_at(&target_path);
             // Transfer side effects
             self.transfer_and_refine(
                 &function_summary.side_effects,
@@ -792,27 +6291,25 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 return_value_path,
                 &actual_args,
             );
-            for (i, arg) in actual_args.iter().enumerate() {
-                if let AbstractValue {
-                    domain:
-                        AbstractDomain {
-                            expression: Expression::Reference(target_path),
-                            ..
-                        },
-                    ..
-                } = arg
-                {
-                    let parameter_path = Path::LocalVariable { ordinal: i + 1 };
-                    self.transfer_and_refine(
-                        &function_summary.side_effects,
-                        target_path,
-                        parameter_path,
-                        &actual_args,
-                    );
+            self.transfer_unchanged_parts_of_argument(&target_path, args, function_summary);
+            self.transfer_parameter_and_static_side_effects(
+                &function_summary.side_effects,
+                actual_args,
+                function_summary,
+                callee_is_closure,
+            );
+            // Transfer the contents of heap blocks that were allocated by the callee and that
+            // escaped from it.
+            for (path, value) in function_summary.side_effects.iter() {
+                if let Path::AbstractHeapAddress { .. } = path.get_root() {
+                    let rvalue = value.refine_parameters(&actual_args);
+                    self.current_environment
+                        .update_value_at(path.clone(), rvalue);
                 }
             }
             let mut exit_condition = self.exit_environment.entry_condition.clone();
             if let Some(unwind_condition) = &function_summary.unwind_condition {
+                let unwind_condition = unwind_condition.refine_parameters(actual_args);
                 exit_condition =
                     exit_condition.and(&unwind_condition.not(None), Some(self.current_span));
             }
@@ -822,11 +6319,239 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         }
     }
 
+    /// If the callee returns one of its parameters, like a builder method that takes self by value,
+    /// sets a field and returns self, the parts of the result that the callee does not assign to
+    /// keep the values that they have in the corresponding argument. This keeps track of the
+    /// fields that earlier calls in a chain of builder methods have set.
+    fn transfer_unchanged_parts_of_argument(
+        &mut self,
+        target_path: &Path,
+        args: &[mir::Operand<'tcx>],
+        function_summary: &Summary,
+    ) {
+        let ordinal = match &function_summary.result {
+            Some(AbstractValue {
+                domain:
+                    AbstractDomain {
+                        expression: Expression::Variable { path, .. },
+                        ..
+                    },
+                ..
+            }) => match **path {
+                Path::LocalVariable { ordinal } if 0 < ordinal && ordinal <= args.len() => ordinal,
+                _ => return,
+            },
+            _ => return,
+        };
+        let argument_path = match &args[ordinal - 1] {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => self.visit_place(place),
+            mir::Operand::Constant(..) => return,
+        };
+        let return_value_path = Path::LocalVariable { ordinal: 0 };
+        let assigned_paths: Vec<Path> = function_summary
+            .side_effects
+            .iter()
+            .filter(|(path, _)| path.is_rooted_by(&return_value_path))
+            .map(|(path, _)| path.replace_root(&return_value_path, target_path.clone()))
+            .collect();
+        let unchanged_parts: Vec<(Path, AbstractValue)> = self
+            .current_environment
+            .value_map
+            .iter()
+            .filter(|(path, _)| path.is_rooted_by(&argument_path))
+            .map(|(path, value)| {
+                let part_path = path.replace_root(&argument_path, target_path.clone());
+                (part_path, value.clone())
+            })
+            .filter(|(part_path, _)| {
+                !assigned_paths
+                    .iter()
+                    .any(|assigned| part_path == assigned || part_path.is_rooted_by(assigned))
+            })
+            .collect();
+        for (path, value) in unchanged_parts {
+            self.current_environment.update_value_at(path, value);
+        }
+    }
+
+    /// Forgets everything known about the values of static variables, since the callee may have
+    /// modified them, and then updates the current state with the values that the callee is
+    /// known to have assigned to static variables.
+    fn transfer_static_side_effects(
+        &mut self,
+        effects: &[(Path, AbstractValue)],
+        arguments: &[AbstractValue],
+    ) {
+        let static_paths: Vec<Path> = self
+            .current_environment
+            .value_map
+            .keys()
+            .filter(|p| p.is_rooted_by_static())
+            .cloned()
+            .collect();
+        for path in static_paths.iter() {
+            self.current_environment.forget_value_at(path);
+        }
+        for (path, value) in effects.iter().filter(|(p, _)| p.is_rooted_by_static()) {
+            let rvalue = value.refine_parameters(arguments);
+            self.current_environment
+                .update_value_at(path.clone(), rvalue);
+        }
+    }
+
+    /// Updates the current state with the side effects that the callee has on the places that
+    /// the arguments refer to and, if it modifies static state, on static variables.
+    fn transfer_parameter_and_static_side_effects(
+        &mut self,
+        effects: &[(Path, AbstractValue)],
+        actual_args: &[AbstractValue],
+        function_summary: &Summary,
+        callee_is_closure: bool,
+    ) {
+        for (i, arg) in actual_args.iter().enumerate() {
+            if function_summary
+                .parameters_modified
+                .binary_search(&(i + 1))
+                .is_err()
+            {
+                // The callee does not modify anything via this parameter.
+                continue;
+            }
+            if callee_is_closure && i == 0 {
+                let parameter_path = Path::LocalVariable { ordinal: 1 };
+                self.transfer_closure_side_effects(effects, arg, parameter_path, actual_args);
+                continue;
+            }
+            if let AbstractValue {
+                domain:
+                    AbstractDomain {
+                        expression: Expression::Reference(target_path),
+                        ..
+                    },
+                ..
+            } = arg
+            {
+                let parameter_path = Path::LocalVariable { ordinal: i + 1 };
+                self.transfer_and_refine(effects, target_path, parameter_path, actual_args);
+            }
+        }
+        if function_summary.modifies_static_state {
+            self.transfer_static_side_effects(effects, actual_args);
+        }
+    }
+
+    /// The environment of a closure that captures a variable by reference has a field that refers
+    /// to the variable in the frame that created the closure. The closure body cannot make the
+    /// field refer to anything else, so the side effects that the closure has on the field are
+    /// assignments to the captured variable and they are transferred to the variable, rather than
+    /// overwriting the reference. This way the variables that the closure does not assign to keep
+    /// their values. The other side effects on the environment are transferred as for any other
+    /// parameter, which means that they are dropped if the closure is passed by value.
+    fn transfer_closure_side_effects(
+        &mut self,
+        effects: &[(Path, AbstractValue)],
+        closure: &AbstractValue,
+        parameter_path: Path,
+        actual_args: &[AbstractValue],
+    ) {
+        let closure_path = match Self::get_referenced_path(closure) {
+            Some(path) => path,
+            None => return,
+        };
+        let captured_variables =
+            self.get_modified_captured_variables(effects, &parameter_path, &closure_path);
+        if let Expression::Reference(..) = closure.domain.expression {
+            let other_effects: Vec<(Path, AbstractValue)> = effects
+                .iter()
+                .filter(|(path, _)| {
+                    captured_variables
+                        .iter()
+                        .all(|(field_path, _)| path != field_path && !path.is_rooted_by(field_path))
+                })
+                .cloned()
+                .collect();
+            self.transfer_and_refine(&other_effects, &closure_path, parameter_path, actual_args);
+        }
+        for (field_path, variable_path) in captured_variables.into_iter() {
+            self.transfer_and_refine(effects, &variable_path, field_path, actual_args);
+        }
+    }
+
+    /// Returns a (field_path, variable_path) pair for every field of the environment of the closure
+    /// at closure_path that refers to the variable at variable_path and that the given side
+    /// effects modify. The field path is rooted in parameter_path, the environment parameter of
+    /// the closure body.
+    fn get_modified_captured_variables(
+        &self,
+        effects: &[(Path, AbstractValue)],
+        parameter_path: &Path,
+        closure_path: &Path,
+    ) -> Vec<(Path, Path)> {
+        let mut captured_variables: Vec<(Path, Path)> = Vec::new();
+        for (path, _) in effects.iter() {
+            let mut field_path = path;
+            while let Path::QualifiedPath { qualifier, .. } = field_path {
+                if **qualifier == *parameter_path {
+                    break;
+                }
+                field_path = qualifier;
+            }
+            if let Path::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } = field_path
+            {
+                if **qualifier != *parameter_path
+                    || captured_variables.iter().any(|(p, _)| p == field_path)
+                {
+                    continue;
+                }
+                if let PathSelector::Field(field) = **selector {
+                    let closure_field_path = Self::get_field_path(closure_path.clone(), field);
+                    if let Some(AbstractValue {
+                        domain:
+                            AbstractDomain {
+                                expression: Expression::Reference(variable_path),
+                                ..
+                            },
+                        ..
+                    }) = self.current_environment.value_at(&closure_field_path)
+                    {
+                        captured_variables.push((field_path.clone(), variable_path.clone()));
+                    }
+                }
+            }
+        }
+        captured_variables
+    }
+
     /// Handle the case where the called function does not complete normally.
-    fn transfer_and_refine_cleanup_state(&mut self, cleanup: Option<mir::BasicBlock>) {
-        if let Some(cleanup_target) = cleanup {
-            //todo: transfer and refine the actual cleanup state (once summaries actually include it)
-            // this will also require the addition of something like self.current_unwind_environment.
+    /// The cleanup block starts out with the state before the call, updated with the side effects
+    /// that the callee may have had before it unwound. This state is kept in unwind_states, since
+    /// the current state at the end of the block is that of a normal return.
+    fn transfer_and_refine_cleanup_state(
+        &mut self,
+        cleanup: Option<mir::BasicBlock>,
+        pre_call_environment: Option<Environment>,
+        actual_args: &[AbstractValue],
+        function_summary: &Summary,
+        callee_is_closure: bool,
+    ) {
+        if let (Some(cleanup_target), Some(pre_call_environment)) = (cleanup, pre_call_environment)
+        {
+            let normal_return_environment =
+                std::mem::replace(&mut self.current_environment, pre_call_environment);
+            self.transfer_parameter_and_static_side_effects(
+                &function_summary.unwind_side_effects,
+                actual_args,
+                function_summary,
+                callee_is_closure,
+            );
+            let unwind_environment =
+                std::mem::replace(&mut self.current_environment, normal_return_environment);
+            self.unwind_states
+                .insert(self.current_location.block, unwind_environment);
             self.current_environment.exit_conditions.insert(
                 cleanup_target,
                 self.current_environment.entry_condition.clone(),
@@ -834,78 +6559,171 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         }
     }
 
+    /// Records that the function being analyzed unwinds if control reaches the current location
+    /// and the given condition holds. The disjunction of all such conditions becomes the unwind
+    /// condition of the summary, which callers use to refine the conditions of normal returns.
+    fn record_unwind_condition(&mut self, condition: &AbstractValue) {
+        debug_assert!(self.check_for_errors);
+        let unwind_condition = self
+            .current_environment
+            .entry_condition
+            .and(condition, Some(self.current_span));
+        self.unwind_condition = Some(match &self.unwind_condition {
+            Some(previous_condition) => previous_condition.or(&unwind_condition, None),
+            None => unwind_condition,
+        });
+    }
+
     /// If the function being called is a special function like unreachable or panic,
     /// then report a diagnostic if the call is definitely reachable.
     /// If the call might be reached then add a precondition that requires the caller of this
     /// function to ensure that the call to the special function will never be reached at runtime.
     fn report_calls_to_special_functions(
         &mut self,
-        func_to_call: AbstractValue,
+        known_function: Option<KnownFunction>,
         actual_args: Vec<AbstractValue>,
     ) {
         debug_assert!(self.check_for_errors);
-        let cache = &mut self.constant_value_cache;
-        if let Expression::CompileTimeConstant(fun) = func_to_call.domain.expression {
-            if cache.check_if_std_panicking_begin_panic_function(&fun) {
-                let mut path_cond = self.current_environment.entry_condition.as_bool_if_known();
-                if path_cond.is_none() {
-                    // Try the SMT solver
-                    let path_expr = &self.current_environment.entry_condition.domain.expression;
-                    let path_smt = self.smt_solver.get_as_smt_predicate(path_expr);
-                    if self.smt_solver.solve_expression(&path_smt) == SmtResult::Unsatisfiable {
-                        path_cond = Some(false)
-                    }
-                }
-                if !path_cond.unwrap_or(true) {
-                    // We never get to this call, so nothing to report.
-                    return;
-                }
+        let msg = match known_function {
+            Some(KnownFunction::CorePanickingPanic) => self.get_core_panic_message(&actual_args),
+            Some(KnownFunction::StdPanickingBeginPanic) => {
+                Self::get_constant_message(actual_args.get(0))
+            }
+            Some(KnownFunction::StdPanickingBeginPanicFmt) => {
+                self.get_formatted_panic_message(&actual_args)
+            }
+            _ => return,
+        };
+        if self.is_skipped_obligation(ObligationClass::Reachability) {
+            let message = format!(
+                "possible error: {}",
+                msg.unwrap_or_else(|| String::from("execution panic"))
+            );
+            self.push_skipped_obligation(&abstract_value::FALSE, &message);
+            return;
+        }
+        let entry_condition = self.current_environment.entry_condition.clone();
+        self.start_reachability_obligation();
+        let mut path_cond = entry_condition.as_bool_if_known();
+        if path_cond.is_none() && self.may_send_to_solver(&entry_condition) {
+            // Try the SMT solver
+            self.use_solver_strategy(ObligationClass::Reachability);
+            let path_expr = &entry_condition.domain.expression;
+            let path_smt = self.smt_solver.get_as_smt_predicate(path_expr);
+            if self.smt_solver.solve_expression(&path_smt) == SmtResult::Unsatisfiable {
+                path_cond = Some(false)
+            }
+        }
+        if !path_cond.unwrap_or(true) {
+            // We never get to this call, so nothing to report.
+            return;
+        }
 
-                let msg = if let Expression::CompileTimeConstant(ConstantDomain::Str(ref msg)) =
-                    actual_args[0].domain.expression
-                {
-                    if msg.contains("entered unreachable code") {
-                        // We tread unreachable!() as an assumption rather than an assertion to prove.
-                        return;
-                    } else {
-                        msg.clone()
-                    }
-                } else {
-                    String::from("execution panic")
-                };
-                let span = self.current_span;
+        let msg = match msg {
+            Some(ref msg)
+                if msg.contains("entered unreachable code") && !self.options.verify_no_panics =>
+            {
+                // We treat unreachable!() as an assumption rather than an assertion to prove,
+                // unless every panic has to be proven unreachable.
+                return;
+            }
+            Some(msg) => msg,
+            None => String::from("execution panic"),
+        };
+        // The call is part of the expansion of a macro such as panic! or assert!,
+        // so the warning points at the invocation of the macro in the code of the user.
+        let span = self.current_span;
 
-                if path_cond.unwrap_or(false) && is_public(self.def_id, &self.tcx) {
-                    // We always get to this call and we have to assume that the function will
-                    // get called, so keep the message certain.
-                    let mut err = self.session.struct_span_warn(span, msg.as_str());
-                    (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
-                } else {
-                    // We might get to this call, depending on the state at the call site.
-
-                    // In the case when an assert macro has been called, the inverse of the assertion
-                    // was conjoined into the entry condition and this condition was simplified.
-                    // We therefore cannot distinguish between the case of maybe reaching a definitely
-                    // false assertion from the case of definitely reaching a maybe false assertion.
-                    // We therefore report both cases, even though the first would be a candidate for being
-                    // an inferred precondition, rather than a diagnostic.
-                    //todo: we need our own library for contract assertions so that we can distinguish
-                    // such cases.
-
-                    let mut maybe_message = String::from("possible error: ");
-                    maybe_message.push_str(msg.as_str());
-                    let mut err = self.session.struct_span_warn(span, maybe_message.as_str());
-                    (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        if path_cond.unwrap_or(false) && self.is_analysis_root() {
+            // We always get to this call and we have to assume that the function will
+            // get called, so keep the message certain.
+            let mut err = self.struct_span_panic_for_user(span, msg.as_str());
+            err.code(Confidence::Definite.diagnostic_code());
+            (self.emit_diagnostic)(&mut err, &mut self.buffered_diagnostics);
+        } else {
+            // We might get to this call, depending on the state at the call site.
 
-                    // We also push a precondition in both cases.
-                    self.preconditions.push((
-                        self.current_environment
-                            .entry_condition
-                            .not(Some(self.current_span)),
-                        maybe_message,
-                    ));
+            // In the case when an assert macro has been called, the inverse of the assertion
+            // was conjoined into the entry condition and this condition was simplified.
+            // We therefore cannot distinguish between the case of maybe reaching a definitely
+            // false assertion from the case of definitely reaching a maybe false assertion.
+            // We therefore report both cases, even though the first would be a candidate for being
+            // an inferred precondition, rather than a diagnostic.
+            //todo: we need our own library for contract assertions so that we can distinguish
+            // such cases.
+
+            let mut maybe_message = String::from("possible error: ");
+            maybe_message.push_str(msg.as_str());
+            let mut err = self.struct_span_panic_for_user(span, maybe_message.as_str());
+            self.set_confidence(&mut err, false);
+            self.emit_unproven_condition_diagnostic(&mut err);
+
+            // We also push a precondition in both cases.
+            self.preconditions.push((
+                self.current_environment
+                    .entry_condition
+                    .not(Some(self.current_span)),
+                maybe_message,
+            ));
+        }
+    }
+
+    /// Returns the string if the given value is a constant string.
+    fn get_constant_message(value: Option<&AbstractValue>) -> Option<String> {
+        if let Some(AbstractValue {
+            domain:
+                AbstractDomain {
+                    expression: Expression::CompileTimeConstant(ConstantDomain::Str(msg)),
+                    ..
+                },
+            ..
+        }) = value
+        {
+            Some(msg.clone())
+        } else {
+            None
+        }
+    }
+
+    /// The first argument of core::panicking::panic references a (message, file, line, column)
+    /// tuple. Returns the message if it is known.
+    fn get_core_panic_message(&self, actual_args: &[AbstractValue]) -> Option<String> {
+        let tuple_path = actual_args.get(0).and_then(Self::get_referenced_path)?;
+        let message_path = Path::QualifiedPath {
+            length: tuple_path.path_length() + 1,
+            qualifier: box tuple_path,
+            selector: box PathSelector::Field(0),
+        };
+        Self::get_constant_message(self.current_environment.value_at(&message_path))
+    }
+
+    /// The first argument of begin_panic_fmt references the core::fmt::Arguments value that was
+    /// constructed by format_args!. The construction is modeled as an uninterpreted call (see
+    /// model_formatting_call), so the message is known if all of the values that flow into it
+    /// are constant strings, which is the case if format_args! has no arguments to format.
+    fn get_formatted_panic_message(&self, actual_args: &[AbstractValue]) -> Option<String> {
+        let arguments_path = actual_args.get(0).and_then(Self::get_referenced_path)?;
+        if let Some(AbstractValue {
+            domain:
+                AbstractDomain {
+                    expression: Expression::UninterpretedCall { arguments, .. },
+                    ..
+                },
+            ..
+        }) = self.current_environment.value_at(&arguments_path)
+        {
+            let mut msg = String::new();
+            for argument in arguments.iter() {
+                match &argument.expression {
+                    Expression::CompileTimeConstant(ConstantDomain::Str(piece)) => {
+                        msg.push_str(piece.as_str())
+                    }
+                    _ => return None,
                 }
             }
+            Some(msg)
+        } else {
+            None
         }
     }
 
@@ -927,6 +6745,12 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             let tpath = path.replace_root(&source_path, target_path.clone());
             let rvalue = value.refine_parameters(arguments);
             //todo: if refined_precondition is a Variable, look it up
+            if rvalue.is_top() {
+                // The callee may have modified the value in arbitrary ways. Values that are known
+                // for parts of the value are no longer valid. Effects are sorted so that any
+                // parts that the callee does know about are transferred after this.
+                self.current_environment.forget_value_at(&tpath);
+            }
             self.current_environment.update_value_at(tpath, rvalue);
         }
     }
@@ -962,12 +6786,33 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 .insert(cleanup_target, cleanup_condition);
         };
         if self.check_for_errors {
+            self.record_unwind_condition(if expected { &not_cond_val } else { &cond_val });
             if let mir::Operand::Constant(..) = cond {
                 // Do not complain about compile time constants known to the compiler.
                 // Leave that to the compiler.
             } else {
+                // With --check-overflow, arithmetic overflow is reported where it happens, rather
+                // than becoming a precondition that is reported at the calls of the function.
+                let is_checked_overflow = self.options.check_overflow
+                    && match msg {
+                        mir::interpret::InterpError::Overflow(..)
+                        | mir::interpret::InterpError::OverflowNeg => true,
+                        _ => false,
+                    };
+                let obligation_class = Self::get_obligation_class(msg);
+                if self.is_skipped_obligation(obligation_class) {
+                    if !is_checked_overflow {
+                        let expected_cond = if expected {
+                            cond_val
+                        } else {
+                            cond_val.not(Some(self.current_span))
+                        };
+                        self.push_skipped_obligation(&expected_cond, msg.description());
+                    }
+                    return;
+                }
                 let (cond_as_bool, entry_cond_as_bool) =
-                    self.check_condition_value_and_reachability(&cond_val);
+                    self.check_condition_value_and_reachability(&cond_val, obligation_class);
 
                 // Quick exit if things are known.
                 if cond_as_bool.is_some() {
@@ -977,66 +6822,211 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                     }
                     // If we always get here if called, give an error.
                     if entry_cond_as_bool.is_some() && entry_cond_as_bool.unwrap() {
-                        let error = msg.description();
+                        let error = if is_checked_overflow {
+                            self.get_overflow_message(true)
+                        } else {
+                            String::from(msg.description())
+                        };
                         let span = self.current_span;
-                        let mut error = self.session.struct_span_err(span, error);
+                        let mut error = self.struct_span_err_for_user(span, error.as_str());
+                        error.code(Confidence::Definite.diagnostic_code());
+                        Self::add_shift_overflow_witness(&mut error, &cond_val);
                         (self.emit_diagnostic)(&mut error, &mut self.buffered_diagnostics);
                         // No need to push a precondition, the caller can never satisfy it.
                         return;
                     }
                 }
 
-                // At this point, we don't know that this assert is unreachable and we don't know
-                // that the condition is as expected, so we need to warn about it somewhere.
-                if is_public(self.def_id, &self.tcx) {
-                    // We expect public functions to have programmer supplied preconditions
-                    // that preclude any assertions from failing. So, if at this stage we get to
-                    // complain a bit.
-                    let warning = format!("possible {}", msg.description());
-                    let span = self.current_span;
-                    let mut warning = self.session.struct_span_warn(span, warning.as_str());
-                    (self.emit_diagnostic)(&mut warning, &mut self.buffered_diagnostics);
-                }
+                // At this point, we don't know that this assert is unreachable and we don't know
+                // that the condition is as expected, so we need to warn about it somewhere.
+                if self.is_analysis_root() || is_checked_overflow {
+                    // We expect public functions to have programmer supplied preconditions
+                    // that preclude any assertions from failing. So, if at this stage we get to
+                    // complain a bit.
+                    let warning = if is_checked_overflow {
+                        self.get_overflow_message(false)
+                    } else {
+                        format!("possible {}", msg.description())
+                    };
+                    let span = self.current_span;
+                    let mut warning = self.struct_span_panic_for_user(span, warning.as_str());
+                    Self::add_shift_overflow_witness(&mut warning, &cond_val);
+                    self.set_confidence(&mut warning, false);
+                    self.emit_unproven_condition_diagnostic(&mut warning);
+                }
+                if is_checked_overflow {
+                    return;
+                }
+
+                // Regardless, it is still the caller's problem, so push a precondition.
+                let expected_cond = if expected {
+                    cond_val
+                } else {
+                    cond_val.not(Some(self.current_span))
+                };
+                // To make sure that this assertion never fails, we should either never
+                // get here (!entry_condition) or expected_cond should be true.
+                let pre_cond = self
+                    .current_environment
+                    .entry_condition
+                    .not(None)
+                    .or(&expected_cond, Some(self.current_span));
+                self.preconditions
+                    .push((pre_cond, String::from(msg.description())));
+            }
+        };
+    }
+
+    /// A suspension point of a generator, such as an .await in an async function. The generator
+    /// yields the given value and is later either resumed at resume, or dropped at drop.
+    fn visit_yield(
+        &mut self,
+        value: &mir::Operand<'tcx>,
+        resume: mir::BasicBlock,
+        drop: Option<mir::BasicBlock>,
+    ) {
+        debug!(
+            "default visit_yield(value: {:?}, resume: {:?}, drop: {:?})",
+            value, resume, drop
+        );
+        self.visit_operand(value);
+        self.invalidate_state_at_suspension();
+        // Propagate the entry condition to the successor blocks.
+        self.current_environment
+            .exit_conditions
+            .insert(resume, self.current_environment.entry_condition.clone());
+        if let Some(drop_target) = drop {
+            self.current_environment.exit_conditions.insert(
+                drop_target,
+                self.current_environment.entry_condition.clone(),
+            );
+        }
+    }
+
+    /// While a generator is suspended, other code runs. That code cannot change the locals of the
+    /// generator, nor the targets of the references that the generator holds, since these are
+    /// borrowed for as long as the generator lives. The exceptions are static variables, targets
+    /// of raw pointers, and targets of shared references to types with interior mutability, such
+    /// as Cell or Mutex. Only what is known about the values of these is forgotten.
+    fn invalidate_state_at_suspension(&mut self) {
+        let changeable_paths: Vec<Path> = self
+            .current_environment
+            .value_map
+            .iter()
+            .map(|(path, _)| path)
+            .filter(|path| self.may_change_while_suspended(path))
+            .cloned()
+            .collect();
+        for path in changeable_paths.iter() {
+            self.current_environment.forget_value_at(path);
+        }
+    }
+
+    /// True if the value at the given path can legally be changed by other code while the
+    /// generator that is being analyzed is suspended. See invalidate_state_at_suspension.
+    fn may_change_while_suspended(&self, path: &Path) -> bool {
+        let ordinal = match path.get_root() {
+            Path::StaticVariable { .. } => return true,
+            Path::LocalVariable { ordinal } if *ordinal < self.mir.local_decls.len() => *ordinal,
+            _ => return false,
+        };
+        // Find the selector that is applied to the local itself.
+        let mut qualified_path = path;
+        while let Path::QualifiedPath { qualifier, .. } = qualified_path {
+            if let Path::QualifiedPath { .. } = **qualifier {
+                qualified_path = &**qualifier;
+            } else {
+                break;
+            }
+        }
+        match qualified_path {
+            Path::QualifiedPath { selector, .. } if **selector == PathSelector::Deref => {
+                let local_type = self.mir.local_decls[mir::Local::from(ordinal)].ty;
+                match local_type.sty {
+                    TyKind::RawPtr(..) => true,
+                    TyKind::Ref(_, target_type, hir::Mutability::MutImmutable) => {
+                        let param_env = self.tcx.param_env(self.def_id);
+                        !target_type.is_freeze(self.tcx, param_env, self.current_span)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Classifies the proof obligation of a compiler inserted assertion by the kind of failure it guards against.
+    fn get_obligation_class(msg: &mir::AssertMessage<'tcx>) -> ObligationClass {
+        match msg {
+            mir::interpret::InterpError::BoundsCheck { .. } => ObligationClass::BoundsCheck,
+            mir::interpret::InterpError::Overflow(..)
+            | mir::interpret::InterpError::OverflowNeg
+            | mir::interpret::InterpError::DivisionByZero
+            | mir::interpret::InterpError::RemainderByZero => ObligationClass::Overflow,
+            _ => ObligationClass::Contract,
+        }
+    }
 
-                // Regardless, it is still the caller's problem, so push a precondition.
-                let expected_cond = if expected {
-                    cond_val
-                } else {
-                    cond_val.not(Some(self.current_span))
-                };
-                // To make sure that this assertion never fails, we should either never
-                // get here (!entry_condition) or expected_cond should be true.
-                let pre_cond = self
-                    .current_environment
-                    .entry_condition
-                    .not(None)
-                    .or(&expected_cond, Some(self.current_span));
-                self.preconditions
-                    .push((pre_cond, String::from(msg.description())));
-            }
-        };
+    /// If the given condition is true when a shift operation overflows, adds a note to the
+    /// diagnostic that says which shift amounts are possible, as a witness of the overflow.
+    /// A shift overflows if the shift amount is negative or not less than the bit width.
+    fn add_shift_overflow_witness(
+        diagnostic: &mut DiagnosticBuilder<'_>,
+        cond_val: &AbstractValue,
+    ) {
+        if let Expression::ShlOverflows {
+            right, result_type, ..
+        }
+        | Expression::ShrOverflows {
+            right, result_type, ..
+        } = &cond_val.domain.expression
+        {
+            let interval = right.get_as_interval();
+            let bit_length = result_type.bit_length();
+            let note = match (interval.lower_bound(), interval.upper_bound()) {
+                (Some(lower_bound), Some(upper_bound)) if !interval.is_bottom() => format!(
+                    "the shift amount can be any value from {} to {}, but must be less than {}",
+                    lower_bound, upper_bound, bit_length
+                ),
+                _ => format!(
+                    "the shift amount is not known to be at least 0 and less than {}",
+                    bit_length
+                ),
+            };
+            diagnostic.note(note.as_str());
+        }
     }
 
     /// Checks the given condition value and also checks if the current entry condition can be true.
     /// If the abstract domains are undecided, resort to using the SMT solver.
+    /// The solver uses the strategy configured for the given class of obligation.
     /// Only call this when doing actual error checking, since this is expensive.
     fn check_condition_value_and_reachability(
         &mut self,
         cond_val: &AbstractValue,
+        obligation_class: ObligationClass,
     ) -> (Option<bool>, Option<bool>) {
+        // The condition is simplified once, here, so that the domains, the solver and the exported
+        // obligation, if any, all get the same simplified condition.
+        let cond_val = &cond_val.simplify();
+        self.start_obligation(cond_val);
+        self.use_solver_strategy(obligation_class);
         // Check if the condition is always true (or false) if we get here.
         let mut cond_as_bool = cond_val.as_bool_if_known();
         // Check if we can prove that every call to the current function will reach this call site.
         let mut entry_cond_as_bool = self.current_environment.entry_condition.as_bool_if_known();
         // Use SMT solver if need be.
         if entry_cond_as_bool.is_none() {
+            let entry_condition = self.current_environment.entry_condition.clone();
+            if !self.may_send_to_solver(&entry_condition) {
+                return (cond_as_bool, entry_cond_as_bool);
+            }
             // The abstract domains are unable to decide if the entry condition is always true.
             // (If it could decide that the condition is always false, we wouldn't be here.)
             // See if the SMT solver can prove that the entry condition is always true.
-            let smt_expr = {
-                let ec = &self.current_environment.entry_condition.domain.expression;
-                self.smt_solver.get_as_smt_predicate(ec)
-            };
+            let smt_expr = self
+                .smt_solver
+                .get_as_smt_predicate(&entry_condition.domain.expression);
             self.smt_solver.set_backtrack_position();
             self.smt_solver.assert(&smt_expr);
             if self.smt_solver.solve() == SmtResult::Unsatisfiable {
@@ -1053,6 +7043,9 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
     }
 
     fn solve_condition(&mut self, cond_val: &AbstractValue) -> Option<bool> {
+        if !self.may_send_to_solver(cond_val) {
+            return None;
+        }
         let ce = &cond_val.domain.expression;
         let cond_smt_expr = self.smt_solver.get_as_smt_predicate(ce);
         match self.smt_solver.solve_expression(&cond_smt_expr) {
@@ -1065,17 +7058,462 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 // So lets see if !cond_val is provably false.
                 let not_cond_expr = cond_val.not(None).domain.expression;
                 let smt_expr = self.smt_solver.get_as_smt_predicate(&not_cond_expr);
-                if self.smt_solver.solve_expression(&smt_expr) == SmtResult::Unsatisfiable {
-                    // The solver can prove that !cond_val is always false.
-                    Some(true)
-                } else {
-                    None
+                let variables = self.get_source_variables(&not_cond_expr);
+                let model_expressions: Vec<E> = variables
+                    .iter()
+                    .map(|(_, variable)| self.smt_solver.get_as_smt_predicate(variable))
+                    .collect();
+                match self
+                    .smt_solver
+                    .solve_expression_for_model(&smt_expr, &model_expressions)
+                {
+                    (SmtResult::Unsatisfiable, _) => {
+                        // The solver can prove that !cond_val is always false.
+                        Some(true)
+                    }
+                    (SmtResult::Satisfiable, values) => {
+                        // The solver found a model in which cond_val is false.
+                        self.counterexample_found = true;
+                        let assignments: Vec<String> = variables
+                            .iter()
+                            .zip(values.iter())
+                            .filter_map(|((name, _), value)| {
+                                value.as_ref().map(|value| format!("{} = {}", name, value))
+                            })
+                            .collect();
+                        if !assignments.is_empty() {
+                            self.counterexample = Some(assignments.join(", "));
+                        }
+                        None
+                    }
+                    (SmtResult::Undefined, _) => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the variables of the given expression that stand for places that can be named in
+    /// the source of the function, such as its parameters and local variables, along with those
+    /// names. The values that a model of the solver gives these variables make up a counterexample.
+    fn get_source_variables(&self, expression: &Expression) -> Vec<(String, Expression)> {
+        let mut variables = Vec::new();
+        expression.record_variables(&mut variables);
+        variables
+            .into_iter()
+            .filter_map(|variable| {
+                let name = match &variable {
+                    Expression::Variable { path, .. } => self.get_source_name(path)?,
+                    _ => return None,
+                };
+                Some((name, variable))
+            })
+            .collect()
+    }
+
+    /// Returns the name of the place identified by the given path, as it would be written in the
+    /// source of the function, or None if the place has no such name, as is the case for compiler
+    /// temporaries and heap blocks.
+    fn get_source_name(&self, path: &Path) -> Option<String> {
+        match path {
+            Path::LocalVariable { ordinal }
+                if *ordinal > 0 && *ordinal < self.mir.local_decls.len() =>
+            {
+                self.mir.local_decls[mir::Local::from(*ordinal)]
+                    .name
+                    .map(|name| name.to_string())
+            }
+            Path::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } => {
+                let qualifier_name = self.get_source_name(qualifier)?;
+                match &**selector {
+                    PathSelector::ArrayLength => Some(format!("{}.len()", qualifier_name)),
+                    // References are dereferenced implicitly in most source expressions.
+                    PathSelector::Deref => Some(qualifier_name),
+                    PathSelector::Field(ordinal) => Some(format!("{}.{}", qualifier_name, ordinal)),
+                    PathSelector::Index(index) => {
+                        if let Expression::CompileTimeConstant(ConstantDomain::U128(index)) =
+                            &index.domain.expression
+                        {
+                            Some(format!("{}[{}]", qualifier_name, index))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
                 }
             }
             _ => None,
         }
     }
 
+    /// Tells the solver to use the strategy that the options configure for the given class of
+    /// obligation, or its default strategy if none is configured.
+    fn use_solver_strategy(&mut self, obligation_class: ObligationClass) {
+        let strategy = self.options.solver_strategies.get(&obligation_class);
+        self.smt_solver.set_strategy(strategy.map(String::as_str));
+    }
+
+    /// In linear arithmetic mode, conditions that involve nonlinear arithmetic are not sent to the
+    /// solver, so that analysis time stays predictable. Returns false for such conditions and records
+    /// that this happened, so that the resulting diagnostic can say why the condition was not proven.
+    /// Functions of modules with the Fast precision preset are always analyzed in this mode.
+    fn may_send_to_solver(&mut self, condition: &AbstractValue) -> bool {
+        let linear_arithmetic_only =
+            self.options.linear_arithmetic_only || self.precision_preset == PrecisionPreset::Fast;
+        if linear_arithmetic_only && condition.domain.expression.is_nonlinear() {
+            self.unsolved_nonlinear_condition = true;
+            return false;
+        }
+        true
+    }
+
+    /// Resets what is known about why the obligation to prove the given condition, at the current
+    /// location, may go unproven. If the condition, or the entry condition, depends on a value that
+    /// was replaced with Top because of a k-limit, this is recorded so that the resulting diagnostic
+    /// can point out where precision was limited. Likewise, if it depends on the result of a call
+    /// to a function without a summary or model, this is recorded for the confidence of the
+    /// resulting diagnostic.
+    fn start_obligation(&mut self, condition: &AbstractValue) {
+        self.obligation_goal = condition.clone();
+        self.counterexample = None;
+        self.counterexample_found = false;
+        self.unsolved_nonlinear_condition = false;
+        let entry_condition = &self.current_environment.entry_condition.domain.expression;
+        self.unmodeled_condition = self.unmodeled_call_results.iter().any(|path| {
+            condition
+                .domain
+                .expression
+                .refers_to_variable_rooted_by(path)
+                || entry_condition.refers_to_variable_rooted_by(path)
+        });
+        self.untrusted_input_spans = self.get_untrusted_inputs_of(condition);
+        self.precision_limited_condition = !self.precision_limits.is_empty()
+            && (condition.domain.expression.contains_top()
+                || self
+                    .current_environment
+                    .entry_condition
+                    .domain
+                    .expression
+                    .contains_top());
+    }
+
+    /// Like start_obligation, for the obligation to prove that the current location cannot be
+    /// reached, i.e. that the entry condition is false.
+    fn start_reachability_obligation(&mut self) {
+        let entry_condition = self.current_environment.entry_condition.clone();
+        self.start_obligation(&entry_condition);
+        self.obligation_goal = abstract_value::FALSE;
+    }
+
+    /// Returns the locations where untrusted inputs, on which the given condition or the current
+    /// entry condition depends, entered the program. These are the locations where the values
+    /// were tagged as untrusted.
+    fn get_untrusted_inputs_of(&self, condition: &AbstractValue) -> Vec<syntax_pos::Span> {
+        let entry_condition = &self.current_environment.entry_condition.domain.expression;
+        let mut spans: Vec<syntax_pos::Span> = self
+            .current_environment
+            .value_map
+            .iter()
+            .filter_map(|(path, value)| match path {
+                Path::QualifiedPath {
+                    qualifier,
+                    selector,
+                    ..
+                } if **selector == PathSelector::Tag(Tag::Untrusted)
+                    && value.as_bool_if_known() == Some(true)
+                    && (condition
+                        .domain
+                        .expression
+                        .refers_to_variable_rooted_by(qualifier)
+                        || entry_condition.refers_to_variable_rooted_by(qualifier)) =>
+                {
+                    value.provenance.last().cloned()
+                }
+                _ => None,
+            })
+            .collect();
+        spans.sort();
+        spans.dedup();
+        spans
+    }
+
+    /// Gives the diagnostic the code for its confidence. If the diagnostic is about a condition
+    /// that is known to fail, definite should be true. Otherwise the confidence depends on why
+    /// the current obligation could not be proven. If the condition depends on untrusted input,
+    /// the diagnostic also gets a note for each location where such input entered the program,
+    /// which marks it as a security finding (see security::export_findings). If the options have
+    /// a template for the message or confidence of the diagnostic, the message is replaced by it.
+    fn set_confidence(&self, diagnostic: &mut DiagnosticBuilder<'_>, definite: bool) {
+        let confidence = if definite || self.counterexample_found {
+            Confidence::Definite
+        } else if self.unmodeled_condition {
+            Confidence::MissingModels
+        } else {
+            Confidence::LimitedPrecision
+        };
+        diagnostic.code(confidence.diagnostic_code());
+        for span in self.untrusted_input_spans.iter() {
+            diagnostic.span_note(*span, security::UNTRUSTED_INPUT_NOTE);
+        }
+        let message = diagnostic.message();
+        if let Some(template) = message_templates::get_template(
+            &self.options.message_templates,
+            &message,
+            confidence.name(),
+        ) {
+            let facts = self.get_message_facts(message, confidence);
+            diagnostic.set_primary_message(message_templates::interpolate(template, &facts));
+        }
+    }
+
+    /// Returns what is known about the current obligation, which a diagnostic with the given
+    /// message and confidence reports, for use by the message templates of the options.
+    fn get_message_facts(&self, message: String, confidence: Confidence) -> MessageFacts {
+        let range = match &self.obligation_goal.domain.expression {
+            Expression::Equals { left, .. }
+            | Expression::Ne { left, .. }
+            | Expression::GreaterOrEqual { left, .. }
+            | Expression::GreaterThan { left, .. }
+            | Expression::LessOrEqual { left, .. }
+            | Expression::LessThan { left, .. } => left.get_as_interval(),
+            _ => interval_domain::TOP,
+        };
+        let source_map = self.session.source_map();
+        let sources: Vec<String> = self
+            .untrusted_input_spans
+            .iter()
+            .map(|span| source_map.span_to_string(*span))
+            .collect();
+        MessageFacts {
+            message,
+            confidence: confidence.name().to_string(),
+            range: if range.is_top() || range.is_bottom() {
+                String::from("unknown")
+            } else {
+                range.to_string()
+            },
+            sources: if sources.is_empty() {
+                String::from("none")
+            } else {
+                sources.join(", ")
+            },
+            path_length: Self::count_conjuncts(
+                &self.current_environment.entry_condition.domain.expression,
+            ),
+            counterexample: self
+                .counterexample
+                .clone()
+                .unwrap_or_else(|| String::from("none")),
+        }
+    }
+
+    /// Returns the number of conditions that the given condition conjoins, which is the number of
+    /// branch conditions on the path to a location if it is the entry condition of the location.
+    fn count_conjuncts(condition: &Expression) -> usize {
+        match condition {
+            Expression::And { left, right } => {
+                Self::count_conjuncts(&left.expression) + Self::count_conjuncts(&right.expression)
+            }
+            Expression::CompileTimeConstant(ConstantDomain::True) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Returns true if nothing is known about what the function to call does, because there is
+    /// neither a model for it, nor a summary that was obtained by analyzing its body.
+    fn is_unmodeled_function(&self, func_to_call: &AbstractValue) -> bool {
+        match func_to_call.domain.expression {
+            Expression::CompileTimeConstant(ConstantDomain::Function {
+                def_id: Some(def_id),
+                ..
+            }) => self.summary_cache.is_signature_summary(def_id),
+            _ => true,
+        }
+    }
+
+    /// Starts a warning about the code at the given span. If the span is part of the expansion of a
+    /// macro, such as assert!, the warning is given at the invocation of the macro. If the span
+    /// is inside the sources of the standard library, which is the case for code from the standard
+    /// library that ended up in the body of the current function, the warning is given at the
+    /// definition of the current function, with a note saying where in the library the problem
+    /// is. Users cannot act on diagnostics located in files that they do not own.
+    fn struct_span_warn_for_user(
+        &self,
+        span: syntax_pos::Span,
+        message: &str,
+    ) -> DiagnosticBuilder<'tcx> {
+        let (user_span, origin) = self.get_user_span(span);
+        let mut warning = self.session.struct_span_warn(user_span, message);
+        Self::add_library_origin_note(&mut warning, origin);
+        warning
+    }
+
+    /// Like struct_span_warn_for_user, but starts an error.
+    fn struct_span_err_for_user(
+        &self,
+        span: syntax_pos::Span,
+        message: &str,
+    ) -> DiagnosticBuilder<'tcx> {
+        let (user_span, origin) = self.get_user_span(span);
+        let mut error = self.session.struct_span_err(user_span, message);
+        Self::add_library_origin_note(&mut error, origin);
+        error
+    }
+
+    /// Starts a diagnostic about a possible panic at the code at the given span, which is an error
+    /// with --verify-no-panics and a warning otherwise. See struct_span_warn_for_user.
+    fn struct_span_panic_for_user(
+        &self,
+        span: syntax_pos::Span,
+        message: &str,
+    ) -> DiagnosticBuilder<'tcx> {
+        if self.options.verify_no_panics {
+            self.struct_span_err_for_user(span, message)
+        } else {
+            self.struct_span_warn_for_user(span, message)
+        }
+    }
+
+    /// Returns the location in the code of the user where a diagnostic about the code at the given
+    /// span should be given, along with a description of the location in the library, if the
+    /// span is inside the sources of the standard library.
+    fn get_user_span(&self, span: syntax_pos::Span) -> (syntax_pos::Span, Option<String>) {
+        let callsite = span.source_callsite();
+        if !self.is_in_library_code(callsite) {
+            return (callsite, None);
+        }
+        let origin = self.session.source_map().span_to_string(callsite);
+        (self.tcx.def_span(self.def_id), Some(origin))
+    }
+
+    /// Adds a note that says where in the library the problem that the diagnostic is about is.
+    fn add_library_origin_note(diagnostic: &mut DiagnosticBuilder<'_>, origin: Option<String>) {
+        if let Some(origin) = origin {
+            diagnostic.note(format!("this originates in library code at {}", origin).as_str());
+        }
+    }
+
+    /// True if the given span is located in a source file that was imported from another crate,
+    /// such as the standard library.
+    fn is_in_library_code(&self, span: syntax_pos::Span) -> bool {
+        !span.is_dummy()
+            && self
+                .session
+                .source_map()
+                .lookup_char_pos(span.lo())
+                .file
+                .is_imported()
+    }
+
+    /// Emits a diagnostic about a condition that was not proven, with the notes of
+    /// add_unproven_condition_notes, unless the proofs file of the options says that the obligation
+    /// was proven by other means. In that case the diagnostic is cancelled and the obligation is
+    /// assumed to hold, which is recorded in the assumption log.
+    fn emit_unproven_condition_diagnostic(&mut self, diagnostic: &mut DiagnosticBuilder<'_>) {
+        if self.assume_externally_proven_obligation(diagnostic) {
+            diagnostic.cancel();
+            return;
+        }
+        self.add_unproven_condition_notes(diagnostic);
+        (self.emit_diagnostic)(diagnostic, &mut self.buffered_diagnostics);
+    }
+
+    /// Returns true if the current obligation, which the given diagnostic reports, is one of the
+    /// proven obligations of the options, in which case it is added to the assumed obligations.
+    fn assume_externally_proven_obligation(&mut self, diagnostic: &DiagnosticBuilder<'_>) -> bool {
+        let options = self.options;
+        let summary_key = utils::summary_key_str(&self.tcx, self.def_id);
+        let proofs = match options.proven_obligations.get(&summary_key) {
+            Some(proofs) => proofs,
+            None => return false,
+        };
+        let obligation = self.get_current_obligation(diagnostic);
+        let checksum = smt_lib::obligation_checksum(&obligation);
+        match proofs.get(&checksum) {
+            Some(proof) => {
+                debug!("assuming {} at {}", obligation.message, obligation.location);
+                self.assumed_obligations.push(AssumedObligation {
+                    function: summary_key,
+                    checksum,
+                    proof: proof.clone(),
+                    message: obligation.message,
+                    location: obligation.location,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds notes to a diagnostic about a condition that was not proven: the values of the
+    /// counterexample that the solver found, if any, or the reasons other than the solver being
+    /// unable to prove it: the condition was not sent to the solver in linear arithmetic mode, or it
+    /// depends on values whose precision was limited by the k-limits. The obligation is recorded
+    /// for export if the options ask for that.
+    fn add_unproven_condition_notes(&mut self, diagnostic: &mut DiagnosticBuilder<'_>) {
+        self.record_unproven_obligation(diagnostic);
+        if let Some(counterexample) = &self.counterexample {
+            diagnostic.note(&format!("counterexample: {}", counterexample));
+        }
+        if self.unsolved_nonlinear_condition {
+            diagnostic.note(
+                "this involves nonlinear arithmetic, which is not sent to the solver in linear arithmetic mode or for fast modules",
+            );
+        }
+        if self.precision_limited_condition {
+            for (span, reason) in &self.precision_limits {
+                let span = span.source_callsite();
+                if self.is_in_library_code(span) {
+                    continue;
+                }
+                let note = format!("precision was limited here, because {}", reason);
+                diagnostic.span_note(span, note.as_str());
+            }
+        }
+    }
+
+    /// If the options give a directory for unproven obligations, records the current obligation,
+    /// along with the message and location of the diagnostic that reports it, so that it can be
+    /// written to the directory once the function has been analyzed.
+    fn record_unproven_obligation(&mut self, diagnostic: &DiagnosticBuilder<'_>) {
+        if self.options.obligations_directory.is_none() {
+            return;
+        }
+        let obligation = self.get_current_obligation(diagnostic);
+        self.unproven_obligations.push(obligation);
+    }
+
+    /// Returns the current obligation, along with the message and location of the given diagnostic,
+    /// which reports it.
+    fn get_current_obligation(&self, diagnostic: &DiagnosticBuilder<'_>) -> UnprovenObligation {
+        UnprovenObligation {
+            message: diagnostic.message(),
+            location: self.get_location(diagnostic.span.primary_span()),
+            entry_condition: self
+                .current_environment
+                .entry_condition
+                .domain
+                .expression
+                .clone(),
+            goal: self.obligation_goal.domain.expression.clone(),
+        }
+    }
+
+    /// Returns the given span as file:line:column, if there is one.
+    fn get_location(&self, span: Option<syntax_pos::Span>) -> String {
+        match span {
+            Some(span) => {
+                let loc = self.session.source_map().lookup_char_pos(span.lo());
+                format!("{}:{}:{}", loc.file.name, loc.line, loc.col.0 + 1)
+            }
+            None => String::from("unknown location"),
+        }
+    }
+
     /// Calls a specialized visitor for each kind of Rvalue
     fn visit_rvalue(&mut self, path: Path, rvalue: &mir::Rvalue<'tcx>) {
         match rvalue {
@@ -1156,6 +7594,7 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             "default visit_used_copy(target_path: {:?}, place: {:?})",
             target_path, place
         );
+        self.record_use_of(place);
         let rpath = self.visit_place(place);
         let rtype = self.get_place_type(place);
         self.copy_or_move_elements(target_path, rpath, rtype, false);
@@ -1309,6 +7748,7 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             "default visit_used_move(target_path: {:?}, place: {:?})",
             target_path, place
         );
+        self.record_use_of(place);
         let rpath = self.visit_place(place);
         let rtype = self.get_place_type(place);
         self.copy_or_move_elements(target_path, rpath, rtype, true);
@@ -1320,14 +7760,29 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             "default visit_repeat(path: {:?}, operand: {:?}, count: {:?})",
             path, operand, count
         );
-        self.visit_operand(operand);
-        //todo: needs #62
-        // get a heap address and put it in Path::AbstractHeapAddress
-        // get an abs value for x
-        // create a PathSelector::Index paths where the value is the range 0..count
-        // add qualified path to the environment with value x.
+        let element_value = self.visit_operand(operand);
+        let array_value = self.get_new_heap_address();
+        self.current_environment
+            .update_value_at(path.clone(), array_value);
+        // All elements have the same value, so the elements that are not tracked individually
+        // are summarized by it as well.
+        let element_count = cmp::min(count, k_limits::MAX_TRACKED_ARRAY_ELEMENTS as u64 + 1);
+        self.current_environment.initialize_elements_at(
+            &path,
+            iter::repeat(element_value).take(element_count as usize),
+        );
+        let length_path = Path::QualifiedPath {
+            qualifier: box path.clone(),
+            selector: box PathSelector::ArrayLength,
+            length: path.path_length() + 1,
+        };
+        let length_value = self
+            .constant_value_cache
+            .get_u128_for(u128::from(count))
+            .clone()
+            .into();
         self.current_environment
-            .update_value_at(path, abstract_value::TOP);
+            .update_value_at(length_path, length_value);
     }
 
     /// path = &x or &mut x
@@ -1342,6 +7797,7 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             "default visit_ref(path: {:?}, region: {:?}, borrow_kind: {:?}, place: {:?})",
             path, region, borrow_kind, place
         );
+        self.record_use_of(place);
         let value_path = self.visit_place(place);
         let value = Expression::Reference(value_path).into();
         self.current_environment.update_value_at(path, value);
@@ -1385,7 +7841,71 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             "default visit_cast(path: {:?}, cast_kind: {:?}, operand: {:?}, ty: {:?})",
             path, cast_kind, operand, ty
         );
-        self.visit_use(path, operand)
+        self.visit_use(path.clone(), operand);
+        if let TyKind::RawPtr(..) = ty.sty {
+            if operand.ty(&self.mir.local_decls, self.tcx).is_integral() {
+                self.visit_int_to_ptr_cast(path.clone());
+            }
+        }
+        if self.options.check_determinism {
+            // The address of a value differs from run to run.
+            let is_address = match operand.ty(&self.mir.local_decls, self.tcx).sty {
+                TyKind::RawPtr(..) | TyKind::Ref(..) | TyKind::FnPtr(..) => true,
+                _ => false,
+            };
+            if is_address && ty.is_integral() {
+                let tag_path = Self::get_tag_path(path, Tag::Nondeterministic);
+                self.current_environment.update_value_at(
+                    tag_path,
+                    abstract_value::TRUE.with_provenance(self.current_span),
+                );
+            }
+        }
+    }
+
+    /// Treats the cast of an integer to the raw pointer at the given path as selected by the
+    /// --int-to-ptr-casts option. Under strict provenance, such a pointer cannot be used to access
+    /// memory, so the cast is reported, as an error or a warning, unless the integer is known to
+    /// be zero, since null pointers are never used to access memory anyway. Angelic casts are not
+    /// reported and give the pointer an allocation of its own, which is what it must point to if
+    /// the program is correct.
+    fn visit_int_to_ptr_cast(&mut self, path: Path) {
+        match self.options.int_to_ptr_casts {
+            IntToPointerCasts::Angelic => {
+                let address = self.get_new_heap_address();
+                self.current_environment.update_value_at(path, address);
+            }
+            treatment => {
+                if !self.check_for_errors
+                    || self.current_environment.entry_condition.as_bool_if_known() == Some(false)
+                {
+                    return;
+                }
+                let is_null = match self.current_environment.value_at(&path) {
+                    Some(value) => match value.domain.expression {
+                        Expression::CompileTimeConstant(ConstantDomain::U128(0))
+                        | Expression::CompileTimeConstant(ConstantDomain::I128(0)) => true,
+                        _ => false,
+                    },
+                    None => false,
+                };
+                if is_null {
+                    return;
+                }
+                let span = self.current_span;
+                let message = "integer to pointer cast: the pointer has no provenance";
+                let mut diagnostic = if treatment == IntToPointerCasts::Strict {
+                    self.struct_span_err_for_user(span, message)
+                } else {
+                    self.struct_span_warn_for_user(span, message)
+                };
+                diagnostic.code(Confidence::Definite.diagnostic_code());
+                diagnostic.note(
+                    "under strict provenance, derive the pointer from a pointer to the allocation instead",
+                );
+                (self.emit_diagnostic)(&mut diagnostic, &mut self.buffered_diagnostics);
+            }
+        }
     }
 
     /// Apply the given binary operator to the two operands and assign result to path.
@@ -1402,6 +7922,9 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         );
         let mut left = self.visit_operand(left_operand);
         let mut right = self.visit_operand(right_operand);
+        if self.check_for_errors && bin_op == mir::BinOp::Offset {
+            self.check_pointer_offset(&left, &right);
+        }
         let result = match bin_op {
             mir::BinOp::Add => left.add(&right, Some(self.current_span)),
             mir::BinOp::BitAnd => left.bit_and(&right, Some(self.current_span)),
@@ -1425,9 +7948,99 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             }
             mir::BinOp::Sub => left.sub(&right, Some(self.current_span)),
         };
+        let result = if self.session.overflow_checks() {
+            // Operations that can overflow are checked operations, followed by assertions.
+            result
+        } else {
+            self.wrap_on_overflow(bin_op, left_operand, &mut left, &mut right, result)
+        };
         self.current_environment.update_value_at(path, result);
     }
 
+    /// When the crate is compiled without overflow checks, an arithmetic operation that overflows
+    /// wraps around rather than panic, and a shift by too many bits shifts by the amount masked to
+    /// the number of bits of the operand. The result of an operation on constants is known, but
+    /// wrapping is otherwise not modeled precisely, so the result of an operation that
+    /// overflows is unknown. If the user opted into it, a diagnostic is given for operations that
+    /// might overflow.
+    fn wrap_on_overflow(
+        &mut self,
+        bin_op: mir::BinOp,
+        left_operand: &mir::Operand<'tcx>,
+        left: &mut AbstractValue,
+        right: &mut AbstractValue,
+        result: AbstractValue,
+    ) -> AbstractValue {
+        let operand_type: ExpressionType = (&left_operand.ty(self.mir, self.tcx).sty).into();
+        let span = Some(self.current_span);
+        let overflows = match bin_op {
+            mir::BinOp::Add => left.add_overflows(right, operand_type, span),
+            mir::BinOp::Mul => left.mul_overflows(right, operand_type, span),
+            mir::BinOp::Shl => left.shl_overflows(right, operand_type, span),
+            mir::BinOp::Shr => left.shr_overflows(right, operand_type, span),
+            mir::BinOp::Sub => left.sub_overflows(right, operand_type, span),
+            _ => return result,
+        };
+        if self.check_for_errors
+            && (self.options.lint_wrapping_arithmetic || self.options.check_overflow)
+            && !self.is_skipped_obligation(ObligationClass::Overflow)
+        {
+            let (overflows_as_bool, entry_cond_as_bool) =
+                self.check_condition_value_and_reachability(&overflows, ObligationClass::Overflow);
+            if overflows_as_bool.unwrap_or(true) && entry_cond_as_bool.unwrap_or(true) {
+                let definite = overflows_as_bool.is_some() && entry_cond_as_bool.is_some();
+                let warning = if self.options.check_overflow {
+                    self.get_overflow_message(definite)
+                } else if definite {
+                    String::from("arithmetic operation will overflow and wrap around")
+                } else {
+                    String::from("possible arithmetic operation overflow and wrap around")
+                };
+                let span = self.current_span;
+                let mut warning = self.struct_span_warn_for_user(span, warning.as_str());
+                self.set_confidence(&mut warning, definite);
+                Self::add_shift_overflow_witness(&mut warning, &overflows);
+                self.emit_unproven_condition_diagnostic(&mut warning);
+            }
+        }
+        let wrapped = match (bin_op, &left.domain.expression, &right.domain.expression) {
+            (
+                mir::BinOp::Shl,
+                Expression::CompileTimeConstant(value),
+                Expression::CompileTimeConstant(amount),
+            ) => value.wrapping_shl(amount, &operand_type),
+            (
+                mir::BinOp::Shr,
+                Expression::CompileTimeConstant(value),
+                Expression::CompileTimeConstant(amount),
+            ) => value.wrapping_shr(amount, &operand_type),
+            (mir::BinOp::Shl, ..) | (mir::BinOp::Shr, ..) => ConstantDomain::Bottom,
+            _ => match &result.domain.expression {
+                Expression::CompileTimeConstant(value) => value.wrap_to(&operand_type),
+                _ => ConstantDomain::Bottom,
+            },
+        };
+        if wrapped != ConstantDomain::Bottom {
+            return wrapped.into();
+        }
+        abstract_value::TOP.join(&result, &overflows)
+    }
+
+    /// Returns the message of a diagnostic about an arithmetic operation, at the current span,
+    /// that overflows, or might, as reported with --check-overflow. The message quotes the source
+    /// of the operation, so that the diagnostic says which expression overflows.
+    fn get_overflow_message(&self, definite: bool) -> String {
+        let message = if definite {
+            "arithmetic overflow"
+        } else {
+            "possible arithmetic overflow"
+        };
+        match self.session.source_map().span_to_snippet(self.current_span) {
+            Ok(expression) => format!("{}: {}", message, expression),
+            Err(_) => String::from(message),
+        }
+    }
+
     /// Apply the given binary operator to the two operands, with overflow checking where appropriate
     /// and assign the result to path.
     fn visit_checked_binary_op(
@@ -1508,6 +8121,46 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             .clone()
     }
 
+    /// The heap blocks that a summary refers to were numbered when the callee was analyzed,
+    /// possibly by another run of MIRAI if the summary was stored, so their ordinals may be those
+    /// of blocks of the function being analyzed. Replaces them with fresh ordinals, which are
+    /// cached like the addresses of the blocks that the function allocates itself, keyed with the
+    /// current location and the ordinal in the summary.
+    fn renumber_callee_heap_addresses(&mut self, summary: &mut Summary) {
+        let location = self.current_location;
+        let addresses = &mut self.callee_heap_addresses;
+        let constants = &mut self.constant_value_cache;
+        let mut renumber = |ordinal: usize| {
+            *addresses.entry((location, ordinal)).or_insert_with(|| {
+                match constants.get_new_heap_address() {
+                    Expression::AbstractHeapAddress(new_ordinal) => new_ordinal,
+                    _ => unreachable!(),
+                }
+            })
+        };
+        for (path, value) in summary
+            .side_effects
+            .iter_mut()
+            .chain(summary.unwind_side_effects.iter_mut())
+        {
+            *path = path.renumber_heap_addresses(&mut renumber);
+            *value = value.renumber_heap_addresses(&mut renumber);
+        }
+        for (precondition, _) in summary.preconditions.iter_mut() {
+            *precondition = precondition.renumber_heap_addresses(&mut renumber);
+        }
+        for post_condition in summary.post_conditions.iter_mut() {
+            *post_condition = post_condition.renumber_heap_addresses(&mut renumber);
+        }
+        for value in summary
+            .result
+            .iter_mut()
+            .chain(summary.unwind_condition.iter_mut())
+        {
+            *value = value.renumber_heap_addresses(&mut renumber);
+        }
+    }
+
     /// Apply the given unary operator to the operand and assign to path.
     fn visit_unary_op(&mut self, path: Path, un_op: mir::UnOp, operand: &mir::Operand<'tcx>) {
         debug!(
@@ -1531,6 +8184,7 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
             "default visit_discriminant(path: {:?}, place: {:?})",
             path, place
         );
+        self.record_use_of(place);
         let adtd_path = self.visit_place(place);
         let adtd_type = self.get_place_type(place);
         let adtd_value = self.lookup_path_and_refine_result(adtd_path, adtd_type);
@@ -1633,12 +8287,44 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         }
     }
 
+    /// Returns the value of an argument of a call. If the argument is moved or copied from a place
+    /// whose parts are tracked separately, such as a struct whose fields have been assigned one at
+    /// a time, the result is a variable that refers to that place, so that preconditions and side
+    /// effects of the callee that involve parts of the parameter refer to the parts of the place.
+    fn visit_argument(&mut self, arg: &mir::Operand<'tcx>) -> AbstractValue {
+        let value = self.visit_operand(arg);
+        let place = match arg {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => place,
+            mir::Operand::Constant(..) => return value,
+        };
+        if let Expression::Variable { var_type, .. } = &value.domain.expression {
+            let path = self.visit_place(place);
+            if self
+                .current_environment
+                .value_map
+                .iter()
+                .any(|(p, _)| p.is_rooted_by(&path))
+            {
+                return AbstractValue {
+                    provenance: value.provenance.clone(),
+                    domain: Expression::Variable {
+                        path: box path,
+                        var_type: var_type.clone(),
+                    }
+                    .into(),
+                };
+            }
+        }
+        value
+    }
+
     /// Copy: The value must be available for use afterwards.
     ///
     /// This implies that the type of the place must be `Copy`; this is true
     /// by construction during build, but also checked by the MIR type checker.
     fn visit_copy(&mut self, place: &mir::Place<'tcx>) -> AbstractValue {
         debug!("default visit_copy(place: {:?})", place);
+        self.record_use_of(place);
         let path = self.visit_place(place);
         let place_type = self.get_place_type(place);
         self.lookup_path_and_refine_result(path, place_type)
@@ -1651,6 +8337,7 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
     /// `Copy` may be converted to `Move` to enable "last-use" optimizations.
     fn visit_move(&mut self, place: &mir::Place<'tcx>) -> AbstractValue {
         debug!("default visit_move(place: {:?})", place);
+        self.record_use_of(place);
         let path = self.visit_place(place);
         let place_type = self.get_place_type(place);
         self.lookup_path_and_refine_result(path, place_type)
@@ -1712,7 +8399,8 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                             _ => unreachable!(),
                         };
                     }
-                    TyKind::FnDef(def_id, ..) => {
+                    TyKind::FnDef(def_id, substs) => {
+                        let def_id = self.resolve_trait_method(def_id, substs);
                         result = self.visit_function_reference(def_id);
                     }
                     TyKind::Int(..) => {
@@ -1872,27 +8560,10 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 unreachable!()
             };
         let array_path = Path::AbstractHeapAddress { ordinal };
-        let mut last_index: u128 = 0;
-        for (i, operand) in self
-            .get_element_values(bytes, elem_type, len)
-            .into_iter()
-            .enumerate()
-        {
-            last_index = i as u128;
-            let index_value = self
-                .constant_value_cache
-                .get_u128_for(last_index)
-                .clone()
-                .into();
-            let selector = box PathSelector::Index(box index_value);
-            let index_path = Path::QualifiedPath {
-                qualifier: box array_path.clone(),
-                selector,
-                length: array_path.path_length() + 1,
-            };
-            self.current_environment
-                .update_value_at(index_path, operand);
-        }
+        let element_values = self.get_element_values(bytes, elem_type, len);
+        let element_count = element_values.len() as u128;
+        self.current_environment
+            .initialize_elements_at(&array_path, element_values.into_iter());
         let length_path = Path::QualifiedPath {
             qualifier: box array_path.clone(),
             selector: box PathSelector::ArrayLength,
@@ -1900,7 +8571,7 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         };
         let length_value = self
             .constant_value_cache
-            .get_u128_for(last_index + 1)
+            .get_u128_for(element_count)
             .clone()
             .into();
         self.current_environment
@@ -2030,6 +8701,31 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
         result
     }
 
+    /// Calls of trait methods name the method of the trait. If the type that implements the trait
+    /// does not depend on the type parameters of the function being analyzed and the implementation
+    /// is part of the current crate, this returns the method of the implementation, so that its
+    /// summary is used for the call. Otherwise the call is analyzed with the summary of the trait
+    /// method, which serves as the contract that all implementations must satisfy (see
+    /// check_implementation_preconditions). This way the body of a generic function is verified
+    /// once, against the bounds of its type parameters, rather than once per instantiation.
+    fn resolve_trait_method(
+        &self,
+        def_id: hir::def_id::DefId,
+        substs: SubstsRef<'tcx>,
+    ) -> hir::def_id::DefId {
+        if self.tcx.trait_of_item(def_id).is_none() || substs.needs_subst() {
+            return def_id;
+        }
+        let param_env = self.tcx.param_env(self.def_id);
+        match ty::Instance::resolve(self.tcx, param_env, def_id, substs) {
+            Some(ty::Instance {
+                def: ty::InstanceDef::Item(impl_method_def_id),
+                ..
+            }) if impl_method_def_id.is_local() => impl_method_def_id,
+            _ => def_id,
+        }
+    }
+
     /// The anonymous type of a function declaration/definition. Each
     /// function has a unique type, which is output (for a function
     /// named `foo` returning an `i32`) as `fn() -> i32 {foo}`.
@@ -2080,6 +8776,19 @@ impl<'a, 'b: 'a, 'tcx: 'b, E> MirVisitor<'a, 'b, 'tcx, E> {
                 if let PathSelector::Deref = selector {
                     // Strip the Deref in order to canonicalize paths
                     let base_val = self.lookup_path_and_refine_result(base.clone(), base_type);
+                    if self.check_for_errors {
+                        let is_raw_pointer =
+                            match self.get_rustc_place_type(&boxed_place_projection.base) {
+                                TyKind::RawPtr(..) => true,
+                                _ => false,
+                            };
+                        if is_raw_pointer {
+                            self.check_ffi_pointer_is_not_null(
+                                &base_val,
+                                "null pointer dereference",
+                            );
+                        }
+                    }
                     return match base_val.domain.expression {
                         Expression::Reference(dereferenced_path) => dereferenced_path,
                         _ => {
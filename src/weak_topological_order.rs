@@ -0,0 +1,108 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use rustc::mir;
+
+/// An element of a weak topological order of the basic blocks of a function body.
+#[derive(Debug)]
+pub enum WtoComponent {
+    /// A basic block that is not part of a loop at this level of nesting.
+    Vertex(mir::BasicBlock),
+    /// A loop, given by its head and the weak topological order of the rest of its blocks.
+    /// Every edge that enters the loop enters it at the head.
+    Component {
+        head: mir::BasicBlock,
+        body: Vec<WtoComponent>,
+    },
+}
+
+/// Returns a weak topological order of the basic blocks of the given body, as computed by the
+/// algorithm of Bourdoncle, "Efficient chaotic iteration strategies with widenings" (1993).
+/// Every block comes after its predecessors, except for the heads of loops, which come before
+/// the blocks of their loops. Blocks that cannot be reached from the entry block come last, in
+/// the order of their indices.
+pub fn weak_topological_order(mir: &mir::Mir<'_>) -> Vec<WtoComponent> {
+    let mut builder = WtoBuilder {
+        mir,
+        dfn: vec![0; mir.basic_blocks().len()],
+        num: 0,
+        stack: Vec::new(),
+    };
+    let mut partition = Vec::new();
+    builder.visit(mir::START_BLOCK, &mut partition);
+    partition.reverse();
+    for bb in mir.basic_blocks().indices() {
+        if builder.dfn[bb.index()] == 0 {
+            partition.push(WtoComponent::Vertex(bb));
+        }
+    }
+    partition
+}
+
+/// The state of the depth first traversal that computes a weak topological order.
+struct WtoBuilder<'m, 'tcx> {
+    mir: &'m mir::Mir<'tcx>,
+    /// The depth first number of each block. 0 if the block has not been visited (or has to be
+    /// visited again, as part of a component), usize::MAX once it has been placed in the order.
+    dfn: Vec<usize>,
+    /// The depth first number of the block visited last.
+    num: usize,
+    /// The blocks that have been visited but not yet placed in the order.
+    stack: Vec<mir::BasicBlock>,
+}
+
+impl<'m, 'tcx> WtoBuilder<'m, 'tcx> {
+    /// Visits the given block and the blocks reachable from it that have not been visited yet,
+    /// placing the ones that are not part of an enclosing loop in the partition. Since the
+    /// partition is built back to front, it is reversed once it is complete.
+    /// Returns the smallest depth first number of the blocks reachable from the given block.
+    fn visit(&mut self, vertex: mir::BasicBlock, partition: &mut Vec<WtoComponent>) -> usize {
+        self.stack.push(vertex);
+        self.num += 1;
+        self.dfn[vertex.index()] = self.num;
+        let mut head = self.num;
+        let mut is_loop = false;
+        let mir = self.mir;
+        for successor in mir[vertex].terminator().successors() {
+            let min = if self.dfn[successor.index()] == 0 {
+                self.visit(*successor, partition)
+            } else {
+                self.dfn[successor.index()]
+            };
+            if min <= head {
+                head = min;
+                is_loop = true;
+            }
+        }
+        if head == self.dfn[vertex.index()] {
+            self.dfn[vertex.index()] = usize::max_value();
+            let mut element = self.stack.pop().unwrap();
+            if is_loop {
+                // The other blocks of the loop are visited again, as the body of its component.
+                while element != vertex {
+                    self.dfn[element.index()] = 0;
+                    element = self.stack.pop().unwrap();
+                }
+                partition.push(self.component(vertex));
+            } else {
+                partition.push(WtoComponent::Vertex(vertex));
+            }
+        }
+        head
+    }
+
+    /// Returns the component of the loop with the given head.
+    fn component(&mut self, head: mir::BasicBlock) -> WtoComponent {
+        let mut body = Vec::new();
+        let mir = self.mir;
+        for successor in mir[head].terminator().successors() {
+            if self.dfn[successor.index()] == 0 {
+                self.visit(*successor, &mut body);
+            }
+        }
+        body.reverse();
+        WtoComponent::Component { head, body }
+    }
+}
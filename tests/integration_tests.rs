@@ -24,9 +24,12 @@ extern crate syntax;
 extern crate tempdir;
 
 use mirai::callbacks;
+use mirai::models;
+use mirai::options::Options;
 use mirai::utils;
 use rustc_rayon::iter::IntoParallelIterator;
 use rustc_rayon::iter::ParallelIterator;
+use std::env;
 use std::fs;
 use std::fs::File;
 use std::io::BufRead;
@@ -45,11 +48,28 @@ fn run_pass() {
     assert_eq!(run_directory(run_pass_path), 0);
 }
 
+// Run the tests in the tests/run-pass-cvc5 directory. These cases select the cvc5 solver with
+// --smt-backend=cvc5, because they check conclusions that need a solver. They are only run if the
+// cvc5 executable is given by the MIRAI_CVC5 environment variable.
+#[test]
+fn run_pass_cvc5() {
+    if env::var("MIRAI_CVC5").is_err() {
+        println!("skipping tests/run-pass-cvc5, since MIRAI_CVC5 is not set");
+        return;
+    }
+    let run_pass_path = PathBuf::from_str("tests/run-pass-cvc5").unwrap();
+    assert_eq!(run_directory(run_pass_path), 0);
+}
+
 // Iterates through the files in the directory at the given path and runs each as a separate test
 // case. For each case, a temporary output directory is created. The cases are then iterated in
-// parallel and run via invoke_driver.
+// parallel and run via invoke_driver. The state of the analysis that does not depend on the crate
+// being analyzed is built once and shared by all of the cases, except for the cases that set their
+// own options (see options_of_test_case). Files in subdirectories are not test cases, so they can
+// hold the auxiliary files of test cases, such as proofs files.
 fn run_directory(directory_path: PathBuf) -> usize {
     let sys_root = utils::find_sysroot();
+    let shared_state = callbacks::SharedState::from_environment();
     let mut files_and_temp_dirs = Vec::new();
     for entry in fs::read_dir(directory_path).expect("failed to read run-pass dir") {
         let entry = entry.unwrap();
@@ -72,7 +92,12 @@ fn run_directory(directory_path: PathBuf) -> usize {
         .fold(
             || 0,
             |acc, (file_name, temp_dir_path)| {
-                acc + self::invoke_driver(file_name, temp_dir_path, sys_root.clone())
+                acc + self::invoke_driver(
+                    file_name,
+                    temp_dir_path,
+                    sys_root.clone(),
+                    shared_state.clone(),
+                )
             },
         )
         .reduce(|| 0, |acc, code| acc + code)
@@ -80,29 +105,35 @@ fn run_directory(directory_path: PathBuf) -> usize {
 
 // Runs the single test case found in file_name, using temp_dir_path as the place
 // to put compiler output, which for Mirai includes the persistent summary store.
-fn invoke_driver(file_name: String, temp_dir_path: String, sys_root: String) -> usize {
+fn invoke_driver(
+    file_name: String,
+    temp_dir_path: String,
+    sys_root: String,
+    shared_state: callbacks::SharedState,
+) -> usize {
     let f_name = file_name.clone();
     let result = std::panic::catch_unwind(|| {
+        let testfile = PathBuf::from_str(&file_name).unwrap();
+        let (options, rustc_flags) = options_of_test_case(&testfile);
+        let mut shared_state = match &options {
+            Some(options) => shared_state.with_options(options.clone()),
+            None => shared_state,
+        };
+        if let Some(baseline_file_name) = baseline_of_test_case(&testfile) {
+            let summary_store = analyze_baseline(
+                baseline_file_name,
+                &temp_dir_path,
+                sys_root.clone(),
+                shared_state.clone(),
+            );
+            let mut options = options.unwrap_or_else(Options::from_environment);
+            options.baseline_summary_store = Some(summary_store);
+            shared_state = shared_state.with_options(options);
+        }
         rustc_driver::run(|| {
             let f_name = file_name.clone();
-            let command_line_arguments: Vec<String> = vec![
-                String::from("--crate-name mirai"),
-                file_name,
-                String::from("--crate-type"),
-                String::from("lib"),
-                String::from("-C"),
-                String::from("debuginfo=2"),
-                String::from("--out-dir"),
-                temp_dir_path,
-                String::from("--sysroot"),
-                sys_root,
-                String::from("-Z"),
-                String::from("span_free_formats"),
-                String::from("-Z"),
-                String::from("mir-emit-retag"),
-                String::from("-Z"),
-                String::from("mir-opt-level=0"),
-            ];
+            let mut command_line_arguments = compiler_arguments(file_name, temp_dir_path, sys_root);
+            command_line_arguments.extend(rustc_flags);
 
             let call_backs = callbacks::MiraiCallbacks::with_buffered_diagnostics(
                 box move |diagnostics| {
@@ -113,7 +144,8 @@ fn invoke_driver(file_name: String, temp_dir_path: String, sys_root: String) ->
                     db.cancel();
                     db.clone().buffer(buf);
                 },
-            );
+            )
+            .with_shared_state(shared_state);
 
             rustc_driver::run_compiler(
                 &command_line_arguments,
@@ -133,6 +165,72 @@ fn invoke_driver(file_name: String, temp_dir_path: String, sys_root: String) ->
     }
 }
 
+// Analyzes the earlier version of a test case found in baseline_file_name, so that the test case
+// can check the reported differences in behavior (see baseline_of_test_case). The output goes to a
+// subdirectory of the output directory of the test case, since both versions have the same crate
+// name. The diagnostics of the earlier version are ignored. Returns the path of the summary store
+// of the earlier version.
+fn analyze_baseline(
+    baseline_file_name: String,
+    temp_dir_path: &str,
+    sys_root: String,
+    shared_state: callbacks::SharedState,
+) -> String {
+    let baseline_dir_path = PathBuf::from(temp_dir_path).join("baseline");
+    let output_dir_path = baseline_dir_path.join("output");
+    fs::create_dir_all(output_dir_path.as_path()).expect("failed to create baseline output dir");
+    rustc_driver::run(|| {
+        let command_line_arguments = compiler_arguments(
+            baseline_file_name,
+            output_dir_path.into_os_string().into_string().unwrap(),
+            sys_root,
+        );
+        let call_backs = callbacks::MiraiCallbacks::with_buffered_diagnostics(
+            box |_diagnostics| {},
+            |db: &mut DiagnosticBuilder, buf: &mut Vec<Diagnostic>| {
+                db.cancel();
+                db.clone().buffer(buf);
+            },
+        )
+        .with_shared_state(shared_state);
+
+        rustc_driver::run_compiler(
+            &command_line_arguments,
+            box call_backs,
+            None, // use default file loader
+            None, // emit output to default destination
+        )
+    });
+    baseline_dir_path
+        .join(".summary_store")
+        .into_os_string()
+        .into_string()
+        .unwrap()
+}
+
+// Returns the arguments of the compiler that compile the file with the given name as a library,
+// with its output, which for Mirai includes the persistent summary store, in the given directory.
+fn compiler_arguments(file_name: String, output_dir_path: String, sys_root: String) -> Vec<String> {
+    vec![
+        String::from("--crate-name mirai"),
+        file_name,
+        String::from("--crate-type"),
+        String::from("lib"),
+        String::from("-C"),
+        String::from("debuginfo=2"),
+        String::from("--out-dir"),
+        output_dir_path,
+        String::from("--sysroot"),
+        sys_root,
+        String::from("-Z"),
+        String::from("span_free_formats"),
+        String::from("-Z"),
+        String::from("mir-emit-retag"),
+        String::from("-Z"),
+        String::from("mir-opt-level=0"),
+    ]
+}
+
 /// A collection of error strings that are expected for a test case.
 struct ExpectedErrors {
     messages: Vec<String>,
@@ -170,11 +268,53 @@ impl ExpectedErrors {
 /// Scans the contents of test file for patterns of the form "//~ message"
 /// and returns a vector of the matching messages.
 fn load_errors(testfile: &Path) -> Vec<String> {
+    load_tagged_lines(testfile, "//~")
+}
+
+/// Scans the contents of test file for lines of the form "// MIRAI_FLAGS flag flag ..." and
+/// "// MIRAI_CONFIG toml" and "// MIRAI_MODELS toml", which give the test case its own options. The
+/// flags are the command line options of Mirai, such as --check-overflow. Flags that are not
+/// options of Mirai are passed on to rustc. The toml lines are joined into the contents of the
+/// configuration file and of the models file of the case, respectively. Returns None for the
+/// options of a test case without such lines, which uses the shared options.
+fn options_of_test_case(testfile: &Path) -> (Option<Options>, Vec<String>) {
+    let flags: Vec<String> = load_tagged_lines(testfile, "// MIRAI_FLAGS")
+        .iter()
+        .flat_map(|line| line.split_whitespace().map(String::from))
+        .collect();
+    let config = load_tagged_lines(testfile, "// MIRAI_CONFIG");
+    let function_models = load_tagged_lines(testfile, "// MIRAI_MODELS");
+    if flags.is_empty() && config.is_empty() && function_models.is_empty() {
+        return (None, vec![]);
+    }
+    let mut options = Options::from_environment_and_configuration(&config.join("\n"));
+    if !function_models.is_empty() {
+        options.function_models =
+            models::parse_models(&function_models.join("\n"), &testfile.to_string_lossy());
+    }
+    let rustc_flags = flags
+        .into_iter()
+        .filter(|flag| !options.parse_command_line_option(flag))
+        .collect();
+    (Some(options), rustc_flags)
+}
+
+/// Scans the contents of test file for a line of the form "// MIRAI_BASELINE path", which names an
+/// earlier version of the test case. The earlier version is analyzed first and the test case is
+/// then analyzed with --diff-against pointing at its summary store. The earlier version must have
+/// the same file name as the test case, so that its functions have the same summary keys, and must
+/// thus be in a subdirectory, such as tests/run-pass/baselines.
+fn baseline_of_test_case(testfile: &Path) -> Option<String> {
+    load_tagged_lines(testfile, "// MIRAI_BASELINE").pop()
+}
+
+/// Scans the contents of test file for patterns of the form "tag text"
+/// and returns a vector of the text that follows the tag.
+fn load_tagged_lines(testfile: &Path, tag: &str) -> Vec<String> {
     let rdr = BufReader::new(File::open(testfile).unwrap());
-    let tag = "//~";
     rdr.lines()
         .enumerate()
-        .filter_map(|(_line_num, line)| parse_expected(&line.unwrap(), &tag))
+        .filter_map(|(_line_num, line)| parse_expected(&line.unwrap(), tag))
         .collect()
 }
 
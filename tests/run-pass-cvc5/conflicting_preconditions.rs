@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that preconditions which contradict each other are reported, while
+// preconditions that can all be satisfied together are not.
+
+// MIRAI_FLAGS --smt-backend=cvc5
+
+fn conflicting(x: i32) { //~ the preconditions of this function contradict each other, so no caller can satisfy them
+    debug_assert!(x < 10); //~ related location
+    debug_assert!(x > 20); //~ related location
+}
+
+fn consistent(x: i32) {
+    debug_assert!(x < 10);
+    debug_assert!(x > 0);
+}
+
+pub fn main() {
+    consistent(5);
+}
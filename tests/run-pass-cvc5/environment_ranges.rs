@@ -0,0 +1,37 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that a number parsed from an environment variable lies within the range
+// that the configuration declares for the variable.
+
+// MIRAI_FLAGS --smt-backend=cvc5
+// MIRAI_CONFIG [environment.WORKERS]
+// MIRAI_CONFIG trusted = true
+// MIRAI_CONFIG range = [1, 64]
+
+use std::env;
+
+pub fn workers_plus_one() -> u8 {
+    match env::var("WORKERS").unwrap().parse::<u8>() {
+        Ok(workers) => workers + 1,
+        Err(_) => 1,
+    }
+}
+
+pub fn workers_minus_one() -> u8 {
+    match env::var("WORKERS").unwrap().parse::<u8>() {
+        Ok(workers) => workers - 1,
+        Err(_) => 0,
+    }
+}
+
+pub fn threads_minus_one() -> u8 {
+    match env::var("THREADS").unwrap().parse::<u8>() {
+        Ok(threads) => threads - 1, //~ possible attempt to subtract with overflow
+        //~ untrusted input enters the program here
+        Err(_) => 0,
+    }
+}
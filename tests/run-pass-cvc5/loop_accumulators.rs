@@ -0,0 +1,29 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the sum of a bounded number of bounded values is known not to overflow,
+// since the accumulator at the loop head is bounded by the iteration count.
+
+// MIRAI_FLAGS --smt-backend=cvc5
+
+pub fn bounded_sum(xs: &[u16]) -> u32 {
+    if xs.len() > 65535 {
+        return 0;
+    }
+    let mut total: u32 = 0;
+    for x in xs {
+        total += *x as u32;
+    }
+    total
+}
+
+pub fn unbounded_sum(xs: &[u32]) -> u32 {
+    let mut total: u32 = 0;
+    for x in xs {
+        total += *x; //~ possible attempt to add with overflow
+    }
+    total
+}
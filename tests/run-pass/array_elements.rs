@@ -0,0 +1,18 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the elements of small arrays are tracked individually and that the
+// elements of large arrays are summarized.
+
+pub fn main() {
+    let mut a = [0; 4];
+    a[1] = 5;
+    debug_assert!(a[0] == 0);
+    debug_assert!(a[1] == 5);
+    let mut buffer = [7u8; 4096];
+    buffer[2000] = 7;
+    debug_assert!(buffer[3000] == 7);
+}
@@ -0,0 +1,20 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the ASCII methods of char and u8 are modeled precisely.
+
+pub fn main() {
+    let digit = '7';
+    debug_assert!(digit.is_ascii_digit());
+    debug_assert!(!digit.is_ascii_alphabetic());
+    let letter = b'q';
+    debug_assert!(letter.is_ascii_lowercase());
+    debug_assert!(letter.to_ascii_uppercase() == b'Q');
+    let upper = 'A';
+    debug_assert!(upper.to_ascii_lowercase() == 'a');
+    let space = ' ';
+    debug_assert!(space.is_ascii_whitespace());
+}
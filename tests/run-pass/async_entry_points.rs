@@ -0,0 +1,26 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the bodies of async functions that are annotated as entry points are
+// analyzed as roots, while those of other private async functions only get preconditions.
+
+// MIRAI_FLAGS --edition=2018
+
+#![feature(async_await)]
+#![feature(custom_attribute)]
+
+#[entry_point]
+async fn handle_request(a: &[u8; 4], i: usize) -> u8 {
+    a[i] //~ possible array index out of bounds
+}
+
+async fn lookup(a: &[u8; 4], i: usize) -> u8 {
+    a[i]
+}
+
+pub fn make_lookup(a: &[u8; 4]) {
+    let _ = lookup(a, 3);
+}
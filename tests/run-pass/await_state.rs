@@ -0,0 +1,34 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that only the state that other code can change while an async function is
+// suspended is forgotten at an await point.
+
+// MIRAI_FLAGS --edition=2018
+
+#![feature(async_await)]
+
+async fn ready() {}
+
+pub async fn local_index(a: &[u8; 4]) -> u8 {
+    let i = 3;
+    ready().await;
+    a[i]
+}
+
+pub async fn exclusive_index(a: &[u8; 4], i: &mut usize) -> u8 {
+    *i = 3;
+    ready().await;
+    a[*i]
+}
+
+pub async fn raw_pointer_index(a: &[u8; 4], p: *mut usize) -> u8 {
+    unsafe {
+        *p = 3;
+        ready().await;
+        a[*p] //~ possible array index out of bounds
+    }
+}
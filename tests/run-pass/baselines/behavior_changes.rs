@@ -0,0 +1,28 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// The earlier version of tests/run-pass/behavior_changes.rs, against whose summaries the
+// behavior of that test case is compared.
+
+pub fn checked(x: u8) -> u8 {
+    x
+}
+
+pub fn level(high: bool) -> u8 {
+    if high {
+        10
+    } else {
+        1
+    }
+}
+
+pub fn reset(n: &mut u32) {
+    let _ = n;
+}
+
+pub fn unchanged(x: u8) -> u8 {
+    x / 2
+}
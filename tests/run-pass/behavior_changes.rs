@@ -0,0 +1,42 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the differences between the behavior of the public functions and their
+// behavior in an earlier version, tests/run-pass/baselines/behavior_changes.rs, are reported.
+
+// MIRAI_BASELINE tests/run-pass/baselines/behavior_changes.rs
+
+pub fn checked(x: u8) -> u8 {
+    //~ the behavior of this function has changed since the baseline
+    //~ may now panic: assertion failed: x < 100
+    //~ may now unwind
+    debug_assert!(x < 100); //~ possible error: assertion failed: x < 100
+    x
+}
+
+pub fn level(high: bool) -> u8 {
+    //~ the behavior of this function has changed since the baseline
+    //~ the range of the result changed from [1, 10] to [1, 20]
+    if high {
+        20
+    } else {
+        1
+    }
+}
+
+pub fn reset(n: &mut u32) {
+    //~ the behavior of this function has changed since the baseline
+    //~ may now modify what parameter 1 refers to
+    *n = 0;
+}
+
+pub fn unchanged(x: u8) -> u8 {
+    x / 2
+}
+
+pub fn added(x: u8) -> u8 {
+    x
+}
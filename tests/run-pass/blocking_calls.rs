@@ -0,0 +1,32 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that calls that may block are reported inside async functions, along with
+// the chain of calls that leads to the blocking operation, but not outside of them.
+
+// MIRAI_FLAGS --edition=2018
+
+#![feature(async_await)]
+
+use std::thread;
+use std::time::Duration;
+
+fn pause() {
+    thread::sleep(Duration::from_millis(10));
+}
+
+pub async fn sleep_directly() {
+    thread::sleep(Duration::from_millis(10)); //~ this call may block the thread of the async executor: std.thread.sleep
+}
+
+pub async fn sleep_indirectly() {
+    pause(); //~ this call may block the thread of the async executor: blocking_calls.pause -> std.thread.sleep
+}
+
+pub fn sleep_synchronously() {
+    pause();
+    thread::sleep(Duration::from_millis(10));
+}
@@ -0,0 +1,34 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks reads from slices of a buffer against the lengths of the slices, which are
+// known in terms of the offsets used to slice the buffer.
+
+pub fn header_checksum(buf: &[u8]) -> u8 {
+    let header = &buf[..4]; //~ possible slice range out of bounds
+    header[0] ^ header[3]
+}
+
+pub fn read_past_header(buf: &[u8; 8]) -> u8 {
+    let header = &buf[2..6];
+    header[4] //~ array index out of bounds
+}
+
+pub fn slice_past_end(buf: &[u8; 8]) -> u8 {
+    let body = &buf[4..10]; //~ slice range out of bounds
+    body[0]
+}
+
+pub fn split_fields(buf: &[u8; 8]) -> u8 {
+    let (tag, rest) = buf.split_at(3);
+    let _ = tag[2] ^ rest[4];
+    rest[5] //~ array index out of bounds
+}
+
+pub fn split_past_end(buf: &[u8; 8]) -> u8 {
+    let (tag, _) = buf.split_at(9); //~ split index out of bounds
+    tag[0]
+}
@@ -0,0 +1,46 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the fields set by a chain of builder methods are tracked, so that
+// the preconditions of the build method can be checked at each of its calls.
+
+struct Builder {
+    name: Option<u32>,
+    port: Option<u16>,
+}
+
+impl Builder {
+    fn new() -> Builder {
+        Builder {
+            name: None,
+            port: None,
+        }
+    }
+
+    fn name(mut self, name: u32) -> Builder {
+        self.name = Some(name);
+        self
+    }
+
+    fn port(mut self, port: u16) -> Builder {
+        self.port = Some(port);
+        self
+    }
+
+    fn build(self) -> (u32, u16) {
+        let name = match self.name {
+            Some(name) => name,
+            None => panic!("the name must be set"), //~ related location
+        };
+        (name, self.port.unwrap_or(80))
+    }
+}
+
+pub fn main() {
+    let _ = Builder::new().name(1).port(8080).build();
+    let _ = Builder::new().port(8080).name(2).build();
+    let _ = Builder::new().port(8080).build(); //~ the name must be set
+}
@@ -0,0 +1,18 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that integer to byte array conversions round trip.
+
+pub fn main() {
+    let bytes = 0x1234_5678u32.to_le_bytes();
+    debug_assert!(bytes[0] == 0x78);
+    debug_assert!(u32::from_le_bytes(bytes) == 0x1234_5678);
+}
+
+pub fn round_trip(x: u32) {
+    let bytes = x.to_be_bytes();
+    debug_assert!(u32::from_be_bytes(bytes) == x);
+}
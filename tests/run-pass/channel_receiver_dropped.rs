@@ -0,0 +1,22 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that sends on channels whose receiver has been dropped are reported.
+
+use std::sync::mpsc;
+
+pub fn main() {
+    let (live_sender, live_receiver) = mpsc::channel();
+    if live_sender.send(1).is_err() {
+        return;
+    }
+    drop(live_receiver);
+    let (sender, receiver) = mpsc::channel();
+    drop(receiver);
+    if sender.send(2).is_err() { //~ the receiver of this channel has been dropped, so sending on it fails
+        return;
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that --check-determinism reports the iterations over hash maps and sets on
+// which the result of a function depends, unless the collected elements are sorted, and that
+// sums of the elements, and functions that do not iterate, are not reported.
+
+// MIRAI_FLAGS --check-determinism
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+pub fn keys_in_hash_order(m: &HashMap<u32, u32>) -> Vec<u32> {
+    m.keys() //~ the result of the function depends on the iteration order of a hash map or set
+        .cloned()
+        .collect()
+}
+
+pub fn elements_in_hash_order(s: &HashSet<u32>) -> Vec<u32> {
+    s.iter() //~ the result of the function depends on the iteration order of a hash map or set
+        .map(|e| e + 1)
+        .collect()
+}
+
+pub fn sorted_keys(m: &HashMap<u32, u32>) -> Vec<u32> {
+    let mut keys: Vec<u32> = m.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+pub fn sorted_elements(s: &HashSet<u32>) -> Vec<u32> {
+    let mut elements: Vec<u32> = s.iter().cloned().collect();
+    elements.sort_unstable();
+    elements
+}
+
+pub fn sum_of_values(m: &HashMap<u32, u32>) -> u32 {
+    m.values().sum()
+}
+
+pub fn number_of_keys(m: &HashMap<u32, u32>) -> usize {
+    m.len()
+}
@@ -0,0 +1,32 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that --check-overflow reports every arithmetic operation that may overflow
+// where it occurs, quoting the operation, including those in private functions, and that such
+// operations no longer become preconditions that are reported at the calls of their functions.
+
+// MIRAI_FLAGS --check-overflow
+
+pub fn add(a: u8, b: u8) -> u8 {
+    a + b //~ possible arithmetic overflow: a + b
+}
+
+pub fn negate(a: i8) -> i8 {
+    -a //~ possible arithmetic overflow: -a
+}
+
+fn double(x: u8) -> u8 {
+    x * 2 //~ possible arithmetic overflow: x * 2
+}
+
+pub fn quadruple(x: u8) -> u8 {
+    double(double(x))
+}
+
+pub fn increment_safe(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFE } else { 1 };
+    a + 1
+}
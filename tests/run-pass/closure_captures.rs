@@ -0,0 +1,14 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that calls of closures update the variables that they capture by reference.
+
+pub fn main() {
+    let mut x = 1;
+    let mut set_x = || x = 3;
+    set_x();
+    debug_assert!(x == 3);
+}
@@ -0,0 +1,69 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the lengths of the std collections are tracked, so that it is known
+// whether pop, peek and friends find an element.
+
+use std::collections::{BTreeSet, BinaryHeap, VecDeque};
+
+pub fn vec_push_pop() {
+    let mut v = Vec::new();
+    v.push(1);
+    v.push(2);
+    debug_assert!(v.len() == 2);
+    match v.pop() {
+        Some(_) => {}
+        None => panic!("the vector is empty"),
+    }
+    debug_assert!(v.len() == 1);
+    debug_assert!(!v.is_empty());
+    v.clear();
+    debug_assert!(v.is_empty());
+}
+
+pub fn vec_pop_empty() {
+    let mut v: Vec<i32> = Vec::new();
+    match v.pop() {
+        Some(_) => {}
+        None => panic!("popped from an empty vector"), //~ popped from an empty vector
+    }
+}
+
+pub fn vec_deque_front_back() {
+    let mut d = VecDeque::new();
+    d.push_back(1);
+    d.push_front(0);
+    debug_assert!(d.len() == 2);
+    match d.front() {
+        Some(_) => {}
+        None => panic!("the deque is empty"),
+    }
+    d.pop_front();
+    d.pop_back();
+    debug_assert!(d.is_empty());
+}
+
+pub fn binary_heap_peek() {
+    let mut h = BinaryHeap::new();
+    h.push(3);
+    match h.peek() {
+        Some(_) => {}
+        None => panic!("the heap is empty"),
+    }
+    h.pop();
+    debug_assert!(h.len() == 1); //~ assertion failed: h.len() == 1
+}
+
+pub fn btree_set_members() {
+    let mut s = BTreeSet::new();
+    s.insert(1);
+    s.insert(1);
+    debug_assert!(s.len() == 1);
+    debug_assert!(s.contains(&1));
+    s.remove(&1);
+    debug_assert!(!s.contains(&1));
+    debug_assert!(s.is_empty());
+}
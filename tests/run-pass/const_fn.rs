@@ -0,0 +1,17 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that evaluates a call of a const fn with constant arguments
+
+const fn scale(x: u32, factor: u32) -> u32 {
+    x * factor
+}
+
+pub fn main() {
+    let a = [0; 12];
+    let i = scale(3, 4) - 1;
+    let _e = a[i as usize];
+}
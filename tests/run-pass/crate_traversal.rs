@@ -30,7 +30,7 @@ impl TestStruct {
     pub fn test_method(&self) {}
 }
 
-pub unsafe fn test1() {
+pub unsafe fn test1() { //~ public unsafe function without a safety contract, declare one with #[mirai::safety_requires(predicate)]
     #[derive(Copy, Clone)]
     enum Void {}
     union A { a: (), v: Void }
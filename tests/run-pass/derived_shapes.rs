@@ -0,0 +1,42 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the derived methods of types with the same shape share a summary that
+// is right for all of them, and that types with different shapes do not share summaries.
+
+#[derive(Clone, PartialEq)]
+pub struct Meters(u8);
+
+#[derive(Clone, PartialEq)]
+pub struct Seconds(u8);
+
+#[derive(Clone, PartialEq)]
+pub struct Span(u8, u8);
+
+pub fn same_shape() {
+    let a = Meters(1);
+    let b = Meters(1);
+    debug_assert!(a == b);
+    let c = Seconds(1);
+    let d = Seconds(2);
+    debug_assert!(!(c == d));
+    let e = d.clone();
+    debug_assert!(e.0 == 2);
+}
+
+pub fn different_shapes() {
+    let a = Span(1, 2);
+    let b = Span(1, 3);
+    debug_assert!(!(a == b));
+    let c = b.clone();
+    debug_assert!(c.1 == 3);
+}
+
+pub fn wrong_assumption() {
+    let a = Span(1, 2);
+    let b = Span(1, 3);
+    debug_assert!(a == b); //~ assertion failed: a == b
+}
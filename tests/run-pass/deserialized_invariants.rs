@@ -0,0 +1,73 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that implementations of serde::Deserialize are checked against the invariants
+// of the types they deserialize, and that only diagnostics about derived implementations of
+// Deserialize are suppressed. The test cannot depend on serde, so it vendors the trait.
+
+pub mod serde {
+    pub mod de {
+        pub trait Deserialize: Sized {
+            fn deserialize(input: u32) -> Result<Self, ()>;
+        }
+    }
+}
+
+use crate::serde::de::Deserialize;
+
+pub struct Percentage {
+    value: u32,
+}
+
+impl Percentage {
+    pub fn invariant(&self) -> bool {
+        self.value <= 100
+    }
+}
+
+impl Deserialize for Percentage {
+    fn deserialize(_input: u32) -> Result<Self, ()> {
+        Ok(Percentage { value: 50 })
+    }
+}
+
+pub struct Unchecked {
+    value: u32,
+}
+
+impl Unchecked {
+    pub fn invariant(&self) -> bool {
+        self.value <= 100 //~ related location
+    }
+}
+
+impl Deserialize for Unchecked {
+    fn deserialize(input: u32) -> Result<Self, ()> { //~ deserialized value might not satisfy the invariant of its type
+        Ok(Unchecked { value: input }) //~ related location
+    }
+}
+
+pub struct Quiet {}
+
+#[automatically_derived]
+impl Deserialize for Quiet {
+    fn deserialize(_input: u32) -> Result<Self, ()> {
+        let a: u8 = 255;
+        let _b = a + 1;
+        Ok(Quiet {})
+    }
+}
+
+pub struct Loud {}
+
+#[automatically_derived]
+impl Default for Loud {
+    fn default() -> Self {
+        let a: u8 = 255;
+        let _b = a + 1; //~ attempt to add with overflow
+        Loud {}
+    }
+}
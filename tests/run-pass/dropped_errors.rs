@@ -0,0 +1,60 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that results of fallible calls that are not looked at on every path are
+// reported, including those that are bound to _.
+
+fn fallible(x: i32) -> Result<i32, String> {
+    if x > 0 {
+        Ok(x)
+    } else {
+        Err(String::from("not positive"))
+    }
+}
+
+fn drops_error() {
+    fallible(0);
+}
+
+fn ignores_error() {
+    let _ = fallible(0);
+}
+
+fn checks_error_on_some_paths(flag: bool) -> i32 {
+    let result = fallible(6);
+    if flag && result.is_err() {
+        return 0;
+    }
+    1
+}
+
+fn checks_error_on_every_path(flag: bool) -> bool {
+    let result = fallible(7);
+    if flag {
+        result.is_ok()
+    } else {
+        result.is_err()
+    }
+}
+
+fn propagates_error() -> Result<i32, String> {
+    let y = fallible(3)?;
+    Ok(y + 1)
+}
+
+pub fn main() {
+    fallible(1); //~ the result of this call is dropped without being checked
+    let _unused = fallible(4); //~ the result of this call is dropped without being checked
+    let _ = fallible(5); //~ the result of this call is dropped without being checked
+    if fallible(2).is_err() {
+        return;
+    }
+    let _ = propagates_error().is_ok();
+    drops_error(); //~ the called function may drop the result of a fallible call without checking it
+    ignores_error(); //~ the called function may drop the result of a fallible call without checking it
+    let _ = checks_error_on_some_paths(true); //~ the called function may drop the result of a fallible call without checking it
+    let _ = checks_error_on_every_path(true);
+}
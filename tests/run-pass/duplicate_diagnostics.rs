@@ -0,0 +1,25 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that diagnostics with the same span and message, found in several functions,
+// are reported once, with a note that lists the functions.
+
+macro_rules! fourth_byte_getters {
+    ($($name:ident),*) => {
+        $(
+            pub fn $name(bytes: &[u8]) -> u8 {
+                bytes[3]
+            }
+        )*
+    };
+}
+
+fourth_byte_getters!(first, second); //~ possible array index out of bounds
+//~ this applies to 2 instantiations: duplicate_diagnostics.first, duplicate_diagnostics.second
+
+pub fn third(bytes: &[u8]) -> u8 {
+    bytes[3] //~ possible array index out of bounds
+}
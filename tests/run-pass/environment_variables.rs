@@ -0,0 +1,26 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that numbers parsed from environment variables are untrusted input, unless
+// the configuration declares the variable to be trusted.
+
+// MIRAI_CONFIG [environment.WORKERS]
+// MIRAI_CONFIG trusted = true
+// MIRAI_CONFIG range = [1, 64]
+
+use std::env;
+
+pub fn untrusted_count() -> u8 {
+    let count: u8 = env::var("COUNT").unwrap().parse().unwrap(); //~ untrusted input enters the program here
+    count + 1 //~ possible attempt to add with overflow
+}
+
+pub fn trusted_workers() -> u8 {
+    let workers: u8 = env::var("WORKERS").unwrap().parse().unwrap();
+    // The declared range is an assumption for the solver, which this test runs without.
+    // See tests/run-pass-cvc5/environment_ranges.rs.
+    workers + 1 //~ possible attempt to add with overflow
+}
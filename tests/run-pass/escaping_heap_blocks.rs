@@ -0,0 +1,31 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the contents of heap blocks returned by a function are part of its
+// summary while the contents of blocks that are used only locally are not, and that the blocks
+// returned by different calls of a function are distinct.
+
+#![feature(box_syntax)]
+
+fn make_box() -> Box<[i32; 2]> {
+    let local = box [3, 4];
+    let mut result = box [1, 2];
+    result[1] = local[0];
+    result
+}
+
+fn make_pair(x: i32) -> Box<[i32; 2]> {
+    box [x, x]
+}
+
+pub fn main() {
+    let b = make_box();
+    debug_assert!(b[0] == 1);
+    let p1 = make_pair(1);
+    let p2 = make_pair(2);
+    debug_assert!(p1[0] == 1);
+    debug_assert!(p2[1] == 2);
+}
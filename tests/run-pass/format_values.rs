@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that values that flow into formatted strings are tracked without
+// analyzing the internals of core::fmt and without disturbing the values that are formatted.
+
+use std::fmt::Write;
+
+pub fn main() {
+    let x = 1;
+    let y = 2;
+    let s = format!("<{} {}>", x, y);
+    debug_assert!(s.starts_with("<"));
+    debug_assert!(s.ends_with(">"));
+    let mut t = String::new();
+    if write!(t, "{}", s).is_err() {
+        return;
+    }
+    debug_assert!(x == 1);
+    debug_assert!(y == 2);
+}
@@ -0,0 +1,35 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that calls to unchecked functions must satisfy their implicit preconditions.
+
+fn element_at(arr: &[i32], i: usize) -> i32 {
+    unsafe { *arr.get_unchecked(i) } //~ possible error: unchecked index out of bounds
+}
+
+pub fn main() {
+    let arr = [1, 2, 3];
+    let slice: &[i32] = &arr;
+    let _ = unsafe { slice.get_unchecked(1) };
+    let _ = unsafe { slice.get_unchecked(3) }; //~ unchecked index out of bounds
+    let _ = element_at(slice, 0);
+
+    let s = "hello";
+    let _ = unsafe { s.get_unchecked(1..3) };
+    let _ = unsafe { s.get_unchecked(..5) };
+    //~ unchecked range out of bounds
+    let _ = unsafe { s.get_unchecked(3..9) };
+
+    let _ = unsafe { std::char::from_u32_unchecked(0x41) };
+    //~ value is not a valid char
+    let _ = unsafe { std::char::from_u32_unchecked(0xD800) };
+    //~ value is not a valid char
+    let _ = unsafe { std::char::from_u32_unchecked(0x11_0000) };
+
+    let _ = unsafe { std::slice::from_raw_parts(&arr[0] as *const i32, 2) };
+    //~ the pointer given to slice::from_raw_parts is null
+    let _ = unsafe { std::slice::from_raw_parts(0usize as *const i32, 0) };
+}
@@ -0,0 +1,17 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that calls to functions without summaries only modify the places
+// that are reachable via their mutable reference parameters.
+
+pub fn main() {
+    let mut a = 1;
+    let mut b = 2;
+    let c = 3;
+    std::mem::swap(&mut a, &mut b);
+    debug_assert!(c == 3);
+    debug_assert!(a == 1); //~ possible error: assertion failed: a == 1
+}
@@ -0,0 +1,16 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that casts of integers to raw pointers are reported, unless they yield null.
+
+pub fn main() {
+    let address: usize = 0x1000;
+    //~ integer to pointer cast: the pointer has no provenance
+    //~ under strict provenance, derive the pointer from a pointer to the allocation instead
+    let p = address as *const u8;
+    let q = 0usize as *const u8;
+    debug_assert!(p != q);
+}
@@ -0,0 +1,17 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that --int-to-ptr-casts=angelic does not report casts of integers to raw
+// pointers.
+
+// MIRAI_FLAGS --int-to-ptr-casts=angelic
+
+pub fn main() {
+    let address: usize = 0x2000;
+    let p = address as *const u32;
+    let q = (address + 4) as *const u32;
+    let _ = (p, q);
+}
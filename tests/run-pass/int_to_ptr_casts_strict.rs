@@ -0,0 +1,19 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that --int-to-ptr-casts=strict reports casts of integers to raw pointers,
+// unless they yield null.
+
+// MIRAI_FLAGS --int-to-ptr-casts=strict
+
+pub fn main() {
+    let address: usize = 0x2000;
+    //~ integer to pointer cast: the pointer has no provenance
+    //~ under strict provenance, derive the pointer from a pointer to the allocation instead
+    let p = address as *mut u32;
+    let q = 0usize as *mut u32;
+    debug_assert!(p != q);
+}
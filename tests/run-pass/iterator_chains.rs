@@ -0,0 +1,14 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that chains of iterator adapters over ranges know how many elements they produce.
+
+pub fn main() {
+    let v: Vec<u32> = (0..10u32).map(|i| i * 2).collect();
+    debug_assert!(v.len() == 10);
+    let s: u32 = (1..5u32).sum();
+    debug_assert!(s == 10);
+}
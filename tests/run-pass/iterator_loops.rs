@@ -0,0 +1,37 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that loops over arrays and slices are known to run once per element.
+
+pub fn count_elements(s: &[i32]) {
+    let mut n = 0;
+    for _ in s.iter() {
+        n += 1;
+    }
+    debug_assert!(n == s.len());
+}
+
+pub fn count_array_elements() {
+    let arr = [1, 2, 3];
+    let mut n = 0;
+    for _ in &arr {
+        n += 1;
+    }
+    debug_assert!(n == 3);
+}
+
+pub fn count_from_one(s: &[i32]) {
+    let mut n = 1;
+    for _ in s {
+        n += 1; //~ possible attempt to add with overflow
+    }
+    debug_assert!(n == s.len()); //~ possible error: assertion failed: n == s.len()
+}
+
+pub fn main() {
+    count_elements(&[1, 2]);
+    count_array_elements();
+}
@@ -0,0 +1,44 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the diagnostics of a body that is large enough for the out states of its
+// basic blocks to be cached during the fixed point loop, such as a generated state machine, are
+// still reported, both inside and after the loop.
+
+macro_rules! transitions {
+    ($state:ident; $($from:expr => $to:expr),*) => {
+        $(
+            if $state == $from {
+                $state = $to;
+            }
+        )*
+    };
+}
+
+fn get(a: &[u8], i: usize) -> u8 {
+    a[i] //~ related location
+         //~ related location
+}
+
+pub fn run(mut state: u8, stop: bool) -> u8 {
+    loop {
+        transitions!(state;
+            0 => 1, 1 => 2, 2 => 3, 3 => 4, 4 => 5, 5 => 6, 6 => 7, 7 => 8,
+            8 => 9, 9 => 10, 10 => 11, 11 => 12, 12 => 13, 13 => 14, 14 => 15, 15 => 16,
+            16 => 17, 17 => 18, 18 => 19, 19 => 20, 20 => 21, 21 => 22, 22 => 23, 23 => 24,
+            24 => 25, 25 => 26, 26 => 27, 27 => 28, 28 => 29, 29 => 30, 30 => 31, 31 => 32,
+            32 => 33, 33 => 34, 34 => 35, 35 => 36, 36 => 37, 37 => 38, 38 => 39, 39 => 40
+        );
+        if state == 40 {
+            get(&[1, 2], 2); //~ possible error: array index out of bounds
+        }
+        if stop {
+            break;
+        }
+    }
+    // The loop is only left if stop is true, so this call is not always reached either.
+    get(&[1, 2], 2) //~ possible error: array index out of bounds
+}
@@ -0,0 +1,23 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that conditions with nonlinear arithmetic are not sent to the solver in
+// linear arithmetic mode, and that the diagnostics about them say so.
+
+// MIRAI_FLAGS --linear-arithmetic-only
+
+pub fn area(width: u32, height: u32) -> u32 {
+    width * height //~ possible attempt to multiply with overflow
+    //~ this involves nonlinear arithmetic, which is not sent to the solver in linear arithmetic mode or for fast modules
+}
+
+pub fn scaled(width: u32) -> u32 {
+    width * 3 //~ possible attempt to multiply with overflow
+}
+
+pub fn perimeter(width: u32, height: u32) -> u32 {
+    width + height //~ possible attempt to add with overflow
+}
@@ -0,0 +1,32 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks loop invariants and relies on them in the bodies of their loops.
+
+fn mirai_loop_invariant(_condition: bool) {}
+
+pub fn fill(a: &mut [u8; 10]) {
+    let mut i = 0;
+    while i < 10 {
+        mirai_loop_invariant(i < 10);
+        a[i] = 1;
+        i += 1;
+    }
+}
+
+pub fn count_down(n: usize) {
+    let mut i = n;
+    while i > 0 {
+        mirai_loop_invariant(i <= 10); //~ possible error: loop invariant does not hold
+        i -= 1;
+    }
+}
+
+pub fn main() {
+    let mut a = [0; 10];
+    fill(&mut a);
+    count_down(5);
+}
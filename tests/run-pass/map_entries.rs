@@ -0,0 +1,18 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that maps keep track of their keys and of the values of their entries.
+
+use std::collections::HashMap;
+
+pub fn main() {
+    let mut m = HashMap::new();
+    m.insert(1, 10);
+    debug_assert!(m.contains_key(&1));
+    debug_assert!(m.len() == 1);
+    m.remove(&1);
+    debug_assert!(!m.contains_key(&1));
+}
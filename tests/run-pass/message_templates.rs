@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the message templates of the configuration replace the messages of the
+// diagnostics they are given for, with the facts that they refer to interpolated.
+
+// MIRAI_CONFIG [messages]
+// MIRAI_CONFIG "possible array index out of bounds" = "index in {range} may exceed the buffer (input from {sources}, counterexample {counterexample}, {unknown})"
+
+pub fn pick(a: &[u8], c: bool) -> u8 {
+    let i = if c { 3 } else { 7 };
+    a[i] //~ index in [3, 7] may exceed the buffer (input from none, counterexample none, {unknown})
+}
+
+pub fn first(a: &[u8]) -> u8 {
+    a[0] //~ index in [0, 0] may exceed the buffer (input from none, counterexample none, {unknown})
+}
+
+pub fn positive(x: u8) {
+    debug_assert!(x > 0); //~ possible error: assertion failed: x > 0
+}
@@ -0,0 +1,34 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that relies on the invariant of a newtype that is established by its constructor
+
+mod index {
+    pub struct Idx(usize);
+
+    impl Idx {
+        pub fn invariant(&self) -> bool {
+            self.0 < 10
+        }
+
+        pub fn new(i: usize) -> Option<Idx> {
+            if i < 10 {
+                Some(Idx(i))
+            } else {
+                None
+            }
+        }
+
+        pub fn get(&self) -> usize {
+            self.0
+        }
+    }
+}
+
+pub fn main(i: &index::Idx) {
+    let a = [0; 10];
+    let _e = a[i.get()];
+}
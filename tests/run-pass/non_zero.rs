@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that values obtained from niche types are known to be non zero
+// and that values used to construct them without checking must be non zero.
+
+use std::num::NonZeroU32;
+
+fn divide(n: NonZeroU32) -> u32 {
+    let v = n.get();
+    100 / v
+}
+
+pub fn main() {
+    if let Some(n) = NonZeroU32::new(5) {
+        let q = divide(n);
+        debug_assert!(n.get() != 0);
+        let _ = q;
+    }
+    let _ = unsafe { NonZeroU32::new_unchecked(0) }; //~ value of niche type must not be zero
+}
@@ -0,0 +1,35 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that --check-determinism reports the writes to the console of values that
+// depend on a source of nondeterminism, with a note at the source, and that neither writes of
+// other values nor sources whose values are not written are reported.
+
+// MIRAI_FLAGS --check-determinism
+
+use std::io::Write;
+
+pub fn write_process_id() -> std::io::Result<()> {
+    let id = std::process::id(); //~ source of nondeterminism
+    let bytes = id.to_le_bytes();
+    std::io::stdout().write_all(&bytes) //~ a value that depends on a source of nondeterminism is written to an output
+}
+
+pub fn write_address(x: &u64) -> std::io::Result<()> {
+    let address = x as *const u64 as usize; //~ source of nondeterminism
+    let bytes = address.to_le_bytes();
+    std::io::stdout().write_all(&bytes) //~ a value that depends on a source of nondeterminism is written to an output
+}
+
+pub fn write_constant() -> std::io::Result<()> {
+    let bytes = 42u32.to_le_bytes();
+    std::io::stdout().write_all(&bytes)
+}
+
+pub fn elapsed_nanos() -> u128 {
+    let start = std::time::Instant::now();
+    start.elapsed().as_nanos()
+}
@@ -0,0 +1,27 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that obligations that exceed the budget are skipped, but still become
+// preconditions of the function, so that its callers are checked against them.
+
+// MIRAI_CONFIG [obligations]
+// MIRAI_CONFIG budget = 1
+
+pub fn sum(x: u8, y: u8) -> u8 { //~ this function has 2 proof obligations, more than the budget of 1, so 2 overflow checks were skipped
+    //~ errors in this function may go unreported, since skipped obligations become preconditions at best
+    let a = x + 1;
+    a + y
+}
+
+fn pick(a: &[u8], i: usize, j: usize) -> u8 { //~ this function has 2 proof obligations, more than the budget of 1, so 2 bounds checks were skipped
+    //~ errors in this function may go unreported, since skipped obligations become preconditions at best
+    a[i] ^ a[j] //~ related location
+}
+
+pub fn caller() -> u8 {
+    let a = [1, 2];
+    pick(&a, 0, 5) //~ array index out of bounds
+}
@@ -0,0 +1,17 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that a panic inside a macro is reported at the invocation of the macro
+
+macro_rules! fail {
+    () => {
+        panic!("failed")
+    };
+}
+
+pub fn main() {
+    fail!(); //~ failed
+}
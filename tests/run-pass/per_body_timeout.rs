@@ -0,0 +1,14 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that a function whose analysis runs out of time is reported as such,
+// without the diagnostics that an incomplete analysis would otherwise give.
+
+// MIRAI_FLAGS --per-body-timeout-ms=0
+
+pub fn get(a: &[i32], i: usize) -> i32 { //~ analysis incomplete: this function could not be analyzed within 0 ms, so nothing is known about what it does
+    a[i]
+}
@@ -0,0 +1,22 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that pointer arithmetic stays within the bounds of a known allocation, unless
+// it wraps.
+
+pub fn main() {
+    let arr = [1, 2, 3];
+    let p = &arr[0] as *const i32;
+    unsafe {
+        let q = p.add(3);
+        let _ = q.sub(1);
+        let _ = p.add(4); //~ pointer offset is out of bounds
+        //~ the allocation has 3 elements
+    }
+    // Wrapping offsets may point outside of the allocation, as long as they are not dereferenced.
+    let _ = p.wrapping_offset(10);
+    let _ = p.wrapping_add(4);
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that a function whose assertion cannot be proven with the default limits
+// is analyzed again with elevated limits, before the assertion is reported.
+
+pub struct L0 {
+    pub v: u32,
+}
+pub struct L1 {
+    pub n: L0,
+}
+pub struct L2 {
+    pub n: L1,
+}
+pub struct L3 {
+    pub n: L2,
+}
+pub struct L4 {
+    pub n: L3,
+}
+pub struct L5 {
+    pub n: L4,
+}
+pub struct L6 {
+    pub n: L5,
+}
+pub struct L7 {
+    pub n: L6,
+}
+pub struct L8 {
+    pub n: L7,
+}
+pub struct L9 {
+    pub n: L8,
+}
+pub struct L10 {
+    pub n: L9,
+}
+pub struct L11 {
+    pub n: L10,
+}
+
+pub fn deeply_nested(s: L11) {
+    let a = s.n.n.n.n.n.n.n.n.n.n.n.v;
+    let b = s.n.n.n.n.n.n.n.n.n.n.n.v;
+    assert!(a == b);
+}
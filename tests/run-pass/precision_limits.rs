@@ -0,0 +1,22 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that diagnostics point out where precision was lost because an
+// expression grew too large to track.
+
+pub fn xor_chain(a: u32) {
+    let b = a ^ a;
+    let c = b ^ b;
+    let d = c ^ c;
+    let e = d ^ d;
+    let f = e ^ e;
+    let g = f ^ f;
+    let h = g ^ g;
+    let i = h ^ h;
+    let j = i ^ i;
+    assert!(j == 0); //~ possible error: assertion failed: j == 0
+    //~ precision was limited here, because the expression is too large to track
+}
@@ -0,0 +1,19 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that an obligation is assumed to hold if a proofs file lists its checksum,
+// but not if the proofs file lists the checksum of a different obligation.
+
+// MIRAI_CONFIG [obligations]
+// MIRAI_CONFIG proven = "tests/run-pass/proofs/proven_obligations.toml"
+
+pub fn listed(a: &[u8; 4], i: usize) -> u8 {
+    a[i]
+}
+
+pub fn stale(a: &[u8; 4], i: usize) -> u8 {
+    a[i] //~ possible array index out of bounds
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the quantified preconditions of function models are unrolled over the
+// elements of arrays with known lengths, and that those that cannot be unrolled are reported.
+
+// MIRAI_MODELS [functions."quantified_models.first"]
+// MIRAI_MODELS preconditions = ["forall i: arg0[i] > 0"]
+// MIRAI_MODELS [functions."quantified_models.first_of_slice"]
+// MIRAI_MODELS preconditions = ["forall i: arg0[i] > 0"]
+// MIRAI_MODELS [functions."quantified_models.second"]
+// MIRAI_MODELS preconditions = ["exists i: arg0[i] == 0"]
+
+fn first(a: &[i32; 3]) -> i32 {
+    a[0]
+}
+
+fn first_of_slice(a: &[i32]) -> i32 {
+    a.len() as i32
+}
+
+fn second(a: &[i32; 2]) -> i32 {
+    a[1]
+}
+
+pub fn all_positive() -> i32 {
+    let a = [1, 2, 3];
+    first(&a)
+}
+
+pub fn some_negative() -> i32 {
+    let a = [1, -2, 3];
+    first(&a) //~ the precondition "forall i: arg0[i] > 0" of the model of the called function is not satisfied
+}
+
+pub fn unknown_length(v: &[i32]) -> i32 {
+    first_of_slice(v) //~ the precondition "forall i: arg0[i] > 0" of the model of the called function could not be checked, because the length of the collection is not known or exceeds 32
+}
+
+pub fn with_zero() -> i32 {
+    let a = [1, 0];
+    second(&a)
+}
+
+pub fn without_zero() -> i32 {
+    let a = [1, 2];
+    second(&a) //~ the precondition "exists i: arg0[i] == 0" of the model of the called function is not satisfied
+}
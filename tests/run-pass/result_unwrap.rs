@@ -0,0 +1,13 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that unwrapping a Result produces the value wrapped by its Ok variant.
+
+pub fn unwrap_ok() -> u32 {
+    let r: Result<u32, ()> = Ok(5);
+    let x = r.unwrap();
+    10 / (x - 5) //~ attempt to divide by zero
+}
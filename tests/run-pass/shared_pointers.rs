@@ -0,0 +1,27 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that clones of an Rc share its value and count the pointers to it,
+// and that the value is updated precisely as long as all of the pointers are local.
+
+use std::rc::Rc;
+
+pub fn main() {
+    let a = Rc::new(5);
+    let b = a.clone();
+    debug_assert!(*a == *b);
+    debug_assert!(Rc::strong_count(&a) == 2);
+    drop(b);
+    debug_assert!(Rc::strong_count(&a) == 1);
+
+    let c = Rc::new(1);
+    let d = c.clone();
+    let p = &*c as *const i32 as *mut i32;
+    unsafe {
+        *p = 2;
+    }
+    debug_assert!(*d == 2);
+}
@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that shift amounts must be less than the bit width and that
+// diagnostics say which shift amounts are possible.
+
+pub fn shift_by_unknown(x: u32, n: u32) -> u32 {
+    x << n //~ possible attempt to shift left with overflow
+    //~ the shift amount is not known to be at least 0 and less than 32
+}
+
+pub fn shift_by_masked(x: u32, n: bool) -> u32 {
+    let m = if n { 31 } else { 0 };
+    x << m
+}
+
+pub fn shift_by_negative() -> i32 {
+    let x: i32 = 1;
+    let n: i32 = -1;
+    x >> n //~ attempt to shift right with overflow
+}
@@ -38,7 +38,7 @@ pub fn ti8_sub_safe(cond: bool) -> i8 {
 
 pub fn ti8_shl_overflows(cond: bool) -> i8 {
     let a: i8 = if cond { 8 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 8, but must be less than 8
 }
 
 pub fn ti8_shl_safe(cond: bool) -> i8 {
@@ -48,7 +48,7 @@ pub fn ti8_shl_safe(cond: bool) -> i8 {
 
 pub fn ti8_shr_overflows(cond: bool) -> i8 {
     let a: i8 = if cond { 8 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 8, but must be less than 8
 }
 
 pub fn ti8_shr_safe(cond: bool) -> i8 {
@@ -88,7 +88,7 @@ pub fn ti16_sub_safe(cond: bool) -> i16 {
 
 pub fn ti16_shl_overflows(cond: bool) -> i16 {
     let a: i16 = if cond { 16 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 16, but must be less than 16
 }
 
 pub fn ti16_shl_safe(cond: bool) -> i16 {
@@ -98,7 +98,7 @@ pub fn ti16_shl_safe(cond: bool) -> i16 {
 
 pub fn ti16_shr_overflows(cond: bool) -> i16 {
     let a: i16 = if cond { 16 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 16, but must be less than 16
 }
 
 pub fn ti16_shr_safe(cond: bool) -> i16 {
@@ -138,7 +138,7 @@ pub fn ti32_sub_safe(cond: bool) -> i32 {
 
 pub fn ti32_shl_overflows(cond: bool) -> i32 {
     let a: i32 = if cond { 32 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 32, but must be less than 32
 }
 
 pub fn ti32_shl_safe(cond: bool) -> i32 {
@@ -148,7 +148,7 @@ pub fn ti32_shl_safe(cond: bool) -> i32 {
 
 pub fn ti32_shr_overflows(cond: bool) -> i32 {
     let a: i32 = if cond { 32 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 32, but must be less than 32
 }
 
 pub fn ti32_shr_safe(cond: bool) -> i32 {
@@ -188,7 +188,7 @@ pub fn ti64_sub_safe(cond: bool) -> i64 {
 
 pub fn ti64_shl_overflows(cond: bool) -> i64 {
     let a: i64 = if cond { 64 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 64, but must be less than 64
 }
 
 pub fn ti64_shl_safe(cond: bool) -> i64 {
@@ -198,7 +198,7 @@ pub fn ti64_shl_safe(cond: bool) -> i64 {
 
 pub fn ti64_shr_overflows(cond: bool) -> i64 {
     let a: i64 = if cond { 64 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 64, but must be less than 64
 }
 
 pub fn ti64_shr_safe(cond: bool) -> i64 {
@@ -240,7 +240,7 @@ pub fn ti128_sub_safe(cond: bool) -> i128 {
 
 pub fn ti128_shl_overflows(cond: bool) -> i128 {
     let a: i128 = if cond { 128 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 128, but must be less than 128
 }
 
 pub fn ti128_shl_safe(cond: bool) -> i128 {
@@ -250,7 +250,7 @@ pub fn ti128_shl_safe(cond: bool) -> i128 {
 
 pub fn ti128_shr_overflows(cond: bool) -> i128 {
     let a: i128 = if cond { 128 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 128, but must be less than 128
 }
 
 pub fn ti128_shr_safe(cond: bool) -> i128 {
@@ -0,0 +1,102 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks the rules with which values are simplified, and that the flattening of
+// nested conditional expressions keeps their meaning.
+
+pub fn identities(x: u8) {
+    debug_assert!(x + 0 == x);
+    debug_assert!(x - 0 == x);
+    debug_assert!(x * 1 == x);
+    debug_assert!(x * 0 == 0);
+    debug_assert!(x / 1 == x);
+    debug_assert!(x | 0 == x);
+    debug_assert!(x & 0 == 0);
+    debug_assert!(x ^ 0 == x);
+    debug_assert!(x << 0 == x);
+    debug_assert!(x >> 0 == x);
+}
+
+pub fn disjoint_intervals(x: u8) {
+    let half = x / 2;
+    debug_assert!(half != 200);
+    debug_assert!(!(half == 200));
+}
+
+pub fn boolean_selections(c: bool) {
+    let b = if c { true } else { false };
+    debug_assert!(b == c);
+    let n = if c { false } else { true };
+    debug_assert!(n == !c);
+}
+
+// c ? (c ? x : y) : z is c ? x : z
+fn select_same(c: bool) -> u8 {
+    if c {
+        if c {
+            1
+        } else {
+            2
+        }
+    } else {
+        3
+    }
+}
+
+pub fn same_condition() {
+    debug_assert!(select_same(true) == 1);
+    debug_assert!(select_same(false) == 3);
+}
+
+// c1 ? (c2 ? x : y) : y is (c1 && c2) ? x : y
+fn select_both(c1: bool, c2: bool) -> u8 {
+    if c1 {
+        if c2 {
+            1
+        } else {
+            2
+        }
+    } else {
+        2
+    }
+}
+
+pub fn same_alternate() {
+    debug_assert!(select_both(true, true) == 1);
+    debug_assert!(select_both(true, false) == 2);
+    debug_assert!(select_both(false, true) == 2);
+    debug_assert!(select_both(false, false) == 2);
+}
+
+pub fn same_alternate_wrong() {
+    debug_assert!(select_both(true, false) == 1); //~ assertion failed: select_both(true, false) == 1
+}
+
+pub fn same_alternate_unknown(c1: bool, c2: bool) {
+    debug_assert!(select_both(c1, c2) == 2); //~ possible error: assertion failed: select_both(c1, c2) == 2
+}
+
+// c1 ? x : (c2 ? x : y) is (c1 || c2) ? x : y
+fn select_either(c1: bool, c2: bool) -> u8 {
+    if c1 {
+        1
+    } else if c2 {
+        1
+    } else {
+        2
+    }
+}
+
+pub fn same_consequent() {
+    debug_assert!(select_either(true, true) == 1);
+    debug_assert!(select_either(true, false) == 1);
+    debug_assert!(select_either(false, true) == 1);
+    debug_assert!(select_either(false, false) == 2);
+}
+
+pub fn same_consequent_wrong() {
+    debug_assert!(select_either(false, true) == 2); //~ assertion failed: select_either(false, true) == 2
+}
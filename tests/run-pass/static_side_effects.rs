@@ -0,0 +1,28 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that assignments to statics show up as side effects at call sites.
+
+static mut COUNTER: i32 = 0;
+
+fn set_counter() {
+    unsafe {
+        COUNTER = 5;
+    }
+}
+
+fn add_one(x: i32) -> i32 {
+    x + 1
+}
+
+pub fn main() {
+    set_counter();
+    let y = add_one(1);
+    unsafe {
+        debug_assert!(COUNTER == 5);
+    }
+    debug_assert!(y == 2);
+}
@@ -0,0 +1,17 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that what is known about the contents of strings decides assertions about them.
+
+pub fn main() {
+    let mut url = String::from("http");
+    url.push_str("s://");
+    debug_assert!(url.len() == 8);
+    debug_assert!(url.starts_with("https"));
+    debug_assert!(url.ends_with("://"));
+    let greeting = format!("hello {}!", url.len());
+    debug_assert!(greeting.starts_with("hello "));
+}
@@ -0,0 +1,48 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks the preconditions of implementations of trait methods against the contracts
+// declared by the default bodies of the trait methods, including bodies that accept every argument,
+// and only against such contracts.
+
+pub trait Shelf {
+    fn slot(&self, i: usize) -> usize {
+        debug_assert!(i < 10); //~ possible error: assertion failed: i < 10
+        i
+    }
+
+    fn position(&self, i: usize) -> usize {
+        i
+    }
+
+    fn label(&self, i: usize) -> usize;
+}
+
+struct Narrow;
+
+impl Shelf for Narrow {
+    fn slot(&self, i: usize) -> usize { //~ the implementation requires more than the trait method it implements: possible error: assertion failed: i < 5
+        debug_assert!(i < 5); //~ possible error: assertion failed: i < 5
+        //~ related location
+        i
+    }
+
+    fn position(&self, i: usize) -> usize { //~ the implementation requires more than the trait method it implements: possible error: assertion failed: i < 5
+        debug_assert!(i < 5); //~ possible error: assertion failed: i < 5
+        //~ related location
+        i
+    }
+
+    fn label(&self, i: usize) -> usize {
+        debug_assert!(i < 5); //~ possible error: assertion failed: i < 5
+        i
+    }
+}
+
+pub fn main() {
+    let shelf = Narrow;
+    let _ = shelf.slot(1) + shelf.position(3) + shelf.label(2);
+}
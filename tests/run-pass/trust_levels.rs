@@ -0,0 +1,28 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the trust levels of the configuration change how calls to the
+// functions of other crates are analyzed.
+
+// MIRAI_CONFIG [trust]
+// MIRAI_CONFIG std = "untrusted"
+// MIRAI_CONFIG alloc = "verified"
+
+use std::net::SocketAddr;
+
+pub fn next_port(addr: &SocketAddr) -> u16 {
+    addr.port() + 1 //~ possible attempt to add with overflow
+    //~ untrusted input enters the program here
+}
+
+pub fn shout(s: &str) -> String {
+    s.to_uppercase() //~ the called function belongs to a crate that must be verified, but it has not been analyzed
+}
+
+pub fn first_byte(s: &str) -> Option<u8> {
+    // Functions of crates without a trust level are used as they are.
+    s.bytes().next()
+}
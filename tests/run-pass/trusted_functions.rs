@@ -0,0 +1,41 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that calls to the functions that the configuration trusts to be panic free
+// have no preconditions, and that declarations without a reason are ignored.
+
+// MIRAI_CONFIG [trusted_functions."trusted_functions.vendored_lookup"]
+// MIRAI_CONFIG guarantee = "panic_free"
+// MIRAI_CONFIG reason = "reviewed along with the vendored sources"
+// MIRAI_CONFIG [trusted_functions."trusted_functions.unreviewed_lookup"]
+// MIRAI_CONFIG guarantee = "panic_free"
+
+fn lookup(a: &[i32], i: usize) -> i32 {
+    a[i] //~ related location
+}
+
+fn vendored_lookup(a: &[i32], i: usize) -> i32 {
+    a[i]
+}
+
+fn unreviewed_lookup(a: &[i32], i: usize) -> i32 {
+    a[i] //~ related location
+}
+
+pub fn untrusted() -> i32 {
+    let a = [1, 2];
+    lookup(&a, 3) //~ array index out of bounds
+}
+
+pub fn trusted() -> i32 {
+    let a = [1, 2];
+    vendored_lookup(&a, 3)
+}
+
+pub fn trusted_without_reason() -> i32 {
+    let a = [1, 2];
+    unreviewed_lookup(&a, 3) //~ array index out of bounds
+}
@@ -38,7 +38,7 @@ pub fn tu8_sub_safe(cond: bool) -> u8 {
 
 pub fn tu8_shl_overflows(cond: bool) -> u8 {
     let a: u8 = if cond { 8 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 8, but must be less than 8
 }
 
 pub fn tu8_shl_safe(cond: bool) -> u8 {
@@ -48,7 +48,7 @@ pub fn tu8_shl_safe(cond: bool) -> u8 {
 
 pub fn tu8_shr_overflows(cond: bool) -> u8 {
     let a: u8 = if cond { 8 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 8, but must be less than 8
 }
 
 pub fn tu8_shr_safe(cond: bool) -> u8 {
@@ -88,7 +88,7 @@ pub fn tu16_sub_safe(cond: bool) -> u16 {
 
 pub fn tu16_shl_overflows(cond: bool) -> u16 {
     let a: u16 = if cond { 16 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 16, but must be less than 16
 }
 
 pub fn tu16_shl_safe(cond: bool) -> u16 {
@@ -98,7 +98,7 @@ pub fn tu16_shl_safe(cond: bool) -> u16 {
 
 pub fn tu16_shr_overflows(cond: bool) -> u16 {
     let a: u16 = if cond { 16 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 16, but must be less than 16
 }
 
 pub fn tu16_shr_safe(cond: bool) -> u16 {
@@ -138,7 +138,7 @@ pub fn tu32_sub_safe(cond: bool) -> u32 {
 
 pub fn tu32_shl_overflows(cond: bool) -> u32 {
     let a: u32 = if cond { 32} else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 32, but must be less than 32
 }
 
 pub fn tu32_shl_safe(cond: bool) -> u32 {
@@ -148,7 +148,7 @@ pub fn tu32_shl_safe(cond: bool) -> u32 {
 
 pub fn tu32_shr_overflows(cond: bool) -> u32 {
     let a: u32 = if cond { 32 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 32, but must be less than 32
 }
 
 pub fn tu32_shr_safe(cond: bool) -> u32 {
@@ -188,7 +188,7 @@ pub fn tu64_sub_safe(cond: bool) -> u64 {
 
 pub fn tu64_shl_overflows(cond: bool) -> u64 {
     let a: u64 = if cond { 64 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 64, but must be less than 64
 }
 
 pub fn tu64_shl_safe(cond: bool) -> u64 {
@@ -198,7 +198,7 @@ pub fn tu64_shl_safe(cond: bool) -> u64 {
 
 pub fn tu64_shr_overflows(cond: bool) -> u64 {
     let a: u64 = if cond { 64 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 64, but must be less than 64
 }
 
 pub fn tu64_shr_safe(cond: bool) -> u64 {
@@ -240,7 +240,7 @@ pub fn tu128_sub_safe(cond: bool) -> u128 {
 
 pub fn tu128_shl_overflows(cond: bool) -> u128 {
     let a: u128 = if cond { 128 } else { 1 }; //~ possible attempt to shift left with overflow
-    a << a
+    a << a //~ the shift amount can be any value from 1 to 128, but must be less than 128
 }
 
 pub fn tu128_shl_safe(cond: bool) -> u128 {
@@ -250,7 +250,7 @@ pub fn tu128_shl_safe(cond: bool) -> u128 {
 
 pub fn tu128_shr_overflows(cond: bool) -> u128 {
     let a: u128 = if cond { 128 } else { 1 }; //~ possible attempt to shift right with overflow
-    a >> a
+    a >> a //~ the shift amount can be any value from 1 to 128, but must be less than 128
 }
 
 pub fn tu128_shr_safe(cond: bool) -> u128 {
@@ -0,0 +1,18 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that discarded results of calls that have no other effect are reported.
+
+fn first(x: i32, _y: i32) -> i32 {
+    x
+}
+
+pub fn main() {
+    first(1, 2); //~ the result of this call is dropped, but computing it is all that the call does
+    let _ = first(3, 4);
+    let x = first(5, 6);
+    let _y = first(x, 7);
+}
@@ -0,0 +1,26 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that unchecked conversions of bytes to str are preceded by validation.
+
+use std::str;
+
+fn to_str(bytes: &[u8]) -> &str {
+    unsafe { str::from_utf8_unchecked(bytes) } //~ possible error: bytes have not been validated as UTF-8
+}
+
+fn validated_to_str(bytes: &[u8]) -> &str {
+    match str::from_utf8(bytes) {
+        Ok(_) => unsafe { str::from_utf8_unchecked(bytes) },
+        Err(_) => "",
+    }
+}
+
+pub fn main() {
+    let bytes: &[u8] = b"abc";
+    let _ = to_str(bytes);
+    let _ = validated_to_str(bytes);
+}
@@ -0,0 +1,34 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that --verify-no-panics reports every panic that cannot be proven
+// unreachable, including unreachable!(), which is otherwise assumed to be unreachable.
+
+// MIRAI_FLAGS --verify-no-panics
+
+pub fn parity(b: bool) -> u8 {
+    if b {
+        1
+    } else {
+        unreachable!() //~ possible error: internal error: entered unreachable code
+    }
+}
+
+pub fn second(a: &[u8]) -> u8 {
+    a[1] //~ possible array index out of bounds
+}
+
+pub fn second_of_pair() -> u8 {
+    let a = [1, 2];
+    a[1]
+}
+
+pub fn known_parity(x: u8) -> u8 {
+    match x % 2 {
+        0 => 1,
+        _ => 2,
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that arithmetic on a crate compiled without overflow checks wraps around,
+// that shifts by too many bits shift by the masked amount, without diagnostics about overflow.
+
+// MIRAI_FLAGS -C overflow-checks=off
+
+pub fn wrapping_add() {
+    let a: u8 = 200;
+    let b = a + 100;
+    debug_assert!(b == 44);
+}
+
+pub fn wrapping_mul() {
+    let a: u8 = 16;
+    let b = a * 17;
+    debug_assert!(b == 16);
+}
+
+pub fn wrapping_signed_add() {
+    let a: i8 = 127;
+    let b = a + 1;
+    debug_assert!(b == -128);
+}
+
+pub fn wrapping_shl() {
+    let a: u8 = 1;
+    let n: u32 = 9;
+    let b = a << n;
+    debug_assert!(b == 2);
+}
+
+pub fn wrapping_shr() {
+    let a: i16 = -256;
+    let n: u32 = 20;
+    let b = a >> n;
+    debug_assert!(b == -16);
+}
+
+pub fn unknown_add(x: u8) -> u8 {
+    x + 1
+}